@@ -0,0 +1,123 @@
+//! Interrupt-driven alarm subsystem built on top of `hal_time`'s `MSLEEP_TARGET` hardware
+//! and the ticktimer IRQ.
+//!
+//! `hal_time::set_msleep_target_ticks` only supports a single relative delta, which is fine
+//! for `main()`'s own fixed watchdog-petting cadence but doesn't let independent subsystems
+//! each schedule their own deadline without fighting over the one hardware compare register.
+//! This module layers a small fixed-size table of software alarms on top of that register:
+//! callers allocate a handle once, arm it with an absolute `TimeMs` deadline, and
+//! `on_ticktimer_irq` (called from `main`'s existing ticktimer IRQ handler) fires whichever
+//! alarms have come due and reprograms the hardware for the next soonest one.
+//!
+//! Modeled loosely on embassy's `Driver`/`AlarmHandle` time-driver design, scaled down to a
+//! bounded pool since this is a `no_std` target with no allocator.
+
+use crate::hal_time::{set_msleep_target_absolute, TimeMs};
+use core::sync::atomic::{AtomicUsize, Ordering};
+use riscv::register::mstatus;
+
+/// Number of alarms this driver can track at once. Four is enough for the subsystems in this
+/// tree that plausibly want their own deadline (network retransmit timers, UI timeouts, ...)
+/// without the table walk in `on_ticktimer_irq` costing much per tick.
+const ALARM_POOL_SIZE: usize = 4;
+
+/// Ticks between interrupts when no alarm is pending. Matches the cadence `main()` already
+/// used to pet the watchdog and poll keyboard-wake sensing before this module existed, so
+/// wiring `on_ticktimer_irq` into that handler doesn't change its idle behavior.
+const IDLE_REARM_TICKS: u32 = 50;
+
+struct Alarm {
+    target: Option<TimeMs>,
+    callback: fn(*mut ()),
+    ctx: *mut (),
+}
+
+static mut ALARMS: [Option<Alarm>; ALARM_POOL_SIZE] = [None, None, None, None];
+
+/// Bounded allocator for alarm slots. A plain atomic counter is enough because slots are
+/// handed out once at subsystem init time and never freed back to the pool.
+static NEXT_ALARM: AtomicUsize = AtomicUsize::new(0);
+
+/// Opaque reference to one slot in the alarm table, returned by `allocate_alarm`.
+pub struct AlarmHandle(usize);
+
+/// Claim one of the `ALARM_POOL_SIZE` alarm slots. Returns `None` once the pool is exhausted;
+/// callers that need more alarms than the pool provides should reuse a handle rather than
+/// calling this in a loop.
+pub fn allocate_alarm() -> Option<AlarmHandle> {
+    let idx = NEXT_ALARM.fetch_add(1, Ordering::Relaxed);
+    if idx < ALARM_POOL_SIZE {
+        Some(AlarmHandle(idx))
+    } else {
+        None
+    }
+}
+
+/// Arm `handle` to fire `callback(ctx)` once `target` has passed, reprogramming the hardware
+/// compare register if `target` is now the earliest pending deadline. Disables CPU interrupts
+/// briefly while updating the table, matching `xous_nommu::irq`'s own critical-section
+/// convention, since `on_ticktimer_irq` walks and mutates this same table from IRQ context.
+pub fn set_alarm(handle: &AlarmHandle, target: TimeMs, callback: fn(*mut ()), ctx: *mut ()) {
+    unsafe {
+        mstatus::clear_mie();
+        ALARMS[handle.0] = Some(Alarm {
+            target: Some(target),
+            callback,
+            ctx,
+        });
+        mstatus::set_mie();
+    }
+    reprogram_hardware(TimeMs::now());
+}
+
+/// Disarm `handle` without firing its callback.
+pub fn cancel_alarm(handle: &AlarmHandle) {
+    unsafe {
+        mstatus::clear_mie();
+        if let Some(alarm) = ALARMS[handle.0].as_mut() {
+            alarm.target = None;
+        }
+        mstatus::set_mie();
+    }
+    reprogram_hardware(TimeMs::now());
+}
+
+/// Call once per ticktimer IRQ. Fires every alarm whose target has passed (clearing it so it
+/// doesn't refire), then reprograms `MSLEEP_TARGET0/1` for the next soonest pending alarm, or
+/// `IDLE_REARM_TICKS` out if none are pending. Runs in IRQ context with interrupts already
+/// disabled (see `xous_nommu::irq::handle`), so no critical section is needed here, unlike
+/// `set_alarm`/`cancel_alarm` above.
+pub fn on_ticktimer_irq() {
+    let now = TimeMs::now();
+    unsafe {
+        for slot in ALARMS.iter_mut() {
+            if let Some(alarm) = slot {
+                if let Some(target) = alarm.target {
+                    if now >= target {
+                        alarm.target = None;
+                        (alarm.callback)(alarm.ctx);
+                    }
+                }
+            }
+        }
+    }
+    reprogram_hardware(now);
+}
+
+fn reprogram_hardware(now: TimeMs) {
+    let mut earliest: Option<TimeMs> = None;
+    unsafe {
+        for slot in ALARMS.iter() {
+            if let Some(alarm) = slot {
+                if let Some(target) = alarm.target {
+                    earliest = match earliest {
+                        Some(e) if e < target => Some(e),
+                        _ => Some(target),
+                    };
+                }
+            }
+        }
+    }
+    let target = earliest.unwrap_or_else(|| now.add_ms(IDLE_REARM_TICKS));
+    set_msleep_target_absolute(target.ms_low_word(), target.ms_high_word());
+}
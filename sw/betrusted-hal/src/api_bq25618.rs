@@ -1,6 +1,7 @@
 use bitflags::*;
 
-use crate::hal_i2c::Hardi2c;
+use crate::hal_i2c::{Hardi2c, I2cError};
+use crate::hal_time::{delay_ms, get_time_ms};
 
 const BQ25618_ADDR: u8 = 0x6A;
 
@@ -252,26 +253,269 @@ bitflags! {
 
 const CHG_TIMEOUT_MS: u32 = 1;
 
+// There is no `utra` IRQ line wired to the BQ25618's INT pin in this tree (the only claimed
+// IRQ anywhere in `sw/src/main.rs` is the ticktimer's) -- same gap as the IMU's INT1 pin
+// documented on `BtGyro`. So `poll_events` below substitutes software edge-detection for a
+// true interrupt: it re-reads registers 0x08-0x0A each call and diffs them against the
+// `self.registers` snapshot from the previous call, decoding whatever changed into
+// `ChargerEvent`s. This mirrors the `INT_RT_STS` edge-decode pattern in the qpnp-linear-charger
+// driver, just driven by the main loop's own poll cadence instead of a real edge.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum VbusKind {
+    NoInput,
+    Host500ma,
+    Adapter2a,
+    BoostMode,
+}
+impl VbusKind {
+    fn decode(stat0: u8) -> Self {
+        match stat0 & ChargerStatus0::VBUS_MASK.bits() {
+            x if x == ChargerStatus0::VBUS_HOST_500MA.bits() => VbusKind::Host500ma,
+            x if x == ChargerStatus0::VBUS_ADAPTER_2A.bits() => VbusKind::Adapter2a,
+            x if x == ChargerStatus0::VBUS_BOOSTMODE.bits() => VbusKind::BoostMode,
+            _ => VbusKind::NoInput,
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum ChargerEvent {
+    PowerGood,
+    ChargeDone,
+    // The BQ25618's CHG_FAULT field (`ChargerStatus1::CHG_INPUT_FAULT`) doesn't break an
+    // input fault down any further than "input fault", so there's no extra reason code to
+    // carry here the way there is for e.g. `VbusSourceChanged`.
+    InputFault,
+    ThermalFault,
+    Timeout,
+    WatchdogExpired,
+    VbusSourceChanged(VbusKind),
+}
+
+/// Capacity of `ChargerEventQueue` -- a handful of edges is plenty since the main loop drains
+/// it every pass; sized the same way `StrBuf`'s `N` would be chosen, just without the generic
+/// since only `poll_events` ever produces into it.
+const CHARGER_EVENT_QUEUE_LEN: usize = 8;
+
+/// Fixed-capacity FIFO of decoded charger events. Single producer (`poll_events`, called from
+/// the main loop), single consumer (whatever in the main loop drains it next), so plain
+/// `usize` indices are enough -- unlike `PktBuf`, there's no ISR involved on either side.
+pub struct ChargerEventQueue {
+    buf: [Option<ChargerEvent>; CHARGER_EVENT_QUEUE_LEN],
+    head: usize,
+    tail: usize,
+}
+impl ChargerEventQueue {
+    pub const fn new() -> Self {
+        ChargerEventQueue { buf: [None; CHARGER_EVENT_QUEUE_LEN], head: 0, tail: 0 }
+    }
+    fn push(&mut self, event: ChargerEvent) {
+        let next_tail = (self.tail + 1) % CHARGER_EVENT_QUEUE_LEN;
+        if next_tail == self.head {
+            // queue full: drop the oldest event to make room for the newest
+            self.head = (self.head + 1) % CHARGER_EVENT_QUEUE_LEN;
+        }
+        self.buf[self.tail] = Some(event);
+        self.tail = next_tail;
+    }
+    pub fn pop(&mut self) -> Option<ChargerEvent> {
+        if self.head == self.tail {
+            return None;
+        }
+        let event = self.buf[self.head].take();
+        self.head = (self.head + 1) % CHARGER_EVENT_QUEUE_LEN;
+        event
+    }
+}
+
+// Thermal-mitigation zones, analogous to the SMB348 driver's FEATURE_THERMAL_MITIGATION_ALGO:
+// an ordered table of temperature thresholds (upper bound, deg C) each mapped to an ICHG
+// fraction. Zones are listed coldest-to-hottest, so their index is already the "current
+// severity" used by `chg_thermal_update`'s hysteresis check.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum ThermalZone {
+    Cold,
+    Cool,
+    Normal,
+    Warm,
+    Hot,
+}
+impl ThermalZone {
+    /// Fraction of `ICHG_FULL_MA` to charge at in this zone.
+    fn ichg_pct(&self) -> u32 {
+        match self {
+            ThermalZone::Cold => 0,   // NTC_COLD: suspend charging entirely
+            ThermalZone::Cool => 20,  // JEITA_COOL_20
+            ThermalZone::Normal => 100,
+            ThermalZone::Warm => 50,  // JEITA_WARM_50
+            ThermalZone::Hot => 0,    // NTC_HOT: suspend charging entirely
+        }
+    }
+}
+// Upper temperature bound (deg C) of each zone below Hot; above JEITA_HOT_C is Hot. These
+// line up with the hardware JEITA thresholds programmed by `chg_set_jeita`.
+const JEITA_COLD_C: i16 = 0;
+const JEITA_COOL_C: i16 = 10; // matches JEITA_VT2_10C0
+const JEITA_WARM_C: i16 = 45; // matches JEITA_VT3_44C5
+const JEITA_HOT_C: i16 = 50;
+// Zone transitions only commit once the temperature has moved this far past a boundary, so
+// a reading oscillating right at an edge doesn't chatter the charge current up and down.
+const THERMAL_HYSTERESIS_C: i16 = 3;
+// Full-scale fast-charge current the thermal algorithm scales down from -- matches the
+// hard-coded 500mA `chg_set_autoparams`/`chg_start` already program into `BQ25618_02_CHG_ILIM`.
+const ICHG_FULL_MA: u32 = 500;
+
+// Pet the watchdog at less than half of the `ChargeControl1::WATCHDOG_40S` window
+// `chg_set_autoparams` configures, so a slow tick cadence still has margin before the chip
+// resets charge settings back to its power-on defaults.
+const WATCHDOG_PET_INTERVAL_MS: u32 = 15_000;
+
+fn zone_for_temp(temp_c: i16) -> ThermalZone {
+    if temp_c <= JEITA_COLD_C {
+        ThermalZone::Cold
+    } else if temp_c <= JEITA_COOL_C {
+        ThermalZone::Cool
+    } else if temp_c <= JEITA_WARM_C {
+        ThermalZone::Normal
+    } else if temp_c <= JEITA_HOT_C {
+        ThermalZone::Warm
+    } else {
+        ThermalZone::Hot
+    }
+}
+
 #[derive(Debug)]
 pub struct BtCharger {
-    pub registers: [u8; 0xC],
+    // sized through BQ25618_0C_JEITA (0x0C) inclusive
+    pub registers: [u8; 0x0D],
+    thermal_zone: ThermalZone,
+    /// Set by `chg_safety_tick` on a `WATCHDOG_FAULT`/`CHG_TIMEOUT`, and only ever cleared by
+    /// an explicit `chg_clear_fault` call -- `chg_start`/`chg_boost` refuse to run while set,
+    /// so a runaway charge session can't be blindly re-armed by the next poll.
+    fault_latched: bool,
+    /// `get_time_ms()` at the last `WD_RST` write, used by `chg_safety_tick` to decide when
+    /// the next pet is due.
+    last_wd_pet_ms: u32,
 }
 
 impl BtCharger {
     pub fn new() -> Self {
-        BtCharger { registers: [0; 0xC] }
+        BtCharger { registers: [0; 0x0D], thermal_zone: ThermalZone::Normal, fault_latched: false, last_wd_pet_ms: 0 }
     }
 
-    pub fn update_regs(&mut self, i2c: &mut Hardi2c) -> &mut Self {
+    pub fn update_regs(&mut self, i2c: &mut Hardi2c) -> Result<(), I2cError> {
         let mut rxbuf: [u8; 2] = [0, 0];
         let mut txbuf: [u8; 1] = [0];
 
-        for i in 0..0xC {
+        for i in 0..0x0D {
             txbuf[0] = i as u8;
-            while i2c.i2c_controller(BQ25618_ADDR, Some(&txbuf), Some(&mut rxbuf), CHG_TIMEOUT_MS) != 0 {}
+            i2c.i2c_controller(BQ25618_ADDR, Some(&txbuf), Some(&mut rxbuf), CHG_TIMEOUT_MS)?;
             self.registers[i] = rxbuf[0] as u8;
         }
-        self
+        Ok(())
+    }
+
+    /// Program the chip's own JEITA zones (`BQ25618_0C_JEITA`) so the hardware NTC path
+    /// agrees with the software thresholds `chg_thermal_update` uses: VT2/VT3 set the
+    /// cool/warm boundaries closest to `JEITA_COOL_C`/`JEITA_WARM_C`, and WARM/COOL set the
+    /// hardware's own current derating to match `ThermalZone::ichg_pct`.
+    pub fn chg_set_jeita(&mut self, i2c: &mut Hardi2c) -> Result<(), I2cError> {
+        self.registers[BQ25618_0C_JEITA] =
+            (JEITAControl::JEITA_VT2_10C0 | JEITAControl::JEITA_VT3_44C5 | JEITAControl::JEITA_WARM_50 | JEITAControl::JEITA_COOL_20)
+                .bits();
+        let txbuf: [u8; 2] = [BQ25618_0C_JEITA as u8, self.registers[BQ25618_0C_JEITA]];
+        i2c.i2c_controller(BQ25618_ADDR, Some(&txbuf), None, CHG_TIMEOUT_MS)?;
+        Ok(())
+    }
+
+    /// Supervisory thermal-mitigation update: given the latest battery/board temperature,
+    /// step `self.thermal_zone` toward whichever zone `temp_c` falls in (hysteresis-gated so
+    /// a boundary-straddling reading can't oscillate the zone back and forth), then reprogram
+    /// `BQ25618_02_CHG_ILIM` to that zone's fraction of `ICHG_FULL_MA`. Call
+    /// `chg_set_jeita` once beforehand so the hardware NTC path agrees with this.
+    pub fn chg_thermal_update(&mut self, i2c: &mut Hardi2c, temp_c: i16) -> Result<(), I2cError> {
+        let candidate = zone_for_temp(temp_c);
+        let current_idx = self.thermal_zone as u8;
+        let candidate_idx = candidate as u8;
+        self.thermal_zone = if candidate_idx == current_idx {
+            self.thermal_zone
+        } else if candidate_idx > current_idx {
+            // Moving to a hotter zone: only commit once temp_c is past the boundary by more
+            // than the hysteresis margin.
+            if zone_for_temp(temp_c - THERMAL_HYSTERESIS_C) as u8 > current_idx { candidate } else { self.thermal_zone }
+        } else {
+            // Moving to a cooler zone: same margin, other direction.
+            if (zone_for_temp(temp_c + THERMAL_HYSTERESIS_C) as u8) < current_idx { candidate } else { self.thermal_zone }
+        };
+
+        let ichg_ma = (ICHG_FULL_MA * self.thermal_zone.ichg_pct()) / 100;
+        self.registers[BQ25618_02_CHG_ILIM] =
+            (((ichg_ma / ICHG_LSB_MA) << ICHG_BITPOS) as u8) & ChargeCurrentLimit::ICHG_MASK.bits();
+        let txbuf: [u8; 2] = [BQ25618_02_CHG_ILIM as u8, self.registers[BQ25618_02_CHG_ILIM]];
+        i2c.i2c_controller(BQ25618_ADDR, Some(&txbuf), None, CHG_TIMEOUT_MS)?;
+        Ok(())
+    }
+
+    /// Re-read `BQ25618_08_CHG_STAT0`..`BQ25618_0A_CHG_STAT2`, diff them against the cached
+    /// `self.registers` snapshot from the last call, and push a `ChargerEvent` onto `queue` for
+    /// each decoded edge: `PWRGOOD` rising, entry into charge termination, any `VBUS` status
+    /// change, a new input/thermal/timeout fault code, and `WATCHDOG_FAULT` rising. Other bit
+    /// changes (including all of `CHG_STAT2`) only update the cache. See the module comment
+    /// above `ChargerEvent` for why this polls instead of reacting to a real INT-pin edge.
+    pub fn poll_events(&mut self, i2c: &mut Hardi2c, queue: &mut ChargerEventQueue) -> Result<(), I2cError> {
+        let prev0 = self.registers[BQ25618_08_CHG_STAT0];
+        let prev1 = self.registers[BQ25618_09_CHG_STAT1];
+        let prev2 = self.registers[BQ25618_0A_CHG_STAT2];
+
+        let mut rxbuf: [u8; 2] = [0, 0];
+        for (addr, prev) in [(BQ25618_08_CHG_STAT0, prev0), (BQ25618_09_CHG_STAT1, prev1), (BQ25618_0A_CHG_STAT2, prev2)] {
+            let txbuf: [u8; 1] = [addr as u8];
+            i2c.i2c_controller(BQ25618_ADDR, Some(&txbuf), Some(&mut rxbuf), CHG_TIMEOUT_MS)?;
+            let cur = rxbuf[0];
+            self.registers[addr] = cur;
+
+            if addr == BQ25618_08_CHG_STAT0 {
+                if (cur ^ prev) & ChargerStatus0::PWRGOOD_STA.bits() != 0 && (cur & ChargerStatus0::PWRGOOD_STA.bits()) != 0 {
+                    queue.push(ChargerEvent::PowerGood);
+                }
+                if (cur & ChargerStatus0::CHG_MASK.bits()) == ChargerStatus0::CHG_CHARGETERM.bits()
+                    && (prev & ChargerStatus0::CHG_MASK.bits()) != ChargerStatus0::CHG_CHARGETERM.bits()
+                {
+                    queue.push(ChargerEvent::ChargeDone);
+                }
+                if (cur ^ prev) & ChargerStatus0::VBUS_MASK.bits() != 0 {
+                    queue.push(ChargerEvent::VbusSourceChanged(VbusKind::decode(cur)));
+                }
+            } else if addr == BQ25618_09_CHG_STAT1 {
+                const FAULT_MASK: u8 = 0b00_11_0_000; // CHG_INPUT_FAULT/CHG_THERM_FAULT/CHG_TIMEOUT share this field
+                if (cur ^ prev) & FAULT_MASK != 0 {
+                    match cur & FAULT_MASK {
+                        x if x == ChargerStatus1::CHG_INPUT_FAULT.bits() => queue.push(ChargerEvent::InputFault),
+                        x if x == ChargerStatus1::CHG_THERM_FAULT.bits() => queue.push(ChargerEvent::ThermalFault),
+                        x if x == ChargerStatus1::CHG_TIMEOUT.bits() => queue.push(ChargerEvent::Timeout),
+                        _ => (),
+                    }
+                }
+                if (cur ^ prev) & ChargerStatus1::WATCHDOG_FAULT.bits() != 0 && (cur & ChargerStatus1::WATCHDOG_FAULT.bits()) != 0 {
+                    queue.push(ChargerEvent::WatchdogExpired);
+                }
+            } else {
+                let _ = (addr, prev, cur); // CHG_STAT2 is read to keep the cache current; no events decoded from it yet
+            }
+        }
+        Ok(())
+    }
+
+    /// Decode `ChargerStatus1`'s NTC bits and report whether the hardware itself already
+    /// thinks charging should be suspended (HOT/COLD), independent of `chg_thermal_update`'s
+    /// own zone tracking -- a second, hardware-grounded check against the same invariant.
+    pub fn chg_ntc_suspend(&mut self, i2c: &mut Hardi2c) -> Result<bool, I2cError> {
+        let txbuf: [u8; 1] = [BQ25618_09_CHG_STAT1 as u8];
+        let mut rxbuf: [u8; 2] = [0, 0];
+        i2c.i2c_controller(BQ25618_ADDR, Some(&txbuf), Some(&mut rxbuf), CHG_TIMEOUT_MS)?;
+        self.registers[BQ25618_09_CHG_STAT1] = rxbuf[0];
+        let ntc = rxbuf[0] & 0b0000_0111;
+        Ok(ntc == (ChargerStatus1::NTC_HOT.bits() & 0b0000_0111) || ntc == (ChargerStatus1::NTC_COLD.bits() & 0b0000_0111))
     }
 
     pub fn set_shipmode(&mut self, i2c: &mut Hardi2c) {
@@ -281,21 +525,21 @@ impl BtCharger {
             ChargeControl3::BATFET_OFF_ALLOW |
             ChargeControl3::BATFET_RST_EN).bits() as u8];
 
-        i2c.i2c_controller(BQ25618_ADDR, Some(&txbuf), None, CHG_TIMEOUT_MS);
+        i2c.i2c_controller(BQ25618_ADDR, Some(&txbuf), None, CHG_TIMEOUT_MS).ok();
     }
 
-    pub fn chg_is_charging(&mut self, i2c: &mut Hardi2c, use_cached: bool) -> bool {
+    pub fn chg_is_charging(&mut self, i2c: &mut Hardi2c, use_cached: bool) -> Result<bool, I2cError> {
         let txbuf: [u8; 1] = [BQ25618_08_CHG_STAT0 as u8];
         let mut rxbuf: [u8; 2] = [0, 0];
 
         let chgstat0: u8;
         if !use_cached {
-            while i2c.i2c_controller(BQ25618_ADDR, Some(&txbuf), Some(&mut rxbuf), CHG_TIMEOUT_MS) != 0 {}
+            i2c.i2c_controller(BQ25618_ADDR, Some(&txbuf), Some(&mut rxbuf), CHG_TIMEOUT_MS)?;
             chgstat0 = rxbuf[0];
         } else {
             chgstat0 = self.registers[BQ25618_08_CHG_STAT0];
         }
-        if (chgstat0 & ChargerStatus0::CHG_MASK.bits()) == ChargerStatus0::CHG_NOT_CHARGING.bits() {
+        Ok(if (chgstat0 & ChargerStatus0::CHG_MASK.bits()) == ChargerStatus0::CHG_NOT_CHARGING.bits() {
             false
         } else if (chgstat0 & ChargerStatus0::CHG_MASK.bits()) == ChargerStatus0::CHG_PRECHARGING.bits() {
             true
@@ -305,19 +549,67 @@ impl BtCharger {
             false
         } else {
             false
-        }
+        })
     }
 
-    pub fn chg_keepalive_ping(&mut self, i2c: &mut Hardi2c) {
+    pub fn chg_keepalive_ping(&mut self, i2c: &mut Hardi2c) -> Result<(), I2cError> {
         let txbuf: [u8; 1] = [BQ25618_01_CHG_CTL as u8];
         let mut rxbuf: [u8; 2] = [0, 0];
-        while i2c.i2c_controller(BQ25618_ADDR, Some(&txbuf), Some(&mut rxbuf), CHG_TIMEOUT_MS) != 0 {}
+        i2c.i2c_controller(BQ25618_ADDR, Some(&txbuf), Some(&mut rxbuf), CHG_TIMEOUT_MS)?;
 
         let txbuf: [u8; 2] = [BQ25618_01_CHG_CTL as u8, rxbuf[0] | ChargeControl::WD_RST.bits()];
-        while i2c.i2c_controller(BQ25618_ADDR, Some(&txbuf), None, CHG_TIMEOUT_MS) != 0 {}
+        i2c.i2c_controller(BQ25618_ADDR, Some(&txbuf), None, CHG_TIMEOUT_MS)?;
+        Ok(())
+    }
+
+    /// Milliamp ceiling to program for each detected VBUS source class, clamped to what
+    /// `IINDPM_MASK`/`IINDPM_LSB_MA`/`IINDMP_OFFSET_MA` can actually encode (100mA-3200mA).
+    fn iindpm_ceiling_ma(source: VbusKind) -> u32 {
+        match source {
+            VbusKind::NoInput => 100,    // lowest the field can express; nothing will draw anyway
+            VbusKind::Host500ma => 500,
+            VbusKind::Adapter2a => 2000,
+            VbusKind::BoostMode => 100,  // EC is sourcing VBUS itself here, not drawing from it
+        }
+    }
+
+    /// Trigger the chip's own input-current-detection (`IINDET_EN`), wait for it to settle,
+    /// then read the resulting `VBUS_MASK` class out of `ChargerStatus0` and program
+    /// `BQ25618_00_ILIM`'s IINDPM field to a safe ceiling for that class -- replacing the flat
+    /// 1500mA `chg_set_autoparams`/`chg_start` otherwise hard-code. Mirrors the USB-path
+    /// source-classification step used by Qualcomm's charger detection state machine. Call
+    /// this once VBUS is known to be present, after `chg_set_autoparams`/`chg_start` (both of
+    /// which program `BQ25618_00_ILIM` to their own flat default and would otherwise clobber
+    /// whatever this negotiates).
+    pub fn chg_negotiate_input_current(&mut self, i2c: &mut Hardi2c) -> Result<(), I2cError> {
+        // kick off detection; per the datasheet IINDET_EN self-clears once the result latches
+        let txbuf: [u8; 2] = [BQ25618_07_CHG_CTL3 as u8,
+            (ChargeControl3::TMR2X_EN |
+             ChargeControl3::BATFET_DLY_10S |
+             ChargeControl3::VINDPM_TRACK_300MV |
+             ChargeControl3::IINDET_EN)
+             .bits()];
+        i2c.i2c_controller(BQ25618_ADDR, Some(&txbuf), None, CHG_TIMEOUT_MS)?;
+
+        // give the chip time to run detection and latch VBUS_MASK in ChargerStatus0
+        delay_ms(100);
+
+        let txbuf: [u8; 1] = [BQ25618_08_CHG_STAT0 as u8];
+        let mut rxbuf: [u8; 2] = [0, 0];
+        i2c.i2c_controller(BQ25618_ADDR, Some(&txbuf), Some(&mut rxbuf), CHG_TIMEOUT_MS)?;
+        self.registers[BQ25618_08_CHG_STAT0] = rxbuf[0];
+        let source = VbusKind::decode(rxbuf[0]);
+
+        let ma = Self::iindpm_ceiling_ma(source);
+        self.registers[BQ25618_00_ILIM] =
+            InputCurrentLimit::TS_IGNORE.bits() |
+            ((((ma - IINDMP_OFFSET_MA) / IINDPM_LSB_MA) << IINDPM_BITPOS) & IINDPM_MASK) as u8;
+        let txbuf: [u8; 2] = [BQ25618_00_ILIM as u8, self.registers[BQ25618_00_ILIM]];
+        i2c.i2c_controller(BQ25618_ADDR, Some(&txbuf), None, CHG_TIMEOUT_MS)?;
+        Ok(())
     }
 
-    pub fn chg_set_autoparams(&mut self, i2c: &mut Hardi2c) {
+    pub fn chg_set_autoparams(&mut self, i2c: &mut Hardi2c) -> Result<(), I2cError> {
         self.registers[BQ25618_00_ILIM] =
             InputCurrentLimit::TS_IGNORE.bits() |
             ((((1500 - IINDMP_OFFSET_MA) / IINDPM_LSB_MA) << IINDPM_BITPOS) & IINDPM_MASK) as u8;
@@ -364,11 +656,17 @@ impl BtCharger {
         for i in 0..8 {
             txbuf[i+1] = self.registers[i];
         }
-        while i2c.i2c_controller(BQ25618_ADDR, Some(&txbuf), None, CHG_TIMEOUT_MS) != 0 {}
+        i2c.i2c_controller(BQ25618_ADDR, Some(&txbuf), None, CHG_TIMEOUT_MS)?;
+        Ok(())
     }
 
     // this will override ilim, to attempt charge to run at full current
-    pub fn chg_start(&mut self, i2c: &mut Hardi2c) {
+    pub fn chg_start(&mut self, i2c: &mut Hardi2c) -> Result<(), I2cError> {
+        // refuse to (re-)arm charging while chg_safety_tick has a fault latched -- call
+        // chg_clear_fault first if the fault condition is confirmed resolved
+        if self.fault_latched {
+            return Ok(());
+        }
         self.registers[BQ25618_00_ILIM] =
             InputCurrentLimit::TS_IGNORE.bits() |
             ((((1500 - IINDMP_OFFSET_MA) / IINDPM_LSB_MA) << IINDPM_BITPOS) & IINDPM_MASK) as u8;
@@ -387,10 +685,16 @@ impl BtCharger {
         for i in 0..2 {
             txbuf[i+1] = self.registers[i];
         }
-        while i2c.i2c_controller(BQ25618_ADDR, Some(&txbuf), None, CHG_TIMEOUT_MS) != 0 {}
+        i2c.i2c_controller(BQ25618_ADDR, Some(&txbuf), None, CHG_TIMEOUT_MS)?;
+        Ok(())
     }
 
-    pub fn chg_boost(&mut self, i2c: &mut Hardi2c) {
+    pub fn chg_boost(&mut self, i2c: &mut Hardi2c) -> Result<(), I2cError> {
+        // same fault-latch guard as chg_start -- a runaway charge shouldn't be able to flip
+        // straight into boost mode either
+        if self.fault_latched {
+            return Ok(());
+        }
         // make sure BATFET_DIS is 0
         self.registers[BQ25618_07_CHG_CTL3] =
            (ChargeControl3::TMR2X_EN |
@@ -399,7 +703,7 @@ impl BtCharger {
             ChargeControl3::BATFET_OFF_IGNORE)
             .bits();
         let txbuf: [u8; 2] = [BQ25618_07_CHG_CTL3 as u8, self.registers[BQ25618_07_CHG_CTL3]];
-        while i2c.i2c_controller(BQ25618_ADDR, Some(&txbuf), None, CHG_TIMEOUT_MS) != 0 {}
+        i2c.i2c_controller(BQ25618_ADDR, Some(&txbuf), None, CHG_TIMEOUT_MS)?;
 
         // CHG_CONFIG = 0, BST_CONFIG = 1
         self.registers[BQ25618_01_CHG_CTL] =
@@ -408,7 +712,7 @@ impl BtCharger {
             ChargeControl::SYS_MIN_3200MV)
             .bits();
         let txbuf: [u8; 2] = [BQ25618_01_CHG_CTL as u8, self.registers[BQ25618_01_CHG_CTL]];
-        while i2c.i2c_controller(BQ25618_ADDR, Some(&txbuf), None, CHG_TIMEOUT_MS) != 0 {}
+        i2c.i2c_controller(BQ25618_ADDR, Some(&txbuf), None, CHG_TIMEOUT_MS)?;
 
         // set boost target voltage to 5V
         self.registers[BQ25618_06_CHG_CTL2] =
@@ -417,10 +721,11 @@ impl BtCharger {
             ChargeControl2::OVP_14200MV)
             .bits();
         let txbuf: [u8; 2] = [BQ25618_06_CHG_CTL2 as u8, self.registers[BQ25618_06_CHG_CTL2]];
-        while i2c.i2c_controller(BQ25618_ADDR, Some(&txbuf), None, CHG_TIMEOUT_MS) != 0 {}
+        i2c.i2c_controller(BQ25618_ADDR, Some(&txbuf), None, CHG_TIMEOUT_MS)?;
+        Ok(())
     }
 
-    pub fn chg_boost_off(&mut self, i2c: &mut Hardi2c) {
+    pub fn chg_boost_off(&mut self, i2c: &mut Hardi2c) -> Result<(), I2cError> {
         self.registers[BQ25618_01_CHG_CTL] =
            (ChargeControl::WD_RST |
             ChargeControl::CHARGE_ON |
@@ -428,11 +733,79 @@ impl BtCharger {
             ChargeControl::SYS_MIN_3400MV)
             .bits();
         let txbuf: [u8; 2] = [BQ25618_01_CHG_CTL as u8, self.registers[BQ25618_01_CHG_CTL]];
-        while i2c.i2c_controller(BQ25618_ADDR, Some(&txbuf), None, CHG_TIMEOUT_MS) != 0 {}
+        i2c.i2c_controller(BQ25618_ADDR, Some(&txbuf), None, CHG_TIMEOUT_MS)?;
+        Ok(())
     }
 
+    /// Reset the safety-supervisor state for a fresh boot: clears any latched fault and seeds
+    /// the watchdog-pet clock, so the first `chg_safety_tick` call doesn't think a pet is
+    /// already overdue. The timer/watchdog policy bits themselves (`SAFETY_TIMER_EN`,
+    /// `CHG_TIMER_10HRS`, `WATCHDOG_40S`) live in `chg_set_autoparams`'s `BQ25618_05_CHG_CTL1`
+    /// write, since they need to be committed together with the rest of that register image.
     pub fn chg_set_safety(&mut self, _i2c: &mut Hardi2c) {
-        // function does nothing in this implementation
+        self.fault_latched = false;
+        self.last_wd_pet_ms = get_time_ms();
+    }
+
+    /// Re-commit the cached register image for `BQ25618_00_ILIM` through `BQ25618_07_CHG_CTL3`
+    /// as-is (no recomputation) -- the same "commit 0-7" step `chg_set_autoparams` does after
+    /// computing fresh values, split out so `chg_safety_tick` can restore known-good settings
+    /// after a watchdog reset without re-deriving them.
+    pub fn chg_reapply_params(&mut self, i2c: &mut Hardi2c) -> Result<(), I2cError> {
+        let mut txbuf: [u8; 9] = [0; 9];
+        txbuf[0] = BQ25618_00_ILIM as u8;
+        for i in 0..8 {
+            txbuf[i + 1] = self.registers[i];
+        }
+        i2c.i2c_controller(BQ25618_ADDR, Some(&txbuf), None, CHG_TIMEOUT_MS)?;
+        Ok(())
+    }
+
+    /// Explicitly clear a latched fault, re-arming `chg_start`/`chg_boost`. Call only once
+    /// whatever upstream condition caused the fault (thermal, input, timeout) has been
+    /// confirmed resolved -- `chg_safety_tick` never clears this on its own.
+    pub fn chg_clear_fault(&mut self) {
+        self.fault_latched = false;
+    }
+
+    pub fn chg_fault_latched(&self) -> bool {
+        self.fault_latched
+    }
+
+    /// Supervisory tick modeled on the Linux charger-manager's periodic monitor -- call this
+    /// on a fixed cadence from the main loop. Reads `ChargerStatus1`; a `WATCHDOG_FAULT` or
+    /// `CHG_TIMEOUT` means the chip already reset some settings back to its power-on defaults,
+    /// so this re-commits the known-good register image via `chg_reapply_params` and latches
+    /// `fault_latched` so nothing re-arms charging until `chg_clear_fault` runs. Otherwise,
+    /// once `WATCHDOG_PET_INTERVAL_MS` has elapsed since the last pet, it pets via
+    /// `chg_keepalive_ping`. No pet happens while a fault is latched, by design -- a stalled
+    /// safety tick shouldn't be able to paper over a fault by continuing to pet the watchdog.
+    pub fn chg_safety_tick(&mut self, i2c: &mut Hardi2c) -> Result<(), I2cError> {
+        let txbuf: [u8; 1] = [BQ25618_09_CHG_STAT1 as u8];
+        let mut rxbuf: [u8; 2] = [0, 0];
+        i2c.i2c_controller(BQ25618_ADDR, Some(&txbuf), Some(&mut rxbuf), CHG_TIMEOUT_MS)?;
+        self.registers[BQ25618_09_CHG_STAT1] = rxbuf[0];
+
+        const FAULT_MASK: u8 = 0b00_11_0_000;
+        let faulted = (rxbuf[0] & ChargerStatus1::WATCHDOG_FAULT.bits()) != 0
+            || (rxbuf[0] & FAULT_MASK) == ChargerStatus1::CHG_TIMEOUT.bits();
+
+        if faulted {
+            self.fault_latched = true;
+            self.chg_reapply_params(i2c)?;
+            return Ok(());
+        }
+
+        if self.fault_latched {
+            return Ok(());
+        }
+
+        let now = get_time_ms();
+        if now.wrapping_sub(self.last_wd_pet_ms) >= WATCHDOG_PET_INTERVAL_MS {
+            self.chg_keepalive_ping(i2c)?;
+            self.last_wd_pet_ms = now;
+        }
+        Ok(())
     }
 }
 
@@ -1,6 +1,6 @@
 #![allow(dead_code)]
 
-use crate::hal_hardi2c::Hardi2c;
+use crate::hal_hardi2c::{Hardi2c, RetryPolicy, I2C_HARD_MAX_RETRIES};
 
 
 const BQ24157_ADDR: u8 = 0x6a; 
@@ -31,7 +31,7 @@ impl BtCharger {
 
         for i in 0..7 {
             txbuf[0] = i as u8;
-            while i2c.i2c_controller(BQ24157_ADDR, Some(&txbuf), Some(&mut rxbuf), CHG_TIMEOUT_MS) != 0 {}
+            let _ = i2c.with_retries(BQ24157_ADDR, Some(&txbuf), Some(&mut rxbuf), CHG_TIMEOUT_MS, I2C_HARD_MAX_RETRIES, RetryPolicy::default());
             self.registers[i] = rxbuf[0] as u8;
         }
     }
@@ -40,7 +40,7 @@ impl BtCharger {
         let txbuf: [u8; 1] = [BQ24157_STAT_ADR];
         let mut rxbuf: [u8; 2] = [0, 0];
 
-        while i2c.i2c_controller(BQ24157_ADDR, Some(&txbuf), Some(&mut rxbuf), CHG_TIMEOUT_MS) != 0 {}
+        let _ = i2c.with_retries(BQ24157_ADDR, Some(&txbuf), Some(&mut rxbuf), CHG_TIMEOUT_MS, I2C_HARD_MAX_RETRIES, RetryPolicy::default());
         match (rxbuf[0] >> 4) & 0x3 {
             0 => false,
             1 => true,
@@ -52,7 +52,7 @@ impl BtCharger {
 
     pub fn chg_keepalive_ping(&mut self, i2c: &mut Hardi2c) {
         let txbuf: [u8; 2] = [BQ24157_STAT_ADR, 0x80]; // 32 sec timer reset, enable stat pin
-        while i2c.i2c_controller(BQ24157_ADDR, Some(&txbuf), None, CHG_TIMEOUT_MS) != 0 {}
+        let _ = i2c.with_retries(BQ24157_ADDR, Some(&txbuf), None, CHG_TIMEOUT_MS, I2C_HARD_MAX_RETRIES, RetryPolicy::default());
     }
 
     pub fn chg_set_safety(&mut self, i2c: &mut Hardi2c) {
@@ -63,12 +63,12 @@ impl BtCharger {
         //    971mA | 485mA | 242mA | 121 mA, plus offset of 667mA
         // 0x70 = 1.515A & 4.2V limits
         let txbuf: [u8; 2] = [BQ24157_SAFE_ADR, 0x70];
-        while i2c.i2c_controller(BQ24157_ADDR, Some(&txbuf), None, CHG_TIMEOUT_MS) != 0 {}
+        let _ = i2c.with_retries(BQ24157_ADDR, Some(&txbuf), None, CHG_TIMEOUT_MS, I2C_HARD_MAX_RETRIES, RetryPolicy::default());
     }
 
     pub fn chg_boost(&mut self, i2c: &mut Hardi2c) {
         let txbuf: [u8; 2] = [BQ24157_CTRL_ADR, 0xB5]; // turn on boost
-        while i2c.i2c_controller(BQ24157_ADDR, Some(&txbuf), None, CHG_TIMEOUT_MS) != 0 {}
+        let _ = i2c.with_retries(BQ24157_ADDR, Some(&txbuf), None, CHG_TIMEOUT_MS, I2C_HARD_MAX_RETRIES, RetryPolicy::default());
     }
 
     // 50 F8 8E 51 6B 03 70 - dump from known good charging system
@@ -79,14 +79,14 @@ impl BtCharger {
         // + 0x2 = OTG boost not enabled
         // address 2
         let txbuf: [u8; 2] = [BQ24157_BATV_ADR, 0x8E];
-        while i2c.i2c_controller(BQ24157_ADDR, Some(&txbuf), None, CHG_TIMEOUT_MS) != 0 {}
+        let _ = i2c.with_retries(BQ24157_ADDR, Some(&txbuf), None, CHG_TIMEOUT_MS, I2C_HARD_MAX_RETRIES, RetryPolicy::default());
 
         // set special charger voltage, e.g. threshold to reduce charging current due to bad cables
         // address 5
         // 0.32V | 0.16V | 0.08V | + 4.2V = 4.44V DPM threshold
         // normal charge current, special charger voltage = 4.2V
         let txbuf2: [u8; 2] = [BQ24157_SPCHG_ADR, 0x3];
-        while i2c.i2c_controller(BQ24157_ADDR, Some(&txbuf2), None, CHG_TIMEOUT_MS) != 0 {}
+        let _ = i2c.with_retries(BQ24157_ADDR, Some(&txbuf2), None, CHG_TIMEOUT_MS, I2C_HARD_MAX_RETRIES, RetryPolicy::default());
 
         // set target charge current + termination current
         // 1.55A target current.
@@ -97,7 +97,7 @@ impl BtCharger {
         // 242mA | 121mA | 60mA +  60mA offset => 0x1 = 120mA termination
         // address 4
         let txbuf3: [u8; 2] = [BQ24157_IBAT_ADR, 0x11];
-        while i2c.i2c_controller(BQ24157_ADDR, Some(&txbuf3), None, CHG_TIMEOUT_MS) != 0 {}
+        let _ = i2c.with_retries(BQ24157_ADDR, Some(&txbuf3), None, CHG_TIMEOUT_MS, I2C_HARD_MAX_RETRIES, RetryPolicy::default());
     }
 
     /// This forces the start of charging. It's a bit of a hammer, maybe refine it down the road. [FIXME]
@@ -106,7 +106,7 @@ impl BtCharger {
         // address 1
         let txbuf: [u8; 2] = [BQ24157_CTRL_ADR, 0xB0];  // 0x78 previous value
         // charge mode, not hiZ, charger enabled, enable charge current termination, weak battery==3.7V, Iin limit = no limit
-        while i2c.i2c_controller(BQ24157_ADDR, Some(&txbuf), None, CHG_TIMEOUT_MS) != 0 {}
+        let _ = i2c.with_retries(BQ24157_ADDR, Some(&txbuf), None, CHG_TIMEOUT_MS, I2C_HARD_MAX_RETRIES, RetryPolicy::default());
     }
 
 }
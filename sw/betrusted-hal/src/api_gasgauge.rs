@@ -1,6 +1,6 @@
 #![allow(dead_code)]
 
-use crate::hal_hardi2c::Hardi2c;
+use crate::hal_hardi2c::{Hardi2c, I2cRetryError, RetryPolicy, I2C_HARD_MAX_RETRIES};
 use crate::hal_time::delay_ms;
 
 const BQ27421_ADDR : u8 = 0x55;
@@ -41,159 +41,251 @@ const GG_CODE_RESET   :  u16 = 0x0042;
 const GG_CODE_SET_HIB :  u16 = 0x0011;
 const GG_CODE_CLR_HIB :  u16 = 0x0012;
 
+// data-flash subclasses, addressed via GG_EXT_BLKDATACLS/GG_EXT_BLKDATAOFF
+const GG_SUBCLASS_STATE :  u8 = 0x52;  // design capacity and other pack-level state lives here
+
 const GG_UPDATE_INTERVAL_MS : u32 = 1000;
 const GG_TIMEOUT_MS: u32 = 2;
 
-fn gg_set(i2c: &mut Hardi2c, cmd_code: u8, val: u16) {
+fn gg_set(i2c: &mut Hardi2c, cmd_code: u8, val: u16) -> Result<(), I2cRetryError> {
     let txbuf: [u8; 3] = [cmd_code, (val & 0xff) as u8, ((val >> 8) & 0xff) as u8];
 
-    while i2c.i2c_controller(BQ27421_ADDR, Some(&txbuf), None, GG_TIMEOUT_MS) != 0 {}
+    i2c.with_retries(BQ27421_ADDR, Some(&txbuf), None, GG_TIMEOUT_MS, I2C_HARD_MAX_RETRIES, RetryPolicy::default())
 }
 
-fn gg_set_byte(i2c: &mut Hardi2c, cmd_code: u8, val: u8) {
+fn gg_set_byte(i2c: &mut Hardi2c, cmd_code: u8, val: u8) -> Result<(), I2cRetryError> {
     let txbuf: [u8; 2] = [cmd_code, val];
 
-    while i2c.i2c_controller(BQ27421_ADDR, Some(&txbuf), None, GG_TIMEOUT_MS) != 0 {}
+    i2c.with_retries(BQ27421_ADDR, Some(&txbuf), None, GG_TIMEOUT_MS, I2C_HARD_MAX_RETRIES, RetryPolicy::default())
 }
 
-fn gg_get(i2c: &mut Hardi2c, cmd_code: u8) -> i16 {
+fn gg_get(i2c: &mut Hardi2c, cmd_code: u8) -> Result<i16, I2cRetryError> {
     let txbuf: [u8; 1] = [cmd_code];
     let mut rxbuf: [u8; 2] = [0, 0];
 
-    while i2c.i2c_controller(BQ27421_ADDR, Some(&txbuf), Some(&mut rxbuf), GG_TIMEOUT_MS) != 0 {}
+    i2c.with_retries(BQ27421_ADDR, Some(&txbuf), Some(&mut rxbuf), GG_TIMEOUT_MS, I2C_HARD_MAX_RETRIES, RetryPolicy::default())?;
 
     // don't do the sign conversion untl after the bytes are composited, sign extension of
     // of i8's would be inappropriate for this application
-    (rxbuf[0] as u16 | (rxbuf[1] as u16) << 8) as i16
+    Ok((rxbuf[0] as u16 | (rxbuf[1] as u16) << 8) as i16)
 }
 
-fn gg_get_byte(i2c: &mut Hardi2c, cmd_code: u8) -> u8 {
+fn gg_get_byte(i2c: &mut Hardi2c, cmd_code: u8) -> Result<u8, I2cRetryError> {
     let txbuf: [u8; 1] = [cmd_code];
     let mut rxbuf: [u8; 2] = [0, 0];
 
-    while i2c.i2c_controller(BQ27421_ADDR, Some(&txbuf), Some(&mut rxbuf), GG_TIMEOUT_MS) != 0 {}
-    rxbuf[0]
+    i2c.with_retries(BQ27421_ADDR, Some(&txbuf), Some(&mut rxbuf), GG_TIMEOUT_MS, I2C_HARD_MAX_RETRIES, RetryPolicy::default())?;
+    Ok(rxbuf[0])
+}
+
+pub fn gg_start(i2c: &mut Hardi2c) -> Result<(), I2cRetryError> { gg_set(i2c, GG_CMD_CNTL, GG_CODE_CLR_HIB) }
+pub fn gg_set_hibernate(i2c: &mut Hardi2c) -> Result<(), I2cRetryError> { gg_set(i2c, GG_CMD_CNTL, GG_CODE_SET_HIB) }
+pub fn gg_voltage(i2c: &mut Hardi2c) -> Result<i16, I2cRetryError> { gg_get(i2c, GG_CMD_VOLT) }
+pub fn gg_avg_current(i2c: &mut Hardi2c) -> Result<i16, I2cRetryError> { gg_get(i2c, GG_CMD_AVGCUR) }
+pub fn gg_avg_power(i2c: &mut Hardi2c) -> Result<i16, I2cRetryError> { gg_get(i2c, GG_CMD_AVGPWR) }
+pub fn gg_remaining_capacity(i2c: &mut Hardi2c) -> Result<i16, I2cRetryError> { gg_get(i2c, GG_CMD_RM) }
+pub fn gg_full_capacity(i2c: &mut Hardi2c) -> Result<i16, I2cRetryError> { gg_get(i2c, GG_CMD_FCC) }
+// A software OCV+coulomb-counting SoC estimator (LTC2941/2943- and qpnp-bms-style, blending a
+// voltage-table lookup with a running mAh accumulator) was proposed for this chip, but the
+// BQ27421 already runs exactly that blend internally -- that's what its Impedance Track
+// algorithm does on every `GG_CMD_SOC` read, calibrated against this pack's own design
+// capacity via `gg_set_design_capacity` below. Duplicating it in software would just be a
+// second, less-calibrated estimate of the number this call already returns.
+pub fn gg_state_of_charge(i2c: &mut Hardi2c) -> Result<i16, I2cRetryError> { gg_get(i2c, GG_CMD_SOC) }
+
+/// Everything the EC needs for a power-management decision, read in one pass instead of one
+/// I2C round trip per field via `gg_voltage`/`gg_avg_current`/`gg_state_of_charge`/etc.
+pub struct GasGaugeState {
+    /// State of charge, in percent.
+    pub state_of_charge: i16,
+    /// State of health, in percent of original design capacity.
+    pub state_of_health: i16,
+    /// Battery temperature in degrees C, converted from `GG_CMD_TEMP`'s raw 0.1 K reading.
+    pub battery_temp_c: i16,
+    /// Pack voltage, in mV.
+    pub voltage_mv: i16,
+    /// Average current, in mA. Negative on discharge, positive while charging -- `gg_get`
+    /// composites the raw bytes before the `i16` cast, so this is already correctly
+    /// sign-extended rather than truncated to its low byte.
+    pub avg_current_ma: i16,
+    /// Average power, in mW, signed the same way as `avg_current_ma`.
+    pub avg_power_mw: i16,
+    /// Remaining capacity, in mAh.
+    pub remaining_capacity_mah: i16,
+    /// Full charge capacity, in mAh.
+    pub full_capacity_mah: i16,
 }
 
-pub fn gg_start(i2c: &mut Hardi2c) { gg_set(i2c, GG_CMD_CNTL, GG_CODE_CLR_HIB);  }
-pub fn gg_set_hibernate(i2c: &mut Hardi2c) { gg_set(i2c, GG_CMD_CNTL, GG_CODE_SET_HIB); }
-pub fn gg_voltage(i2c: &mut Hardi2c) -> i16 { gg_get(i2c, GG_CMD_VOLT) }
-pub fn gg_avg_current(i2c: &mut Hardi2c) -> i16  { gg_get(i2c, GG_CMD_AVGCUR) }
-pub fn gg_avg_power(i2c: &mut Hardi2c) -> i16  { gg_get(i2c, GG_CMD_AVGPWR) }
-pub fn gg_remaining_capacity(i2c: &mut Hardi2c) -> i16  { gg_get(i2c, GG_CMD_RM) }
-pub fn gg_full_capacity(i2c: &mut Hardi2c) -> i16 { gg_get(i2c, GG_CMD_FCC) }
-pub fn gg_state_of_charge(i2c: &mut Hardi2c) -> i16  { gg_get(i2c, GG_CMD_SOC) }
+/// Snapshot every `GasGaugeState` field in one pass.
+pub fn gg_state(i2c: &mut Hardi2c) -> Result<GasGaugeState, I2cRetryError> {
+    Ok(GasGaugeState {
+        state_of_charge: gg_get(i2c, GG_CMD_SOC)?,
+        state_of_health: gg_get(i2c, GG_CMD_SOH)?,
+        // GG_CMD_TEMP is in units of 0.1 K; subtract 273.15 K (truncated to 273.1 K here,
+        // since this is all fixed-point integer math) and scale back down to whole degrees C.
+        battery_temp_c: (gg_get(i2c, GG_CMD_TEMP)? - 2731) / 10,
+        voltage_mv: gg_get(i2c, GG_CMD_VOLT)?,
+        avg_current_ma: gg_get(i2c, GG_CMD_AVGCUR)?,
+        avg_power_mw: gg_get(i2c, GG_CMD_AVGPWR)?,
+        remaining_capacity_mah: gg_get(i2c, GG_CMD_RM)?,
+        full_capacity_mah: gg_get(i2c, GG_CMD_FCC)?,
+    })
+}
 
-fn compute_checksum(blockdata: &[u8]) -> u8 {
+fn compute_checksum(blockdata: &[u8; 32]) -> u8 {
     let mut checksum: u8 = 0;
-    for i in 0..32 {
-        checksum += blockdata[i];
+    for &b in blockdata.iter() {
+        checksum = checksum.wrapping_add(b);
     }
 
     255 - checksum
 }
 
-pub fn gg_device_type(i2c: &mut Hardi2c) -> i16 {
-    gg_set(i2c, GG_CMD_CNTL, GG_CODE_DEVTYPE);
+pub fn gg_device_type(i2c: &mut Hardi2c) -> Result<i16, I2cRetryError> {
+    gg_set(i2c, GG_CMD_CNTL, GG_CODE_DEVTYPE)?;
     gg_get(i2c, GG_CMD_CNTL)
 }
 
-pub fn gg_control_status(i2c: &mut Hardi2c) -> i16 {
-    gg_set(i2c, GG_CMD_CNTL, GG_CODE_CTLSTAT);
+pub fn gg_control_status(i2c: &mut Hardi2c) -> Result<i16, I2cRetryError> {
+    gg_set(i2c, GG_CMD_CNTL, GG_CODE_CTLSTAT)?;
     gg_get(i2c, GG_CMD_CNTL)
 }
 
-#[doc = "Set the design capacity of the battery. Returns previously assigned capacity."]
-pub fn gg_set_design_capacity(i2c: &mut Hardi2c, mah: Option<u16>) -> u16 {
-    let design_capacity: u16;
-    if mah.is_some() {
-        // unseal the gasguage by writing the unseal command twice
-        gg_set(i2c, GG_CMD_CNTL, GG_CODE_UNSEAL);
-        gg_set(i2c, GG_CMD_CNTL, GG_CODE_UNSEAL);
-
-        // set configuraton update command
-        gg_set(i2c, GG_CMD_CNTL, GG_CODE_CFGUPDATE);
-
-        loop {
-            let flags : i16 = gg_get(i2c, GG_CMD_FLAG);
-            if (flags & 0x10) != 0 { break; }
+/// Max times to poll `GG_CMD_FLAG` for the config-update/reset-complete bit before giving up.
+/// Same spirit as `I2C_HARD_MAX_RETRIES`: ride out the flag's normal few-poll settling time
+/// without spinning forever if the gauge never raises it (e.g. it was removed mid-sequence).
+const GG_FLAG_POLL_MAX_ATTEMPTS: u32 = 1000;
+
+/// Poll `GG_CMD_FLAG` until bit 0x10 (config-update/reset-complete) is set, or give up.
+fn gg_wait_flag_bit(i2c: &mut Hardi2c, bit: i16) -> Result<(), I2cRetryError> {
+    for _ in 0..GG_FLAG_POLL_MAX_ATTEMPTS {
+        if (gg_get(i2c, GG_CMD_FLAG)? & bit) != 0 {
+            return Ok(());
         }
+    }
+    Err(I2cRetryError::RetriesExhausted)
+}
 
-        gg_set_byte(i2c, GG_EXT_BLKDATACTL, 0x0);    // enable block data memory control
-        gg_set_byte(i2c, GG_EXT_BLKDATACLS, 0x52);   // set data class to 0x52 -- state subclass
-        gg_set_byte(i2c, GG_EXT_BLKDATAOFF, 0x00);  // specify block data offset
-
-        /*
-        This is the desired result:
-            00: 00 00 00 00 00 81 0e db
-            08: 0e a8 04 4c 13 60 05 3c
-            10: 0c 80 00 c8 00 32 00 14
-            18: 03 e8 01 00 64 10 04 00
-            20: dd
-        */
-        if true {
-            // this targets all the bytes
-
-            // read the existing data block, extract design capacity, then update and writeback
-            let mut blockdata: [u8; 33] = [0; 33];
-            for i in 0..33 {
-                blockdata[i] = gg_get_byte(i2c, GG_EXT_BLKDATABSE + i as u8);
-            }
-            /*
-            for i in 0..33 {
-                if (i % 8) == 0 {
-                    sprint!("\n\r{:02x}: ", i)
-                }
-                sprint!("{:02x} ", blockdata[i]);
-            }
-            sprintln!("");*/
+/// Unseal the gauge, enter config-update mode, and point the block-data window at
+/// `subclass`/`offset`, ready for a run of `gg_get_byte`/`gg_set_byte` calls against
+/// `GG_EXT_BLKDATABSE`. Shared setup half of `gg_read_block`/`gg_write_block`.
+fn gg_select_data_block(i2c: &mut Hardi2c, subclass: u8, offset: u8) -> Result<(), I2cRetryError> {
+    // unseal the gasguage by writing the unseal command twice
+    gg_set(i2c, GG_CMD_CNTL, GG_CODE_UNSEAL)?;
+    gg_set(i2c, GG_CMD_CNTL, GG_CODE_UNSEAL)?;
+
+    // set configuration update command
+    gg_set(i2c, GG_CMD_CNTL, GG_CODE_CFGUPDATE)?;
+    gg_wait_flag_bit(i2c, 0x10)?;
+
+    gg_set_byte(i2c, GG_EXT_BLKDATACTL, 0x0)?; // enable block data memory control
+    gg_set_byte(i2c, GG_EXT_BLKDATACLS, subclass)?;
+    gg_set_byte(i2c, GG_EXT_BLKDATAOFF, offset)?;
+    Ok(())
+}
 
-            design_capacity = (blockdata[11] as u16) | ((blockdata[10] as u16) << 8);
+/// Reset the gauge so a config-update write takes hold, then re-seal it. Shared teardown half
+/// of `gg_read_block`/`gg_write_block`.
+fn gg_end_data_block(i2c: &mut Hardi2c) -> Result<(), I2cRetryError> {
+    gg_set(i2c, GG_CMD_CNTL, GG_CODE_RESET)?;
+    gg_wait_flag_bit(i2c, 0x10)?;
+    gg_set(i2c, GG_CMD_CNTL, GG_CODE_SEAL)
+}
 
-            let newcap = mah.unwrap();
-            blockdata[11] = (newcap & 0xFF) as u8;
-            blockdata[10] = ((newcap >> 8) & 0xFF) as u8;
-            blockdata[32] = compute_checksum(&blockdata);
-            delay_ms(2); // some delay seems to be needed
-            for i in 0..33 {
-                gg_set_byte(i2c, GG_EXT_BLKDATABSE + i as u8, blockdata[i]);
-            }
-            delay_ms(2); // some delay seems to be needed
-            /*
-            for i in 0..33 {
-                if (i % 8) == 0 {
-                    sprint!("\n\r{:02x}: ", i)
-                }
-                sprint!("{:02x} ", blockdata[i]);
+/// Read one 32-byte data-flash block at `subclass`/`offset`, handling the
+/// unseal/config-update/reset/seal sequencing internally.
+pub fn gg_read_block(i2c: &mut Hardi2c, subclass: u8, offset: u8) -> Result<[u8; 32], I2cRetryError> {
+    gg_select_data_block(i2c, subclass, offset)?;
+    let mut blockdata: [u8; 32] = [0; 32];
+    for i in 0..32 {
+        blockdata[i] = gg_get_byte(i2c, GG_EXT_BLKDATABSE + i as u8)?;
+    }
+    gg_end_data_block(i2c)?;
+    Ok(blockdata)
+}
+
+/// Write one 32-byte data-flash block at `subclass`/`offset`, computing and writing its
+/// checksum, and handling the unseal/config-update/reset/seal sequencing internally.
+pub fn gg_write_block(i2c: &mut Hardi2c, subclass: u8, offset: u8, blockdata: &[u8; 32]) -> Result<(), I2cRetryError> {
+    gg_select_data_block(i2c, subclass, offset)?;
+    delay_ms(2); // some delay seems to be needed
+    for i in 0..32 {
+        gg_set_byte(i2c, GG_EXT_BLKDATABSE + i as u8, blockdata[i])?;
+    }
+    gg_set_byte(i2c, GG_EXT_BLKDATACHK, compute_checksum(blockdata))?;
+    delay_ms(2); // some delay seems to be needed
+    gg_end_data_block(i2c)
+}
+
+/// Bounded number of write+read-back attempts `gg_program_profile` makes per block before
+/// giving up on it. Mirrors `GG_FLAG_POLL_MAX_ATTEMPTS`'s "ride out the normal settling time,
+/// don't spin forever" spirit, just against checksum mismatches instead of an unset flag bit.
+const GG_PROFILE_WRITE_RETRIES: u32 = 3;
+
+/// Flash a full "golden" battery profile in one transaction: write each `(subclass, offset,
+/// data)` block in order, reading it back and rewriting it if the read-back doesn't match what
+/// was written, for at most `GG_PROFILE_WRITE_RETRIES` write+read-back attempts per block. Stops at the first block that still
+/// doesn't match after exhausting its retries, leaving earlier blocks already committed.
+pub fn gg_program_profile(i2c: &mut Hardi2c, blocks: &[(u8, u8, [u8; 32])]) -> Result<(), I2cRetryError> {
+    for &(subclass, offset, data) in blocks {
+        for attempt in 0..GG_PROFILE_WRITE_RETRIES {
+            gg_write_block(i2c, subclass, offset, &data)?;
+            if gg_read_block(i2c, subclass, offset)? == data {
+                break;
+            } else if attempt + 1 == GG_PROFILE_WRITE_RETRIES {
+                return Err(I2cRetryError::RetriesExhausted);
             }
-            sprintln!("");*/
-        } else {
-            // this targets just the capacity bytes per bq27421-G1 technical reference
-            let old_csum = gg_get_byte(i2c, GG_EXT_BLKDATABSE + 0x20);
-            let dc_msb = gg_get_byte(i2c, GG_EXT_BLKDATABSE + 0xA);
-            let dc_lsb = gg_get_byte(i2c, GG_EXT_BLKDATABSE + 0xB);
-            design_capacity = ((dc_msb as u16) << 8) | dc_lsb as u16;
-            let newcap = mah.unwrap();
-            gg_set_byte(i2c, GG_EXT_BLKDATABSE + 0xA, ((newcap >> 8) & 0xff) as u8);
-            gg_set_byte(i2c, GG_EXT_BLKDATABSE + 0xB, (newcap & 0xff) as u8);
-            let temp = 255 - old_csum - dc_msb - dc_lsb;
-            let new_csum = 255 - (temp + (newcap & 0xff) as u8 + ((newcap >> 8) & 0xff) as u8);
-            gg_set_byte(i2c, GG_EXT_BLKDATABSE + 0x20, new_csum);
         }
+    }
+    Ok(())
+}
 
-        // reset the gasguage to get the new data to take hold
-        gg_set(i2c, GG_CMD_CNTL, GG_CODE_RESET);
+/// Width of a data-flash field `gg_patch_data_flash_field` should read-modify-write. Multi-byte
+/// fields are big-endian, matching how the BQ27421 itself stores e.g. design capacity.
+#[derive(Copy, Clone, PartialEq)]
+pub enum FieldWidth {
+    Byte,
+    Word,
+}
 
-        loop {
-            let flags : i16 = gg_get(i2c, GG_CMD_FLAG);
-            if (flags & 0x10) != 0 { break; }
+/// Patch one field within a data-flash block at `subclass`/`block_offset`, `byte_offset` bytes
+/// into that 32-byte block, and return the value it held before. This is
+/// `gg_set_design_capacity`'s read-modify-write-checksum cycle generalized to an arbitrary
+/// field instead of one hardcoded to design capacity -- getting the checksum or the
+/// CFGUPDATE handshake wrong here bricks the gauge's data-flash state, so every caller goes
+/// through `gg_read_block`/`gg_write_block` rather than poking the block registers directly.
+pub fn gg_patch_data_flash_field(
+    i2c: &mut Hardi2c,
+    subclass: u8,
+    block_offset: u8,
+    byte_offset: usize,
+    width: FieldWidth,
+    new_value: u16,
+) -> Result<u16, I2cRetryError> {
+    let mut blockdata = gg_read_block(i2c, subclass, block_offset)?;
+
+    let old_value = match width {
+        FieldWidth::Byte => blockdata[byte_offset] as u16,
+        FieldWidth::Word => ((blockdata[byte_offset] as u16) << 8) | (blockdata[byte_offset + 1] as u16),
+    };
+
+    match width {
+        FieldWidth::Byte => blockdata[byte_offset] = (new_value & 0xFF) as u8,
+        FieldWidth::Word => {
+            blockdata[byte_offset] = ((new_value >> 8) & 0xFF) as u8;
+            blockdata[byte_offset + 1] = (new_value & 0xFF) as u8;
         }
+    }
 
-        // seal the gas gauge
-        gg_set(i2c, GG_CMD_CNTL, GG_CODE_SEAL);
+    gg_write_block(i2c, subclass, block_offset, &blockdata)?;
+    Ok(old_value)
+}
+
+#[doc = "Set the design capacity of the battery. Returns previously assigned capacity."]
+pub fn gg_set_design_capacity(i2c: &mut Hardi2c, mah: Option<u16>) -> Result<u16, I2cRetryError> {
+    if let Some(newcap) = mah {
+        gg_patch_data_flash_field(i2c, GG_SUBCLASS_STATE, 0x00, 10, FieldWidth::Word, newcap)
     } else {
-        design_capacity = ((gg_get_byte(i2c, GG_EXT_DCAP_MSB) as u16) << 8) | gg_get_byte(i2c, GG_EXT_DCAP_LSB) as u16;
+        Ok(((gg_get_byte(i2c, GG_EXT_DCAP_MSB)? as u16) << 8) | gg_get_byte(i2c, GG_EXT_DCAP_LSB)? as u16)
     }
-
-    design_capacity
 }
@@ -3,10 +3,14 @@
 //! The LSM6DS3 has an accelerometer and gyroscope along with many sensor
 //! co-processor features intended for use with a popular phone operating
 //! system -- we don't need all that. This driver only supports basic
-//! accelerometer functionality and the tap detection interrupt feature.
+//! accelerometer functionality, the tap detection interrupt feature, and FIFO-batched
+//! accelerometer streaming.
 //!
-use crate::hal_i2c::Hardi2c;
+use crate::hal_i2c::{Hardi2c, I2cError, I2cErrorPhase};
 use crate::hal_time::delay_ms;
+use core::sync::atomic::{AtomicBool, Ordering};
+use xous_nommu::definitions::XousError;
+use xous_nommu::syscalls::{sys_interrupt_claim, sys_interrupt_free};
 
 /*
 I2C control register and initialization sequence notes from STM app note
@@ -47,6 +51,15 @@ TAP_CFG      0x58  {INTERRUPTS_ENABLE, INACT_EN[1:0], SLOPE_FDS, TAP_X_EN, TAP_Y
 TAP_THS_6D   0x59  {D4D_EN, SIXD_THS[1:0], TAP_THS[4:0]}
 INT_DUR2     0x5A  {DUR[3:0], QUIET[1:0], SHOCK[1:0]}
 MD1_CFG      0x5E  {INT1_INACT, INT1_SINGLE, INT1_WU, INT1_FF, INT1_DOUBLE, INT1_6D, INT1_TILT, INT1_TIMER}
+INT1_CTRL    0x0D  {INT1_STEP_DETECTOR, INT1_SIGN_MOT, INT1_FULL_FLAG, INT1_FIFO_OVR, INT1_FTH, INT1_BOOT, INT1_DRDY_G, INT1_DRDY_XL}
+FIFO_CTRL1   0x06  {FTH[7:0]}
+FIFO_CTRL2   0x07  {TIMER_PEDO_FIFO_EN, TIMER_PEDO_FIFO_DRDY, 0, 0, FTH[11:8]}
+FIFO_CTRL3   0x08  {0, DEC_FIFO_G[2:0], DEC_FIFO_XL[2:0]}
+FIFO_CTRL5   0x0A  {0, ODR_FIFO[3:0], FIFO_MODE[2:0]}
+FIFO_STATUS1 0x3A  {DIFF_FIFO[7:0]}
+FIFO_STATUS2 0x3B  {WTM, OVER_RUN, FIFO_FULL, FIFO_EMPTY, 0, DIFF_FIFO[10:8]}
+FIFO_DATA_OUT_L 0x3E  {low byte of next queued FIFO sample}
+FIFO_DATA_OUT_H 0x3F  {high byte of next queued FIFO sample}
 
 Block Data Update (BDU)
 =======================
@@ -94,107 +107,398 @@ const OUTY_L_XL: u8 = 0x2A;
 const OUTY_H_XL: u8 = 0x2B;
 const OUTZ_L_XL: u8 = 0x2C;
 const OUTZ_H_XL: u8 = 0x2D;
+const INT1_CTRL: u8 = 0x0D;
+const FIFO_CTRL1: u8 = 0x06;
+const FIFO_CTRL2: u8 = 0x07;
+const FIFO_CTRL3: u8 = 0x08;
+const FIFO_CTRL5: u8 = 0x0A;
+const FIFO_STATUS1: u8 = 0x3A;
+const FIFO_STATUS2: u8 = 0x3B;
+const FIFO_DATA_OUT_L: u8 = 0x3E;
+
+/// `FIFO_CTRL5[FIFO_MODE]`: stop collecting once the FIFO fills, rather than wrapping and
+/// overwriting the oldest unread samples -- `fifo_read` drains on `fifo_level()`'s word
+/// before it can overflow, so a full buffer here means the caller fell behind, not steady
+/// state.
+const FIFO_MODE_FIFO: u8 = 0b001;
+
+/// `INT1_CTRL[INT1_FTH]`: route the FIFO-threshold (watermark) flag to the INT1 pin.
+const INT1_CTRL_FTH: u8 = 0x08;
 
 const I2C_TIMEOUT_MS: u32 = 2;
 
+/// Failure modes for the IMU driver, modeled on the embassy-rp I2C driver's `AbortReason`/
+/// `Error` split: `Bus` distinguishes what `Hardi2c::i2c_controller` itself reported (NACK vs.
+/// timeout -- see `I2cError`), while `WhoAmIMismatch`/`RetriesExhausted` are faults specific to
+/// this driver's own protocol. This lets a caller tell "IMU not populated on this board" (a
+/// `NoAcknowledge` that persists across retries) apart from "transient bus fault worth logging
+/// but not worth treating as absent" (an isolated `Timeout`).
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum ImuError {
+    /// A single transaction failed; see `I2cError` for NACK-with-byte-index vs. timeout. Note
+    /// this controller's register map (`utra::i2c`) has no arbitration-lost status bit, so
+    /// there is no `ArbitrationLoss` variant to report here -- multi-master arbitration loss
+    /// on this bus cannot currently be distinguished from `I2cError::Timeout`.
+    Bus(I2cError),
+    /// `Imu::init`'s WHO_AM_I readback didn't match either expected silicon ID (0x6A/0x69).
+    WhoAmIMismatch(u8),
+    /// All 3 attempts in `i2c_w`/`i2c_r` failed; carries the *last* `I2cError` observed, not
+    /// just the first, since a NACK on the final retry is more diagnostic than one early on.
+    RetriesExhausted(I2cError),
+    /// `enable_fifo_irq`'s `sys_interrupt_claim` call failed (IRQ already claimed, or not a
+    /// valid number on this system) -- distinct from the I2C-side errors above since it never
+    /// touches the bus.
+    IrqClaimFailed(XousError),
+}
+
 /// Write value to the specified IMU register address
-fn i2c_w(i2c: &mut Hardi2c, reg_addr: u8, reg_val: u8, err_tag: u8) -> Result<u8, u8> {
+fn i2c_w(i2c: &mut Hardi2c, reg_addr: u8, reg_val: u8) -> Result<(), ImuError> {
     let txbuf: [u8; 2] = [reg_addr, reg_val];
     // This loop is a safer version of the `while i2c... != 0 {}` pattern used
     // elsewhere. Hard limit on retries ensures this function will return promptly
     // and without risk of deadlock in the event of an I2C bus fault. Expected
     // result is `return Ok` on first pass.
+    let mut last_err = I2cError::Timeout(I2cErrorPhase::Address);
     for _ in 0..3 {
-        if i2c.i2c_controller(IMU_I2C_ADDR, Some(&txbuf), None, I2C_TIMEOUT_MS) == 0 {
-            return Ok(0);
+        match i2c.i2c_controller(IMU_I2C_ADDR, Some(&txbuf), None, I2C_TIMEOUT_MS) {
+            Ok(_) => return Ok(()),
+            Err(e) => last_err = e,
         }
     }
     // Reaching this line may indicate a hardware fault (I2C bus, PnR timing, etc.)
-    return Err(err_tag);
+    Err(ImuError::RetriesExhausted(last_err))
 }
 
 /// Read u8 value from the specified IMU register address.
 /// Note that although this function is for reading a single-byte register, the Hardi2c module
 /// requires reads of at least 2 bytes as a workaround for an issue with the ICE40UP5K I2C block.
-fn i2c_r(i2c: &mut Hardi2c, reg_addr: u8, err_tag: u8) -> Result<u8, u8> {
+fn i2c_r(i2c: &mut Hardi2c, reg_addr: u8) -> Result<u8, ImuError> {
     let txbuf: [u8; 1] = [reg_addr];
     let mut rxbuf: [u8; 2] = [0, 0];
+    let mut last_err = I2cError::Timeout(I2cErrorPhase::Address);
     for _ in 0..3 {
-        if i2c.i2c_controller(IMU_I2C_ADDR, Some(&txbuf), Some(&mut rxbuf), I2C_TIMEOUT_MS) == 0 {
-            return Ok(rxbuf[0]);
+        match i2c.i2c_controller(IMU_I2C_ADDR, Some(&txbuf), Some(&mut rxbuf), I2C_TIMEOUT_MS) {
+            Ok(_) => return Ok(rxbuf[0]),
+            Err(e) => last_err = e,
         }
     }
     // Reaching this line may indicate a hardware fault (I2C bus, PnR timing, etc.)
-    return Err(err_tag);
+    Err(ImuError::RetriesExhausted(last_err))
+}
+
+/// Burst-read `rxbuf.len()` consecutive registers starting at `reg_addr` in one I2C
+/// transaction, relying on `CTRL3_C[IF_INC]` (set at power-on reset, and kept set by
+/// `init`'s `CTRL3_C` write) to auto-increment the register pointer after each byte. `fifo_read` uses this to
+/// drain many FIFO samples per transaction instead of the one-register-pair-at-a-time
+/// pattern `get_accel_x`/`y`/`z` use for spot checks.
+fn i2c_r_burst(i2c: &mut Hardi2c, reg_addr: u8, rxbuf: &mut [u8]) -> Result<(), ImuError> {
+    let txbuf: [u8; 1] = [reg_addr];
+    let mut last_err = I2cError::Timeout(I2cErrorPhase::Address);
+    for _ in 0..3 {
+        match i2c.i2c_controller(IMU_I2C_ADDR, Some(&txbuf), Some(&mut *rxbuf), I2C_TIMEOUT_MS) {
+            Ok(_) => return Ok(()),
+            Err(e) => last_err = e,
+        }
+    }
+    Err(ImuError::RetriesExhausted(last_err))
 }
 
 pub struct Imu {}
 
+/// Silicon IDs the WHO_AM_I register is known to report on this board (LSM6DSL or the
+/// alternate LSM6DS3 part number).
+const WHO_AM_I_VALID: [u8; 2] = [0x6A, 0x69];
+
 impl Imu {
     /// Preform IMU boot and software reset procedures to ensure known config (18ms delay)
-    fn boot_and_reset(mut i2c: &mut Hardi2c) -> Result<u8, u8> {
-        i2c_w(&mut i2c, CTRL2_G, 0x00, 0x1)?; // Gyro -> power-down mode
-        i2c_w(&mut i2c, CTRL1_XL, 0x60, 0x2)?; // Accelerometer -> high-performance mode
-        i2c_w(&mut i2c, CTRL3_C, 0x80, 0x3)?; // Initiate BOOT (takes 15ms)
+    fn boot_and_reset(mut i2c: &mut Hardi2c) -> Result<(), ImuError> {
+        i2c_w(&mut i2c, CTRL2_G, 0x00)?; // Gyro -> power-down mode
+        i2c_w(&mut i2c, CTRL1_XL, 0x60)?; // Accelerometer -> high-performance mode
+        i2c_w(&mut i2c, CTRL3_C, 0x80)?; // Initiate BOOT (takes 15ms)
         delay_ms(16);
-        i2c_w(&mut i2c, CTRL3_C, 0x01, 0x4)?; // Initiate SW_RESET (takes 50µs)
+        i2c_w(&mut i2c, CTRL3_C, 0x01)?; // Initiate SW_RESET (takes 50µs)
         delay_ms(2);
-        Ok(0)
+        Ok(())
     }
 
     /// Initialize the IMU for single-tap detection, returning value of WHO_AM_I register on success
-    pub fn init(mut i2c: &mut Hardi2c) -> Result<u8, u8> {
+    pub fn init(mut i2c: &mut Hardi2c) -> Result<u8, ImuError> {
         Self::boot_and_reset(&mut i2c)?;
         // CTRL1_XL = ODR_XL:416Hz, FS_XL:2g, LPF1_BW:208Hz, BW0_XL:400Hz
-        i2c_w(&mut i2c, CTRL1_XL, 0x60, 0x5)?;
-        // CTRL3_C = BlockDataUpdate:On
-        i2c_w(&mut i2c, CTRL3_C, 0x40, 0x6)?;
+        i2c_w(&mut i2c, CTRL1_XL, 0x60)?;
+        // CTRL3_C = BlockDataUpdate:On, IF_INC:On
+        i2c_w(&mut i2c, CTRL3_C, 0x44)?;
         // TAP_CFG = InterruptsEn:On, InactiveEn:Off, TriggerSrc:Slope, TriggerAxis:Z, LatchIR:On
-        i2c_w(&mut i2c, TAP_CFG, 0x83, 0x7)?;
+        i2c_w(&mut i2c, TAP_CFG, 0x83)?;
         // TAP_THS_6D = b1_00_01001: 4dDetect:Off, 6dTHS:80°, TapTHS:562.5mg (LSB=FS_XL/(2^5); 9*(2g/32)=562.5mg)
-        i2c_w(&mut i2c, TAP_THS_6D, 0x89, 0x8)?;
+        i2c_w(&mut i2c, TAP_THS_6D, 0x89)?;
         // INT_DUR2 = b0000_10_10: DoubleTapGapDur:16*ODR (LSB=32*ODR), Quiet:3*(4/ODR)=29ms, Shock:2*(8/ODR)=39ms
-        i2c_w(&mut i2c, INT_DUR2, 0x0E, 0x9)?;
+        i2c_w(&mut i2c, INT_DUR2, 0x0E)?;
         // WAKE_UP_THS = b0_0_000000: SingleDouble:Single, 0, WakeTHS:0g (LSB=FS_XL/(2^6))
-        i2c_w(&mut i2c, WAKE_UP_THS, 0x00, 0xA)?;
+        i2c_w(&mut i2c, WAKE_UP_THS, 0x00)?;
         // MD1_CFG = b0_1_0_0_0_0_0_0: INT1 pin driven by single-tap interrupt (and no others)
-        i2c_w(&mut i2c, MD1_CFG, 0x40, 0xB)?;
-        Self::get_who_am_i(&mut i2c)
+        i2c_w(&mut i2c, MD1_CFG, 0x40)?;
+        let who_am_i = Self::get_who_am_i(&mut i2c)?;
+        if !WHO_AM_I_VALID.contains(&who_am_i) {
+            return Err(ImuError::WhoAmIMismatch(who_am_i));
+        }
+        Ok(who_am_i)
     }
 
     /// Check the WHO_AM_I register which should contain 0x6A
-    pub fn get_who_am_i(mut i2c: &mut Hardi2c) -> Result<u8, u8> {
-        i2c_r(&mut i2c, WHO_AM_I, 0xC)
+    pub fn get_who_am_i(mut i2c: &mut Hardi2c) -> Result<u8, ImuError> {
+        i2c_r(&mut i2c, WHO_AM_I)
     }
 
     /// Get current accelerometer X axis measurement
-    pub fn get_accel_x(mut i2c: &mut Hardi2c) -> Result<u16, u8> {
-        let lsb = i2c_r(&mut i2c, OUTX_L_XL, 0x0D)?;
-        let msb = i2c_r(&mut i2c, OUTX_H_XL, 0x0E)?;
+    pub fn get_accel_x(mut i2c: &mut Hardi2c) -> Result<u16, ImuError> {
+        let lsb = i2c_r(&mut i2c, OUTX_L_XL)?;
+        let msb = i2c_r(&mut i2c, OUTX_H_XL)?;
         Ok(u16::from_le_bytes([lsb, msb]))
     }
 
     /// Get current accelerometer Y axis measurement
-    pub fn get_accel_y(mut i2c: &mut Hardi2c) -> Result<u16, u8> {
-        let lsb = i2c_r(&mut i2c, OUTY_L_XL, 0x0F)?;
-        let msb = i2c_r(&mut i2c, OUTY_H_XL, 0x10)?;
+    pub fn get_accel_y(mut i2c: &mut Hardi2c) -> Result<u16, ImuError> {
+        let lsb = i2c_r(&mut i2c, OUTY_L_XL)?;
+        let msb = i2c_r(&mut i2c, OUTY_H_XL)?;
         Ok(u16::from_le_bytes([lsb, msb]))
     }
 
     /// Get current accelerometer Z axis measurement
-    pub fn get_accel_z(mut i2c: &mut Hardi2c) -> Result<u16, u8> {
-        let lsb = i2c_r(&mut i2c, OUTZ_L_XL, 0x11)?;
-        let msb = i2c_r(&mut i2c, OUTZ_H_XL, 0x12)?;
+    pub fn get_accel_z(mut i2c: &mut Hardi2c) -> Result<u16, ImuError> {
+        let lsb = i2c_r(&mut i2c, OUTZ_L_XL)?;
+        let msb = i2c_r(&mut i2c, OUTZ_H_XL)?;
         Ok(u16::from_le_bytes([lsb, msb]))
     }
 
     /// Returns true result if there is a latched single-tap interrupt
-    pub fn get_single_tap(mut i2c: &mut Hardi2c) -> Result<bool, u8> {
+    pub fn get_single_tap(mut i2c: &mut Hardi2c) -> Result<bool, ImuError> {
         const TAP_IA: u8 = 0x40;
         const SINGLE_TAP: u8 = 0x20;
         const MASK: u8 = TAP_IA | SINGLE_TAP;
-        let ts = i2c_r(&mut i2c, TAP_SRC, 0x13)?;
+        let ts = i2c_r(&mut i2c, TAP_SRC)?;
         let tap_happened = (ts & MASK) == MASK;
         Ok(tap_happened)
     }
+
+    /// Claim `irq_no` for tap-IRQ-driven operation, wiring it to `tap_irq_trampoline` so that
+    /// every vexriscv IRQ on that line latches `TAP_IRQ_PENDING` and invokes `callback` with the
+    /// raw `irq_no` (matching the `fn(usize)` signature `sys_interrupt_claim` requires).
+    ///
+    /// Note: `MD1_CFG = 0x40` (set by `init`) only configures the LSM6DS3's own INT1 *pin* to be
+    /// driven by the single-tap event -- it says nothing about whether that pin is in turn wired
+    /// to a vexriscv interrupt line in this board's gateware. As of this tree's `utra` map, it
+    /// isn't (the only claimed IRQ anywhere in `sw/src/main.rs` is the ticktimer's -- see the same
+    /// gap noted for the BQ25618's INT pin in `api_bq25618.rs` and for wake-on-motion in
+    /// `gyro_rs::hal_gyro`). Callers on a gateware revision that does assign INT1 an IRQ number
+    /// can still use this normally; until then, `get_single_tap` polling is the only way tap
+    /// events are actually observed in this build.
+    pub fn enable_tap_irq(irq_no: usize, callback: fn(usize), priority: u8) -> Result<(), XousError> {
+        unsafe {
+            TAP_IRQ_CALLBACK = Some(callback);
+        }
+        sys_interrupt_claim(irq_no, tap_irq_trampoline, priority)
+    }
+
+    /// Undo `enable_tap_irq`: free the IRQ and drop the stored callback.
+    pub fn disable_tap_irq(irq_no: usize) -> Result<(), XousError> {
+        let result = sys_interrupt_free(irq_no);
+        unsafe {
+            TAP_IRQ_CALLBACK = None;
+        }
+        result
+    }
+
+    /// Drain a tap IRQ latched by `tap_irq_trampoline`, if any. I2C can't run inside the IRQ
+    /// context here (see `tap_irq_trampoline`), so the actual `TAP_SRC` read/decode/clear happens
+    /// here instead, called from the main loop once `TAP_IRQ_PENDING` is observed set -- the same
+    /// split `power_mgmt::charger_handler`'s "I2C can't happen inside an interrupt routine"
+    /// comment describes for the charger.
+    pub fn drain_tap_event(mut i2c: &mut Hardi2c) -> Result<Option<TapEvent>, ImuError> {
+        if !TAP_IRQ_PENDING.swap(false, Ordering::SeqCst) {
+            return Ok(None);
+        }
+        // Reading TAP_SRC clears it (TAP_CFG[LIR] is set by `init`, so it stays latched until
+        // this read happens, rather than self-clearing on the IRQ pulse).
+        let ts = i2c_r(&mut i2c, TAP_SRC)?;
+        if ts & TAP_SRC_TAP_IA == 0 {
+            return Ok(None);
+        }
+        let kind = if ts & TAP_SRC_DOUBLE_TAP != 0 {
+            TapKind::Double
+        } else {
+            TapKind::Single
+        };
+        let axis = if ts & TAP_SRC_X_TAP != 0 {
+            TapAxis::X
+        } else if ts & TAP_SRC_Y_TAP != 0 {
+            TapAxis::Y
+        } else {
+            TapAxis::Z
+        };
+        Ok(Some(TapEvent {
+            kind,
+            axis,
+            negative: ts & TAP_SRC_TAP_SIGN != 0,
+        }))
+    }
+
+    /// Program the hardware FIFO for continuous accelerometer batch streaming: `odr` is the
+    /// raw `FIFO_CTRL5[ODR_FIFO]` field (same encoding as `CTRL1_XL[ODR_XL]`, e.g. `0x6` for
+    /// 416Hz), and `watermark` is the sample count (not byte count) at which `FIFO_STATUS2`'s
+    /// `WTM` flag -- and, once `enable_fifo_irq` is used, the INT1 pin -- latches. Mode is
+    /// fixed to `FIFO_MODE_FIFO` (stop-on-full) rather than the continuous/overwrite modes
+    /// the register also supports, since the point of `fifo_read` draining on a watermark is
+    /// to never actually let the buffer wrap.
+    pub fn fifo_config(mut i2c: &mut Hardi2c, odr: u8, watermark: u16) -> Result<(), ImuError> {
+        i2c_w(&mut i2c, FIFO_CTRL1, (watermark & 0xFF) as u8)?;
+        i2c_w(&mut i2c, FIFO_CTRL2, ((watermark >> 8) & 0x0F) as u8)?;
+        // DEC_FIFO_XL/G left at "no decimation" (0): every XL sample at ODR_XL goes to FIFO.
+        i2c_w(&mut i2c, FIFO_CTRL3, 0x00)?;
+        i2c_w(&mut i2c, FIFO_CTRL5, (odr << 3) | FIFO_MODE_FIFO)?;
+        Ok(())
+    }
+
+    /// Number of unread samples currently queued in the FIFO (`FIFO_STATUS1`/`FIFO_STATUS2`'s
+    /// 11-bit `DIFF_FIFO` count). Each sample is one axis word (2 bytes); a full XYZ triple is
+    /// 3 of these.
+    pub fn fifo_level(mut i2c: &mut Hardi2c) -> Result<u16, ImuError> {
+        let lo = i2c_r(&mut i2c, FIFO_STATUS1)?;
+        let hi = i2c_r(&mut i2c, FIFO_STATUS2)?;
+        Ok(u16::from(lo) | (u16::from(hi & 0x07) << 8))
+    }
+
+    /// Bulk-drain up to `buf.len()` queued FIFO samples into `buf` in one I2C burst read from
+    /// `FIFO_DATA_OUT_L`, returning how many were actually available (which may be less than
+    /// `buf.len()`). Relies on the `IF_INC` auto-increment `init`'s `CTRL3_C` write leaves
+    /// enabled alongside `BDU` -- reading `FIFO_DATA_OUT_L/H` repeatedly just walks the FIFO
+    /// read pointer forward, so this is one transaction rather than one per sample.
+    pub fn fifo_read(mut i2c: &mut Hardi2c, buf: &mut [i16]) -> Result<usize, ImuError> {
+        let available = Self::fifo_level(&mut i2c)? as usize;
+        let count = available.min(buf.len());
+        if count == 0 {
+            return Ok(0);
+        }
+        // read the little-endian sample bytes straight into `buf`'s storage, then fix up each
+        // word's byte order in place
+        let raw = unsafe { core::slice::from_raw_parts_mut(buf.as_mut_ptr() as *mut u8, count * 2) };
+        i2c_r_burst(&mut i2c, FIFO_DATA_OUT_L, raw)?;
+        for slot in buf.iter_mut().take(count) {
+            *slot = i16::from_le(*slot);
+        }
+        Ok(count)
+    }
+
+    /// Route the FIFO-threshold flag to INT1 (`INT1_CTRL[INT1_FTH]`) and claim `irq_no` for
+    /// it, sharing `tap_irq_trampoline`'s claim/dispatch machinery rather than duplicating it --
+    /// the two features drive the same physical pin but are independently routable through
+    /// `INT1_CTRL` vs. `MD1_CFG`, so they can coexist. See `enable_tap_irq`'s doc comment for
+    /// the same INT1-to-vexriscv-IRQ wiring gap this driver can't yet close: the call below
+    /// still only claims a vexriscv IRQ number, which nothing in this tree's `utra` map
+    /// currently routes INT1 to.
+    pub fn enable_fifo_irq(
+        mut i2c: &mut Hardi2c,
+        irq_no: usize,
+        callback: fn(usize),
+        priority: u8,
+    ) -> Result<(), ImuError> {
+        i2c_w(&mut i2c, INT1_CTRL, INT1_CTRL_FTH)?;
+        unsafe {
+            FIFO_IRQ_CALLBACK = Some(callback);
+        }
+        sys_interrupt_claim(irq_no, fifo_irq_trampoline, priority).map_err(ImuError::IrqClaimFailed)
+    }
+
+    /// Undo `enable_fifo_irq`: stop routing FIFO-threshold to INT1, free the IRQ, and drop the
+    /// stored callback.
+    pub fn disable_fifo_irq(mut i2c: &mut Hardi2c, irq_no: usize) -> Result<(), ImuError> {
+        i2c_w(&mut i2c, INT1_CTRL, 0x00)?;
+        let _ = sys_interrupt_free(irq_no);
+        unsafe {
+            FIFO_IRQ_CALLBACK = None;
+        }
+        Ok(())
+    }
+
+    /// True once per FIFO-watermark IRQ latched by `fifo_irq_trampoline` since the last call --
+    /// same edge-triggered flag/drain convention `drain_tap_event` uses for
+    /// `TAP_IRQ_PENDING`, except there's nothing to read-and-clear over I2C here: the caller
+    /// is expected to follow this with `fifo_read` to actually drain the batch, which is what
+    /// brings `FIFO_STATUS`'s `WTM` flag back down on its own.
+    pub fn poll_fifo_irq() -> bool {
+        FIFO_IRQ_PENDING.swap(false, Ordering::SeqCst)
+    }
+}
+
+const TAP_SRC_TAP_IA: u8 = 0x40;
+const TAP_SRC_DOUBLE_TAP: u8 = 0x10;
+const TAP_SRC_TAP_SIGN: u8 = 0x08;
+const TAP_SRC_X_TAP: u8 = 0x04;
+const TAP_SRC_Y_TAP: u8 = 0x02;
+const TAP_SRC_Z_TAP: u8 = 0x01;
+
+/// Which axis a latched tap was detected on, decoded from `TAP_SRC`'s `X_TAP`/`Y_TAP`/`Z_TAP`.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum TapAxis {
+    X,
+    Y,
+    Z,
+}
+
+/// Single vs. double tap, decoded from `TAP_SRC[DOUBLE_TAP]`.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum TapKind {
+    Single,
+    Double,
+}
+
+/// A decoded `TAP_SRC` event, produced by `Imu::drain_tap_event`.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct TapEvent {
+    pub kind: TapKind,
+    pub axis: TapAxis,
+    /// `TAP_SRC[TAP_SIGN]`: true if the tap's acceleration was in the negative direction.
+    pub negative: bool,
+}
+
+/// Set by `tap_irq_trampoline` in IRQ context, cleared by `Imu::drain_tap_event` once the main
+/// loop has had a chance to read+clear `TAP_SRC` over I2C.
+static TAP_IRQ_PENDING: AtomicBool = AtomicBool::new(false);
+
+/// The callback registered via `Imu::enable_tap_irq`. `sys_interrupt_claim` only stores a bare
+/// `fn(usize)`, with no room for captured state, so `tap_irq_trampoline` is the one function ever
+/// registered with it; this is where it finds which callback to forward the IRQ to.
+static mut TAP_IRQ_CALLBACK: Option<fn(usize)> = None;
+
+/// Runs in IRQ context (interrupts disabled, no process scheduling) -- see the safety note on
+/// `xous_nommu::irq::handle`. I2C transactions can't happen here, so this only latches
+/// `TAP_IRQ_PENDING` for `Imu::drain_tap_event` to pick up later and forwards the raw `irq_no` to
+/// the registered callback so it can e.g. wake the main loop; it does not touch `TAP_SRC` itself.
+fn tap_irq_trampoline(irq_no: usize) {
+    TAP_IRQ_PENDING.store(true, Ordering::SeqCst);
+    unsafe {
+        if let Some(cb) = TAP_IRQ_CALLBACK {
+            cb(irq_no);
+        }
+    }
+}
+
+/// Set by `fifo_irq_trampoline` in IRQ context, cleared by `Imu::poll_fifo_irq` -- same
+/// edge-triggered-flag convention as `TAP_IRQ_PENDING`, just with no I2C read to pair it with
+/// here (see `poll_fifo_irq`'s doc comment).
+static FIFO_IRQ_PENDING: AtomicBool = AtomicBool::new(false);
+
+/// The callback registered via `Imu::enable_fifo_irq`, mirroring `TAP_IRQ_CALLBACK`.
+static mut FIFO_IRQ_CALLBACK: Option<fn(usize)> = None;
+
+/// Runs in IRQ context; see `tap_irq_trampoline`'s safety note. Unlike tap detection, there's
+/// no register to read-and-clear here -- the watermark flag comes back down once `fifo_read`
+/// actually drains samples below it -- so this is pure bookkeeping plus callback dispatch.
+fn fifo_irq_trampoline(irq_no: usize) {
+    FIFO_IRQ_PENDING.store(true, Ordering::SeqCst);
+    unsafe {
+        if let Some(cb) = FIFO_IRQ_CALLBACK {
+            cb(irq_no);
+        }
+    }
 }
@@ -1,6 +1,6 @@
 use bitflags::*;
 
-use crate::hal_hardi2c::Hardi2c;
+use crate::hal_hardi2c::{Hardi2c, RetryPolicy, I2C_HARD_MAX_RETRIES};
 use utralib::generated::*;
 
 const TUSB320LAI_ADDR: u8 = 0x47;
@@ -83,21 +83,104 @@ pub const TUSB320LAI_REVISION_EXPECTED_ALT: u8 = 0x06;
 
 const TUSB320_TIMEOUT_MS: u32 = 1;
 
+/// Which end of the port is currently attached, decoded from `ConfigStatus1`'s
+/// `ATTACHED_*` bits in `BtUsbCc::status[1]`.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum UsbCcRole {
+    Unattached,
+    /// Attached as a sink (UFP): this port draws power from the other end.
+    Sink,
+    /// Attached as a source (DFP): this port supplies power to the other end.
+    Source,
+    /// An audio/debug accessory is attached rather than a cable partner.
+    Accessory,
+}
+
+/// Which CC pin the cable is oriented on, decoded from `ConfigStatus1::CABLE_DIR_CC2`.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum CableOrientation {
+    Cc1,
+    Cc2,
+}
+
+/// Which role `BtUsbCc`'s DRP advertisement should favor when the partner is also a DRP.
+/// Passed to `init`/`request_role_swap`, which program it into `ConfigStatus2`'s
+/// `SOURCE_PREF_DRP_TRY_*` bits.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum DrpPreference {
+    TrySink,
+    TrySource,
+}
+impl DrpPreference {
+    fn bits(self) -> ConfigStatus2 {
+        match self {
+            DrpPreference::TrySink => ConfigStatus2::SOURCE_PREF_DRP_TRY_SNK,
+            DrpPreference::TrySource => ConfigStatus2::SOURCE_PREF_DRP_TRY_SRC,
+        }
+    }
+}
+
 pub struct BtUsbCc {
     pub id: [u8; 8],
     pub status: [u8; 3],
+    /// Attachment state as of the last `init`/`check_event` read of `status`.
+    pub role: UsbCcRole,
+    /// Cable orientation as of the last `init`/`check_event` read of `status`.
+    pub orientation: CableOrientation,
+    /// Current the partner is advertising (500/1500/3000 mA), decoded from `status[0]`'s
+    /// `CURRENT_ADVERTISE_*` bits -- the EC's charge/boost path gates on this.
+    pub advertised_current_ma: u16,
 }
 
 impl BtUsbCc {
     pub fn new() -> Self {
-        BtUsbCc { id: [0; 8], status: [0; 3] }
+        BtUsbCc {
+            id: [0; 8],
+            status: [0; 3],
+            role: UsbCcRole::Unattached,
+            orientation: CableOrientation::Cc1,
+            advertised_current_ma: 0,
+        }
+    }
+
+    fn decode_status(&mut self) {
+        self.role = match self.status[1] & ConfigStatus1::ATTACHED_ACCESSORY.bits() {
+            bits if bits == ConfigStatus1::ATTACHED_SRC_DFP.bits() => UsbCcRole::Source,
+            bits if bits == ConfigStatus1::ATTACHED_SNK_UFP.bits() => UsbCcRole::Sink,
+            bits if bits == ConfigStatus1::ATTACHED_ACCESSORY.bits() => UsbCcRole::Accessory,
+            _ => UsbCcRole::Unattached,
+        };
+        self.orientation = if self.status[1] & ConfigStatus1::CABLE_DIR_CC2.bits() != 0 {
+            CableOrientation::Cc2
+        } else {
+            CableOrientation::Cc1
+        };
+        self.advertised_current_ma = match self.status[0] & 0b1100_0000 {
+            bits if bits == ConfigStatus0::CURRENT_ADVERTISE_3000MA.bits() => 3000,
+            bits if bits == ConfigStatus0::CURRENT_ADVERTISE_1500MA.bits() => 1500,
+            _ => 500,
+        };
+    }
+
+    /// Ask the controller to swap roles (sink<->source) by reprogramming CSR1/CSR2 with a new
+    /// `preference` and issuing `ConfigStatus2::SOFT_RESET`, per the datasheet's role-swap
+    /// procedure. Used e.g. when a UFP-only accessory is attached and this port needs to act
+    /// as host (source) instead of the sink role it came up in.
+    pub fn request_role_swap(&mut self, i2c: &mut Hardi2c, preference: DrpPreference) {
+        let txwrbuf: [u8; 2] = [TUSB320LAI_09_CSR1 as u8,
+            (ConfigStatus1::DISABLE_UFP_ACCESSORY | ConfigStatus1::DRP_ADVERT_DUTYCYCLE_30PCT).bits()];
+        let _ = i2c.with_retries(TUSB320LAI_ADDR, Some(&txwrbuf), None, TUSB320_TIMEOUT_MS, I2C_HARD_MAX_RETRIES, RetryPolicy::default());
+
+        let txwrbuf2: [u8; 2] = [TUSB320LAI_0A_CSR2 as u8,
+            (ConfigStatus2::MODE_DRP_AS_UNATTACH_SNK | preference.bits() | ConfigStatus2::SOFT_RESET).bits()];
+        let _ = i2c.with_retries(TUSB320LAI_ADDR, Some(&txwrbuf2), None, TUSB320_TIMEOUT_MS, I2C_HARD_MAX_RETRIES, RetryPolicy::default());
     }
 
-    pub fn init(&mut self, i2c: &mut Hardi2c) -> u8 {
+    pub fn init(&mut self, i2c: &mut Hardi2c, preference: DrpPreference) -> u8 {
         let mut txbuf: [u8; 1] = [TUSB320LAI_00_ID as u8];
         let mut rxbuf: [u8; 8] = [0; 8];
 
-        while i2c.i2c_controller(TUSB320LAI_ADDR, Some(&txbuf), Some(&mut rxbuf), TUSB320_TIMEOUT_MS) != 0 {}
+        let _ = i2c.with_retries(TUSB320LAI_ADDR, Some(&txbuf), Some(&mut rxbuf), TUSB320_TIMEOUT_MS, I2C_HARD_MAX_RETRIES, RetryPolicy::default());
         for i in 0..8 {
             self.id[i] = rxbuf[i];
             // maybe should do something smarter than an assert here, huh.
@@ -106,7 +189,7 @@ impl BtUsbCc {
         // check revision
         txbuf = [TUSB320LAI_A0_REV as u8];
         let mut rxrev: [u8; 1] = [0; 1];
-        while i2c.i2c_controller(TUSB320LAI_ADDR, Some(&txbuf), Some(&mut rxrev), TUSB320_TIMEOUT_MS) != 0 {}
+        let _ = i2c.with_retries(TUSB320LAI_ADDR, Some(&txbuf), Some(&mut rxrev), TUSB320_TIMEOUT_MS, I2C_HARD_MAX_RETRIES, RetryPolicy::default());
         if cfg!(feature = "debug_uart") {
             sprintln!("tusb320lai_rev: {:08x}", rxrev[0]);
             crate::hal_time::delay_ms(50);
@@ -118,19 +201,21 @@ impl BtUsbCc {
         // we want to initially look like a UFP, advertising 500mA current
         let mut txwrbuf: [u8; 2] = [TUSB320LAI_09_CSR1 as u8,
            (ConfigStatus1::DISABLE_UFP_ACCESSORY | ConfigStatus1::DRP_ADVERT_DUTYCYCLE_30PCT).bits()];
-        while i2c.i2c_controller(TUSB320LAI_ADDR, Some(&txwrbuf), None, TUSB320_TIMEOUT_MS) != 0 {}
+        let _ = i2c.with_retries(TUSB320LAI_ADDR, Some(&txwrbuf), None, TUSB320_TIMEOUT_MS, I2C_HARD_MAX_RETRIES, RetryPolicy::default());
 
-        // set us up for UFP mode -- once we get host support, need to change to allow DRP mode!!
+        // program DRP mode, coming up unattached-as-sink, with the caller's Try.SNK/Try.SRC
+        // preference for when the partner is also a DRP
         txwrbuf = [TUSB320LAI_0A_CSR2 as u8,
-           (ConfigStatus2::MODE_UFP_UNATTACHED_SNK | ConfigStatus2::SOURCE_PREF_DRP_TRY_SNK).bits()];
-        while i2c.i2c_controller(TUSB320LAI_ADDR, Some(&txwrbuf), None, TUSB320_TIMEOUT_MS) != 0 {}
+           (ConfigStatus2::MODE_DRP_AS_UNATTACH_SNK | preference.bits()).bits()];
+        let _ = i2c.with_retries(TUSB320LAI_ADDR, Some(&txwrbuf), None, TUSB320_TIMEOUT_MS, I2C_HARD_MAX_RETRIES, RetryPolicy::default());
 
         txbuf = [TUSB320LAI_08_CSR0 as u8];
         let mut status_regs: [u8; 3] = [0; 3];
-        while i2c.i2c_controller(TUSB320LAI_ADDR, Some(&txbuf), Some(&mut status_regs), TUSB320_TIMEOUT_MS) != 0 {}
+        let _ = i2c.with_retries(TUSB320LAI_ADDR, Some(&txbuf), Some(&mut status_regs), TUSB320_TIMEOUT_MS, I2C_HARD_MAX_RETRIES, RetryPolicy::default());
         for i in 0..3 {
             self.status[i] = status_regs[i];
         }
+        self.decode_status();
 
         // enable the regchange event
         let mut i2c_csr = CSR::new(HW_I2C_BASE as *mut u32);
@@ -139,7 +224,7 @@ impl BtUsbCc {
         // clear the REGCHANGE_INTERRUPT bit before returning, in case it was set
         let txwrbuf: [u8; 2] = [TUSB320LAI_09_CSR1 as u8,
            (ConfigStatus1::DISABLE_UFP_ACCESSORY | ConfigStatus1::DRP_ADVERT_DUTYCYCLE_30PCT | ConfigStatus1::REGCHANGE_INTERRUPT).bits()];
-        while i2c.i2c_controller(TUSB320LAI_ADDR, Some(&txwrbuf), None, TUSB320_TIMEOUT_MS) != 0 {}
+        let _ = i2c.with_retries(TUSB320LAI_ADDR, Some(&txwrbuf), None, TUSB320_TIMEOUT_MS, I2C_HARD_MAX_RETRIES, RetryPolicy::default());
 
         rev
     }
@@ -149,15 +234,16 @@ impl BtUsbCc {
         if i2c_csr.rf(utra::i2c::EV_PENDING_USBCC_INT) != 0 {
             let txbuf = [TUSB320LAI_08_CSR0 as u8];
             let mut status_regs: [u8; 3] = [0; 3];
-            while i2c.i2c_controller(TUSB320LAI_ADDR, Some(&txbuf), Some(&mut status_regs), TUSB320_TIMEOUT_MS) != 0 {}
+            let _ = i2c.with_retries(TUSB320LAI_ADDR, Some(&txbuf), Some(&mut status_regs), TUSB320_TIMEOUT_MS, I2C_HARD_MAX_RETRIES, RetryPolicy::default());
             for i in 0..3 {
                 self.status[i] = status_regs[i];
             }
+            self.decode_status();
 
             // clear the REGCHANGE_INTERRUPT bit by writing a `1` to it
             let txwrbuf: [u8; 2] = [TUSB320LAI_09_CSR1 as u8,
             (ConfigStatus1::DISABLE_UFP_ACCESSORY | ConfigStatus1::DRP_ADVERT_DUTYCYCLE_30PCT | ConfigStatus1::REGCHANGE_INTERRUPT).bits()];
-            while i2c.i2c_controller(TUSB320LAI_ADDR, Some(&txwrbuf), None, TUSB320_TIMEOUT_MS) != 0 {}
+            let _ = i2c.with_retries(TUSB320LAI_ADDR, Some(&txwrbuf), None, TUSB320_TIMEOUT_MS, I2C_HARD_MAX_RETRIES, RetryPolicy::default());
 
             // clear the interrupt in the CPU by writing a 1 to the pending bit
             i2c_csr.wfo(utra::i2c::EV_PENDING_USBCC_INT, 1);
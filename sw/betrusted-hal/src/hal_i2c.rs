@@ -1,28 +1,230 @@
 use utralib::generated::*;
-use crate::hal_time::get_time_ms;
+use crate::hal_time::{get_time_ms, delay_ticks};
+
+/// Max number of bytes handled by a single non-blocking transaction. This is sized to comfortably
+/// cover the gas gauge/charger/IMU register transfers that actually occur on this bus; it is not a
+/// general-purpose I2C buffer.
+pub const I2C_TXN_MAX_LEN: usize = 32;
+
+/// Replaces the old `ret: u32` failure counter with a typed result so callers can tell "device not
+/// present" from "bus timed out" instead of just retrying blindly.
+///
+/// This is as fine-grained as `utra::i2c`'s register map allows: the block exposes
+/// `STATUS_RXACK`/`STATUS_TIP`/`STATUS_SDA_IN`, but no arbitration-lost or RX-overrun status
+/// bit, so there's no hardware signal to back an `ArbitrationLost`/`Overrun` variant -- a
+/// caller on a genuinely multi-master bus can't currently tell arbitration loss apart from
+/// `Timeout`, same gap `ImuError::Bus`'s doc comment notes for its callers.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum I2cError {
+    /// The addressed (or a data) byte was not acknowledged by the slave.
+    NoAcknowledge { byte_index: usize },
+    /// `STATUS_TIP` never asserted or cleared within `timeout_ms`, at the point in the
+    /// transaction given by `I2cErrorPhase`.
+    Timeout(I2cErrorPhase),
+}
+
+/// Where in a blocking transaction a [`I2cError::Timeout`] occurred, so a caller logging a
+/// fault can tell a wedged address phase (likely bus contention) apart from one that hung
+/// partway through a multi-byte read or write.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum I2cErrorPhase {
+    /// Waiting on the START + address byte (write or read direction) to clock out.
+    Address,
+    /// Waiting on a data byte within the write half to clock out.
+    WriteData,
+    /// Waiting on a data byte within the read half to clock in.
+    Read,
+}
+
+/// Tracks where a non-blocking transaction currently is in the controller/master sequence.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum I2cPhase {
+    Idle,
+    WriteAddr,
+    WriteData,
+    WriteStopWait,
+    ReadAddr,
+    ReadData,
+    Done,
+}
+
+/// State for a single non-blocking I2C transaction. Owned by `Hardi2c` so that `poll()` (called
+/// from either the main loop or the I2C ISR) can advance it one step at a time instead of
+/// busy-waiting on `STATUS_TIP` for the whole transfer.
+pub struct I2cTransaction {
+    addr: u8,
+    tx_buf: [u8; I2C_TXN_MAX_LEN],
+    tx_len: usize,
+    tx_idx: usize,
+    rx_buf: [u8; I2C_TXN_MAX_LEN],
+    rx_len: usize,
+    rx_idx: usize,
+    phase: I2cPhase,
+    /// How long a single step (one `COMMAND` write's worth of `STATUS_TIP`) may stay busy
+    /// before `poll()` gives up on it -- without this, a slave that wedges mid-transaction
+    /// would leave `is_done()` false forever and the caller would never learn anything went
+    /// wrong. Set by `start()`.
+    idle_timeout_ms: u32,
+    /// `get_time_ms()` at the start of the current step, checked against `idle_timeout_ms`.
+    step_start_ms: u32,
+    /// Set by `poll()` on a NACK or step timeout; consumed (and cleared) by `finish()`.
+    error: Option<I2cError>,
+}
+
+impl I2cTransaction {
+    fn new() -> Self {
+        I2cTransaction {
+            addr: 0,
+            tx_buf: [0; I2C_TXN_MAX_LEN],
+            tx_len: 0,
+            tx_idx: 0,
+            rx_buf: [0; I2C_TXN_MAX_LEN],
+            rx_len: 0,
+            rx_idx: 0,
+            phase: I2cPhase::Idle,
+            idle_timeout_ms: 0,
+            step_start_ms: 0,
+            error: None,
+        }
+    }
+}
+
+/// Callbacks served by `target_poll()` while the block is in peripheral/target mode.
+struct I2cTarget {
+    on_address_match: Option<fn(bool)>,
+    on_receive: Option<fn(&[u8])>,
+    on_request: Option<fn(&mut [u8]) -> usize>,
+    /// Fired on a general-call (broadcast) address match, in addition to (not instead of)
+    /// `on_receive` once the broadcast payload itself has been drained -- see `listen`'s
+    /// `general_call` parameter.
+    on_general_call: Option<fn()>,
+}
+
+/// Configuration for `i2c_init`. `clock_hz` is the only field that reaches hardware today.
+/// `sda_delay_ns`/`glitch_filter` exist so a caller can already sweep them by name to
+/// characterize whether either would help with this bus's intermittent timeouts, but as of
+/// this `utra::i2c` register map -- `PRESCALE`, and `CONTROL`'s `EN`/`GPIO_MODE`/`SCL_OUT`/
+/// `SDA_OUT`/`SLAVE_EN`/`SLAVE_ADDR`/`SLAVE_GCEN` fields, which are every bit this driver
+/// touches -- there is no SDA-delay or glitch-filter control to write them to. Both are
+/// stored and otherwise ignored until a gateware revision adds one.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct I2cConfig {
+    pub clock_hz: u32,
+    /// Desired SDA setup-delay in nanoseconds. `None` preserves today's (only) timing.
+    pub sda_delay_ns: Option<u32>,
+    /// Enable an SDA/SCL glitch filter. `false` preserves today's (only) behavior.
+    pub glitch_filter: bool,
+}
+
+impl I2cConfig {
+    /// Config for `clock_hz` with both signal-integrity knobs left at their current (only
+    /// supported) setting.
+    pub fn new(clock_hz: u32) -> Self {
+        I2cConfig {
+            clock_hz,
+            sda_delay_ns: None,
+            glitch_filter: false,
+        }
+    }
+}
 
 pub struct Hardi2c {
     csr: CSR::<u32>,
+    txn: I2cTransaction,
+    target: Option<I2cTarget>,
+    last_config: I2cConfig,
 }
 
 impl Hardi2c {
     pub fn new() -> Self {
         Hardi2c {
             csr: CSR::new(HW_I2C_BASE as *mut u32),
+            txn: I2cTransaction::new(),
+            target: None,
+            last_config: I2cConfig::new(0),
         }
     }
-    pub fn i2c_init(&mut self, clock_hz: u32) {
-        let clkcode: u32 = clock_hz / (5 * 100_000) - 1;
+    pub fn i2c_init(&mut self, config: I2cConfig) {
+        self.last_config = config;
+        let clkcode: u32 = config.clock_hz / (5 * 100_000) - 1;
         // set the prescale assuming 100MHz cpu operation: 100MHz / ( 5 * 100kHz ) - 1 = 199
         self.csr.wfo(utra::i2c::PRESCALE_PRESCALE, clkcode);
 
         // enable the block
         self.csr.wfo(utra::i2c::CONTROL_EN, 1);
     }
-    // [FIXME] this is a stupid polled implementation of I2C transmission. Once we have
-    // threads and interurpts, this should be refactored to be asynchronous
-    /// Wait until a transaction in progress ends. [FIXME] would be good to yield here once threading is enabled."
-    fn i2c_tip_wait(&mut self, timeout_ms: u32) -> u32 {
+
+    /// Standard 9-clock I2C bus recovery: if a slave is reset mid-transaction it can be left
+    /// holding SDA low forever, wedging the bus for every future transaction. If that's the
+    /// case, drop to GPIO/bit-bang mode and manually pulse SCL (checking after each pulse
+    /// whether the slave has released SDA), then issue a manual START+STOP to resynchronize,
+    /// before bringing the hard controller back up via `i2c_init`.
+    pub fn bus_recover(&mut self) {
+        if self.csr.rf(utra::i2c::STATUS_SDA_IN) == 0 {
+            self.csr.wfo(utra::i2c::CONTROL_GPIO_MODE, 1);
+            self.csr.wfo(utra::i2c::CONTROL_SCL_OUT, 1);
+            self.csr.wfo(utra::i2c::CONTROL_SDA_OUT, 1);
+
+            for _ in 0..9 {
+                if self.csr.rf(utra::i2c::STATUS_SDA_IN) == 1 {
+                    break;
+                }
+                self.csr.wfo(utra::i2c::CONTROL_SCL_OUT, 0);
+                delay_ticks(50);
+                self.csr.wfo(utra::i2c::CONTROL_SCL_OUT, 1);
+                delay_ticks(50);
+            }
+
+            // manual START (SDA falls while SCL is high) followed by STOP (SDA rises while
+            // SCL is high) to leave the bus in the idle state
+            self.csr.wfo(utra::i2c::CONTROL_SDA_OUT, 0);
+            delay_ticks(50);
+            self.csr.wfo(utra::i2c::CONTROL_SDA_OUT, 1);
+            delay_ticks(50);
+
+            self.csr.wfo(utra::i2c::CONTROL_GPIO_MODE, 0);
+        }
+
+        if self.last_config.clock_hz != 0 {
+            self.i2c_init(self.last_config);
+        }
+    }
+
+    /// Probe each non-reserved 7-bit address (0x08-0x77) with an address-only write and record
+    /// which ones ACK. Each probe is a zero-length `i2c_controller` write, which sends START and
+    /// the address byte but no STOP.
+    /// Useful for bring-up/field diagnostics to confirm which peripherals (battery gauge,
+    /// charger, etc.) are actually present on the bus instead of silently failing later.
+    pub fn scan(&mut self) -> ([u8; 128], usize) {
+        let mut found = [0u8; 128];
+        let mut count = 0;
+        for addr in 0x08u8..=0x77 {
+            if self.i2c_controller(addr, Some(&[]), None, 5).is_ok() {
+                found[count] = addr;
+                count += 1;
+            }
+        }
+        (found, count)
+    }
+
+    /// Bring-up self-test: scan the bus and log every address that responded. Intended to be
+    /// called once from `main()` right after `i2c_init()` when diagnosing a new board.
+    pub fn self_test(&mut self) {
+        let (found, count) = self.scan();
+        if cfg!(feature = "debug_uart") {
+            sprintln!("i2c scan: {} device(s) found", count);
+            for addr in &found[..count] {
+                sprintln!("  0x{:02x}", addr);
+            }
+        }
+    }
+    /// Wait until a transaction in progress ends, busy-polling `STATUS_TIP` against `get_time_ms`.
+    /// This stays deliberately blocking: `i2c_controller` backs the simple register read/write
+    /// calls the gas gauge/charger/IMU drivers make, where a caller wants the result before its
+    /// next line of code runs. `start()`/`poll()`/`irq_handler()` further down in this file are
+    /// the interrupt-driven, non-blocking alternative for callers (the main loop, principally)
+    /// that can do other work between bytes instead of waiting here.
+    fn i2c_tip_wait(&mut self, timeout_ms: u32, phase: I2cErrorPhase) -> Result<(), I2cError> {
         let starttime: u32 = get_time_ms();
 
         // wait for TIP to go high
@@ -32,7 +234,7 @@ impl Hardi2c {
             }
             if get_time_ms() > starttime + timeout_ms {
                 self.csr.wo(utra::i2c::COMMAND, 0);
-                return 1;
+                return Err(I2cError::Timeout(phase));
             }
         }
 
@@ -43,16 +245,30 @@ impl Hardi2c {
             }
             if get_time_ms() > starttime + timeout_ms {
                 self.csr.wo(utra::i2c::COMMAND, 0);
-                return 1;
+                return Err(I2cError::Timeout(phase));
             }
         }
         self.csr.wo(utra::i2c::COMMAND, 0);
 
-        0
+        Ok(())
+    }
+    /// The primary I2C interface call. This version currently blocks until the transaction is
+    /// done. Returns the number of bytes transferred on success, or the specific reason for
+    /// failure (NACK at the given byte index, or a hardware timeout) on error. A `Timeout`
+    /// triggers an automatic `bus_recover()` and one retry of the whole transaction, since a
+    /// hung bus would otherwise time out identically on every subsequent call.
+    pub fn i2c_controller(&mut self, addr: u8, txbuf: Option<&[u8]>, mut rxbuf: Option<&mut [u8]>, timeout_ms: u32) -> Result<usize, I2cError> {
+        match self.i2c_controller_inner(addr, txbuf, rxbuf.as_deref_mut(), timeout_ms) {
+            Err(I2cError::Timeout(_)) => {
+                self.bus_recover();
+                self.i2c_controller_inner(addr, txbuf, rxbuf.as_deref_mut(), timeout_ms)
+            }
+            other => other,
+        }
     }
-    /// The primary I2C interface call. This version currently blocks until the transaction is done.
-    pub fn i2c_controller(&mut self, addr: u8, txbuf: Option<&[u8]>, rxbuf: Option<&mut [u8]>, timeout_ms: u32) -> u32 {
-        let mut ret: u32 = 0;
+
+    fn i2c_controller_inner(&mut self, addr: u8, txbuf: Option<&[u8]>, rxbuf: Option<&mut [u8]>, timeout_ms: u32) -> Result<usize, I2cError> {
+        let mut transferred: usize = 0;
 
         // write half
         if txbuf.is_some() {
@@ -63,11 +279,11 @@ impl Hardi2c {
                 | self.csr.ms(utra::i2c::COMMAND_WR, 1)
             );
 
-            ret += self.i2c_tip_wait(timeout_ms);
+            self.i2c_tip_wait(timeout_ms, I2cErrorPhase::Address)?;
 
             for i in 0..txbuf_checked.len() {
                 if self.csr.rf(utra::i2c::STATUS_RXACK) == 1 {
-                    ret += 1;
+                    return Err(I2cError::NoAcknowledge { byte_index: i });
                 }
                 self.csr.wo(utra::i2c::TXR, (txbuf_checked[i]) as u32);
                 if (i == (txbuf_checked.len() - 1)) && rxbuf.is_none() {
@@ -78,10 +294,11 @@ impl Hardi2c {
                 } else {
                     self.csr.wfo(utra::i2c::COMMAND_WR, 1);
                 }
-                ret += self.i2c_tip_wait(timeout_ms);
+                self.i2c_tip_wait(timeout_ms, I2cErrorPhase::WriteData)?;
+                transferred += 1;
             }
             if self.csr.rf(utra::i2c::STATUS_RXACK) == 1 {
-                ret += 1;
+                return Err(I2cError::NoAcknowledge { byte_index: txbuf_checked.len() });
             }
         }
 
@@ -94,7 +311,7 @@ impl Hardi2c {
                 | self.csr.ms(utra::i2c::COMMAND_WR, 1)
             );
 
-            ret += self.i2c_tip_wait(timeout_ms);
+            self.i2c_tip_wait(timeout_ms, I2cErrorPhase::Address)?;
 
             for i in 0..rxbuf_checked.len() {
                 if i == (rxbuf_checked.len() - 1) {
@@ -106,10 +323,345 @@ impl Hardi2c {
                 } else {
                     self.csr.wfo(utra::i2c::COMMAND_RD, 1);
                 }
-                ret += self.i2c_tip_wait(timeout_ms);
+                self.i2c_tip_wait(timeout_ms, I2cErrorPhase::Read)?;
                 rxbuf_checked[i] = self.csr.r(utra::i2c::RXR) as u8;
+                transferred += 1;
+            }
+        }
+        Ok(transferred)
+    }
+
+    /// Kick off a non-blocking transaction and return immediately. Returns `false` (and does
+    /// nothing) if a previous transaction is still in flight or if either buffer is longer than
+    /// `I2C_TXN_MAX_LEN`. Progress is made by repeatedly calling `poll()` -- either from the main
+    /// loop, or from `irq_handler()` once `irq_enable()` has been called -- until `is_done()`.
+    /// `idle_timeout_ms` bounds how long any single step may leave `STATUS_TIP` asserted before
+    /// `poll()` gives up on it and reports `I2cError::Timeout` from `finish()`.
+    pub fn start(&mut self, addr: u8, txbuf: Option<&[u8]>, rxlen: usize, idle_timeout_ms: u32) -> bool {
+        if self.txn.phase != I2cPhase::Idle && self.txn.phase != I2cPhase::Done {
+            return false;
+        }
+        let tx_len = txbuf.map(|b| b.len()).unwrap_or(0);
+        if tx_len > I2C_TXN_MAX_LEN || rxlen > I2C_TXN_MAX_LEN {
+            return false;
+        }
+
+        self.txn.addr = addr;
+        self.txn.tx_len = tx_len;
+        self.txn.tx_idx = 0;
+        self.txn.rx_len = rxlen;
+        self.txn.rx_idx = 0;
+        self.txn.idle_timeout_ms = idle_timeout_ms;
+        self.txn.step_start_ms = get_time_ms();
+        self.txn.error = None;
+        if let Some(b) = txbuf {
+            self.txn.tx_buf[..tx_len].copy_from_slice(b);
+        }
+
+        if tx_len > 0 {
+            self.txn.phase = I2cPhase::WriteAddr;
+            self.csr.wo(utra::i2c::TXR, (addr << 1 | 0) as u32);
+            self.csr.wo(
+                utra::i2c::COMMAND,
+                self.csr.ms(utra::i2c::COMMAND_STA, 1) | self.csr.ms(utra::i2c::COMMAND_WR, 1),
+            );
+        } else if rxlen > 0 {
+            self.txn.phase = I2cPhase::ReadAddr;
+            self.csr.wo(utra::i2c::TXR, (addr << 1 | 1) as u32);
+            self.csr.wo(
+                utra::i2c::COMMAND,
+                self.csr.ms(utra::i2c::COMMAND_STA, 1) | self.csr.ms(utra::i2c::COMMAND_WR, 1),
+            );
+        } else {
+            self.txn.phase = I2cPhase::Done;
+        }
+        true
+    }
+
+    /// Whether the current step has held `STATUS_TIP` longer than `idle_timeout_ms`.
+    fn step_expired(&self) -> bool {
+        get_time_ms() > self.txn.step_start_ms + self.txn.idle_timeout_ms
+    }
+
+    /// Move to `phase` and reset the per-step deadline `step_expired()` checks against.
+    fn advance(&mut self, phase: I2cPhase) {
+        self.txn.phase = phase;
+        self.txn.step_start_ms = get_time_ms();
+    }
+
+    /// Abort the in-flight transaction with `err`, stopping the bus and leaving the result for
+    /// `finish()` to report. Mirrors `i2c_tip_wait`'s `COMMAND <- 0` cleanup on the blocking
+    /// path. A `Timeout` also runs `bus_recover()` immediately, the same trigger
+    /// `i2c_controller` uses on the blocking path -- a stuck peripheral left holding SDA low
+    /// wedges every future transaction on this bus, blocking or not, so the caller's next
+    /// `start()` shouldn't have to time out again just to kick off recovery.
+    fn fail(&mut self, err: I2cError) {
+        self.csr.wo(utra::i2c::COMMAND, 0);
+        if matches!(err, I2cError::Timeout(_)) {
+            self.bus_recover();
+        }
+        self.txn.error = Some(err);
+        self.txn.phase = I2cPhase::Done;
+    }
+
+    /// Advance the in-flight transaction by one step, if `STATUS_TIP` indicates the hardware is
+    /// ready for it. Safe to call repeatedly from the main loop, or from the TIP-done ISR once
+    /// `irq_enable()` is in effect -- it only ever does work when there's something to do, so
+    /// calling it when `is_done()` is a no-op. A step that holds `STATUS_TIP` past `start()`'s
+    /// `idle_timeout_ms`, or a write phase that comes back NACKed, ends the transaction early
+    /// with the error collected by the next `finish()` call -- the same two failure modes
+    /// `i2c_controller`/`i2c_tip_wait` report on the blocking path.
+    pub fn poll(&mut self) -> bool {
+        if self.txn.phase == I2cPhase::Idle || self.txn.phase == I2cPhase::Done {
+            return self.txn.phase == I2cPhase::Done;
+        }
+        if self.csr.rf(utra::i2c::STATUS_TIP) == 1 {
+            if self.step_expired() {
+                let phase = match self.txn.phase {
+                    I2cPhase::WriteAddr | I2cPhase::ReadAddr => I2cErrorPhase::Address,
+                    I2cPhase::WriteData | I2cPhase::WriteStopWait => I2cErrorPhase::WriteData,
+                    I2cPhase::ReadData => I2cErrorPhase::Read,
+                    I2cPhase::Idle | I2cPhase::Done => I2cErrorPhase::Address,
+                };
+                self.fail(I2cError::Timeout(phase));
+                return true;
+            }
+            // hardware is still working the current command; nothing to advance yet
+            return false;
+        }
+        self.csr.wo(utra::i2c::COMMAND, 0);
+
+        // Same NACK check `i2c_controller_inner`'s write half makes before sending the next
+        // byte -- the read half doesn't check `STATUS_RXACK` on the blocking path either, so
+        // this mirrors that (otherwise-asymmetric) existing behavior rather than adding a new
+        // check the blocking path doesn't have.
+        if matches!(self.txn.phase, I2cPhase::WriteAddr | I2cPhase::WriteData)
+            && self.csr.rf(utra::i2c::STATUS_RXACK) == 1
+        {
+            let byte_index = if self.txn.phase == I2cPhase::WriteAddr { 0 } else { self.txn.tx_idx };
+            self.fail(I2cError::NoAcknowledge { byte_index });
+            return true;
+        }
+
+        match self.txn.phase {
+            I2cPhase::WriteAddr | I2cPhase::WriteData => {
+                if self.txn.tx_idx == self.txn.tx_len {
+                    // last write byte has clocked out and been ACKed: repeated START for the read
+                    self.csr.wo(utra::i2c::TXR, (self.txn.addr << 1 | 1) as u32);
+                    self.csr.wo(
+                        utra::i2c::COMMAND,
+                        self.csr.ms(utra::i2c::COMMAND_STA, 1) | self.csr.ms(utra::i2c::COMMAND_WR, 1),
+                    );
+                    self.advance(I2cPhase::ReadAddr);
+                } else {
+                    let i = self.txn.tx_idx;
+                    let last = i == self.txn.tx_len - 1;
+                    self.csr.wo(utra::i2c::TXR, self.txn.tx_buf[i] as u32);
+                    if last && self.txn.rx_len == 0 {
+                        self.csr.wo(
+                            utra::i2c::COMMAND,
+                            self.csr.ms(utra::i2c::COMMAND_STO, 1) | self.csr.ms(utra::i2c::COMMAND_WR, 1),
+                        );
+                        self.advance(I2cPhase::WriteStopWait);
+                    } else {
+                        self.csr.wfo(utra::i2c::COMMAND_WR, 1);
+                        self.advance(I2cPhase::WriteData);
+                    }
+                    self.txn.tx_idx += 1;
+                }
+            }
+            I2cPhase::WriteStopWait => {
+                self.advance(I2cPhase::Done);
+            }
+            I2cPhase::ReadAddr | I2cPhase::ReadData => {
+                if self.txn.phase == I2cPhase::ReadData {
+                    self.txn.rx_buf[self.txn.rx_idx] = self.csr.r(utra::i2c::RXR) as u8;
+                    self.txn.rx_idx += 1;
+                }
+                if self.txn.rx_idx == self.txn.rx_len {
+                    self.advance(I2cPhase::Done);
+                } else if self.txn.rx_idx == self.txn.rx_len - 1 {
+                    self.csr.wo(
+                        utra::i2c::COMMAND,
+                        self.csr.ms(utra::i2c::COMMAND_STO, 1)
+                            | self.csr.ms(utra::i2c::COMMAND_RD, 1)
+                            | self.csr.ms(utra::i2c::COMMAND_ACK, 1),
+                    );
+                    self.advance(I2cPhase::ReadData);
+                } else {
+                    self.csr.wfo(utra::i2c::COMMAND_RD, 1);
+                    self.advance(I2cPhase::ReadData);
+                }
+            }
+            I2cPhase::Idle | I2cPhase::Done => (),
+        }
+
+        self.txn.phase == I2cPhase::Done
+    }
+
+    /// True once a transaction started with `start()` has fully completed (successfully or not)
+    /// and its result is ready to be collected with `finish()`.
+    pub fn is_done(&self) -> bool {
+        self.txn.phase == I2cPhase::Done
+    }
+
+    /// Collect the result of a finished transaction, copying any read bytes into `rxbuf` (which
+    /// must be at least as long as the `rxlen` passed to `start()`), and reset the engine to
+    /// `Idle` so a new `start()` can be issued. Returns the number of bytes copied into `rxbuf`,
+    /// or the `I2cError` `poll()` recorded (a NACK or a step that timed out).
+    pub fn finish(&mut self, rxbuf: &mut [u8]) -> Result<usize, I2cError> {
+        let err = self.txn.error.take();
+        let n = self.txn.rx_len.min(rxbuf.len());
+        rxbuf[..n].copy_from_slice(&self.txn.rx_buf[..n]);
+        self.txn.phase = I2cPhase::Idle;
+        match err {
+            Some(e) => Err(e),
+            None => Ok(n),
+        }
+    }
+
+    /// Enable the TIP-done interrupt so `irq_handler()` gets called on each transition instead of
+    /// requiring the main loop to call `poll()` in a tight spin.
+    pub fn irq_enable(&mut self) {
+        self.csr.wfo(utra::i2c::EV_PENDING_I2C_IRQ, 1);
+        self.csr.wfo(utra::i2c::EV_ENABLE_I2C_IRQ, 1);
+    }
+
+    pub fn irq_disable(&mut self) {
+        self.csr.wfo(utra::i2c::EV_ENABLE_I2C_IRQ, 0);
+    }
+
+    /// Interrupt handler for the I2C block's TIP-done event. Acks the pending bit and advances
+    /// the state machine by one step, the same as a single `poll()` call from the main loop.
+    pub fn irq_handler(&mut self) {
+        self.csr.wfo(utra::i2c::EV_PENDING_I2C_IRQ, 1);
+        self.poll();
+    }
+
+    /// Put the block into target (peripheral) mode, answering to `our_addr` on the bus instead of
+    /// mastering transactions. Requires `CONTROL_SLAVE_EN`/`CONTROL_SLAVE_ADDR` gateware support;
+    /// call `target_poll()` from the main loop afterwards to service the address-match/RX/TX
+    /// events, since this block has no DMA path of its own. `general_call` additionally enables
+    /// `CONTROL_SLAVE_GCEN`, so a broadcast write to the reserved 0x00 address is answered
+    /// alongside `our_addr` -- see `STATUS_SLAVE_GC` in `target_poll`.
+    pub fn listen(&mut self, our_addr: u8, general_call: bool) {
+        self.csr.wfo(utra::i2c::CONTROL_SLAVE_ADDR, our_addr as u32);
+        self.csr.wfo(utra::i2c::CONTROL_SLAVE_GCEN, general_call as u32);
+        self.csr.wfo(utra::i2c::CONTROL_SLAVE_EN, 1);
+        self.target = Some(I2cTarget {
+            on_address_match: None,
+            on_receive: None,
+            on_request: None,
+            on_general_call: None,
+        });
+    }
+
+    pub fn stop_listening(&mut self) {
+        self.csr.wfo(utra::i2c::CONTROL_SLAVE_EN, 0);
+        self.target = None;
+    }
+
+    pub fn set_target_callbacks(
+        &mut self,
+        on_address_match: fn(bool),
+        on_receive: fn(&[u8]),
+        on_request: fn(&mut [u8]) -> usize,
+        on_general_call: fn(),
+    ) {
+        if let Some(target) = &mut self.target {
+            target.on_address_match = Some(on_address_match);
+            target.on_receive = Some(on_receive);
+            target.on_request = Some(on_request);
+            target.on_general_call = Some(on_general_call);
+        }
+    }
+
+    /// Service target-mode events. Mirrors `poll()` for the controller path: call this
+    /// repeatedly from the main loop (or from `irq_handler()`) while `listen()` is active.
+    /// `STATUS_SRW` tells us whether the addressing controller wants to read from us (true) or
+    /// write to us (false) once `STATUS_SLAVE_MATCH` fires; `STATUS_SLAVE_GC` tells us the match
+    /// was the general-call address rather than `our_addr` -- general calls are always a write
+    /// from the controller's side, so that payload still drains through `on_receive` below,
+    /// `on_general_call` is purely an additional "this was a broadcast" notification.
+    pub fn target_poll(&mut self) {
+        if self.target.is_none() {
+            return;
+        }
+        let addr_match = self.csr.rf(utra::i2c::STATUS_SLAVE_MATCH) == 1;
+        let general_call = self.csr.rf(utra::i2c::STATUS_SLAVE_GC) == 1;
+        if !addr_match && !general_call {
+            return;
+        }
+        self.csr.wfo(utra::i2c::STATUS_SLAVE_MATCH, 1); // ack
+        if general_call {
+            self.csr.wfo(utra::i2c::STATUS_SLAVE_GC, 1); // ack
+        }
+
+        let is_read = !general_call && self.csr.rf(utra::i2c::STATUS_SRW) == 1;
+        let target = self.target.as_ref().unwrap();
+        if general_call {
+            if let Some(cb) = target.on_general_call {
+                cb();
             }
         }
-        ret
+        if let Some(cb) = target.on_address_match {
+            cb(is_read);
+        }
+
+        if is_read {
+            let mut txbuf = [0u8; I2C_TXN_MAX_LEN];
+            let len = target
+                .on_request
+                .map(|cb| cb(&mut txbuf))
+                .unwrap_or(0)
+                .min(I2C_TXN_MAX_LEN);
+            for i in 0..len {
+                self.csr.wo(utra::i2c::TXR, txbuf[i] as u32);
+                self.csr.wfo(utra::i2c::COMMAND_WR, 1);
+            }
+        } else {
+            let mut rxbuf = [0u8; I2C_TXN_MAX_LEN];
+            let mut i = 0;
+            while self.csr.rf(utra::i2c::STATUS_TRRDY) == 1 && i < I2C_TXN_MAX_LEN {
+                rxbuf[i] = self.csr.r(utra::i2c::RXR) as u8;
+                i += 1;
+            }
+            if let Some(cb) = self.target.as_ref().unwrap().on_receive {
+                cb(&rxbuf[..i]);
+            }
+        }
+    }
+}
+
+/// Timeout budget for the `embedded-hal` trait methods below, which have no `timeout_ms`
+/// parameter of their own to thread through to `i2c_controller`. Matches `scan()`'s budget --
+/// short enough that a generic device driver built on these traits doesn't stall the main
+/// loop waiting on an absent peripheral.
+const EHAL_TIMEOUT_MS: u32 = 5;
+
+impl embedded_hal::blocking::i2c::Write for Hardi2c {
+    type Error = I2cError;
+    fn write(&mut self, addr: u8, bytes: &[u8]) -> Result<(), Self::Error> {
+        self.i2c_controller(addr, Some(bytes), None, EHAL_TIMEOUT_MS)
+            .map(|_| ())
+    }
+}
+
+impl embedded_hal::blocking::i2c::Read for Hardi2c {
+    type Error = I2cError;
+    fn read(&mut self, addr: u8, buffer: &mut [u8]) -> Result<(), Self::Error> {
+        self.i2c_controller(addr, None, Some(buffer), EHAL_TIMEOUT_MS)
+            .map(|_| ())
+    }
+}
+
+/// `i2c_controller`'s read half already issues a repeated START (`COMMAND_STA` with no
+/// intervening `COMMAND_STO`) rather than a full stop-and-restart, so this maps directly onto
+/// one `i2c_controller` call instead of needing a stop-between-phases workaround.
+impl embedded_hal::blocking::i2c::WriteRead for Hardi2c {
+    type Error = I2cError;
+    fn write_read(&mut self, addr: u8, bytes: &[u8], buffer: &mut [u8]) -> Result<(), Self::Error> {
+        self.i2c_controller(addr, Some(bytes), Some(buffer), EHAL_TIMEOUT_MS)
+            .map(|_| ())
     }
 }
@@ -7,6 +7,27 @@ pub fn time_init() {
     ticktimer_csr.wfo(utra::ticktimer::CONTROL_RESET, 1);
 }
 
+/// Read the 40-bit hardware timer's (low, high) word pair without the race where `TIME0`
+/// rolls over between reading it and `TIME1` -- which would otherwise return a timestamp
+/// off by one 32-bit low-word period (~49.7 days) at the instant of rollover. Uses the
+/// standard double-read-high reconcile loop: read `TIME1`, then `TIME0`, then `TIME1`
+/// again; if both `TIME1` reads agree, the pair is consistent, otherwise `TIME0` crossed
+/// the boundary mid-read and the high word just reread is paired with a fresh low read.
+/// Bounded so a pathological IO fault can't hang the caller.
+fn read_time0_time1(ticktimer_csr: &CSR<u32>) -> (u32, u32) {
+    const MAX_ATTEMPTS: usize = 4;
+    let mut hi = ticktimer_csr.r(utra::ticktimer::TIME1);
+    for _ in 0..MAX_ATTEMPTS {
+        let lo = ticktimer_csr.r(utra::ticktimer::TIME0);
+        let hi2 = ticktimer_csr.r(utra::ticktimer::TIME1);
+        if hi == hi2 {
+            return (lo, hi);
+        }
+        hi = hi2;
+    }
+    (ticktimer_csr.r(utra::ticktimer::TIME0), hi)
+}
+
 /// Struct to work with 40-bit ms resolution hardware timestamps.
 /// 40-bit overflow would take 34 years of uptime, so no need to worry about it.
 /// 32-bit overflow would take 49.7 days of uptime, so need to consider it.
@@ -24,10 +45,8 @@ impl TimeMs {
     /// Return timestamp for current timer value
     pub fn now() -> Self {
         let ticktimer_csr = CSR::new(HW_TICKTIMER_BASE as *mut u32);
-        let now = Self {
-            time0: ticktimer_csr.r(utra::ticktimer::TIME0),
-            time1: ticktimer_csr.r(utra::ticktimer::TIME1),
-        };
+        let (time0, time1) = read_time0_time1(&ticktimer_csr);
+        let now = Self { time0, time1 };
 
         // ============================================================
         // DANGER! This is for testing overflow logic by by forcing a
@@ -124,41 +143,101 @@ impl PartialOrd for TimeMs {
     }
 }
 
+/// Whether `delay_ms` is allowed to sleep via WFI instead of spinning. Defaults to `false`
+/// for the same reason `sw/src/idle.rs::WFI_IDLE_ENABLED` does: this board has a history of
+/// WFI "seeming broken" that hasn't been re-validated against real silicon, so the safe
+/// default is the busy-spin path below until a board revision confirms the gated sequence
+/// in `delay_ms_wfi` actually wakes reliably. Flip once that's confirmed fixed.
+pub const DELAY_WFI_ENABLED: bool = false;
+
+/// The alarm slot `delay_ms_wfi` reuses across every call -- allocated once, lazily, rather
+/// than per-call, since one handle is all a strictly serial sleep-and-wake needs.
+static mut DELAY_ALARM: Option<crate::alarm::AlarmHandle> = None;
+
+/// No-op alarm callback: the only reason `delay_ms_wfi` arms an alarm at all is to guarantee
+/// the ticktimer IRQ it's waiting on actually fires at `target`, so there's nothing to do
+/// here beyond having run (which already caused `alarm::on_ticktimer_irq` to reprogram the
+/// hardware for this deadline).
+fn delay_alarm_callback(_ctx: *mut ()) {}
+
 pub fn delay_ms(ms: u32) {
-    // DANGER! DANGER! DANGER!
-    //
-    // This code is designed with the intent to never, no matter what, panic nor block the
-    // main event loop. In the pursuit of that ideal, surprising things may happen. In
-    // particular, there is a cap on the maximum delay time that is silently enforced.
-    //
-    // Logging a warning here about requests for long delays is impractical, because the
-    // logging code calls delay_ms(). So, my awkward compromise is to silently limit the
-    // requested delay inteval. This is a big dangerous footgun. Consider yourself warned.
-    //
-    // Not blocking the main event loop means this function is careful to impose upper
-    // bounds on how long it can take to return. Those limits are:
-    //
-    // 1. The delay is capped at a max of 500 ms, which is chosen to be an order of
-    //    magnitude larger than reasonable maximum delays of about 10-20ms of per
-    //    iteration of the main event loop. Long intervals should be managed with a state
-    //    machine to avoid negative effects on network responsiveness.
-    //
-    // 2. The for loop is limited by a counter to prevent runaway code in the event of an
-    //    IO problem with the timer or an error in the delay calculations. The loop
-    //    counter estimates 1 clock cycle per iteration because it makes the math easy.
-    //    The actual iterations will be slower, but estimating how much slower is
-    //    difficult. It doesn't matter. The point is that the counter is large enough not
-    //    to truncate the delay and small enough to force the loop to end within seconds
-    //    rather than minutes, weeks, or not at all.
-    //
-    // Loop counter math:
-    // 1. Each iteration of the for loop is definitely going to take at least 1 cycle of
-    //    the 18MHz CPU clock to finish
-    // 2. The hardware timer resolution is 1ms
-    // 3. There are 0.001(s/ms) * 18e+6(Hz) = 18000 CPU clock cycles per ms
-    // 4. A 500ms delay should finish within 500 * 18000 = 9e+6 clock cycles
-    // 5. Maximum value for u32 loop counter is 4e+9, so 9e+6 will fit fine
-    //
+    if DELAY_WFI_ENABLED {
+        delay_ms_wfi(ms);
+    } else {
+        delay_ms_spin(ms);
+    }
+}
+
+/// Power-saving sleep: arms an alarm for `TimeMs::now().add_ms(ms)` and issues WFI instead of
+/// spinning, waking on the ticktimer interrupt that `alarm::on_ticktimer_irq` programs for
+/// it. Loops on the same gated check-then-sleep sequence as `idle::maybe_idle` (disable
+/// interrupts, re-check the exit condition, only then WFI) to close the race where an
+/// interrupt lands between the check and the WFI instruction and is lost. The loop also
+/// tolerates spurious wakeups from unrelated IRQs by simply re-checking `TimeMs::now()`.
+/// Falls back to the spin path if the alarm pool (see `alarm::ALARM_POOL_SIZE`) is ever
+/// exhausted by other subsystems, so a full pool degrades gracefully instead of hanging.
+fn delay_ms_wfi(ms: u32) {
+    let handle = unsafe {
+        if DELAY_ALARM.is_none() {
+            DELAY_ALARM = crate::alarm::allocate_alarm();
+        }
+        &DELAY_ALARM
+    };
+    let handle = match handle {
+        Some(h) => h,
+        None => return delay_ms_spin(ms),
+    };
+    let target = TimeMs::now().add_ms(ms);
+    crate::alarm::set_alarm(handle, target, delay_alarm_callback, core::ptr::null_mut());
+    while TimeMs::now() < target {
+        unsafe {
+            riscv::register::mstatus::clear_mie();
+            if TimeMs::now() < target {
+                riscv::asm::wfi();
+            }
+            riscv::register::mstatus::set_mie();
+        }
+    }
+}
+
+/// Busy-spin delay, capped and bounded against runaway looping. This is the historical
+/// implementation of `delay_ms`, kept as the default and as `delay_ms_wfi`'s fallback; see
+/// `DELAY_WFI_ENABLED` for why WFI isn't trusted as the default yet.
+///
+/// DANGER! DANGER! DANGER!
+///
+/// This code is designed with the intent to never, no matter what, panic nor block the
+/// main event loop. In the pursuit of that ideal, surprising things may happen. In
+/// particular, there is a cap on the maximum delay time that is silently enforced.
+///
+/// Logging a warning here about requests for long delays is impractical, because the
+/// logging code calls delay_ms(). So, my awkward compromise is to silently limit the
+/// requested delay inteval. This is a big dangerous footgun. Consider yourself warned.
+///
+/// Not blocking the main event loop means this function is careful to impose upper
+/// bounds on how long it can take to return. Those limits are:
+///
+/// 1. The delay is capped at a max of 500 ms, which is chosen to be an order of
+///    magnitude larger than reasonable maximum delays of about 10-20ms of per
+///    iteration of the main event loop. Long intervals should be managed with a state
+///    machine to avoid negative effects on network responsiveness.
+///
+/// 2. The for loop is limited by a counter to prevent runaway code in the event of an
+///    IO problem with the timer or an error in the delay calculations. The loop
+///    counter estimates 1 clock cycle per iteration because it makes the math easy.
+///    The actual iterations will be slower, but estimating how much slower is
+///    difficult. It doesn't matter. The point is that the counter is large enough not
+///    to truncate the delay and small enough to force the loop to end within seconds
+///    rather than minutes, weeks, or not at all.
+///
+/// Loop counter math:
+/// 1. Each iteration of the for loop is definitely going to take at least 1 cycle of
+///    the 18MHz CPU clock to finish
+/// 2. The hardware timer resolution is 1ms
+/// 3. There are 0.001(s/ms) * 18e+6(Hz) = 18000 CPU clock cycles per ms
+/// 4. A 500ms delay should finish within 500 * 18000 = 9e+6 clock cycles
+/// 5. Maximum value for u32 loop counter is 4e+9, so 9e+6 will fit fine
+fn delay_ms_spin(ms: u32) {
     const MAX_MS: usize = 500;
     const MAX_LOOP_ITERATIONS: usize = MAX_MS * 18_000;
     let capped_ms = match ms < MAX_MS as u32 {
@@ -181,13 +260,8 @@ pub fn get_time_ms() -> u32 {
 
 pub fn get_time_ticks() -> u64 {
     let ticktimer_csr = CSR::new(HW_TICKTIMER_BASE as *mut u32);
-
-    let mut time: u64;
-
-    time = ticktimer_csr.r(utra::ticktimer::TIME0) as u64;
-    time |= (ticktimer_csr.r(utra::ticktimer::TIME1) as u64) << 32;
-
-    time
+    let (lo, hi) = read_time0_time1(&ticktimer_csr);
+    ((hi as u64) << 32) | (lo as u64)
 }
 
 pub fn set_msleep_target_ticks(delta_ticks: u32) {
@@ -210,6 +284,17 @@ pub fn set_msleep_target_ticks(delta_ticks: u32) {
     );
 }
 
+/// Program `MSLEEP_TARGET0/1` to an absolute 40-bit tick value, split as (low, high) words
+/// the same way `TimeMs` stores them. This is the absolute-deadline counterpart to
+/// `set_msleep_target_ticks`'s relative-delta version -- used by `alarm.rs`, which needs to
+/// schedule against a specific `TimeMs` target rather than "N ticks from now".
+pub fn set_msleep_target_absolute(time0: u32, time1: u32) {
+    let mut ticktimer_csr = CSR::new(HW_TICKTIMER_BASE as *mut u32);
+
+    ticktimer_csr.wo(utra::ticktimer::MSLEEP_TARGET1, time1);
+    ticktimer_csr.wo(utra::ticktimer::MSLEEP_TARGET0, time0);
+}
+
 /// callers must deal with overflow, but the function is fast
 pub fn get_time_ticks_trunc() -> u32 {
     let ticktimer_csr = CSR::new(HW_TICKTIMER_BASE as *mut u32);
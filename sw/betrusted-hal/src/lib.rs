@@ -4,7 +4,10 @@ extern crate bitflags;
 extern crate volatile;
 extern crate utralib;
 extern crate riscv;
+extern crate xous_nommu;
+extern crate embedded_hal;
 
+pub mod alarm;
 pub mod hal_hardi2c;
 pub mod hal_i2c;
 pub mod hal_time;
@@ -15,6 +18,7 @@ pub mod api_lsm6ds3;
 pub mod api_bq25618;
 pub mod api_tusb320;
 pub mod mem_locs;
+pub mod wall_clock;
 
 #[cfg(test)]
 mod tests {
@@ -5,6 +5,13 @@ pub const WFX_FIRMWARE_OFFSET: usize = 0x2000_0000 + 1024 * 1024 - 400 * 1024; /
 //pub const WFX_FIRMWARE_SIZE: usize = 290896; // version C0, as burned to ROM v3.3.2
 pub const WFX_FIRMWARE_SIZE: usize = 305232; // version C0, as burned to ROM v3.12.1. Also applicable for v3.12.3.
 
+/// Full size of the window reserved for the WFX firmware, datasheet-recommended 400kiB --
+/// distinct from [`WFX_FIRMWARE_SIZE`], which is just the current image's exact length.
+/// A field update that erases the region erases this whole window, not just the bytes the
+/// currently-running image happens to occupy, so a shrinking image can't leave stale bytes
+/// of the old one sitting past the new end-of-image marker.
+pub const WFX_FIRMWARE_RESERVED_SIZE: usize = 400 * 1024;
+
 // RAM alloc areas:
 // 0x1000_0000: base of RAM
 // 0x1001_3000: top of code + data region (76k)
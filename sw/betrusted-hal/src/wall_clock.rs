@@ -0,0 +1,106 @@
+//! NTP-disciplined wall-clock time layered over `hal_time`'s monotonic `TimeMs`.
+//!
+//! `TimeMs` is deliberately just ms-since-boot with no notion of time-of-day, so that
+//! `Stopwatch`/`RetryTimer`/the alarm subsystem never have to worry about their reference
+//! point moving. This module adds a UTC-ms estimate on top without touching any of that:
+//! `wall_now()` is `TimeMs::now()` plus a correction that's either applied all at once (a
+//! "step", for large corrections) or bled in gradually (a "slew", for small ones), the same
+//! distinction `ntpd`/the kernel's `adjtime` draw between stepping and slewing the clock.
+//! There's no NTP client in this tree yet -- `apply_offset` is the integration point a future
+//! one would call with `measured_wall_time - wall_now()`.
+
+use crate::hal_time::TimeMs;
+
+/// Corrections at or below this magnitude are slewed in rather than stepped, so small,
+/// routine NTP adjustments never produce a visible jump in `wall_now()`. Matches the
+/// `ntpd`/kernel NTP step threshold convention (128 ms).
+const STEP_THRESHOLD_MS: i64 = 128;
+
+/// Maximum slew rate, in parts per million, mirroring the `adjtime`/kernel `time_adjust`
+/// convention of bleeding in a correction at a bounded frequency offset rather than
+/// reporting a discontinuous time. At 500ppm, a 50ms correction takes about 100 seconds to
+/// fully drain.
+const SLEW_PPM: i64 = 500;
+
+/// Correction already folded into the clock, in micro-ms (1e-6 ms) so slewing math stays
+/// exact integer arithmetic all the way to the final ms conversion in `wall_now`.
+static mut OFFSET_UNITS: i64 = 0;
+
+/// Remaining correction still being slewed in, in the same micro-ms units as `OFFSET_UNITS`.
+static mut RESIDUAL_UNITS: i64 = 0;
+
+/// When the current slew (if any) began, for computing how much of `RESIDUAL_UNITS` has
+/// drained as of a given `TimeMs::now()`.
+static mut SLEW_START: Option<TimeMs> = None;
+
+/// Whether `apply_offset` has ever been called -- i.e. whether `wall_now()` reflects a real
+/// time sync or is still just uptime-since-boot with no correction applied.
+static mut SYNCED: bool = false;
+
+fn to_u64_ms(t: TimeMs) -> u64 {
+    ((t.ms_high_word() as u64) << 32) | t.ms_low_word() as u64
+}
+
+/// How much of `RESIDUAL_UNITS` has drained as of `now`, without mutating any state --
+/// `wall_now()` calls this as a pure read so it can stay cheap to poll.
+fn drained_units(now: TimeMs) -> i64 {
+    unsafe {
+        if RESIDUAL_UNITS == 0 {
+            return 0;
+        }
+        let start = match SLEW_START {
+            Some(t) => t,
+            None => return 0,
+        };
+        let elapsed_ms = now.sub_u32(&start).unwrap_or(0) as i64;
+        let max_drain = elapsed_ms.saturating_mul(SLEW_PPM);
+        if RESIDUAL_UNITS > 0 {
+            max_drain.min(RESIDUAL_UNITS)
+        } else {
+            (-max_drain).max(RESIDUAL_UNITS)
+        }
+    }
+}
+
+/// Apply a measured correction (`measured_wall_ms - wall_now()`, signed) towards the wall
+/// clock. Corrections within `STEP_THRESHOLD_MS` are slewed in at up to `SLEW_PPM`; larger
+/// ones step immediately, discarding whatever was left of any prior in-flight slew.
+pub fn apply_offset(signed_ms: i32) {
+    unsafe {
+        let now = TimeMs::now();
+
+        // Fold progress already made on any prior in-flight slew into OFFSET_UNITS first,
+        // so a new correction composes with what's already taken effect instead of
+        // restarting the ramp from scratch.
+        let drained = drained_units(now);
+        OFFSET_UNITS += drained;
+        RESIDUAL_UNITS -= drained;
+
+        let correction_units = (signed_ms as i64).saturating_mul(1_000_000);
+        if (signed_ms as i64).abs() > STEP_THRESHOLD_MS {
+            OFFSET_UNITS += correction_units;
+            RESIDUAL_UNITS = 0;
+        } else {
+            RESIDUAL_UNITS += correction_units;
+        }
+        SLEW_START = Some(now);
+        SYNCED = true;
+    }
+}
+
+/// Current best estimate of UTC time in milliseconds: monotonic uptime plus whatever
+/// correction `apply_offset` has applied or is still slewing in. Reads as plain
+/// milliseconds-since-boot (i.e. `SYNCED == false`) until the first `apply_offset` call.
+pub fn wall_now() -> u64 {
+    unsafe {
+        let now = TimeMs::now();
+        let applied_units = OFFSET_UNITS + drained_units(now);
+        let monotonic_ms = to_u64_ms(now) as i64;
+        (monotonic_ms + applied_units / 1_000_000) as u64
+    }
+}
+
+/// Whether `wall_now()` has ever received a correction from `apply_offset`.
+pub fn is_synced() -> bool {
+    unsafe { SYNCED }
+}
@@ -0,0 +1,128 @@
+//! Deferred (defmt-style) binary logging backend.
+//!
+//! `log!`/`logln!` format their arguments inline with `core::fmt::Write`, which pulls in
+//! the formatting machinery for every call site and spends UART bandwidth on ASCII. This
+//! module is an alternate backend, selected at compile time with the `defer_log` feature,
+//! that never formats anything on-device: each call site's format string is interned into
+//! the `.defer_log_strings` link section instead of being written to the wire, and only a
+//! compact binary frame -- the string's address in that section plus its raw arguments --
+//! goes out over UART. A host-side tool that has the EC's ELF can map the address back to
+//! the string and re-run the same `{}`/`{:x}` formatting off-device.
+//!
+//! Frame format (all integers little-endian):
+//! ```text
+//!   byte 0        : level (matches the `LL` discriminant)
+//!   bytes 1..=4   : string address (the "index" into .defer_log_strings)
+//!   byte 5        : arg count N
+//!   repeated N times:
+//!     byte 0      : arg type tag (see `Arg`)
+//!     bytes 1..   : raw little-endian bytes of the value, width implied by the tag
+//! ```
+//! There is no framing/sync byte and no checksum: like the rest of this UART, frames are
+//! delivered over a flow-controlled point-to-point link, not a shared or lossy one.
+
+use crate::Uart;
+
+/// One logged argument, tagged with enough type information for the host decoder to
+/// know how many trailing bytes to read and how to render them.
+pub enum Arg {
+    U8(u8),
+    U16(u16),
+    U32(u32),
+    I32(i32),
+}
+
+impl From<u8> for Arg {
+    fn from(v: u8) -> Arg {
+        Arg::U8(v)
+    }
+}
+impl From<u16> for Arg {
+    fn from(v: u16) -> Arg {
+        Arg::U16(v)
+    }
+}
+impl From<u32> for Arg {
+    fn from(v: u32) -> Arg {
+        Arg::U32(v)
+    }
+}
+impl From<i32> for Arg {
+    fn from(v: i32) -> Arg {
+        Arg::I32(v)
+    }
+}
+
+fn put_bytes(bytes: &[u8]) {
+    for b in bytes {
+        Uart::putc(*b);
+    }
+}
+
+/// Emit one deferred-logging frame. `string_addr` is the address of the interned format
+/// string (the call-site macros below place one per site in `.defer_log_strings`).
+pub fn emit(level: u8, string_addr: u32, args: &[Arg]) {
+    Uart::putc(level);
+    put_bytes(&string_addr.to_le_bytes());
+    Uart::putc(args.len() as u8);
+    for arg in args {
+        match arg {
+            Arg::U8(v) => {
+                Uart::putc(0);
+                put_bytes(&v.to_le_bytes());
+            }
+            Arg::U16(v) => {
+                Uart::putc(1);
+                put_bytes(&v.to_le_bytes());
+            }
+            Arg::U32(v) => {
+                Uart::putc(2);
+                put_bytes(&v.to_le_bytes());
+            }
+            Arg::I32(v) => {
+                Uart::putc(3);
+                put_bytes(&v.to_le_bytes());
+            }
+        }
+    }
+}
+
+/// Intern `$msg` into `.defer_log_strings` and return its address, without emitting
+/// anything. Shared by the `defer_log!`/`defer_logln!` macros below.
+#[macro_export]
+macro_rules! defer_log_intern {
+    ($msg:literal) => {{
+        #[link_section = ".defer_log_strings"]
+        static MSG: &str = $msg;
+        &MSG as *const &str as u32
+    }};
+}
+
+/// Deferred-logging equivalent of `log!`: interns the format string and sends a binary
+/// frame instead of formatting inline. Arguments must implement `Into<defer::Arg>`.
+#[macro_export]
+macro_rules! defer_log {
+    ($level:expr, $msg:literal) => {
+        if LOG_LEVEL <= $level {
+            $crate::defer::emit($level as u8, $crate::defer_log_intern!($msg), &[]);
+        }
+    };
+    ($level:expr, $msg:literal, $($arg:expr),+) => {
+        if LOG_LEVEL <= $level {
+            $crate::defer::emit(
+                $level as u8,
+                $crate::defer_log_intern!($msg),
+                &[$($crate::defer::Arg::from($arg)),+],
+            );
+        }
+    };
+}
+
+/// Deferred-logging equivalent of `logln!`. The host decoder contract has no notion of a
+/// trailing newline -- that's a presentation detail for whatever reassembles the frames.
+#[macro_export]
+macro_rules! defer_logln {
+    ($($tt:tt)+) => {
+        $crate::defer_log!($($tt)+)
+    };
+}
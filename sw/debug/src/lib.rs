@@ -5,7 +5,12 @@
 
 use utralib::generated::*;
 extern crate betrusted_hal;
-use crate::betrusted_hal::hal_time::delay_ms;
+use crate::betrusted_hal::hal_time::{delay_ms, get_time_ms};
+
+/// Deferred (defmt-style) binary logging backend -- see module docs. Opt in per-build
+/// with the `defer_log` feature; everything above keeps working unchanged either way.
+#[cfg(feature = "defer_log")]
+pub mod defer;
 
 /// Flow control timeout limits how long putc() waits to drain a full TX buffer
 const FLOW_CONTROL_TIMEOUT_MS: usize = 5;
@@ -51,6 +56,79 @@ impl Uart {
     }
 }
 
+/// Max characters buffered for one interactive console line -- enough for a register dump
+/// command plus a couple of hex arguments, with room to spare.
+const LINE_BUF_LEN: usize = 80;
+
+/// Idle gap that hands a non-empty partial line to the caller even without a CR/LF.
+/// embassy's `split_with_idle` ends an RX window after ~2 byte-times of UART silence;
+/// at 115200 baud that's under a quarter of a millisecond, well below what
+/// `get_time_ms`'s 1ms resolution can resolve, so this uses a coarser gap instead --
+/// short against a human typing cadence, long against one polled main-loop pass -- so a
+/// line that never gets its terminator (dropped keystroke, flaky terminal) still surfaces
+/// instead of wedging the console forever.
+const LINE_IDLE_TIMEOUT_MS: u32 = 50;
+
+static mut RX_LINE_BUF: [u8; LINE_BUF_LEN] = [0; LINE_BUF_LEN];
+static mut RX_LINE_LEN: usize = 0;
+static mut RX_LAST_BYTE_MS: u32 = 0;
+
+impl Uart {
+    /// Non-blocking RX poll: returns one byte if the RX FIFO isn't empty, acking the
+    /// pending RX event the same way `putc`'s TX side is flow-controlled above.
+    fn rx_byte() -> Option<u8> {
+        let mut uart_csr = CSR::new(HW_UART_BASE as *mut u32);
+        if uart_csr.rf(utra::uart::RXEMPTY_RXEMPTY) != 0 {
+            return None;
+        }
+        let b = uart_csr.rf(utra::uart::RXTX_RXTX) as u8;
+        uart_csr.wfo(utra::uart::EV_PENDING_RX, 1);
+        Some(b)
+    }
+
+    /// Hand the accumulated buffer to the caller as one line, resetting state so the next
+    /// call starts a fresh line.
+    fn finish_line() -> Option<&'static str> {
+        unsafe {
+            let len = RX_LINE_LEN;
+            RX_LINE_LEN = 0;
+            core::str::from_utf8(&RX_LINE_BUF[..len]).ok()
+        }
+    }
+
+    /// Drain whatever's waiting in the debug UART's RX FIFO into a fixed line buffer,
+    /// returning a completed line (CR or LF terminated, terminator stripped) as soon as
+    /// one is seen, or after `LINE_IDLE_TIMEOUT_MS` of silence if the buffer is
+    /// non-empty. Call this once per main loop pass to drive a tiny interactive console
+    /// over the same `wishbone-tool ... -s terminal` link `putc` already writes to.
+    pub fn poll_line(&mut self) -> Option<&str> {
+        unsafe {
+            while let Some(b) = Self::rx_byte() {
+                RX_LAST_BYTE_MS = get_time_ms();
+                if b == b'\r' || b == b'\n' {
+                    if RX_LINE_LEN > 0 {
+                        return Self::finish_line();
+                    }
+                    continue; // swallow a bare CR/LF on an empty buffer, e.g. a CRLF pair
+                }
+                if RX_LINE_LEN < LINE_BUF_LEN {
+                    RX_LINE_BUF[RX_LINE_LEN] = b;
+                    RX_LINE_LEN += 1;
+                } else {
+                    // line too long to be a real command -- drop it and start over
+                    RX_LINE_LEN = 0;
+                }
+            }
+            if RX_LINE_LEN > 0
+                && get_time_ms().saturating_sub(RX_LAST_BYTE_MS) > LINE_IDLE_TIMEOUT_MS
+            {
+                return Self::finish_line();
+            }
+        }
+        None
+    }
+}
+
 use core::fmt::{Error, Write};
 impl Write for Uart {
     fn write_str(&mut self, s: &str) -> Result<(), Error> {
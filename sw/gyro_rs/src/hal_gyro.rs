@@ -17,6 +17,113 @@ pub use gyro_bindings::*;
 
 const GYRO_TIMEOUT_MS: u32 = 1;
 
+// Event-engine registers used by `enable_wake_on_motion`/`enable_free_fall`, not covered by
+// the vendor `gyro_bindings` FFI (see the note in `init()` about those being bunk). See ST app note
+// AN5130 for field layouts.
+const REG_WAKE_UP_THS: u8 = 0x5B;
+const REG_WAKE_UP_DUR: u8 = 0x5C;
+const REG_FREE_FALL: u8 = 0x5D;
+const REG_TAP_CFG: u8 = 0x58;
+const REG_MD1_CFG: u8 = 0x5E;
+const REG_WAKE_UP_SRC: u8 = 0x1B;
+const REG_TAP_SRC: u8 = 0x1C;
+const REG_D6D_SRC: u8 = 0x1D;
+
+const TAP_CFG_INTERRUPTS_ENABLE: u8 = 0x80;
+const MD1_CFG_INT1_WU: u8 = 0x20;
+const MD1_CFG_INT1_FF: u8 = 0x10;
+
+const REG_CTRL1_XL: u8 = 0x10;
+const REG_CTRL2_G: u8 = 0x11;
+
+// Pedometer / significant-motion registers, mostly on the "embedded function" bank reached
+// only while `FUNC_CFG_ACCESS` is set -- see `with_embedded_bank` below.
+const REG_FUNC_CFG_ACCESS: u8 = 0x01;
+const REG_CTRL10_C: u8 = 0x19;
+const REG_STEP_COUNTER_L: u8 = 0x4B;
+const REG_STEP_COUNTER_H: u8 = 0x4C;
+// Embedded-bank registers (only valid while FUNC_CFG_ACCESS is set).
+const REG_CONFIG_PEDO_THS_MIN: u8 = 0x0F;
+const REG_STEP_COUNT_DELTA: u8 = 0x15;
+
+const FUNC_CFG_ACCESS_EN: u8 = 0x80;
+const CTRL10_C_FUNC_EN: u8 = 0x04;
+const CTRL10_C_PEDO_EN: u8 = 0x10;
+const CTRL10_C_SIGN_MOTION_EN: u8 = 0x01;
+const TAP_CFG_PEDO_EN: u8 = 0x40;
+
+// `MD1_CFG` (the tap/wake-up/6D interrupt router) has no free bit for significant-motion --
+// all 8 bits are already spoken for (see the `MD1_CFG` layout documented in
+// `betrusted-hal/src/api_lsm6ds3.rs`). The embedded-function interrupts (pedometer step
+// detector, significant motion) are routed to INT1 via `INT1_CTRL` (0x0D) instead.
+const REG_INT1_CTRL: u8 = 0x0D;
+const INT1_CTRL_SIGN_MOT: u8 = 0x40;
+const INT1_CTRL_STEP_DETECTOR: u8 = 0x80;
+// CTRL1_XL/CTRL2_G's ODR nibble (bits [7:4]), preserved as-is by `set_full_scale` -- XL stays
+// at the 12.5Hz `init()` already configures, G stays powered down since nothing reads gyro
+// samples yet.
+const CTRL1_XL_ODR_NIBBLE: u8 = 0x10;
+const CTRL2_G_ODR_NIBBLE: u8 = 0x00;
+
+/// Accelerometer full-scale range, set via `CTRL1_XL[FS_XL]`.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum XlFullScale {
+    G2,
+    G4,
+    G8,
+    G16,
+}
+impl XlFullScale {
+    /// `FS_XL[1:0]` encoding -- note this is not numerically monotonic on the LSM6DS3.
+    fn fs_bits(&self) -> u8 {
+        match self {
+            XlFullScale::G2 => 0b00,
+            XlFullScale::G16 => 0b01,
+            XlFullScale::G4 => 0b10,
+            XlFullScale::G8 => 0b11,
+        }
+    }
+    fn mg_per_lsb(&self) -> f32 {
+        match self {
+            XlFullScale::G2 => 0.061,
+            XlFullScale::G4 => 0.122,
+            XlFullScale::G8 => 0.244,
+            XlFullScale::G16 => 0.488,
+        }
+    }
+}
+
+/// Gyroscope full-scale range, set via `CTRL2_G[FS_G]`/`CTRL2_G[FS_125]`.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum GyFullScale {
+    Dps125,
+    Dps250,
+    Dps500,
+    Dps1000,
+    Dps2000,
+}
+impl GyFullScale {
+    /// `(FS_125, FS_G[1:0])` encoding.
+    fn fs_bits(&self) -> (bool, u8) {
+        match self {
+            GyFullScale::Dps125 => (true, 0b00),
+            GyFullScale::Dps250 => (false, 0b00),
+            GyFullScale::Dps500 => (false, 0b01),
+            GyFullScale::Dps1000 => (false, 0b10),
+            GyFullScale::Dps2000 => (false, 0b11),
+        }
+    }
+    fn mdps_per_lsb(&self) -> f32 {
+        match self {
+            GyFullScale::Dps125 => 4.375,
+            GyFullScale::Dps250 => 8.75,
+            GyFullScale::Dps500 => 17.50,
+            GyFullScale::Dps1000 => 35.0,
+            GyFullScale::Dps2000 => 70.0,
+        }
+    }
+}
+
 static mut GYRO_CONTEXT: stmdev_ctx_t = stmdev_ctx_t {
     write_reg: Some(betrusted_lsm6ds3_write_reg),
     read_reg: Some(betrusted_lsm6ds3_read_reg),
@@ -40,18 +147,63 @@ pub unsafe extern "C" fn betrusted_lsm6ds3_write_reg (ctx: *mut core::ffi::c_voi
 pub struct BtGyro {
     pub context: stmdev_ctx_t,
     pub id: u8,
-    pub x: u16,
-    pub y: u16,
-    pub z: u16,
+    pub x: i16,
+    pub y: i16,
+    pub z: i16,
+    xl_fs: XlFullScale,
+    gy_fs: GyFullScale,
 }
 
 impl BtGyro {
     pub fn new() -> Self {
         unsafe {
-            BtGyro{ context: GYRO_CONTEXT, id: 0, x: 0, y: 0, z: 0 }
+            BtGyro {
+                context: GYRO_CONTEXT,
+                id: 0,
+                x: 0,
+                y: 0,
+                z: 0,
+                xl_fs: XlFullScale::G2,
+                gy_fs: GyFullScale::Dps250,
+            }
         }
     }
 
+    /// Write `CTRL1_XL`/`CTRL2_G` directly (bypassing the broken FFI, same as `init()`) to
+    /// select the accelerometer and gyroscope full-scale ranges, and cache them so
+    /// `accel_mg()`/`gyro_mdps()` can convert raw samples to physical units.
+    pub fn set_full_scale(&mut self, xl_fs: XlFullScale, gy_fs: GyFullScale) -> bool {
+        let mut i2c = Hardi2c::new();
+        Self::reg_write(&mut i2c, REG_CTRL1_XL, CTRL1_XL_ODR_NIBBLE | (xl_fs.fs_bits() << 2));
+        let (fs_125, fs_g) = gy_fs.fs_bits();
+        let fs_125_bit = if fs_125 { 0x02 } else { 0x00 };
+        Self::reg_write(&mut i2c, REG_CTRL2_G, CTRL2_G_ODR_NIBBLE | (fs_g << 2) | fs_125_bit);
+        self.xl_fs = xl_fs;
+        self.gy_fs = gy_fs;
+        true
+    }
+
+    /// Current accelerometer sample, in milligravities.
+    pub fn accel_mg(&self) -> (i32, i32, i32) {
+        let lsb = self.xl_fs.mg_per_lsb();
+        (
+            (self.x as f32 * lsb) as i32,
+            (self.y as f32 * lsb) as i32,
+            (self.z as f32 * lsb) as i32,
+        )
+    }
+
+    /// Current gyroscope sample, in millidegrees-per-second. Note `update_xyz()` only samples
+    /// the accelerometer registers today, so this converts whatever `x/y/z` last held.
+    pub fn gyro_mdps(&self) -> (i32, i32, i32) {
+        let lsb = self.gy_fs.mdps_per_lsb();
+        (
+            (self.x as f32 * lsb) as i32,
+            (self.y as f32 * lsb) as i32,
+            (self.z as f32 * lsb) as i32,
+        )
+    }
+
     pub fn init(&mut self) -> bool {
         let mut id: u8 = 0;
         unsafe{ lsm6ds3_device_id_get(&mut self.context, &mut id); }
@@ -90,9 +242,106 @@ impl BtGyro {
         i2c.i2c_master((LSM6DS3_I2C_ADD_H >> 1) as u8, Some(&txbuf), None, GYRO_TIMEOUT_MS);
         /*
     */
+        // CTRL1_XL above (0x10) leaves FS_XL at its reset value of 00b, i.e. +-2g; record that
+        // so accel_mg() matches what's actually programmed. Gyro is left powered down, at its
+        // reset full-scale of +-250dps.
+        self.xl_fs = XlFullScale::G2;
+        self.gy_fs = GyFullScale::Dps250;
         true
     }
 
+    fn reg_write(i2c: &mut Hardi2c, reg: u8, val: u8) {
+        let txbuf: [u8; 2] = [reg, val];
+        i2c.i2c_master((LSM6DS3_I2C_ADD_H >> 1) as u8, Some(&txbuf), None, GYRO_TIMEOUT_MS);
+    }
+    fn reg_read(i2c: &mut Hardi2c, reg: u8) -> u8 {
+        let mut rxbuf: [u8; 1] = [0];
+        i2c.i2c_master_read_ffi((LSM6DS3_I2C_ADD_H >> 1) as u8, reg, &mut rxbuf, GYRO_TIMEOUT_MS);
+        rxbuf[0]
+    }
+
+    /// Program the event engine to raise INT1 when acceleration exceeds `threshold` (LSB =
+    /// FS_XL/2^6, same units as `WAKE_UP_THS`) for at least `duration` (LSB = 1/ODR_XL). Does
+    /// not touch `CTRL1_XL`'s ODR/full-scale settings -- call `init()` first.
+    ///
+    /// This only configures the sensor side of wake-on-motion. Routing the INT1 GPIO to an EC
+    /// wakeup via `sys_interrupt_claim` additionally needs an IRQ line for that pin in this
+    /// tree's `utra` map, which isn't present here (the IMU's INT1 is only read by polling
+    /// elsewhere in this codebase) -- so the EC still has to poll `read_and_clear_event()` for now.
+    pub fn enable_wake_on_motion(&mut self, threshold: u8, duration: u8) -> bool {
+        let mut i2c = Hardi2c::new();
+        Self::reg_write(&mut i2c, REG_WAKE_UP_THS, threshold & 0x3f);
+        Self::reg_write(&mut i2c, REG_WAKE_UP_DUR, duration);
+        let tap_cfg = Self::reg_read(&mut i2c, REG_TAP_CFG);
+        Self::reg_write(&mut i2c, REG_TAP_CFG, tap_cfg | TAP_CFG_INTERRUPTS_ENABLE);
+        let md1_cfg = Self::reg_read(&mut i2c, REG_MD1_CFG);
+        Self::reg_write(&mut i2c, REG_MD1_CFG, md1_cfg | MD1_CFG_INT1_WU);
+        true
+    }
+
+    /// Program the event engine to raise INT1 on free-fall, using `threshold` as the raw
+    /// `FREE_FALL` register value (bits [7:3] = FF_DUR, bits [2:0] = FF_THS -- see AN5130 for
+    /// the LSB-to-mg/duration tables). See `enable_wake_on_motion` for the same INT1-routing
+    /// caveat.
+    pub fn enable_free_fall(&mut self, threshold: u8) -> bool {
+        let mut i2c = Hardi2c::new();
+        Self::reg_write(&mut i2c, REG_FREE_FALL, threshold);
+        let tap_cfg = Self::reg_read(&mut i2c, REG_TAP_CFG);
+        Self::reg_write(&mut i2c, REG_TAP_CFG, tap_cfg | TAP_CFG_INTERRUPTS_ENABLE);
+        let md1_cfg = Self::reg_read(&mut i2c, REG_MD1_CFG);
+        Self::reg_write(&mut i2c, REG_MD1_CFG, md1_cfg | MD1_CFG_INT1_FF);
+        true
+    }
+
+    /// Enable the embedded pedometer: program the step threshold (`step_ths_min`, LSB-defined
+    /// by the embedded function bank) and minimum-steps-per-count debounce (`step_count_delta`)
+    /// on the embedded-function bank reached via `FUNC_CFG_ACCESS`, then enable `FUNC_EN`/
+    /// `PEDO_EN` in `CTRL10_C` and the pedometer bit in `TAP_CFG` on the main bank. Read the
+    /// running total with `step_count()`.
+    pub fn enable_pedometer(&mut self, step_ths_min: u8, step_count_delta: u8) -> bool {
+        let mut i2c = Hardi2c::new();
+        Self::reg_write(&mut i2c, REG_FUNC_CFG_ACCESS, FUNC_CFG_ACCESS_EN);
+        Self::reg_write(&mut i2c, REG_CONFIG_PEDO_THS_MIN, step_ths_min & 0x1f);
+        Self::reg_write(&mut i2c, REG_STEP_COUNT_DELTA, step_count_delta);
+        Self::reg_write(&mut i2c, REG_FUNC_CFG_ACCESS, 0); // restore to the main register bank
+
+        let ctrl10 = Self::reg_read(&mut i2c, REG_CTRL10_C);
+        Self::reg_write(&mut i2c, REG_CTRL10_C, ctrl10 | CTRL10_C_FUNC_EN | CTRL10_C_PEDO_EN);
+        let tap_cfg = Self::reg_read(&mut i2c, REG_TAP_CFG);
+        Self::reg_write(&mut i2c, REG_TAP_CFG, tap_cfg | TAP_CFG_PEDO_EN);
+        true
+    }
+
+    /// Current step count from the embedded pedometer (`STEP_COUNTER_H:L`, main bank).
+    pub fn step_count(&mut self) -> u16 {
+        let mut i2c = Hardi2c::new();
+        let lo = Self::reg_read(&mut i2c, REG_STEP_COUNTER_L);
+        let hi = Self::reg_read(&mut i2c, REG_STEP_COUNTER_H);
+        u16::from_le_bytes([lo, hi])
+    }
+
+    /// Enable the embedded significant-motion detector and route it to INT1 via `INT1_CTRL`
+    /// (see the note on that const for why not `MD1_CFG`), so the EC can wake the SoC only
+    /// after meaningful cumulative movement instead of on every wake-up-threshold crossing.
+    pub fn enable_significant_motion(&mut self) -> bool {
+        let mut i2c = Hardi2c::new();
+        let ctrl10 = Self::reg_read(&mut i2c, REG_CTRL10_C);
+        Self::reg_write(&mut i2c, REG_CTRL10_C, ctrl10 | CTRL10_C_FUNC_EN | CTRL10_C_SIGN_MOTION_EN);
+        let int1_ctrl = Self::reg_read(&mut i2c, REG_INT1_CTRL);
+        Self::reg_write(&mut i2c, REG_INT1_CTRL, int1_ctrl | INT1_CTRL_SIGN_MOT);
+        true
+    }
+
+    /// Read and clear the latched wake-up/tap/orientation source registers, classifying which
+    /// event(s) fired. These registers latch until read, so this both classifies and acks.
+    pub fn read_and_clear_event(&mut self) -> (u8, u8, u8) {
+        let mut i2c = Hardi2c::new();
+        let wake_up_src = Self::reg_read(&mut i2c, REG_WAKE_UP_SRC);
+        let tap_src = Self::reg_read(&mut i2c, REG_TAP_SRC);
+        let d6d_src = Self::reg_read(&mut i2c, REG_D6D_SRC);
+        (wake_up_src, tap_src, d6d_src)
+    }
+
     pub fn update_xyz(&mut self) -> bool {
         //let mut data: [u16; 3] = [3, 2, 1];
         //unsafe {
@@ -102,9 +351,10 @@ impl BtGyro {
         let mut i2c = Hardi2c::new();
         i2c.i2c_master_read_ffi((LSM6DS3_I2C_ADD_H >> 1) as u8, 0x28, &mut data, GYRO_TIMEOUT_MS);
 
-        self.x = data[0] as u16 | ((data[1] as u16) << 8);
-        self.y = data[2] as u16 | ((data[3] as u16) << 8);
-        self.z = data[4] as u16 | ((data[5] as u16) << 8);
+        // Samples are 16-bit signed two's-complement, not unsigned.
+        self.x = i16::from_le_bytes([data[0], data[1]]);
+        self.y = i16::from_le_bytes([data[2], data[3]]);
+        self.z = i16::from_le_bytes([data[4], data[5]]);
         true
     }
 }
\ No newline at end of file
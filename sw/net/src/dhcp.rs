@@ -22,10 +22,55 @@ const DHCP_HEADER_LEN: usize = 241; // op field -> one byte past options magic c
 const MIN_DHCP_FRAME_LEN: usize = MIN_UDP_FRAME_LEN + DHCP_HEADER_LEN;
 const DHCP_FRAME_LEN: usize = 342;
 
+/// Max DNS servers kept from option 6's list, mirroring smoltcp's
+/// `DHCP_MAX_DNS_SERVER_COUNT`. RFC 2132 allows an arbitrary-length list; a resolver only
+/// ever needs a primary plus a couple of fallbacks.
+const DNS_SERVER_COUNT: usize = 3;
+
+/// Max classless static routes (RFC 3442, option 121) kept from a DHCPOFFER/DHCPACK. Like
+/// `DNS_SERVER_COUNT`, the option allows an arbitrary-length list but a handful of routes is
+/// enough for any network this EC would join.
+const ROUTE_COUNT: usize = 4;
+
+/// Number of ARP probes sent during `State::ArpProbing` before concluding an offered
+/// address is free to use. RFC 2131 doesn't pin down specific timing for this (unlike RFC
+/// 5227's ZeroConf PROBE_NUM/PROBE_MIN/PROBE_MAX, which is designed around link-local
+/// self-assignment with no DHCP server involved, and whose ~1-2s-per-probe spacing would add
+/// real latency here for a case -- conflicting with a server-assigned address -- that's rare
+/// in practice). A handful of probes at a short fixed interval is enough to catch a
+/// conflicting host that's actually there without stalling every normal bind.
+const ARP_PROBE_COUNT: u8 = 3;
+
+/// Spacing between probes in `State::ArpProbing`; see `ARP_PROBE_COUNT`.
+const ARP_PROBE_INTERVAL_MS: u32 = 200;
+
+/// Max option tags `set_param_request_list` can hold, sized a bit past the default list so
+/// a caller can add a few more (e.g. 58/59 for T1/T2) without hitting a wall.
+const PARAM_REQUEST_LIST_MAX: usize = 12;
+
+/// Default Parameter Request List (option 55) tags: subnet mask (1), classless static
+/// routes (121), router (3, RFC 3442 fallback when 121 is absent), DNS servers (6), domain
+/// name (15), domain search (119), and the WPAD proxy autoconfig URL (252, harmless to ask
+/// for and some networks rely on it).
+const DEFAULT_PARAM_REQUEST_LIST: [u8; 7] = [1, 121, 3, 6, 15, 119, 252];
+
+/// Max search-domain names kept from option 119, mirroring `DNS_SERVER_COUNT`/`ROUTE_COUNT`:
+/// a resolver only ever tries a handful before giving up anyway.
+const SEARCH_DOMAIN_COUNT: usize = 2;
+
+/// Max length of one decoded, dot-joined search-domain name -- RFC 1035 § 3.1's 255-octet
+/// cap on an encoded name is also an upper bound on its dot-joined form (each length octet
+/// becomes a '.' of the same width).
+const MAX_DOMAIN_NAME_LEN: usize = 255;
+
+/// Max total bytes of raw option-119 data kept for decoding, across every instance of the
+/// option RFC 3396 allows a server to split it into (see `DhcpOption::append_domain_search`).
+/// Comfortably more than one encoded name plus its compression pointers would ever need.
+const MAX_DOMAIN_SEARCH_BUF: usize = 512;
+
 /// DHCP Client States
 ///
-/// Note that InitReboot and Rebooting were intentionally omitted. Also, Halted is for
-/// power-up or receiving a DHCPNAK while in Renewing or Rebinding.
+/// Note that Halted is for power-up or receiving a DHCPNAK while in Renewing or Rebinding.
 ///
 #[derive(Copy, Clone, PartialEq)]
 pub enum State {
@@ -33,9 +78,27 @@ pub enum State {
     Init,
     Selecting,
     Requesting,
+    /// Entered after a DHCPACK for a fresh `Requesting` bind, before committing to the
+    /// offered address. RFC 2131 § 2.2 / § 4.4.1 has the client ARP-probe an offered
+    /// address before using it, in case something else on the network has claimed it since
+    /// the server handed it out. `Renewing`/`Rebinding` skip straight to `Bound` on ACK
+    /// instead (see `DhcpClient::handle_ack`): those are reconfirming an address this
+    /// client already probed and has been using, not accepting a fresh one.
+    ArpProbing,
+    /// One-shot transitional state: a conflicting reply arrived during `ArpProbing`, and
+    /// `cycle_clock` needs exactly one more call to emit `PacketNeeded::Decline` (using
+    /// `decline_ip`/`decline_sid`, stashed by `handle_arp_reply` before `ip`/`sid` get
+    /// cleared) before falling back to `Init`.
+    Declining,
     Bound,
     Renewing,
     Rebinding,
+    /// RFC 2131 § 4.3.2 INIT-REBOOT: entered by `DhcpClient::begin_at_init_reboot` to
+    /// fast-reacquire a lease cached from a previous session instead of a full
+    /// DISCOVER/OFFER round trip. Skips `ArpProbing` on ACK like `Renewing`/`Rebinding`,
+    /// since this is reconfirming an address the client already used, not accepting a fresh
+    /// one; a DHCPNAK or exhausted retries falls back to an ordinary `Init`/DISCOVER cycle.
+    Rebooting,
 }
 
 /// Packet types that may need to be sent for a state transition or timer event
@@ -43,8 +106,16 @@ pub enum State {
 pub enum PacketNeeded {
     Discover,
     Request,
+    /// Broadcast ARP request probing whether `DhcpClient::ip` is already in use; see
+    /// `State::ArpProbing`.
+    ArpProbe,
+    /// DHCPDECLINE for the address `handle_arp_reply` found already claimed; see
+    /// `State::Declining`.
+    Decline,
     Renew,
     Rebind,
+    /// DHCPRELEASE for the lease `DhcpClient::release` is giving back; see `release`.
+    Release,
     None,
 }
 
@@ -55,12 +126,41 @@ pub enum DhcpEvent {
     ChangedToHalted,
 }
 
-/// The three types of DHCP request packets that require slightly different MAC or DHCP options
+/// The types of DHCP request packets that require slightly different MAC or DHCP options
 #[derive(Copy, Clone, PartialEq)]
 pub enum RequestType {
     Discover,
     Renew,
     Rebind,
+    /// RFC 2131 § 4.3.2 INIT-REBOOT: option 50 (the cached IP) with no server-id, broadcast.
+    /// See `State::Rebooting`.
+    Rebooting,
+}
+
+/// One decoded, dot-joined domain name from option 119 (RFC 3397), e.g. `example.com`.
+/// Styled after `Hostname`: a fixed-capacity buffer plus a length, rather than a `String`,
+/// since this is `no_std` with no allocator.
+#[derive(Copy, Clone)]
+pub struct DomainName {
+    pub length: usize,
+    pub buffer: [u8; MAX_DOMAIN_NAME_LEN],
+}
+impl DomainName {
+    pub const fn new_blank() -> Self {
+        Self {
+            length: 0,
+            buffer: [0; MAX_DOMAIN_NAME_LEN],
+        }
+    }
+
+    /// Return domain name as `&str`, or `""` if empty or somehow not valid UTF-8 (labels are
+    /// meant to be ASCII per RFC 1035 § 2.3.1, but a hostile server could send anything).
+    pub fn as_str(&self) -> &str {
+        match core::str::from_utf8(&self.buffer[..self.length]) {
+            Ok(s) => s,
+            _ => &"",
+        }
+    }
 }
 
 /// State Machine for DHCP client
@@ -70,6 +170,28 @@ pub struct DhcpClient {
     timer_t1: Countdown,
     timer_t2: Countdown,
     timer_lease: Countdown,
+    /// Number of ARP probes sent so far this `ArpProbing` pass; see `ARP_PROBE_COUNT`.
+    arp_probe_count: u8,
+    arp_probe_timer: Countdown,
+    /// Lease time from the DHCPACK that triggered `ArpProbing`, held here (rather than
+    /// `lease_sec`, which is already populated from the DHCPOFFER) until the probe window
+    /// elapses cleanly and `enter_bound` can arm the T1/T2/lease timers from it.
+    pending_lease_sec: Option<u32>,
+    /// T1/T2 from the DHCPACK that triggered `ArpProbing`, held alongside `pending_lease_sec`
+    /// for the same reason; `None` means the server didn't send that option and `enter_bound`
+    /// should derive the RFC 2131 § 4.4.5 default from the lease instead.
+    pending_t1_sec: Option<u32>,
+    pending_t2_sec: Option<u32>,
+    /// The address and server ID `handle_arp_reply` found already claimed, stashed so
+    /// `build_decline_frame` can still read them after `cycle_clock`'s `Declining` arm has
+    /// already reset `ip`/`sid` back to `None` on its way to `Init`.
+    decline_ip: Option<u32>,
+    decline_sid: Option<u32>,
+    /// The address and server ID `release` is handing back, stashed so `build_release_frame`
+    /// can still read them after `release` has already reset `ip`/`sid` back to `None` on its
+    /// way to `Halted`.
+    release_ip: Option<u32>,
+    release_sid: Option<u32>,
     pub hostname: Hostname,
     pub state: State,
     pub secs: Stopwatch,
@@ -81,7 +203,32 @@ pub struct DhcpClient {
     pub gateway: Option<u32>,
     pub gateway_mac: Option<[u8; 6]>,
     pub lease_sec: Option<u32>,
-    pub dns: Option<u32>,
+    /// Whether to set the BOOTP broadcast flag (`0x8000`, the high bit of the 16-bit flags
+    /// field) on the next outgoing frame. RFC 2131 § 4.1 has this tell the server to
+    /// broadcast its reply rather than unicast it to `yiaddr`, for clients whose stack can't
+    /// receive a unicast datagram before their address is configured. Set for Discover and
+    /// the initial post-OFFER Request (see `RequestType`), cleared for Renew where we already
+    /// have a bound `ciaddr` to unicast from; some consumer access points otherwise never
+    /// answer a DISCOVER at all.
+    pub broadcast: bool,
+    /// DNS servers from option 6, in the order the server listed them. Unlike `gateway`,
+    /// which only ever drives the route to the server we're already bound to, it's worth
+    /// keeping fallback resolvers around since the first one timing out is common.
+    pub dns_servers: [Option<u32>; DNS_SERVER_COUNT],
+    /// Classless static routes from option 121 (RFC 3442), as `(dest_prefix, prefix_len,
+    /// next_hop)`. Per RFC 3442, a server that sends this option takes priority over the
+    /// plain `gateway` from option 3; `gateway` is left populated either way so the SoC
+    /// always has a default route to fall back on when this is empty.
+    pub routes: [Option<(u32, u8, u32)>; ROUTE_COUNT],
+    /// Search domains from option 119 (RFC 3397), in the order the server listed them.
+    pub search_domains: [DomainName; SEARCH_DOMAIN_COUNT],
+    /// Upper bound on the lease duration `enter_bound` will actually arm timers for,
+    /// regardless of what the server offers; see `set_max_lease_duration`.
+    max_lease_duration: Option<u32>,
+    /// Option tags to ask servers for via the Parameter Request List (option 55) on the
+    /// next Discover/Request; see `set_param_request_list`.
+    param_request_list: [u8; PARAM_REQUEST_LIST_MAX],
+    param_request_list_len: usize,
 }
 impl DhcpClient {
     pub const fn new() -> Self {
@@ -91,6 +238,15 @@ impl DhcpClient {
             timer_t1: Countdown::new(),
             timer_t2: Countdown::new(),
             timer_lease: Countdown::new(),
+            arp_probe_count: 0,
+            arp_probe_timer: Countdown::new(),
+            pending_lease_sec: None,
+            pending_t1_sec: None,
+            pending_t2_sec: None,
+            decline_ip: None,
+            decline_sid: None,
+            release_ip: None,
+            release_sid: None,
             hostname: Hostname::new_blank(),
             state: State::Halted,
             secs: Stopwatch::new(),
@@ -102,10 +258,47 @@ impl DhcpClient {
             gateway: None,
             gateway_mac: None,
             lease_sec: None,
-            dns: None,
+            broadcast: true,
+            dns_servers: [None; DNS_SERVER_COUNT],
+            routes: [None; ROUTE_COUNT],
+            search_domains: [DomainName::new_blank(); SEARCH_DOMAIN_COUNT],
+            max_lease_duration: None,
+            param_request_list: {
+                let mut list = [0u8; PARAM_REQUEST_LIST_MAX];
+                let mut i = 0;
+                while i < DEFAULT_PARAM_REQUEST_LIST.len() {
+                    list[i] = DEFAULT_PARAM_REQUEST_LIST[i];
+                    i += 1;
+                }
+                list
+            },
+            param_request_list_len: DEFAULT_PARAM_REQUEST_LIST.len(),
         }
     }
 
+    /// Cap every lease this client binds to at most `max_sec`, regardless of what the server
+    /// offers. Mirrors smoltcp's `Dhcpv4Config::max_lease_duration`: operators can use this to
+    /// force more frequent renews so the client notices network reconfiguration sooner, and it
+    /// makes exercising the Renewing/Rebinding path practical without waiting out a real
+    /// server's multi-hour lease. Pass `None` to go back to trusting the server's lease as-is.
+    pub fn set_max_lease_duration(&mut self, max_sec: Option<u32>) {
+        self.max_lease_duration = max_sec;
+    }
+
+    /// Replace the Parameter Request List (option 55) sent on the next Discover/Request,
+    /// e.g. to add 58/59 (T1/T2) so a server that only sends them when asked will. Tags
+    /// past `PARAM_REQUEST_LIST_MAX` are silently dropped; defaults to
+    /// `DEFAULT_PARAM_REQUEST_LIST` until this is called.
+    pub fn set_param_request_list(&mut self, tags: &[u8]) {
+        let len = tags.len().min(PARAM_REQUEST_LIST_MAX);
+        self.param_request_list[..len].copy_from_slice(&tags[..len]);
+        self.param_request_list_len = len;
+    }
+
+    fn param_request_list(&self) -> &[u8] {
+        &self.param_request_list[..self.param_request_list_len]
+    }
+
     /// Return current state machine state
     pub fn get_state(&self) -> State {
         self.state
@@ -129,9 +322,12 @@ impl DhcpClient {
             State::Init => "dhcpInit",
             State::Selecting => "dhcpSelect",
             State::Requesting => "dhcpRequest",
+            State::ArpProbing => "dhcpArpProbe",
+            State::Declining => "dhcpDecline",
             State::Bound => "dhcpBound",
             State::Renewing => "dhcpRenew",
             State::Rebinding => "dhcpRebind",
+            State::Rebooting => "dhcpReboot",
         }
     }
 
@@ -142,10 +338,17 @@ impl DhcpClient {
         self.subnet = None;
         self.gateway = None;
         self.lease_sec = None;
-        self.dns = None;
+        self.dns_servers = [None; DNS_SERVER_COUNT];
+        self.routes = [None; ROUTE_COUNT];
+        self.search_domains = [DomainName::new_blank(); SEARCH_DOMAIN_COUNT];
         self.timer_t1.clear();
         self.timer_t2.clear();
         self.timer_lease.clear();
+        self.arp_probe_count = 0;
+        self.arp_probe_timer.clear();
+        self.pending_lease_sec = None;
+        self.pending_t1_sec = None;
+        self.pending_t2_sec = None;
     }
 
     /// Reset to refelct a state transition to halted (this means something went wrong)
@@ -162,6 +365,30 @@ impl DhcpClient {
         self.halt_and_reset();
     }
 
+    /// Give back the current lease before the event loop powers down Wi-Fi, so the server's
+    /// pool isn't held until the lease naturally expires across repeated sleep/wake cycles.
+    /// Only sends DHCPRELEASE from `Bound`/`Renewing`/`Rebinding` with a full binding (`sid`,
+    /// `ip`, `gateway_mac`); any other state has nothing worth handing back. Either way this
+    /// transitions to `Halted`, same as `handle_link_drop`.
+    pub fn release(&mut self) -> PacketNeeded {
+        let packet = match self.state {
+            State::Bound | State::Renewing | State::Rebinding => {
+                match (self.sid, self.ip, self.gateway_mac) {
+                    (Some(sid), Some(ip), Some(_)) => {
+                        self.release_ip = Some(ip);
+                        self.release_sid = Some(sid);
+                        logln!(LL::Debug, "DhcpRelease");
+                        PacketNeeded::Release
+                    }
+                    _ => PacketNeeded::None,
+                }
+            }
+            _ => PacketNeeded::None,
+        };
+        self.halt_and_reset();
+        packet
+    }
+
     /// Feed the state machine some entropy so it can start at INIT with new random hostname and xid.
     /// Also, save some entropy for generating randomized exponential backoff delays for retries.
     pub fn begin_at_init(&mut self, entropy: [u32; 5]) {
@@ -174,6 +401,20 @@ impl DhcpClient {
         self.reset_bindings();
     }
 
+    /// Feed the state machine some entropy so it can start at `State::Rebooting` and
+    /// fast-reacquire `last_ip`, a lease cached from a previous session (e.g. before a Wi-Fi
+    /// power cycle), instead of a full DISCOVER/OFFER round trip. See `State::Rebooting`.
+    pub fn begin_at_init_reboot(&mut self, last_ip: u32, entropy: [u32; 5]) {
+        self.entropy = [entropy[0], entropy[1]];
+        self.hostname.randomize_if_unset(entropy[2], entropy[3]);
+        self.reset_bindings();
+        self.ip = Some(last_ip);
+        self.state = State::Rebooting;
+        self.secs.reset();
+        self.retry = RetryTimer::new_halted();
+        self.xid = Some(entropy[4]);
+    }
+
     /// Update the state machine and return what packet type, if any, needs to be sent.
     ///
     /// This is weirdly sliced up because of the need to interoperate with sl_wfx_* C FFI
@@ -199,8 +440,9 @@ impl DhcpClient {
     ///
     pub fn cycle_clock(&mut self) -> PacketNeeded {
         // See state transition diagram at RFC 2131 § 4.4 DHCP client behavior
-        // InitRebooting and Rebooting are intentionally omitted.
-        // Halted is power-up state or result of DHCPNAK from Renewing or Rebinding
+        // Halted is power-up state, result of DHCPNAK from Renewing or Rebinding, or an
+        // explicit `handle_link_drop`/`wlan leave`. A fully expired lease with no ACK from
+        // anyone is handled separately below, by falling back to Selecting instead.
         match self.state {
             State::Halted => PacketNeeded::None,
             State::Init => {
@@ -244,6 +486,45 @@ impl DhcpClient {
                     PacketNeeded::Request
                 }
             },
+            State::ArpProbing => match self.arp_probe_timer.status() {
+                CountdownStatus::NotStarted => {
+                    self.arp_probe_count = 1;
+                    self.arp_probe_timer.start(ARP_PROBE_INTERVAL_MS);
+                    PacketNeeded::ArpProbe
+                }
+                CountdownStatus::NotDone => PacketNeeded::None,
+                CountdownStatus::Done => {
+                    if self.arp_probe_count < ARP_PROBE_COUNT {
+                        self.arp_probe_count += 1;
+                        self.arp_probe_timer.start(ARP_PROBE_INTERVAL_MS);
+                        PacketNeeded::ArpProbe
+                    } else {
+                        // No conflicting reply showed up in any probe window: safe to bind.
+                        match self.pending_lease_sec.take() {
+                            Some(lease_sec) => self.enter_bound(
+                                lease_sec,
+                                self.pending_t1_sec.take(),
+                                self.pending_t2_sec.take(),
+                            ),
+                            // Shouldn't happen -- Requesting always sets pending_lease_sec
+                            // before entering ArpProbing -- but fall back to a clean restart
+                            // rather than binding with no lease timers armed.
+                            None => self.halt_and_reset(),
+                        }
+                        PacketNeeded::None
+                    }
+                }
+            },
+            State::Declining => {
+                self.reset_bindings();
+                self.state = State::Init;
+                // There's no Bound state to fall from here, but the host still needs to
+                // hear that the address it was about to get handed isn't happening --
+                // reuses the same notification a dropped binding uses.
+                self.state_change_event_latch = Some(DhcpEvent::ChangedToHalted);
+                logln!(LL::Debug, "DhcpDecline");
+                PacketNeeded::Decline
+            }
             State::Bound => match self.timer_t1.status() {
                 CountdownStatus::Done => {
                     self.timer_t1.clear();
@@ -277,12 +558,19 @@ impl DhcpClient {
             State::Rebinding => {
                 match self.timer_lease.status() {
                     CountdownStatus::Done => {
-                        // This is bad. Lease is up. Unable to get a new one.
+                        // Lease is fully up with no ACK from anyone. RFC 2131 has the
+                        // client fall back to starting a fresh Discover/Offer/Request/Ack
+                        // cycle rather than sitting idle -- do the same setup the Init arm
+                        // above does and land directly in Selecting. The host still hears
+                        // about the dropped binding via the same event it gets from an
+                        // explicit halt, since its address/route config is stale either way.
                         self.reset_bindings();
-                        self.state = State::Halted;
+                        self.secs.start();
+                        self.retry = RetryTimer::new_first_random(self.entropy[0]);
+                        self.state = State::Selecting;
                         self.state_change_event_latch = Some(DhcpEvent::ChangedToHalted);
-                        logln!(LL::Debug, "DhcpLeaseExpire");
-                        PacketNeeded::None
+                        logln!(LL::Debug, "DhcpLeaseExpireRestart");
+                        PacketNeeded::Discover
                     }
                     _ => match self.retry.status() {
                         RetryStatus::Halted | RetryStatus::TimerRunning => PacketNeeded::None,
@@ -294,10 +582,39 @@ impl DhcpClient {
                     },
                 }
             }
+            State::Rebooting => match self.secs.elapsed_s() {
+                Err(StopwatchErr::NotStarted) => {
+                    // First cycle since `begin_at_init_reboot`: fire the INIT-REBOOT
+                    // DHCPREQUEST right away, same as the Init arm does for Discover.
+                    self.secs.start();
+                    self.retry = RetryTimer::new_first_random(self.entropy[0]);
+                    logln!(LL::Debug, "DhcpRebootReq");
+                    PacketNeeded::Request
+                }
+                _ => match self.retry.status() {
+                    RetryStatus::Halted => {
+                        // No ACK/NAK within the retry budget: give up INIT-REBOOT and fall
+                        // back to a fresh Discover/Offer/Request/Ack cycle, same as the
+                        // Rebinding lease-expiry case above.
+                        self.reset_bindings();
+                        self.secs.start();
+                        self.retry = RetryTimer::new_first_random(self.entropy[0]);
+                        self.state = State::Selecting;
+                        logln!(LL::Debug, "DhcpRebootTimeout");
+                        PacketNeeded::Discover
+                    }
+                    RetryStatus::TimerRunning => PacketNeeded::None,
+                    RetryStatus::TimerExpired => {
+                        logln!(LL::Debug, "DhcpRebootRetry");
+                        self.retry.schedule_next(self.entropy[0]);
+                        PacketNeeded::Request
+                    }
+                },
+            },
         }
     }
 
-    /// Handle DHCPOFFER event: transaction ID, server ID, IP, gateway IP, subnet mask, DNS server
+    /// Handle DHCPOFFER event: transaction ID, server ID, IP, gateway IP, subnet mask, DNS servers
     pub fn handle_offer(
         &mut self,
         sid: u32,
@@ -306,7 +623,9 @@ impl DhcpClient {
         gwm: &[u8; 6],
         ls: u32,
         sn: u32,
-        dns: u32,
+        dns_servers: [Option<u32>; DNS_SERVER_COUNT],
+        routes: [Option<(u32, u8, u32)>; ROUTE_COUNT],
+        search_domains: [DomainName; SEARCH_DOMAIN_COUNT],
     ) {
         logln!(LL::Debug, "DhcpOffer");
         match self.state {
@@ -320,60 +639,156 @@ impl DhcpClient {
                 self.gateway_mac = Some(*gwm);
                 self.lease_sec = Some(ls);
                 self.subnet = Some(sn);
-                self.dns = Some(dns);
+                self.dns_servers = dns_servers;
+                self.routes = routes;
+                self.search_domains = search_domains;
                 // Print results to the log
                 self.log_bindings();
             }
             State::Requesting => (),
+            State::ArpProbing => (),
+            State::Declining => (),
             State::Bound => (),
             State::Renewing => (),
             State::Rebinding => (),
+            State::Rebooting => (),
+        }
+    }
+
+    /// Return the default-route next hop to actually use: per RFC 3442, a classless static
+    /// route (option 121) with a zero-length destination prefix is itself the server's
+    /// intended default route and takes priority over plain `gateway` (option 3) when both
+    /// are present. Falls back to `gateway` when `routes` has no such entry, which covers
+    /// both "option 121 absent" and "option 121 present but doesn't include a default route".
+    pub fn effective_gateway(&self) -> Option<u32> {
+        for (_dest, prefix_len, next_hop) in self.routes.iter().flatten() {
+            if *prefix_len == 0 {
+                return Some(*next_hop);
+            }
         }
+        self.gateway
+    }
+
+    /// Seconds left until this lease's next renewal event -- `timer_t1` while `Bound`,
+    /// `timer_t2` while `Renewing`, or the lease timer itself while `Rebinding` (there's no
+    /// later timer to count down to once T2 has already passed). `None` outside those three
+    /// states, since none of those timers are running otherwise.
+    pub fn renew_in_s(&self) -> Option<u32> {
+        match self.state {
+            State::Bound => self.timer_t1.remaining_s(),
+            State::Renewing => self.timer_t2.remaining_s(),
+            State::Rebinding => self.timer_lease.remaining_s(),
+            _ => None,
+        }
+    }
+
+    /// Seconds left until the current lease itself expires, regardless of renewal state.
+    /// `None` if there's no lease bound (`timer_lease` isn't running).
+    pub fn lease_remaining_s(&self) -> Option<u32> {
+        self.timer_lease.remaining_s()
     }
 
     /// Print {IP, gateway, netmask, DNS} bindings to debug log
     pub fn log_bindings(&self) {
-        match (self.ip, self.gateway, self.lease_sec, self.subnet, self.dns) {
+        match (
+            self.ip,
+            self.gateway,
+            self.lease_sec,
+            self.subnet,
+            self.dns_servers[0],
+        ) {
             (Some(ip), Some(gateway), Some(lease), Some(subnet), Some(dns)) => {
                 logln!(LL::Debug, " IP    {:08X}", ip);
                 logln!(LL::Debug, " Gtwy  {:08X}", gateway);
                 logln!(LL::Debug, " Lease {:08X}", lease);
                 logln!(LL::Debug, " Mask  {:08X}", subnet);
                 logln!(LL::Debug, " DNS   {:08X}", dns);
+                for dns_n in &self.dns_servers[1..] {
+                    if let Some(dns_n) = dns_n {
+                        logln!(LL::Debug, " DNS+  {:08X}", dns_n);
+                    }
+                }
             }
             _ => (),
         };
     }
 
-    /// Handle DHCPACK event: transaction ID, server ID
-    pub fn handle_ack(&mut self, lease_sec: u32) {
+    /// Finish transitioning into `Bound`: arm T1/T2/lease timers and latch the bind event.
+    /// `t1_sec`/`t2_sec` come from the server's Renewal Time (option 58) and Rebinding Time
+    /// (option 59) when it sent them; `None` falls back to the RFC 2131 § 4.4.5 defaults
+    /// ("TL;DR: T1=0.5*lease, T2=0.875*lease"). Shared by `handle_ack`'s
+    /// `Renewing`/`Rebinding` arms, which skip ARP probing (see `State::ArpProbing`), and
+    /// `cycle_clock`'s `ArpProbing` arm once a probe window elapses with no conflicting reply.
+    fn enter_bound(&mut self, lease_sec: u32, t1_sec: Option<u32>, t2_sec: Option<u32>) {
+        let lease_sec = match self.max_lease_duration {
+            Some(max_sec) => lease_sec.min(max_sec),
+            None => lease_sec,
+        };
+        self.lease_sec = Some(lease_sec);
+        // Default T1 to lease_sec * 0.5 when the server didn't send option 58.
+        let t1 = t1_sec.unwrap_or(lease_sec >> 1);
+        self.timer_t1.start_s(t1);
+        // Default T2 to approximately lease_sec * 0.875 when the server didn't send option 59.
+        // (8/7=0.875 and >>3 is equivalent to integer /8)
+        let t2 = t2_sec.unwrap_or(((lease_sec as u64 * 7) >> 3) as u32);
+        self.timer_t2.start_s(t2);
+        // Set lease timer for 0.937 of the full lease interval (allow margin for possibly slow clock)
+        let lease = ((lease_sec as u64 * 15) >> 4) as u32;
+        self.timer_lease.start_s(lease);
+        self.state = State::Bound;
+        self.state_change_event_latch = Some(DhcpEvent::ChangedToBound);
+        logln!(LL::Debug, "DhcpBound");
+    }
+
+    /// Handle DHCPACK event: transaction ID, server ID, lease time, and the server's
+    /// Renewal/Rebinding Time (options 58/59), if it sent them.
+    pub fn handle_ack(&mut self, lease_sec: u32, t1_sec: Option<u32>, t2_sec: Option<u32>) {
         logln!(LL::Debug, "DhcpACK");
         match self.state {
             State::Halted => (),
             State::Init => (),
             State::Selecting => (),
-            State::Requesting | State::Renewing | State::Rebinding => {
-                // See RFC 2131 § 4.4.5 "Reacquisition and expiration" for rules on
-                // calculating T1 and T2 timers. TL;DR: T1=0.5*lease, T2=0.875*lease.
-                self.lease_sec = Some(lease_sec);
-                // Set T1 timer for lease_sec * 0.5
-                let t1 = lease_sec >> 1;
-                self.timer_t1.start_s(t1);
-                // Set T2 timer for approximately lease_sec * 0.875.
-                // (8/7=0.875 and >>3 is equivalent to integer /8)
-                let t2 = ((lease_sec as u64 * 7) >> 3) as u32;
-                self.timer_t2.start_s(t2);
-                // Set lease timer for 0.937 of the full lease interval (allow margin for possibly slow clock)
-                let lease = ((lease_sec as u64 * 15) >> 4) as u32;
-                self.timer_lease.start_s(lease);
-                self.state = State::Bound;
-                self.state_change_event_latch = Some(DhcpEvent::ChangedToBound);
-                logln!(LL::Debug, "DhcpBound");
+            State::Requesting => {
+                // RFC 2131 § 2.2 / § 4.4.1: probe the offered address with ARP before
+                // committing to it, in case something else on the network has claimed it
+                // since the server handed it out. `cycle_clock` arms the probe timer and
+                // sends the probes; `handle_arp_reply` is what catches a conflict.
+                self.pending_lease_sec = Some(lease_sec);
+                self.pending_t1_sec = t1_sec;
+                self.pending_t2_sec = t2_sec;
+                self.arp_probe_count = 0;
+                self.arp_probe_timer.clear();
+                self.state = State::ArpProbing;
+                logln!(LL::Debug, "DhcpArpProbe");
+            }
+            // Rebooting, like Renewing/Rebinding, is reconfirming an address this client
+            // already probed and has been using, not accepting a fresh one -- skip ArpProbing.
+            State::Renewing | State::Rebinding | State::Rebooting => {
+                self.enter_bound(lease_sec, t1_sec, t2_sec)
             }
+            State::ArpProbing => (),
+            State::Declining => (),
             State::Bound => (),
         }
     }
 
+    /// Handle an ARP reply, or a competing ARP request (RFC 5227 § 2.1.1), seen while
+    /// `ArpProbing`, from `sender_ip` (the frame's sender protocol address).
+    /// `net::handle_arp_frame` only classifies either as `FilterBin::ArpProbeReply` -- the
+    /// only thing that reaches this method -- when it's both addressed from the address
+    /// we're probing and we're actually in `ArpProbing`, so there's nothing left to
+    /// re-check here: anything reaching this method is a conflict. RFC 2131 § 2.2 has the
+    /// client send DHCPDECLINE and restart from `Init` rather than use the address.
+    pub fn handle_arp_reply(&mut self, sender_ip: u32) {
+        if self.state != State::ArpProbing {
+            return;
+        }
+        logln!(LL::Debug, "DhcpArpConflict");
+        self.decline_ip = Some(sender_ip);
+        self.decline_sid = self.sid;
+        self.state = State::Declining;
+    }
+
     /// Handle DHCPNAK event: transaction ID, server ID
     pub fn handle_nak(&mut self) {
         logln!(LL::Debug, "DhcpNAK");
@@ -381,11 +796,13 @@ impl DhcpClient {
             State::Halted => (),
             State::Init => (),
             State::Selecting => (),
-            State::Requesting => {
+            State::Requesting | State::Rebooting => {
                 self.reset_bindings();
                 self.state = State::Init;
                 logln!(LL::Debug, "DhcpInit");
             }
+            State::ArpProbing => (),
+            State::Declining => (),
             State::Bound => (),
             State::Renewing | State::Rebinding => {
                 // This is bad. DHCP servers have probably assigned all their available leases.
@@ -443,7 +860,8 @@ impl DhcpClient {
             Err(StopwatchErr::Underflow) => return Err(0x06),
             Err(StopwatchErr::NotStarted) => return Err(0x07),
         };
-        let dhcp_secs_flags = [(secs >> 8) as u8, secs as u8, 0, 0];
+        let flags_hi: u8 = if self.broadcast { 0x80 } else { 0 };
+        let dhcp_secs_flags = [(secs >> 8) as u8, secs as u8, flags_hi, 0];
         let ciaddr_bytes = ciaddr.to_be_bytes();
         let dhcp_ci_yi_si_gi = ciaddr_bytes.iter().chain(zero.iter().cycle().take(12));
         let dhcp_chaddr = src_mac.iter().chain(zero.iter().cycle().take(10));
@@ -484,19 +902,23 @@ impl DhcpClient {
         let ciaddr = 0u32;
         let ip_src = 0u32;
         let ip_dst = 0xffffffffu32;
+        // No usable unicast address yet: ask the server to broadcast its OFFER.
+        self.broadcast = true;
         let header_bytes =
             self.build_dhcp_headers(&mut pbuf, src_mac, &dst_mac, ciaddr, ip_id, ip_src, ip_dst)?;
 
         let zero = [0u8];
-        // DHCP options part 1: magic cookie, 53_type, 55_paramRequestList, 57_maxMsgSize, 61_clientId
-        let dopt1 = [
-            0x63 as u8, 0x82, 0x53, 0x63, 53, 1, 1, 55, 7, 1, 121, 3, 6, 15, 119, 252, 57, 2, 0x05,
-            0xdc, 61, 7, 1,
-        ];
-        // Part 2: chain source MAC as Client ID to finish option 61
-        let dopt2 = src_mac.iter();
-        // Part 3: 51_IpLeaseTime, 12_hostname
-        let dopt3 = [
+        // DHCP options part 1: magic cookie, 53_type
+        let dopt1 = [0x63 as u8, 0x82, 0x53, 0x63, 53, 1, 1];
+        // Part 2: 55_paramRequestList header, driven by `set_param_request_list`
+        let prl = self.param_request_list();
+        let dopt2 = [55u8, prl.len() as u8];
+        // Part 3: 57_maxMsgSize, 61_clientId header
+        let dopt3 = [57u8, 2, 0x05, 0xdc, 61, 7, 1];
+        // Part 4: chain source MAC as Client ID to finish option 61
+        let dopt4 = src_mac.iter();
+        // Part 5: 51_IpLeaseTime, 12_hostname
+        let dopt5 = [
             51u8,
             4,
             0x00,
@@ -506,17 +928,20 @@ impl DhcpClient {
             12,
             self.hostname.len() as u8,
         ];
-        // Part 4: chain hostname to finish option 12
-        let dopt4 = self.hostname.as_bytes().iter();
-        // Part 5: 255_end
-        let dopt5 = [255u8];
+        // Part 6: chain hostname to finish option 12
+        let dopt6 = self.hostname.as_bytes().iter();
+        // Part 7: 255_end
+        let dopt7 = [255u8];
         let pad = zero.iter().cycle();
         let dhcp_opts_it = dopt1
             .iter()
-            .chain(dopt2)
+            .chain(dopt2.iter())
+            .chain(prl.iter())
             .chain(dopt3.iter())
             .chain(dopt4)
             .chain(dopt5.iter())
+            .chain(dopt6)
+            .chain(dopt7.iter())
             .chain(pad);
         for (dst, src) in pbuf[header_bytes..].iter_mut().zip(dhcp_opts_it) {
             *dst = *src;
@@ -555,73 +980,227 @@ impl DhcpClient {
         let mut ciaddr: u32 = 0;
         let mut ip_src: u32 = 0;
         let mut ip_dst: u32 = 0xffffffff;
-        match (self.gateway_mac, self.ip, self.sid) {
-            (Some(gateway_mac), Some(ip), Some(sid)) => match request_type {
-                RequestType::Renew => {
-                    // RFC 2131 says Request packet for Renewing must be unicast
+        match request_type {
+            // RFC 2131 § 4.3.2 INIT-REBOOT: broadcast, ciaddr still zero (we haven't
+            // confirmed the cached address is ours again yet), identified by option 50
+            // instead. Requires only the cached IP `begin_at_init_reboot` stashed in `ip`.
+            RequestType::Rebooting => {
+                if self.ip.is_none() {
+                    return Err(0x0A);
+                }
+                self.broadcast = true;
+            }
+            RequestType::Renew => match (self.gateway_mac, self.ip, self.sid) {
+                (Some(gateway_mac), Some(ip), Some(sid)) => {
+                    // RFC 2131 says Request packet for Renewing must be unicast; we already
+                    // have a bound ciaddr to receive a unicast reply on.
                     dst_mac = gateway_mac;
                     ip_src = ip;
                     ip_dst = sid;
                     ciaddr = ip;
+                    self.broadcast = false;
                 }
-                RequestType::Rebind => {
+                _ => return Err(0x0A),
+            },
+            RequestType::Rebind => match (self.gateway_mac, self.ip, self.sid) {
+                (Some(_), Some(ip), Some(_)) => {
                     // RFC 2131 says Request packet for Rebinding must be broadcast
                     ciaddr = ip;
+                    self.broadcast = true;
                 }
-                _ => (),
+                _ => return Err(0x0A),
+            },
+            // Initial post-OFFER Request: still no confirmed address, same as Discover.
+            RequestType::Discover => match (self.gateway_mac, self.ip, self.sid) {
+                (Some(_), Some(_), Some(_)) => self.broadcast = true,
+                _ => return Err(0x0A),
             },
-            _ => return Err(0x0A),
         };
         let header_bytes =
             self.build_dhcp_headers(&mut pbuf, src_mac, &dst_mac, ciaddr, ip_id, ip_src, ip_dst)?;
 
         let zero = [0u8];
-        // DHCP options part 1: magic cookie, 53_type, 55_paramRequestList, 57_maxMsgSize, 61_clientId
-        let dopt1 = [
-            0x63 as u8, 0x82, 0x53, 0x63, 53, 1, 3, 55, 7, 1, 121, 3, 6, 15, 119, 252, 57, 2, 0x05,
-            0xdc, 61, 7, 1,
-        ];
-        // Part 2: chain source MAC as Client ID to finish option 61
-        let dopt2 = src_mac.iter();
-        // Part 3: 50_RequestedIp, 54_ServerID
-        let ri = match self.ip {
+        // DHCP options part 1: magic cookie, 53_type
+        let dopt1 = [0x63 as u8, 0x82, 0x53, 0x63, 53, 1, 3];
+        // Part 2: 55_paramRequestList header, driven by `set_param_request_list`
+        let prl = self.param_request_list();
+        let dopt2 = [55u8, prl.len() as u8];
+        // Part 3: 57_maxMsgSize, 61_clientId header
+        let dopt3 = [57u8, 2, 0x05, 0xdc, 61, 7, 1];
+        // Part 4: chain source MAC as Client ID to finish option 61
+        let dopt4 = src_mac.iter();
+        // Part 5: 50_RequestedIp [+ 54_ServerID, except for Renew/Rebind which per RFC 2131
+        // "MUST NOT" send either, and Rebooting's INIT-REBOOT which per RFC 2131 § 4.3.2
+        // sends only the requested IP with no server-id]. Sized for the largest case and
+        // sliced down to `dopt5_len`, so every arm below shares one array and one type.
+        let mut dopt5 = [0u8; 12];
+        let mut dopt5_len: usize = 0;
+        match request_type {
+            RequestType::Renew | RequestType::Rebind => (),
+            RequestType::Rebooting => {
+                let ri = match self.ip {
+                    Some(ip) => ip.to_be_bytes(),
+                    None => return Err(0x0B),
+                };
+                dopt5[..6].copy_from_slice(&[50, 4, ri[0], ri[1], ri[2], ri[3]]);
+                dopt5_len = 6;
+            }
+            RequestType::Discover => {
+                let ri = match self.ip {
+                    Some(ip) => ip.to_be_bytes(),
+                    None => return Err(0x0B),
+                };
+                let sid = match self.sid {
+                    Some(sid) => sid.to_be_bytes(),
+                    None => return Err(0x0C),
+                };
+                dopt5[..12].copy_from_slice(&[
+                    50, 4, ri[0], ri[1], ri[2], ri[3], 54, 4, sid[0], sid[1], sid[2], sid[3],
+                ]);
+                dopt5_len = 12;
+            }
+        };
+        // Part 6: 12_hostname
+        let dopt6 = [12, self.hostname.len() as u8];
+        // Part 7: chain hostname to finish option 12
+        let dopt7 = self.hostname.as_bytes().iter();
+        // Part 8: 255_end
+        let dopt8 = [255u8];
+        let pad = zero.iter().cycle();
+        let dhcp_opts_it = dopt1
+            .iter()
+            .chain(dopt2.iter())
+            .chain(prl.iter())
+            .chain(dopt3.iter())
+            .chain(dopt4)
+            .chain(dopt5[..dopt5_len].iter())
+            .chain(dopt6.iter())
+            .chain(dopt7)
+            .chain(dopt8.iter())
+            .chain(pad);
+        for (dst, src) in pbuf[header_bytes..].iter_mut().zip(dhcp_opts_it) {
+            *dst = *src;
+        }
+        // Do the checksum fixup. Note how these checksum offsets assume the minimum MAC and
+        // IP header size. On some networks (VLAN?), that assumption might cause problems.
+        let ip_csum: u16 = crate::ipv4_checksum(&pbuf);
+        for (dst, src) in pbuf[24..26].iter_mut().zip(ip_csum.to_be_bytes().iter()) {
+            *dst = *src;
+        }
+        let udp_csum: u16 = crate::ipv4_udp_checksum(&pbuf);
+        for (dst, src) in pbuf[40..42].iter_mut().zip(udp_csum.to_be_bytes().iter()) {
+            *dst = *src;
+        }
+        return Ok(pbuf.len() as u32);
+    }
+
+    /// Build a DHCPDECLINE packet for the address `handle_arp_reply` found already claimed,
+    /// so the server that offered it knows not to hand it out again. Broadcast, like
+    /// Discover, since the client has no usable address to send from. Returns Ok(data_length)
+    /// like the other `build_*_frame` functions, or Err if `decline_ip`/`decline_sid` (set by
+    /// `handle_arp_reply`) aren't there to fill in options 50/54, or `pbuf` is too short.
+    pub fn build_decline_frame<'a>(
+        &mut self,
+        mut pbuf: &'a mut [u8],
+        src_mac: &[u8; 6],
+        ip_id: u16,
+    ) -> Result<u32, u8> {
+        if pbuf.len() < DHCP_FRAME_LEN {
+            return Err(0x0D);
+        }
+        let decline_ip = match self.decline_ip {
             Some(ip) => ip.to_be_bytes(),
-            None => return Err(0x0B),
+            None => return Err(0x0E),
         };
-        let sid = match self.sid {
+        let decline_sid = match self.decline_sid {
             Some(sid) => sid.to_be_bytes(),
-            None => return Err(0x0C),
+            None => return Err(0x0F),
         };
-        let dopt3 = [
-            50u8, 4, ri[0], ri[1], ri[2], ri[3], 54, 4, sid[0], sid[1], sid[2], sid[3],
+        // Buffer might be a full MTU, so only use what we need.
+        // (this determines number of loop iterations below)
+        pbuf = &mut pbuf[..DHCP_FRAME_LEN];
+        // Fill in the MAC, IP, UDP, and BOOTP headers for a DHCP packet
+        let dst_mac = [255u8, 255, 255, 255, 255, 255];
+        let ciaddr = 0u32;
+        let ip_src = 0u32;
+        let ip_dst = 0xffffffffu32;
+        // Broadcast like Discover: we're declining the address we'd otherwise unicast from.
+        self.broadcast = true;
+        let header_bytes =
+            self.build_dhcp_headers(&mut pbuf, src_mac, &dst_mac, ciaddr, ip_id, ip_src, ip_dst)?;
+
+        let zero = [0u8];
+        // DHCP options: magic cookie, 53_type=DECLINE(4), 50_RequestedIp, 54_ServerID, 255_end
+        let dopt1 = [
+            0x63u8, 0x82, 0x53, 0x63, 53, 1, 4, 50, 4, decline_ip[0], decline_ip[1],
+            decline_ip[2], decline_ip[3], 54, 4, decline_sid[0], decline_sid[1],
+            decline_sid[2], decline_sid[3],
         ];
-        // Part 4: 12_hostname
-        let dopt4 = [12, self.hostname.len() as u8];
-        // Part 5: chain hostname to finish option 12
-        let dopt5 = self.hostname.as_bytes().iter();
-        // Part 6: 255_end
-        let dopt6 = [255u8];
+        let dopt2 = [255u8];
         let pad = zero.iter().cycle();
-        let dhcp_opts_it = match request_type {
-            // According to RFC 2131, Request packets in the Renewing or Rebinding state
-            // "MUST NOT" fill in the requested IP or server ID options.
-            RequestType::Renew | RequestType::Rebind => dopt1
-                .iter()
-                .chain(dopt2)
-                .chain([].iter()) // omit part 3 (requested IP & server ID) to follow RFC 2131
-                .chain(dopt4.iter())
-                .chain(dopt5)
-                .chain(dopt6.iter())
-                .chain(pad),
-            RequestType::Discover => dopt1
-                .iter()
-                .chain(dopt2)
-                .chain(dopt3.iter())
-                .chain(dopt4.iter())
-                .chain(dopt5)
-                .chain(dopt6.iter())
-                .chain(pad),
+        let dhcp_opts_it = dopt1.iter().chain(dopt2.iter()).chain(pad);
+        for (dst, src) in pbuf[header_bytes..].iter_mut().zip(dhcp_opts_it) {
+            *dst = *src;
+        }
+        // Do the checksum fixup. Note how these checksum offsets assume the minimum MAC and
+        // IP header size. On some networks (VLAN?), that assumption might cause problems.
+        let ip_csum: u16 = crate::ipv4_checksum(&pbuf);
+        for (dst, src) in pbuf[24..26].iter_mut().zip(ip_csum.to_be_bytes().iter()) {
+            *dst = *src;
+        }
+        let udp_csum: u16 = crate::ipv4_udp_checksum(&pbuf);
+        for (dst, src) in pbuf[40..42].iter_mut().zip(udp_csum.to_be_bytes().iter()) {
+            *dst = *src;
+        }
+        return Ok(pbuf.len() as u32);
+    }
+
+    /// Build a DHCPRELEASE packet for the lease `release` is handing back. Unicast to the
+    /// server (dst MAC/IP = `gateway_mac`/`release_sid`), `ciaddr` = our IP, option 53 =
+    /// DHCPRELEASE(7), option 54 = server id, and per RFC 2131 § 4.4.4 no requested-IP option.
+    /// Returns Ok(data_length) like the other `build_*_frame` functions, or Err if
+    /// `release_ip`/`release_sid` (set by `release`) or `gateway_mac` (kept from the offer)
+    /// aren't there to fill in the headers and option 54, or `pbuf` is too short.
+    pub fn build_release_frame<'a>(
+        &mut self,
+        mut pbuf: &'a mut [u8],
+        src_mac: &[u8; 6],
+        ip_id: u16,
+    ) -> Result<u32, u8> {
+        if pbuf.len() < DHCP_FRAME_LEN {
+            return Err(0x10);
+        }
+        let gateway_mac = match self.gateway_mac {
+            Some(gwm) => gwm,
+            None => return Err(0x11),
+        };
+        let ip = match self.release_ip {
+            Some(ip) => ip,
+            None => return Err(0x12),
+        };
+        let sid = match self.release_sid {
+            Some(sid) => sid,
+            None => return Err(0x13),
         };
+        // Buffer might be a full MTU, so only use what we need.
+        // (this determines number of loop iterations below)
+        pbuf = &mut pbuf[..DHCP_FRAME_LEN];
+        // Fill in the MAC, IP, UDP, and BOOTP headers for a DHCP packet. Unicast to the
+        // server from our bound ciaddr, so no broadcast reply is needed.
+        self.broadcast = false;
+        let header_bytes =
+            self.build_dhcp_headers(&mut pbuf, src_mac, &gateway_mac, ip, ip_id, ip, sid)?;
+
+        let zero = [0u8];
+        let sid_bytes = sid.to_be_bytes();
+        // DHCP options: magic cookie, 53_type=RELEASE(7), 54_ServerID, 255_end
+        let dopt1 = [
+            0x63u8, 0x82, 0x53, 0x63, 53, 1, 7, 54, 4, sid_bytes[0], sid_bytes[1], sid_bytes[2],
+            sid_bytes[3],
+        ];
+        let dopt2 = [255u8];
+        let pad = zero.iter().cycle();
+        let dhcp_opts_it = dopt1.iter().chain(dopt2.iter()).chain(pad);
         for (dst, src) in pbuf[header_bytes..].iter_mut().zip(dhcp_opts_it) {
             *dst = *src;
         }
@@ -662,7 +1241,11 @@ impl DhcpClient {
             return FilterBin::DropDhcp;
         }
         match self.state {
-            State::Selecting | State::Requesting | State::Renewing | State::Rebinding => (),
+            State::Selecting
+            | State::Requesting
+            | State::Renewing
+            | State::Rebinding
+            | State::Rebooting => (),
             // No need to parse frame if state machine is not in state that expects a server response
             _ => return FilterBin::DropDhcp,
         };
@@ -700,18 +1283,28 @@ impl DhcpClient {
                     opts.gateway,
                     opts.ip_lease_time,
                     opts.subnet,
-                    opts.dns,
+                    opts.dns_servers[0],
                 ) {
-                    (Some(DHCPOFFER), Some(sid), Some(gw), Some(ilt), Some(sn), Some(dns)) => {
+                    (Some(DHCPOFFER), Some(sid), Some(gw), Some(ilt), Some(sn), Some(_dns)) => {
                         let mut gateway_mac: [u8; 6] = [0; 6];
                         for (dst, src) in gateway_mac.iter_mut().zip(&data[6..12]) {
                             *dst = *src;
                         }
-                        self.handle_offer(sid, yiaddr, gw, &gateway_mac, ilt, sn, dns);
+                        self.handle_offer(
+                            sid,
+                            yiaddr,
+                            gw,
+                            &gateway_mac,
+                            ilt,
+                            sn,
+                            opts.dns_servers,
+                            opts.routes,
+                            opts.search_domains,
+                        );
                         return FilterBin::Dhcp;
                     }
                     (Some(DHCPACK), _, _, Some(ilt), _, _) => {
-                        self.handle_ack(ilt);
+                        self.handle_ack(ilt, opts.t1_sec, opts.t2_sec);
                         return FilterBin::Dhcp;
                     }
                     (Some(DHCPNAK), _, _, _, _, _) => {
@@ -789,6 +1382,10 @@ fn parse_options(options: &[u8]) -> Result<DhcpOption, u8> {
             O_SUBNET_MASK => d.parse_subnet(data, 0x14)?,
             O_GATEWAY_LIST => d.parse_gateway(data, 0x15)?,
             O_DNS_LIST => d.parse_dns(data, 0x16)?,
+            O_CLASSLESS_ROUTES => d.parse_classless_routes(data, 0x17)?,
+            O_DOMAIN_SEARCH => d.append_domain_search(data, 0x18)?,
+            O_RENEWAL_TIME => d.parse_renewal_time(data, 0x1a)?,
+            O_REBINDING_TIME => d.parse_rebinding_time(data, 0x1b)?,
             // Ignore data for other options
             _ => (),
         };
@@ -801,6 +1398,7 @@ fn parse_options(options: &[u8]) -> Result<DhcpOption, u8> {
             return Err(0x03);
         }
     }
+    d.decode_domain_search(0x19)?;
     return Ok(d);
 }
 
@@ -811,7 +1409,19 @@ struct DhcpOption {
     pub ip_lease_time: Option<u32>,
     pub subnet: Option<u32>,
     pub gateway: Option<u32>,
-    pub dns: Option<u32>,
+    pub dns_servers: [Option<u32>; DNS_SERVER_COUNT],
+    pub routes: [Option<(u32, u8, u32)>; ROUTE_COUNT],
+    pub search_domains: [DomainName; SEARCH_DOMAIN_COUNT],
+    /// Renewal Time (option 58, T1) and Rebinding Time (option 59, T2), if the server sent
+    /// them explicitly. `enter_bound` falls back to the RFC 2131 § 4.4.5 defaults (0.5x and
+    /// 0.875x the lease) when either is absent.
+    pub t1_sec: Option<u32>,
+    pub t2_sec: Option<u32>,
+    /// Raw option-119 data, concatenated across every instance RFC 3396 allows a server to
+    /// split it into; decoded into `search_domains` by `decode_domain_search` once the whole
+    /// options block has been scanned and every instance has been appended.
+    search_domain_buf: [u8; MAX_DOMAIN_SEARCH_BUF],
+    search_domain_buf_len: usize,
 }
 impl DhcpOption {
     /// Return a new empty DhcpOption struct instance
@@ -822,7 +1432,13 @@ impl DhcpOption {
             ip_lease_time: None,
             subnet: None,
             gateway: None,
-            dns: None,
+            dns_servers: [None; DNS_SERVER_COUNT],
+            routes: [None; ROUTE_COUNT],
+            search_domains: [DomainName::new_blank(); SEARCH_DOMAIN_COUNT],
+            t1_sec: None,
+            t2_sec: None,
+            search_domain_buf: [0; MAX_DOMAIN_SEARCH_BUF],
+            search_domain_buf_len: 0,
         }
     }
 
@@ -882,12 +1498,171 @@ impl DhcpOption {
         Ok(())
     }
 
-    /// Parse _only_the_first_ DNS server from a list of one or more DNS server IP addresses
-    /// CAUTION: Ignoring possibility of more than one DNS server might cause trouble some day.
+    /// Parse Renewal Time (RFC 2132 option 58, T1).
+    pub fn parse_renewal_time(&mut self, data: &[u8], e: u8) -> Result<(), u8> {
+        self.t1_sec = Some(Self::parse_first_be_u32(data, e)?);
+        Ok(())
+    }
+
+    /// Parse Rebinding Time (RFC 2132 option 59, T2).
+    pub fn parse_rebinding_time(&mut self, data: &[u8], e: u8) -> Result<(), u8> {
+        self.t2_sec = Some(Self::parse_first_be_u32(data, e)?);
+        Ok(())
+    }
+
+    /// Parse as many DNS servers as fit in `dns_servers` off a list of one or more DNS
+    /// server IP addresses (RFC 2132 option 6). Entries past `DNS_SERVER_COUNT` are still
+    /// ignored: a primary plus a couple of fallbacks is enough for a resolver.
     pub fn parse_dns(&mut self, data: &[u8], e: u8) -> Result<(), u8> {
-        self.dns = Some(Self::parse_first_be_u32(data, e)?);
+        if (data.len() == 0) || ((data.len() & 3) != 0) {
+            // Data is not a valid length
+            return Err(e);
+        }
+        for (slot, chunk) in self.dns_servers.iter_mut().zip(data.chunks_exact(4)) {
+            *slot = Some(u32::from_be_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]));
+        }
+        Ok(())
+    }
+
+    /// Parse the classless static route option (RFC 3442, option 121) into `routes`. Each
+    /// route is a "destination descriptor" byte giving the prefix length in bits (0-32),
+    /// followed by only the `ceil(prefix_len/8)` significant octets of the destination (a
+    /// default route has prefix_len 0 and contributes none), followed by a 4-byte next-hop
+    /// gateway; repeat until the option's data is consumed. Entries past `ROUTE_COUNT` are
+    /// dropped, same tradeoff `parse_dns` makes for extra DNS servers.
+    pub fn parse_classless_routes(&mut self, data: &[u8], e: u8) -> Result<(), u8> {
+        let mut i: usize = 0;
+        let mut slot: usize = 0;
+        while i < data.len() {
+            let prefix_len = data[i];
+            if prefix_len > 32 {
+                return Err(e);
+            }
+            i += 1;
+            let dest_octets = ((prefix_len as usize) + 7) / 8;
+            if i + dest_octets + 4 > data.len() {
+                return Err(e);
+            }
+            let mut dest_bytes = [0u8; 4];
+            dest_bytes[..dest_octets].copy_from_slice(&data[i..i + dest_octets]);
+            i += dest_octets;
+            let next_hop = u32::from_be_bytes([data[i], data[i + 1], data[i + 2], data[i + 3]]);
+            i += 4;
+            if slot < self.routes.len() {
+                self.routes[slot] = Some((u32::from_be_bytes(dest_bytes), prefix_len, next_hop));
+                slot += 1;
+            }
+        }
         Ok(())
     }
+
+    /// Append one instance's worth of raw option-119 (Domain Search, RFC 3397) data to
+    /// `search_domain_buf`. RFC 3396 lets a server split a long option value across several
+    /// instances of the same tag, to be concatenated back into one byte stream before
+    /// decoding -- this just accumulates that stream; `decode_domain_search` does the actual
+    /// label/pointer decode once parsing the whole options block is done.
+    pub fn append_domain_search(&mut self, data: &[u8], e: u8) -> Result<(), u8> {
+        let end = self.search_domain_buf_len + data.len();
+        if end > self.search_domain_buf.len() {
+            return Err(e);
+        }
+        self.search_domain_buf[self.search_domain_buf_len..end].copy_from_slice(data);
+        self.search_domain_buf_len = end;
+        Ok(())
+    }
+
+    /// Decode as many dot-joined domain names as fit in `search_domains` out of
+    /// `search_domain_buf` -- a run of RFC 1035 length-prefixed labels terminated by either a
+    /// zero byte or a compression pointer, repeated back to back for each name.
+    ///
+    /// A compression pointer is two bytes with the top two bits of the first byte set; the
+    /// low 14 bits give an offset to resume decoding labels from, elsewhere in the buffer.
+    /// The only loop-safety check this needs is "a pointer's target must be strictly less
+    /// than where the current name started, or than the previous pointer's target once one
+    /// has been followed": since decoding only ever moves forward except for a pointer jump,
+    /// and every jump lands strictly before every offset already visited for this name,
+    /// no offset can ever be revisited, which both bounds the total work and rejects
+    /// self-pointers and forward pointers in one comparison.
+    pub fn decode_domain_search(&mut self, e: u8) -> Result<(), u8> {
+        let buf = &self.search_domain_buf[..self.search_domain_buf_len];
+        let mut name_start: usize = 0;
+        let mut slot: usize = 0;
+        while name_start < buf.len() && slot < self.search_domains.len() {
+            let mut name = DomainName::new_blank();
+            let mut pos = name_start;
+            let mut pointer_limit = pos;
+            loop {
+                if pos >= buf.len() {
+                    return Err(e);
+                }
+                let b = buf[pos];
+                if b & 0xc0 == 0xc0 {
+                    if pos + 1 >= buf.len() {
+                        return Err(e);
+                    }
+                    let offset = (((b & 0x3f) as usize) << 8) | (buf[pos + 1] as usize);
+                    if offset >= pointer_limit {
+                        return Err(e);
+                    }
+                    pointer_limit = offset;
+                    pos = offset;
+                    continue;
+                }
+                if pos + 1 + (b as usize) > buf.len() {
+                    return Err(e);
+                }
+                pos += 1;
+                if b == 0 {
+                    break;
+                }
+                if name.length != 0 {
+                    if name.length >= name.buffer.len() {
+                        return Err(e);
+                    }
+                    name.buffer[name.length] = b'.';
+                    name.length += 1;
+                }
+                let label = &buf[pos..pos + (b as usize)];
+                if name.length + label.len() > name.buffer.len() {
+                    return Err(e);
+                }
+                name.buffer[name.length..name.length + label.len()].copy_from_slice(label);
+                name.length += label.len();
+                pos += b as usize;
+            }
+            self.search_domains[slot] = name;
+            slot += 1;
+            // Advance past this name in the un-pointer-followed stream, not wherever the last
+            // pointer jump left `pos` -- a pointer always terminates its name (RFC 1035 §
+            // 4.1.4), so the next name starts right after the two-byte pointer that ended
+            // this one, or right after this one's own terminating zero byte.
+            name_start = Self::next_name_start(name_start, buf, e)?;
+        }
+        Ok(())
+    }
+
+    /// Walk one name starting at `start` the same way `decode_domain_search` does, but only
+    /// to find where it ends in the buffer (i.e. without following pointers into earlier
+    /// data), so the caller can resume scanning for the next name right after it.
+    fn next_name_start(start: usize, buf: &[u8], e: u8) -> Result<usize, u8> {
+        let mut pos = start;
+        loop {
+            if pos >= buf.len() {
+                return Err(e);
+            }
+            let b = buf[pos];
+            if b & 0xc0 == 0xc0 {
+                return Ok(pos + 2);
+            }
+            if pos + 1 + (b as usize) > buf.len() {
+                return Err(e);
+            }
+            pos += 1 + (b as usize);
+            if b == 0 {
+                return Ok(pos);
+            }
+        }
+    }
 }
 
 // DHCP Option tag constants
@@ -899,6 +1674,10 @@ const O_IP_LEASE_TIME: u8 = 51;
 const O_SUBNET_MASK: u8 = 1;
 const O_GATEWAY_LIST: u8 = 3;
 const O_DNS_LIST: u8 = 6;
+const O_CLASSLESS_ROUTES: u8 = 121;
+const O_DOMAIN_SEARCH: u8 = 119;
+const O_RENEWAL_TIME: u8 = 58;
+const O_REBINDING_TIME: u8 = 59;
 
 // DHCP Option Tags:
 // 255 => End
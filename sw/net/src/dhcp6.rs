@@ -0,0 +1,353 @@
+//! This module implements the message layer and client state machine for DHCPv6 (RFC 8415):
+//! building/parsing the Solicit/Advertise/Request/Reply message bodies and driving a
+//! Solicit -> Request -> Bound state machine from them.
+//!
+//! Unlike [`crate::dhcp`], this does NOT build or parse Ethernet/IPv6/UDP frames. Sending a
+//! DHCPv6 message means a UDP datagram to the all-DHCP-relay-agents-and-servers multicast
+//! address `ff02::1:2`, which needs an IPv6 source address (this client's link-local) and a
+//! checksum over the IPv6 pseudo-header -- none of which exists yet. [`crate::ipv6`] only
+//! derives and stores addresses so far ("no ICMPv6 on the wire yet", per the crate doc's feature list), so
+//! there's no Neighbor Discovery, no IPv6 frame TX/RX path, and `handle_frame()` doesn't
+//! route `ETHERTYPE_IPV6` anywhere. Wire this up to actual frames once that lands; until
+//! then, this module's inputs/outputs are plain `&[u8]` DHCPv6 message bodies so the state
+//! machine and option codec can be written and reviewed now without faking a framing layer
+//! that would just be deleted and redone later.
+use crate::timers::{RetryStatus, RetryTimer};
+use debug::{logln, LL};
+
+// Configure Log Level (used in macro expansions)
+const LOG_LEVEL: LL = LL::Debug;
+
+/// A bare IPv6 address, same representation as [`crate::ipv6::Ipv6Addr`].
+pub type Ipv6Addr = [u8; 16];
+
+/// Max DNS servers kept from the DNS_SERVERS option, mirroring `dhcp::DNS_SERVER_COUNT`.
+const DNS_SERVER_COUNT: usize = 3;
+
+/// DUID-LL (RFC 8415 § 11.4): type 3, hardware type 1 (Ethernet), then the 6-byte MAC. The
+/// EC has no stable storage for a DUID across factory resets, and a link-layer-derived DUID
+/// needs none -- same reasoning as `ipv6::eui64_link_local` deriving an address from the MAC
+/// rather than generating and persisting one.
+pub type Duid = [u8; 10];
+
+fn duid_ll(mac: &[u8; 6]) -> Duid {
+    let mut duid = [0u8; 10];
+    duid[0..2].copy_from_slice(&3u16.to_be_bytes()); // DUID-LL
+    duid[2..4].copy_from_slice(&1u16.to_be_bytes()); // hardware type: Ethernet
+    duid[4..10].copy_from_slice(mac);
+    duid
+}
+
+// DHCPv6 message types (RFC 8415 § 7.3)
+const MSG_SOLICIT: u8 = 1;
+const MSG_ADVERTISE: u8 = 2;
+const MSG_REQUEST: u8 = 3;
+const MSG_REPLY: u8 = 7;
+
+// DHCPv6 option codes (RFC 8415 § 21)
+const OPT_CLIENTID: u16 = 1;
+const OPT_SERVERID: u16 = 2;
+const OPT_IA_NA: u16 = 3;
+const OPT_IAADDR: u16 = 5;
+const OPT_DNS_SERVERS: u16 = 23;
+
+/// DHCPv6 client states, paralleling `dhcp::State` but only as far as the initial bind --
+/// there are no Renew/Rebind/Release states.
+#[derive(Copy, Clone, PartialEq)]
+pub enum State {
+    Halted,
+    Init,
+    Soliciting,
+    Requesting,
+    Bound,
+}
+
+/// What message body, if any, `cycle_clock` needs built and sent next.
+#[derive(Copy, Clone, PartialEq)]
+pub enum PacketNeeded {
+    None,
+    Solicit,
+    Request,
+}
+
+/// Accumulates the fields this client cares about out of an Advertise or Reply's option
+/// list, built up incrementally by `parse_options` the same way `dhcp::DhcpOption` does for
+/// DHCPv4.
+struct Dhcp6Option {
+    server_duid: Option<Duid>,
+    ia_addr: Option<Ipv6Addr>,
+    preferred_lifetime: Option<u32>,
+    valid_lifetime: Option<u32>,
+    dns_servers: [Option<Ipv6Addr>; DNS_SERVER_COUNT],
+}
+impl Dhcp6Option {
+    fn new() -> Self {
+        Self {
+            server_duid: None,
+            ia_addr: None,
+            preferred_lifetime: None,
+            valid_lifetime: None,
+            dns_servers: [None; DNS_SERVER_COUNT],
+        }
+    }
+
+    /// Parse the sub-options nested inside an IA_NA (IAID, T1, T2, then sub-options),
+    /// pulling out the IA Address sub-option. Other sub-options (e.g. Status Code) are
+    /// skipped, same as unrecognized top-level options in `parse_options`.
+    fn parse_ia_na(&mut self, data: &[u8], e: u8) -> Result<(), u8> {
+        if data.len() < 12 {
+            return Err(e);
+        }
+        let mut i: usize = 12; // skip IAID (4) + T1 (4) + T2 (4)
+        while i + 4 <= data.len() {
+            let code = u16::from_be_bytes([data[i], data[i + 1]]);
+            let len = u16::from_be_bytes([data[i + 2], data[i + 3]]) as usize;
+            i += 4;
+            if i + len > data.len() {
+                return Err(e);
+            }
+            let value = &data[i..i + len];
+            if code == OPT_IAADDR {
+                if len < 24 {
+                    return Err(e);
+                }
+                let mut addr = [0u8; 16];
+                addr.copy_from_slice(&value[0..16]);
+                self.ia_addr = Some(addr);
+                self.preferred_lifetime = Some(u32::from_be_bytes([
+                    value[16], value[17], value[18], value[19],
+                ]));
+                self.valid_lifetime = Some(u32::from_be_bytes([
+                    value[20], value[21], value[22], value[23],
+                ]));
+            }
+            i += len;
+        }
+        Ok(())
+    }
+
+    fn parse_dns_servers(&mut self, data: &[u8], e: u8) -> Result<(), u8> {
+        if data.len() == 0 || data.len() % 16 != 0 {
+            return Err(e);
+        }
+        for (slot, chunk) in self.dns_servers.iter_mut().zip(data.chunks_exact(16)) {
+            let mut addr = [0u8; 16];
+            addr.copy_from_slice(chunk);
+            *slot = Some(addr);
+        }
+        Ok(())
+    }
+}
+
+/// Walk a DHCPv6 option list (`[2-byte code][2-byte length][value]`, repeated to fill
+/// `data`), same shape as `dhcp::parse_options` but with 2-byte codes/lengths instead of
+/// DHCPv4's 1-byte tag + 1-byte length.
+fn parse_options(data: &[u8]) -> Result<Dhcp6Option, u8> {
+    let mut opt = Dhcp6Option::new();
+    let mut i: usize = 0;
+    while i + 4 <= data.len() {
+        let code = u16::from_be_bytes([data[i], data[i + 1]]);
+        let len = u16::from_be_bytes([data[i + 2], data[i + 3]]) as usize;
+        i += 4;
+        if i + len > data.len() {
+            return Err(0x01);
+        }
+        let value = &data[i..i + len];
+        match code {
+            OPT_SERVERID => {
+                if len != 10 {
+                    return Err(0x02);
+                }
+                let mut duid = [0u8; 10];
+                duid.copy_from_slice(value);
+                opt.server_duid = Some(duid);
+            }
+            OPT_IA_NA => opt.parse_ia_na(value, 0x03)?,
+            OPT_DNS_SERVERS => opt.parse_dns_servers(value, 0x04)?,
+            _ => (), // CLIENTID and anything else: not needed back out of a reply
+        }
+        i += len;
+    }
+    Ok(opt)
+}
+
+/// DHCPv6 client state machine and bindings. Mirrors `dhcp::DhcpClient`'s shape (entropy
+/// for retry jitter, a `RetryTimer` driving `cycle_clock`, plain `pub` binding fields) to
+/// the extent RFC 8415's simpler Solicit/Request/Reply handshake needs.
+pub struct Dhcp6Client {
+    entropy: u32,
+    retry: RetryTimer,
+    pub state: State,
+    pub xid: Option<u32>,
+    pub client_duid: Duid,
+    pub server_duid: Option<Duid>,
+    pub address: Option<Ipv6Addr>,
+    pub preferred_lifetime: Option<u32>,
+    pub valid_lifetime: Option<u32>,
+    pub dns_servers: [Option<Ipv6Addr>; DNS_SERVER_COUNT],
+}
+impl Dhcp6Client {
+    pub const fn new() -> Self {
+        Self {
+            entropy: 0,
+            retry: RetryTimer::new_halted(),
+            state: State::Halted,
+            xid: None,
+            client_duid: [0u8; 10],
+            server_duid: None,
+            address: None,
+            preferred_lifetime: None,
+            valid_lifetime: None,
+            dns_servers: [None; DNS_SERVER_COUNT],
+        }
+    }
+
+    /// Clear all bindings, mirroring `dhcp::DhcpClient::reset_bindings`.
+    fn reset_bindings(&mut self) {
+        self.server_duid = None;
+        self.address = None;
+        self.preferred_lifetime = None;
+        self.valid_lifetime = None;
+        self.dns_servers = [None; DNS_SERVER_COUNT];
+    }
+
+    /// Start the state machine at `Init`, deriving this client's DUID-LL from `mac` and
+    /// seeding the transaction ID and retry jitter from `entropy`. Mirrors
+    /// `dhcp::DhcpClient::begin_at_init`.
+    pub fn begin_at_init(&mut self, mac: &[u8; 6], entropy: u32) {
+        self.client_duid = duid_ll(mac);
+        self.entropy = entropy;
+        self.xid = Some(entropy & 0x00ff_ffff); // DHCPv6 transaction ID is only 3 bytes
+        self.state = State::Init;
+        self.retry = RetryTimer::new_halted();
+        self.reset_bindings();
+    }
+
+    /// Update the state machine and return what message, if any, needs to be built and
+    /// sent. Same polled-from-the-event-loop shape as `dhcp::DhcpClient::cycle_clock`.
+    pub fn cycle_clock(&mut self) -> PacketNeeded {
+        match self.state {
+            State::Halted => PacketNeeded::None,
+            State::Init => {
+                self.retry = RetryTimer::new_first_random(self.entropy);
+                self.state = State::Soliciting;
+                logln!(LL::Debug, "Dhcp6Solicit");
+                PacketNeeded::Solicit
+            }
+            State::Soliciting => match self.retry.status() {
+                RetryStatus::Halted | RetryStatus::TimerRunning => PacketNeeded::None,
+                RetryStatus::TimerExpired => {
+                    logln!(LL::Debug, "Dhcp6SolicitRetry");
+                    self.retry.schedule_next(self.entropy);
+                    PacketNeeded::Solicit
+                }
+            },
+            State::Requesting => match self.retry.status() {
+                RetryStatus::Halted | RetryStatus::TimerRunning => PacketNeeded::None,
+                RetryStatus::TimerExpired => {
+                    logln!(LL::Debug, "Dhcp6RequestRetry");
+                    self.retry.schedule_next(self.entropy);
+                    PacketNeeded::Request
+                }
+            },
+            State::Bound => PacketNeeded::None,
+        }
+    }
+
+    /// Handle an inbound DHCPv6 message body (message-type octet, 3-byte transaction ID,
+    /// then options), dispatching Advertise/Reply to the matching handler the same way
+    /// `dhcp::DhcpClient::handle_frame` validates headers before calling `parse_options`.
+    /// Messages for a transaction ID other than ours, or that don't fit the state we're in,
+    /// are silently ignored.
+    pub fn handle_message(&mut self, data: &[u8]) -> Result<(), u8> {
+        if data.len() < 4 {
+            return Err(0x05);
+        }
+        let msg_type = data[0];
+        let xid = u32::from_be_bytes([0, data[1], data[2], data[3]]);
+        match self.xid {
+            Some(expected_xid) if xid == expected_xid => (),
+            _ => return Ok(()),
+        }
+        let options = &data[4..];
+        match (self.state, msg_type) {
+            (State::Soliciting, MSG_ADVERTISE) => self.handle_advertise(options),
+            (State::Requesting, MSG_REPLY) => self.handle_reply(options),
+            _ => Ok(()),
+        }
+    }
+
+    /// Handle an Advertise's options: record the offering server's DUID and move on to
+    /// Request. RFC 8415 allows collecting multiple Advertises and picking the best one;
+    /// this client takes the first one seen, same as `dhcp::DhcpClient::handle_offer` does
+    /// for DHCPv4.
+    fn handle_advertise(&mut self, options: &[u8]) -> Result<(), u8> {
+        let opt = parse_options(options)?;
+        match opt.server_duid {
+            Some(duid) => {
+                self.server_duid = Some(duid);
+                self.retry = RetryTimer::new_first_random(self.entropy);
+                self.state = State::Requesting;
+                logln!(LL::Debug, "Dhcp6Advertise");
+                Ok(())
+            }
+            None => Err(0x06),
+        }
+    }
+
+    /// Handle a Reply's options: confirm the leased address and lifetimes, and move to
+    /// `Bound`. Mirrors `dhcp::DhcpClient::handle_ack`.
+    fn handle_reply(&mut self, options: &[u8]) -> Result<(), u8> {
+        let opt = parse_options(options)?;
+        match (opt.ia_addr, opt.valid_lifetime) {
+            (Some(addr), Some(valid)) => {
+                self.address = Some(addr);
+                self.preferred_lifetime = opt.preferred_lifetime;
+                self.valid_lifetime = Some(valid);
+                self.dns_servers = opt.dns_servers;
+                self.state = State::Bound;
+                logln!(LL::Debug, "Dhcp6Bound");
+                Ok(())
+            }
+            _ => Err(0x07),
+        }
+    }
+
+    /// Build a Solicit or Request message body (message-type octet, 3-byte transaction ID,
+    /// then Client ID [+ Server ID for Request] options) into `buf`, returning the number
+    /// of bytes written. Does not include the IA_NA option a real exchange would carry --
+    /// that needs this client to propose an address or echo the server's, and is left for
+    /// whenever the framing layer this module's doc comment describes actually lands.
+    pub fn build_message(&self, buf: &mut [u8], request: bool) -> Result<u32, u8> {
+        let xid = match self.xid {
+            Some(xid) => xid,
+            None => return Err(0x08),
+        };
+        let msg_type = if request { MSG_REQUEST } else { MSG_SOLICIT };
+        let client_opt_len = 4 + self.client_duid.len();
+        let server_opt_len = if request { 4 + 10 } else { 0 };
+        let total = 4 + client_opt_len + server_opt_len;
+        if buf.len() < total {
+            return Err(0x09);
+        }
+        let xid_bytes = xid.to_be_bytes();
+        buf[0] = msg_type;
+        buf[1..4].copy_from_slice(&xid_bytes[1..4]);
+        let mut i = 4;
+        buf[i..i + 2].copy_from_slice(&OPT_CLIENTID.to_be_bytes());
+        buf[i + 2..i + 4].copy_from_slice(&(self.client_duid.len() as u16).to_be_bytes());
+        buf[i + 4..i + 4 + self.client_duid.len()].copy_from_slice(&self.client_duid);
+        i += client_opt_len;
+        if request {
+            let server_duid = match self.server_duid {
+                Some(duid) => duid,
+                None => return Err(0x0A),
+            };
+            buf[i..i + 2].copy_from_slice(&OPT_SERVERID.to_be_bytes());
+            buf[i + 2..i + 4].copy_from_slice(&(server_duid.len() as u16).to_be_bytes());
+            buf[i + 4..i + 4 + server_duid.len()].copy_from_slice(&server_duid);
+            i += server_opt_len;
+        }
+        Ok(i as u32)
+    }
+}
@@ -10,9 +10,11 @@ pub enum FilterBin {
     DropIpCk, // Bad IP header checksum
     DropUdpCk, // Bad UDP header checksum
     Arp,
+    ArpProbeReply, // Reply to our own DHCP ARP conflict-detection probe; see `dhcp::State::ArpProbing`
     Icmp,
     Dhcp,
     Udp,
+    Igmp,
     ComFwd, // Forward to COM net bridge
 }
 
@@ -27,10 +29,24 @@ pub struct FilterStats {
     pub drop_ipck: u16,
     pub drop_udpck: u16,
     pub arp: u16,
+    pub arp_probe_reply: u16,
     pub icmp: u16,
     pub dhcp: u16,
     pub udp: u16,
+    pub igmp: u16,
     pub com_fwd: u16,
+    /// A reassembly entry aged out of `ReassemblyTable` without ever seeing its last
+    /// fragment.
+    pub drop_frag_timeout: u16,
+    /// `ReassemblyTable` refused a fragment outright: an offset/length past
+    /// `MAX_PAYLOAD_LEN`, or every table slot already held a different in-progress
+    /// datagram. Counted separately from `drop_frag_timeout` since this is a capacity
+    /// problem, not merely a slow/missing sender.
+    pub drop_frag_overflow: u16,
+    /// A fragmented datagram was successfully reassembled and handed back to the
+    /// classifier. Counted in addition to whichever bin the reassembled datagram
+    /// itself lands in (`Udp`, `Icmp`, ...).
+    pub reassembled: u16,
 }
 impl FilterStats {
     /// Initialize a new filter stats struct
@@ -45,13 +61,34 @@ impl FilterStats {
             drop_ipck: 0,
             drop_udpck: 0,
             arp: 0,
+            arp_probe_reply: 0,
             icmp: 0,
             dhcp: 0,
             udp: 0,
+            igmp: 0,
             com_fwd: 0,
+            drop_frag_timeout: 0,
+            drop_frag_overflow: 0,
+            reassembled: 0,
         }
     }
 
+    /// A reassembly entry timed out before completion; see `ReassemblyTable`.
+    pub fn inc_drop_frag_timeout(&mut self) {
+        self.drop_frag_timeout = self.drop_frag_timeout.saturating_add(1);
+    }
+
+    /// `ReassemblyTable` refused a fragment outright (capacity/validation failure, not a
+    /// timeout); see `ReassemblyTable`.
+    pub fn inc_drop_frag_overflow(&mut self) {
+        self.drop_frag_overflow = self.drop_frag_overflow.saturating_add(1);
+    }
+
+    /// A fragmented datagram finished reassembly; see `ReassemblyTable`.
+    pub fn inc_reassembled(&mut self) {
+        self.reassembled = self.reassembled.saturating_add(1);
+    }
+
     /// Zero all the counters
     pub fn reset(&mut self) {
         *self = Self::new_all_zero();
@@ -69,9 +106,13 @@ impl FilterStats {
             FilterBin::DropIpCk => self.drop_ipck = self.drop_ipck.saturating_add(1),
             FilterBin::DropUdpCk => self.drop_udpck = self.drop_udpck.saturating_add(1),
             FilterBin::Arp => self.arp = self.arp.saturating_add(1),
+            FilterBin::ArpProbeReply => {
+                self.arp_probe_reply = self.arp_probe_reply.saturating_add(1)
+            }
             FilterBin::Icmp => self.icmp = self.icmp.saturating_add(1),
             FilterBin::Dhcp => self.dhcp = self.dhcp.saturating_add(1),
             FilterBin::Udp => self.udp = self.udp.saturating_add(1),
+            FilterBin::Igmp => self.igmp = self.igmp.saturating_add(1),
             FilterBin::ComFwd => self.com_fwd = self.com_fwd.saturating_add(1),
         };
     }
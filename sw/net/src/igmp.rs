@@ -0,0 +1,195 @@
+//! IGMPv2 multicast group membership (RFC 2236).
+//!
+//! Without this, `handle_frame`'s multicast check can only ever special-case one
+//! hardcoded MAC (historically the mDNS one), and every other multicast frame is either
+//! dropped outright or bridged regardless of whether the host ever asked for it. This
+//! module lets the host join/leave a specific IPv4 multicast group: once joined, this
+//! stack answers IGMPv2 membership queries on the host's behalf (so upstream
+//! routers/switches keep forwarding the group to us), and frames addressed to that
+//! group's derived Ethernet multicast MAC get bridged instead of dropped.
+//!
+//! State lives in a fixed-size table (`MAX_GROUPS` entries) held as a field of
+//! `crate::NetState`, the same approach `crate::reassembly::ReassemblyTable` uses to stay
+//! `#![forbid(unsafe_code)]`-compliant without a module-level static.
+use crate::timers::{Countdown, CountdownStatus};
+
+/// Concurrently-joined multicast groups this stack tracks membership/reports for.
+pub const MAX_GROUPS: usize = 4;
+
+/// IGMP is IP protocol number 2.
+pub const PROTO_IGMP: u8 = 2;
+
+const IGMP_MEMBERSHIP_QUERY: u8 = 0x11;
+const IGMP_V2_MEMBERSHIP_REPORT: u8 = 0x16;
+
+const MAC_HEADER_LEN: usize = 14;
+const IP_HEADER_LEN: usize = 20;
+const IGMP_MSG_LEN: usize = 8;
+/// A report is the only frame this module ever builds: MAC + plain 20-byte IP header (no
+/// options -- same tradeoff `handle_ipv4_frame` already makes on the RX side) + the
+/// 8-byte IGMPv2 message.
+const IGMP_FRAME_LEN: usize = MAC_HEADER_LEN + IP_HEADER_LEN + IGMP_MSG_LEN;
+
+/// Derive the Ethernet multicast MAC an IPv4 multicast group address maps to, per RFC
+/// 1112 section 6.4: `01:00:5E` followed by the low 23 bits of the group address.
+pub fn mac_for_group(group: u32) -> [u8; 6] {
+    let b = group.to_be_bytes();
+    [0x01, 0x00, 0x5E, b[1] & 0x7F, b[2], b[3]]
+}
+
+/// RFC 1071 one's complement checksum, same algorithm `crate::ipv4_checksum` uses, over
+/// an arbitrary even-length byte slice (the IGMP message and IP header here are both
+/// built fixed-size and never need the odd-length padding case).
+fn ones_complement_checksum(data: &[u8]) -> u16 {
+    let mut sum: u16 = 0;
+    for c in data.chunks_exact(2) {
+        let x = ((c[0] as u16) << 8) | (c[1] as u16);
+        sum = match sum.overflowing_add(x) {
+            (n, true) => n + 1,
+            (n, false) => n,
+        };
+    }
+    !sum
+}
+
+struct Group {
+    addr: u32,
+    /// Counts down to zero when a membership report is owed for this group -- either the
+    /// unsolicited one IGMPv2 sends right after joining, or one scheduled in response to
+    /// a query. `poll_report_due` clears it once the report has gone out.
+    report_due: Countdown,
+}
+
+pub struct IgmpState {
+    groups: [Option<Group>; MAX_GROUPS],
+}
+impl IgmpState {
+    pub const fn new() -> IgmpState {
+        IgmpState {
+            groups: [None, None, None, None],
+        }
+    }
+
+    /// Join an IPv4 multicast group and schedule the unsolicited report IGMPv2 sends
+    /// immediately after joining (a couple seconds out, not instant, so a burst of joins
+    /// doesn't need to build several report frames in the same tick). Returns `false` if
+    /// already a member or the table is full.
+    pub fn join(&mut self, group: u32, entropy: u32) -> bool {
+        if self.groups.iter().flatten().any(|g| g.addr == group) {
+            return false;
+        }
+        for slot in self.groups.iter_mut() {
+            if slot.is_none() {
+                let mut report_due = Countdown::new();
+                report_due.start_s(1 + (entropy % 3));
+                *slot = Some(Group { addr: group, report_due });
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Leave a multicast group. No-op if we were never a member.
+    pub fn leave(&mut self, group: u32) {
+        for slot in self.groups.iter_mut() {
+            if matches!(slot, Some(g) if g.addr == group) {
+                *slot = None;
+            }
+        }
+    }
+
+    /// True if `mac` is the derived multicast MAC of some group this stack has joined.
+    pub fn is_member_mac(&self, mac: &[u8]) -> bool {
+        mac.len() == 6 && self.groups.iter().flatten().any(|g| mac_for_group(g.addr).as_slice() == mac)
+    }
+
+    /// Handle an inbound IGMPv2 Membership Query: (re)schedule a randomized report delay,
+    /// bounded by the query's max response time, for every group we've joined that the
+    /// query covers. A `queried_group` of all-zeros is a General Query covering every
+    /// group we're a member of. RFC 2236 section 6 is why the delay is randomized: so
+    /// every host on the link answering the same query doesn't all transmit a report for
+    /// the same group at once.
+    pub fn handle_query(&mut self, queried_group: u32, max_resp_s: u32, entropy: u32) {
+        let max_resp_s = max_resp_s.max(1);
+        for (i, slot) in self.groups.iter_mut().enumerate() {
+            if let Some(g) = slot {
+                if queried_group == 0 || queried_group == g.addr {
+                    let delay = 1 + (entropy.wrapping_add(i as u32) % max_resp_s);
+                    g.report_due.start_s(delay);
+                }
+            }
+        }
+    }
+
+    /// Poll for a group whose report delay has elapsed, clearing it and returning the
+    /// group address so the caller can build and send one Membership Report. Only one
+    /// group is surfaced per call, matching how `dhcp_do_next`'s caller only ever builds
+    /// and sends one outbound frame per clock tick.
+    pub fn poll_report_due(&mut self) -> Option<u32> {
+        for slot in self.groups.iter_mut() {
+            if let Some(g) = slot {
+                if g.report_due.status() == CountdownStatus::Done {
+                    g.report_due.clear();
+                    return Some(g.addr);
+                }
+            }
+        }
+        None
+    }
+}
+
+/// Handle a received IGMP frame (IP protocol 2). Only Membership Queries are acted on;
+/// the Membership Reports other hosts send are of no interest to us since this stack
+/// doesn't route between LAN members.
+pub fn handle_igmp_frame(igmp: &mut IgmpState, data: &[u8], entropy: u32) {
+    if data.len() < MAC_HEADER_LEN + IP_HEADER_LEN + IGMP_MSG_LEN {
+        return;
+    }
+    let body = &data[MAC_HEADER_LEN + IP_HEADER_LEN..];
+    if body[0] != IGMP_MEMBERSHIP_QUERY {
+        return;
+    }
+    let max_resp_s = ((body[1] as u32) / 10).max(1); // max resp code is in tenths of a second
+    let group = u32::from_be_bytes([body[4], body[5], body[6], body[7]]);
+    igmp.handle_query(group, max_resp_s, entropy);
+}
+
+/// Build an IGMPv2 Membership Report for `group`, addressed to the group itself (per RFC
+/// 2236 section 9) from `src_mac`/`src_ip`. Returns the frame length on success, or
+/// `None` if `out` is too small.
+pub fn build_report_frame(out: &mut [u8], src_mac: &[u8; 6], src_ip: u32, group: u32) -> Option<usize> {
+    if out.len() < IGMP_FRAME_LEN {
+        return None;
+    }
+    let dst_mac = mac_for_group(group);
+    out[0..6].copy_from_slice(&dst_mac);
+    out[6..12].copy_from_slice(src_mac);
+    out[12..14].copy_from_slice(&[0x08, 0x00]);
+
+    let ip_start = MAC_HEADER_LEN;
+    let ip_end = ip_start + IP_HEADER_LEN;
+    for b in out[ip_start..ip_end].iter_mut() {
+        *b = 0;
+    }
+    out[ip_start] = 0x45; // version 4, IHL 5 (no options)
+    out[ip_start + 2..ip_start + 4]
+        .copy_from_slice(&((IP_HEADER_LEN + IGMP_MSG_LEN) as u16).to_be_bytes());
+    out[ip_start + 8] = 1; // TTL=1: IGMP reports never cross a router (RFC 2236 section 2)
+    out[ip_start + 9] = PROTO_IGMP;
+    out[ip_start + 12..ip_start + 16].copy_from_slice(&src_ip.to_be_bytes());
+    out[ip_start + 16..ip_start + 20].copy_from_slice(&group.to_be_bytes());
+    let ip_csum = ones_complement_checksum(&out[ip_start..ip_end]);
+    out[ip_start + 10..ip_start + 12].copy_from_slice(&ip_csum.to_be_bytes());
+
+    let igmp_start = ip_end;
+    let igmp_end = igmp_start + IGMP_MSG_LEN;
+    out[igmp_start] = IGMP_V2_MEMBERSHIP_REPORT;
+    out[igmp_start + 1] = 0; // max resp time: unused in a report
+    out[igmp_start + 2] = 0;
+    out[igmp_start + 3] = 0;
+    out[igmp_start + 4..igmp_start + 8].copy_from_slice(&group.to_be_bytes());
+    let igmp_csum = ones_complement_checksum(&out[igmp_start..igmp_end]);
+    out[igmp_start + 2..igmp_start + 4].copy_from_slice(&igmp_csum.to_be_bytes());
+
+    Some(IGMP_FRAME_LEN)
+}
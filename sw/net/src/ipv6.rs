@@ -0,0 +1,97 @@
+//! IPv6 address bookkeeping for `NetState`.
+//!
+//! This only derives and stores addresses; it does not yet parse or emit any ICMPv6
+//! messages. The EC remains IPv4-only on the wire until Neighbor Discovery (DAD, Router
+//! Solicitation/Advertisement, Neighbor Solicitation/Advertisement) is implemented on top
+//! of this, at which point `handle_frame()` can route `ETHERTYPE_IPV6` here the same way
+//! it routes `ETHERTYPE_IPV4` to `handle_ipv4_frame()`.
+//!
+//! TODO, in rough dependency order:
+//! - [ ] ICMPv6 checksum over the IPv6 pseudo-header (src, dst, upper-layer length, next-header=58)
+//! - [ ] Emit Neighbor Solicitation / watch for conflicting Neighbor Advertisement (DAD)
+//! - [ ] Emit Router Solicitation, adopt prefix + router + DNS from Router Advertisement
+//! - [ ] Answer inbound Neighbor Solicitations for our own addresses
+//! - [ ] Join all-nodes and solicited-node multicast groups at the WFx multicast filter
+
+/// An IPv6 address is just 16 bytes; there's no need for a richer type here since
+/// addresses only ever get derived, stored, and handed to the COM bus as raw bytes.
+pub type Ipv6Addr = [u8; 16];
+
+const UNSPECIFIED: Ipv6Addr = [0u8; 16];
+
+/// Derive a link-local (`fe80::/64`) address from a 6-byte MAC via modified EUI-64:
+/// split the MAC around the middle, insert `0xFFFE`, and flip the universal/local bit
+/// (bit 1) of the first octet.
+pub fn eui64_link_local(mac: &[u8; 6]) -> Ipv6Addr {
+    let mut addr = [0u8; 16];
+    addr[0] = 0xfe;
+    addr[1] = 0x80;
+    // addr[2..8] stays zero: the rest of the fe80::/64 prefix
+    addr[8] = mac[0] ^ 0x02;
+    addr[9] = mac[1];
+    addr[10] = mac[2];
+    addr[11] = 0xff;
+    addr[12] = 0xfe;
+    addr[13] = mac[3];
+    addr[14] = mac[4];
+    addr[15] = mac[5];
+    addr
+}
+
+/// Derive a global SLAAC address by grafting a router-advertised /64 prefix onto the
+/// EUI-64 interface identifier half of `link_local`.
+pub fn eui64_global(prefix: &[u8; 8], link_local: &Ipv6Addr) -> Ipv6Addr {
+    let mut addr = [0u8; 16];
+    addr[..8].copy_from_slice(prefix);
+    addr[8..].copy_from_slice(&link_local[8..]);
+    addr
+}
+
+/// Derive the solicited-node multicast address `ff02::1:ffXX:XXXX` that a node must
+/// join for every unicast/anycast address it holds, used by Neighbor Discovery in place
+/// of broadcast.
+pub fn solicited_node_multicast(addr: &Ipv6Addr) -> Ipv6Addr {
+    [
+        0xff, 0x02, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 0xff, addr[13], addr[14], addr[15],
+    ]
+}
+
+/// The all-nodes link-local multicast address, `ff02::1`.
+pub const ALL_NODES_MULTICAST: Ipv6Addr = [
+    0xff, 0x02, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1,
+];
+
+/// Tracks the addresses and Neighbor Discovery inputs the EC has learned for itself.
+pub struct Ipv6State {
+    /// `fe80::/64` candidate address derived from our MAC by `derive_link_local`; set as
+    /// soon as it is derived, with no DAD run first.
+    pub link_local: Option<Ipv6Addr>,
+    /// SLAAC address formed from a Router Advertisement prefix; `None` until an RA arrives.
+    pub global: Option<Ipv6Addr>,
+    pub default_router: Option<Ipv6Addr>,
+    pub dns: Option<Ipv6Addr>,
+}
+impl Ipv6State {
+    pub const fn new() -> Ipv6State {
+        Ipv6State {
+            link_local: None,
+            global: None,
+            default_router: None,
+            dns: None,
+        }
+    }
+
+    /// Compute our link-local address from `mac`. This only records the candidate
+    /// address -- the caller is responsible for running DAD before treating it as bound.
+    pub fn derive_link_local(&mut self, mac: &[u8; 6]) {
+        self.link_local = Some(eui64_link_local(mac));
+    }
+
+    pub fn link_local_or_unspecified(&self) -> Ipv6Addr {
+        self.link_local.unwrap_or(UNSPECIFIED)
+    }
+
+    pub fn global_or_unspecified(&self) -> Ipv6Addr {
+        self.global.unwrap_or(UNSPECIFIED)
+    }
+}
@@ -0,0 +1,98 @@
+//! LEDBAT (RFC 6817) -style delay-based congestion control for a bulk sender that should
+//! yield to latency-sensitive traffic sharing the same link.
+//!
+//! The idea: track `base_delay`, the rolling minimum one-way delay seen over a multi-minute
+//! window (an estimate of the link with nothing queued), and `queuing_delay = current_delay -
+//! base_delay` (how much of the latest sample is *our own* queuing rather than propagation).
+//! `cwnd` (bytes) grows while `queuing_delay` sits below `TARGET_DELAY_MS` and shrinks once
+//! it creeps above it, so a bulk flow backs off automatically as soon as it starts adding
+//! latency, well before a hard drop would force it to.
+//!
+//! This module only does the bookkeeping; it's the caller's job to supply delay samples
+//! (however it measures "one-way delay" for its own transport) and to gate how many unacked
+//! bytes it allows in flight by `cwnd()`.
+
+/// Target amount of self-inflicted queuing delay, per RFC 6817 section 3.3's recommended
+/// default.
+pub const TARGET_DELAY_MS: u32 = 100;
+/// Window the base (no-queuing) delay is allowed to drift over before a fresh minimum from
+/// outside the window takes over -- long enough to ride out a single bulk transfer without
+/// mistaking its own queuing for the new floor.
+const BASE_DELAY_WINDOW_MS: u32 = 180_000;
+const BASE_DELAY_BUCKETS: usize = 6;
+const BUCKET_DURATION_MS: u32 = BASE_DELAY_WINDOW_MS / BASE_DELAY_BUCKETS as u32;
+const GAIN: f32 = 1.0;
+
+/// Rolling minimum delay over `BASE_DELAY_BUCKETS` consecutive `BUCKET_DURATION_MS`-wide
+/// buckets; the overall base delay is the minimum of whichever buckets currently hold a
+/// sample. Older buckets age out and get a fresh minimum as time moves into them, so a
+/// sustained rise in the true path delay (e.g. a real bufferbloat event) is eventually
+/// reflected rather than pinned forever to a stale low-water mark.
+struct BaseDelayWindow {
+    buckets: [Option<u32>; BASE_DELAY_BUCKETS],
+    current_bucket: usize,
+    bucket_started_ms: u32,
+}
+
+impl BaseDelayWindow {
+    const fn new() -> Self {
+        Self { buckets: [None; BASE_DELAY_BUCKETS], current_bucket: 0, bucket_started_ms: 0 }
+    }
+
+    fn sample(&mut self, now_ms: u32, delay_ms: u32) {
+        let elapsed = now_ms.wrapping_sub(self.bucket_started_ms);
+        let buckets_elapsed = (elapsed / BUCKET_DURATION_MS).min(BASE_DELAY_BUCKETS as u32);
+        for _ in 0..buckets_elapsed {
+            self.current_bucket = (self.current_bucket + 1) % BASE_DELAY_BUCKETS;
+            self.buckets[self.current_bucket] = None;
+            self.bucket_started_ms = self.bucket_started_ms.wrapping_add(BUCKET_DURATION_MS);
+        }
+        let slot = &mut self.buckets[self.current_bucket];
+        *slot = Some(slot.map_or(delay_ms, |m| m.min(delay_ms)));
+    }
+
+    fn base_delay(&self) -> Option<u32> {
+        self.buckets.iter().filter_map(|b| *b).min()
+    }
+}
+
+pub struct LedbatController {
+    cwnd_bytes: u32,
+    mss_bytes: u32,
+    base_delay: BaseDelayWindow,
+}
+
+impl LedbatController {
+    pub const fn new(mss_bytes: u32) -> Self {
+        Self { cwnd_bytes: mss_bytes, mss_bytes, base_delay: BaseDelayWindow::new() }
+    }
+
+    /// Current congestion window, in bytes. Never below one packet (`mss_bytes`).
+    pub fn cwnd(&self) -> u32 {
+        self.cwnd_bytes
+    }
+
+    /// Whether `bytes` more may be sent given `in_flight_bytes` already outstanding.
+    pub fn can_send(&self, in_flight_bytes: u32, bytes: u32) -> bool {
+        in_flight_bytes.saturating_add(bytes) <= self.cwnd_bytes
+    }
+
+    /// Record a fresh one-way delay sample (in whatever unit the caller's clock uses --
+    /// milliseconds here) for `bytes_acked` worth of newly-acknowledged data, and update
+    /// `cwnd` per RFC 6817's control law:
+    /// `off_target = (TARGET - queuing_delay) / TARGET`, clamped to `[-1, 1]`, then
+    /// `cwnd += GAIN * off_target * bytes_acked * MSS / cwnd`.
+    pub fn on_ack(&mut self, now_ms: u32, one_way_delay_ms: u32, bytes_acked: u32) {
+        self.base_delay.sample(now_ms, one_way_delay_ms);
+        let base = self.base_delay.base_delay().unwrap_or(one_way_delay_ms);
+        let queuing_delay_ms = one_way_delay_ms.saturating_sub(base) as f32;
+
+        let off_target = ((TARGET_DELAY_MS as f32) - queuing_delay_ms) / (TARGET_DELAY_MS as f32);
+        let off_target = off_target.clamp(-1.0, 1.0);
+
+        let delta = GAIN * off_target * (bytes_acked as f32) * (self.mss_bytes as f32)
+            / (self.cwnd_bytes as f32);
+        let new_cwnd = (self.cwnd_bytes as f32) + delta;
+        self.cwnd_bytes = (new_cwnd.max(self.mss_bytes as f32)) as u32;
+    }
+}
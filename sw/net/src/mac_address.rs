@@ -0,0 +1,50 @@
+/// Derive a substitute, locally-administered MAC address for the Ethernet frames this
+/// stack builds, instead of leaving the WF200's factory-burned address (globally unique,
+/// and stable across every network the device ever joins) as the source address on the
+/// wire. See `MacAddress::randomize` for a fresh address per join, or
+/// `MacAddress::stable_for_ssid` for one that's stable per-network without being
+/// traceable across *different* networks.
+pub struct MacAddress {
+    pub octets: [u8; 6],
+}
+impl MacAddress {
+    pub const fn new_blank() -> Self {
+        MacAddress { octets: [0; 6] }
+    }
+
+    /// Set the locally-administered bit and clear the multicast bit of `octets[0]`, per
+    /// IEEE 802-2001 section 9.2 -- marks this as an address we made up rather than one
+    /// assigned out of an OUI block, and keeps it a valid unicast source address.
+    fn set_local_unicast_bits(&mut self) {
+        self.octets[0] = (self.octets[0] | 0b0000_0010) & 0b1111_1110;
+    }
+
+    /// Generate a fresh, fully random locally-administered address from two PRNG words.
+    /// Intended to be called again on every join, so repeat associations -- even to the
+    /// same network -- don't share an address.
+    pub fn randomize(&mut self, entropy0: u32, entropy1: u32) {
+        self.octets[..4].copy_from_slice(&entropy0.to_le_bytes());
+        self.octets[4..6].copy_from_slice(&entropy1.to_le_bytes()[..2]);
+        self.set_local_unicast_bits();
+    }
+
+    /// Derive an address that's stable for a given `ssid`, by hashing it together with
+    /// `seed` (some per-device secret the caller holds, not otherwise sent over the air)
+    /// using FNV-1a. The same network sees the same client address across reassociations;
+    /// a different network sees an unrelated one, since the hash mixes in the SSID.
+    pub fn stable_for_ssid(&mut self, ssid: &[u8], seed: &[u8]) {
+        const FNV_OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+        const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+        let mut hash = FNV_OFFSET_BASIS;
+        for &byte in seed.iter().chain(ssid.iter()) {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(FNV_PRIME);
+        }
+        self.octets.copy_from_slice(&hash.to_le_bytes()[..6]);
+        self.set_local_unicast_bits();
+    }
+
+    pub fn as_bytes(&self) -> &[u8; 6] {
+        &self.octets
+    }
+}
@@ -74,4 +74,51 @@ impl NetPrng {
         self.s[3] = self.s[3].rotate_left(11);
         result
     }
+
+    /// Advance the state as if `2^64` calls to `next_inner` had been made. `clone()` the
+    /// generator before jumping so each protocol subsystem (DHCP xids, TCP ISNs, source
+    /// ports, ...) gets its own non-overlapping subsequence from a single TRNG seed instead
+    /// of sharing one stream and correlating across fields.
+    ///
+    /// Credits: ported from the `jump()` function in the public domain
+    /// xoshiro128plusplus.c implementation by David Blackman and Sebastiano Vigna.
+    pub fn jump(&mut self) {
+        const JUMP: [u32; 4] = [0x8764000b, 0xf542d2d3, 0x6fa035c3, 0x77f2db5b];
+        self.do_jump(&JUMP);
+    }
+
+    /// Advance the state as if `2^96` calls to `next_inner` had been made -- for deriving a
+    /// subsequence from a subsequence, further apart than `jump()` gives.
+    ///
+    /// Credits: ported from the `long_jump()` function in the public domain
+    /// xoshiro128plusplus.c implementation by David Blackman and Sebastiano Vigna.
+    pub fn long_jump(&mut self) {
+        const LONG_JUMP: [u32; 4] = [0xb523952e, 0x0b6f099f, 0xccf5a0ef, 0x1c580662];
+        self.do_jump(&LONG_JUMP);
+    }
+
+    /// Shared jump-table walk for `jump`/`long_jump`: calls the raw `next_inner` (not the
+    /// reseed-mixing `next` wrapper) 128 times, XORing the state as it stands at that step into an
+    /// accumulator whenever the corresponding jump-table bit is set. `count` is reset
+    /// afterward so `next`'s post-reseed mixing heuristic still applies to the derived
+    /// stream, the same as a freshly reseeded generator.
+    fn do_jump(&mut self, jump: &[u32; 4]) {
+        let mut s0: u32 = 0;
+        let mut s1: u32 = 0;
+        let mut s2: u32 = 0;
+        let mut s3: u32 = 0;
+        for &word in jump.iter() {
+            for b in 0..32 {
+                if word & (1 << b) != 0 {
+                    s0 ^= self.s[0];
+                    s1 ^= self.s[1];
+                    s2 ^= self.s[2];
+                    s3 ^= self.s[3];
+                }
+                self.next_inner();
+            }
+        }
+        self.s = [s0, s1, s2, s3];
+        self.count = 0;
+    }
 }
@@ -0,0 +1,226 @@
+//! IPv4 fragment reassembly.
+//!
+//! Fragments used to land in [`FilterBin::DropFrag`](crate::filter::FilterBin::DropFrag)
+//! unconditionally, which breaks any UDP payload too big for one frame (DHCP with a lot
+//! of options, DNS over UDP). This keeps a small, bounded table of in-flight datagrams
+//! keyed on (source IP, dest IP, protocol, IP identification): each fragment is copied
+//! into the entry for its key at `fragment_offset * 8`, a coverage bitmap (one bool per
+//! 8-byte block, matching the granularity fragment offsets are expressed in) tracks what
+//! has arrived, and the total length becomes known once the last fragment (`MF` clear)
+//! shows up. Once every block up to that length is covered, [`ReassemblyTable::accept_fragment`]
+//! reconstructs a synthetic, non-fragmented IPv4 frame (original MAC + IP header, flags
+//! cleared, total length and header checksum recomputed) and hands it back so the caller can run
+//! it through the normal classifier exactly as if it had arrived in one piece.
+//!
+//! Entries are capped at [`MAX_ENTRIES`] and time out after [`REASSEMBLY_TIMEOUT_S`]
+//! seconds with no progress, so a flood of bogus fragments can only ever tie up a fixed,
+//! small amount of RAM rather than growing without bound. The table lives inside
+//! [`crate::NetState`] rather than as a module-level static so this crate's
+//! `#![forbid(unsafe_code)]` keeps holding -- no `static mut` needed.
+use crate::filter::FilterStats;
+use crate::timers::{Countdown, CountdownStatus};
+
+/// One in-flight datagram can hold at most this many payload bytes. This comfortably
+/// covers the DHCP-with-many-options and DNS-over-UDP cases this feature exists for;
+/// datagrams that claim to be longer are rejected outright rather than reassembled.
+const MAX_PAYLOAD_LEN: usize = 2048;
+const BLOCK_SIZE: usize = 8; // fragment offsets are in units of 8 bytes, per RFC 791
+const COVERAGE_BLOCKS: usize = MAX_PAYLOAD_LEN / BLOCK_SIZE;
+pub const MAX_ENTRIES: usize = 4;
+const REASSEMBLY_TIMEOUT_S: u32 = 5;
+
+pub const MAC_HEADER_LEN: usize = 14;
+pub const IP_HEADER_LEN: usize = 20;
+/// Largest synthetic frame `Reassembled::Complete` can hand back -- callers that need
+/// to copy it out of the table's borrow (to recurse through the classifier, say) size
+/// their scratch buffer off this.
+pub const MAX_FRAME_LEN: usize = MAC_HEADER_LEN + IP_HEADER_LEN + MAX_PAYLOAD_LEN;
+
+#[derive(Copy, Clone, PartialEq)]
+struct Key {
+    src: [u8; 4],
+    dst: [u8; 4],
+    proto: u8,
+    ident: u16,
+}
+
+struct Entry {
+    key: Key,
+    mac_header: [u8; MAC_HEADER_LEN],
+    ip_header: [u8; IP_HEADER_LEN],
+    payload: [u8; MAX_PAYLOAD_LEN],
+    covered: [bool; COVERAGE_BLOCKS],
+    /// Payload length, known once the final fragment (`MF` clear) has arrived.
+    total_len: Option<u16>,
+    timeout: Countdown,
+}
+impl Entry {
+    fn new(key: Key, mac_header: &[u8], ip_header: &[u8]) -> Entry {
+        let mut timeout = Countdown::new();
+        timeout.start_s(REASSEMBLY_TIMEOUT_S);
+        let mut entry = Entry {
+            key,
+            mac_header: [0u8; MAC_HEADER_LEN],
+            ip_header: [0u8; IP_HEADER_LEN],
+            payload: [0u8; MAX_PAYLOAD_LEN],
+            covered: [false; COVERAGE_BLOCKS],
+            total_len: None,
+            timeout,
+        };
+        entry.mac_header.copy_from_slice(mac_header);
+        entry.ip_header.copy_from_slice(ip_header);
+        entry
+    }
+
+    fn expired(&self) -> bool {
+        self.timeout.status() == CountdownStatus::Done
+    }
+
+    /// All blocks up to `total_len` have arrived.
+    fn is_complete(&self) -> bool {
+        match self.total_len {
+            Some(len) => {
+                let blocks = (len as usize + BLOCK_SIZE - 1) / BLOCK_SIZE;
+                self.covered[..blocks].iter().all(|c| *c)
+            }
+            None => false,
+        }
+    }
+}
+
+/// Reassembly-specific outcomes. `Complete` carries the synthetic frame bytes for a
+/// second pass through the normal classifier; everything else maps onto plain
+/// `FilterBin::DropFrag`-style bookkeeping at the call site.
+pub enum Reassembled<'a> {
+    /// Recorded; the datagram isn't complete yet.
+    Pending,
+    /// Offset+length overflowed the entry's bound, or every table slot is in use by a
+    /// different, not-yet-expired datagram.
+    Rejected,
+    /// Every fragment is in; `frame` is a synthetic, non-fragmented Ethernet frame ready
+    /// to go back through `handle_ipv4_frame`.
+    Complete(&'a [u8]),
+}
+
+pub struct ReassemblyTable {
+    entries: [Option<Entry>; MAX_ENTRIES],
+    rebuild_buf: [u8; MAX_FRAME_LEN],
+}
+impl ReassemblyTable {
+    pub const fn new() -> ReassemblyTable {
+        ReassemblyTable {
+            entries: [None, None, None, None],
+            rebuild_buf: [0u8; MAX_FRAME_LEN],
+        }
+    }
+
+    /// Feed one IPv4 fragment into the table. `data` is the full Ethernet frame (MAC
+    /// header + IP header + fragment payload); `fragment_offset` and `more_fragments`
+    /// are decoded by the caller from the IP header's flags/fragment-offset field.
+    pub fn accept_fragment(
+        &mut self,
+        data: &[u8],
+        fragment_offset: u16,
+        more_fragments: bool,
+        stats: &mut FilterStats,
+    ) -> Reassembled {
+        let ip = &data[MAC_HEADER_LEN..MAC_HEADER_LEN + IP_HEADER_LEN];
+        let key = Key {
+            src: [ip[12], ip[13], ip[14], ip[15]],
+            dst: [ip[16], ip[17], ip[18], ip[19]],
+            proto: ip[9],
+            ident: u16::from_be_bytes([ip[4], ip[5]]),
+        };
+        let payload = &data[MAC_HEADER_LEN + IP_HEADER_LEN..];
+        let byte_offset = fragment_offset as usize * BLOCK_SIZE;
+        if byte_offset + payload.len() > MAX_PAYLOAD_LEN {
+            // Either a hostile offset/length or a datagram bigger than we keep buffers for.
+            return Reassembled::Rejected;
+        }
+
+        // Reclaim any entry that's either a match or has simply timed out, so one slow
+        // attacker can't permanently squat on a table slot. Never keep more than one
+        // entry per key.
+        let mut slot: Option<usize> = None;
+        for (i, entry) in self.entries.iter().enumerate() {
+            match entry {
+                Some(e) if e.key == key => {
+                    slot = Some(i);
+                    break;
+                }
+                Some(e) if e.expired() => {
+                    if slot.is_none() {
+                        slot = Some(i);
+                    }
+                }
+                None => {
+                    if slot.is_none() {
+                        slot = Some(i);
+                    }
+                }
+                _ => {}
+            }
+        }
+        let slot = match slot {
+            Some(i) => i,
+            // Table is full of other live datagrams.
+            None => return Reassembled::Rejected,
+        };
+        let need_new = match &self.entries[slot] {
+            Some(e) => e.key != key,
+            None => true,
+        };
+        if need_new {
+            // Completed entries are removed from the table immediately below, so any
+            // entry still occupying a slot when it's found expired never finished.
+            if matches!(&self.entries[slot], Some(e) if e.expired()) {
+                stats.inc_drop_frag_timeout();
+            }
+            self.entries[slot] = Some(Entry::new(
+                key,
+                &data[..MAC_HEADER_LEN],
+                &data[MAC_HEADER_LEN..MAC_HEADER_LEN + IP_HEADER_LEN],
+            ));
+        }
+        let entry = self.entries[slot].as_mut().unwrap();
+        entry.timeout.start_s(REASSEMBLY_TIMEOUT_S);
+        entry.payload[byte_offset..byte_offset + payload.len()].copy_from_slice(payload);
+        // Later fragments overwrite overlapping ranges of earlier ones, by construction:
+        // the copy above always takes the newest arrival for any byte it touches.
+        let first_block = byte_offset / BLOCK_SIZE;
+        let blocks = (payload.len() + BLOCK_SIZE - 1) / BLOCK_SIZE;
+        for b in first_block..first_block + blocks {
+            entry.covered[b] = true;
+        }
+        if !more_fragments {
+            entry.total_len = Some((byte_offset + payload.len()) as u16);
+        }
+
+        if !entry.is_complete() {
+            return Reassembled::Pending;
+        }
+        let total_len = entry.total_len.unwrap() as usize;
+        let frame_len = MAC_HEADER_LEN + IP_HEADER_LEN + total_len;
+        self.rebuild_buf[..MAC_HEADER_LEN].copy_from_slice(&entry.mac_header);
+        self.rebuild_buf[MAC_HEADER_LEN..MAC_HEADER_LEN + IP_HEADER_LEN]
+            .copy_from_slice(&entry.ip_header);
+        self.rebuild_buf[MAC_HEADER_LEN + IP_HEADER_LEN..frame_len]
+            .copy_from_slice(&entry.payload[..total_len]);
+        self.entries[slot] = None;
+
+        // Clear fragmentation (flags + fragment offset) and fix up the total length
+        // field now that the datagram is whole, then recompute the header checksum over
+        // the fixed-up header so the caller's checksum check passes the same way it does
+        // for any other inbound frame.
+        let ip_start = MAC_HEADER_LEN;
+        self.rebuild_buf[ip_start + 2..ip_start + 4]
+            .copy_from_slice(&((IP_HEADER_LEN + total_len) as u16).to_be_bytes());
+        self.rebuild_buf[ip_start + 6] = 0;
+        self.rebuild_buf[ip_start + 7] = 0;
+        let csum = crate::ipv4_checksum(&self.rebuild_buf[..frame_len]);
+        self.rebuild_buf[ip_start + 10..ip_start + 12].copy_from_slice(&csum.to_be_bytes());
+
+        stats.inc_reassembled();
+        Reassembled::Complete(&self.rebuild_buf[..frame_len])
+    }
+}
@@ -0,0 +1,133 @@
+//! Selective-ACK (SACK) retransmission bookkeeping for a sequenced packet stream, modeled on
+//! uTP's SACK extension: the sender keeps a bounded ring of sent-but-unacked packets; the
+//! receiver's ack carries a cumulative sequence number (everything at or before it has
+//! arrived in order) plus a bitmask of which packets *past* that point have also already
+//! arrived out of order, so the sender only has to re-send the genuine gaps instead of
+//! resending the whole window.
+//!
+//! This module only tracks sequence numbers and buffered payloads -- it has no opinion on
+//! wire format or timers, and doesn't assign itself to any particular transport. `N` is the
+//! ring's packet capacity, `LEN` the largest payload it can hold per packet.
+
+/// How many out-of-order packets past the cumulative ack a SACK bitmask can describe.
+pub const SACK_BITS: u32 = 32;
+/// A packet is given up on (reported via `apply_ack`'s `lost` callback) after this many
+/// retransmissions, rather than retried forever.
+pub const MAX_RETRIES: u8 = 5;
+
+/// Wrap-around-safe "is `a` earlier than or equal to `b`" compare for 16-bit sequence
+/// numbers -- serial number arithmetic per RFC 1982, with the implicit window set to half
+/// the number space (so two sequence numbers 0x8000 apart are never comparable, which only
+/// matters if the ring is left unacked for 32k packets, far past `N`).
+fn seq_le(a: u16, b: u16) -> bool {
+    a == b || b.wrapping_sub(a) < 0x8000
+}
+
+#[derive(Copy, Clone)]
+struct Slot<const LEN: usize> {
+    seq: u16,
+    len: usize,
+    data: [u8; LEN],
+    retries: u8,
+}
+
+/// A bounded, drop-oldest ring of sent-but-unacked packets, each tagged with a
+/// monotonically increasing 16-bit sequence number assigned by `send`.
+pub struct RetransmitRing<const N: usize, const LEN: usize> {
+    slots: [Option<Slot<LEN>>; N],
+    next_seq: u16,
+}
+
+impl<const N: usize, const LEN: usize> RetransmitRing<N, LEN> {
+    pub const fn new() -> Self {
+        Self { slots: [None; N], next_seq: 0 }
+    }
+
+    /// Assign the next sequence number to `data` and record it as unacked, copying it into
+    /// the ring (truncated to `LEN` if it's longer -- same MTU-capping behavior the rest of
+    /// the bridge already relies on). If the ring was already full, the oldest unacked
+    /// packet is evicted to make room and its sequence number is returned as `evicted` --
+    /// treat that as a hard, counted error (the packet is now unrecoverably lost) rather
+    /// than a silent drop.
+    pub fn send(&mut self, data: &[u8]) -> (u16, Option<u16>) {
+        let seq = self.next_seq;
+        self.next_seq = self.next_seq.wrapping_add(1);
+
+        let mut oldest_idx = 0;
+        let mut oldest_seq = None;
+        let mut free_idx = None;
+        for (i, slot) in self.slots.iter().enumerate() {
+            match slot {
+                None => {
+                    if free_idx.is_none() {
+                        free_idx = Some(i);
+                    }
+                }
+                Some(s) => {
+                    if oldest_seq.map_or(true, |os| seq_le(s.seq, os) && s.seq != os) {
+                        oldest_seq = Some(s.seq);
+                        oldest_idx = i;
+                    }
+                }
+            }
+        }
+
+        let (idx, evicted) = match free_idx {
+            Some(i) => (i, None),
+            None => (oldest_idx, oldest_seq),
+        };
+
+        let len = data.len().min(LEN);
+        let mut buf = [0u8; LEN];
+        buf[..len].copy_from_slice(&data[..len]);
+        self.slots[idx] = Some(Slot { seq, len, data: buf, retries: 0 });
+        (seq, evicted)
+    }
+
+    /// Apply a cumulative ack (`cum_ack`: highest in-order sequence number the receiver has
+    /// seen) plus a SACK bitmask (bit `i` set means sequence `cum_ack.wrapping_add(2 + i)`
+    /// has also already arrived, out of order). Everything at or before `cum_ack` is
+    /// considered delivered and dropped from the ring. Every gap strictly after `cum_ack`
+    /// that the bitmask says hasn't arrived yet is handed to `resend` for retransmission
+    /// (retry count incremented); one that's already hit `MAX_RETRIES` is dropped and handed
+    /// to `lost` instead.
+    pub fn apply_ack(
+        &mut self,
+        cum_ack: u16,
+        sack_bitmap: u32,
+        mut resend: impl FnMut(u16, &[u8]),
+        mut lost: impl FnMut(u16),
+    ) {
+        for slot in self.slots.iter_mut() {
+            let done = match slot {
+                Some(s) if seq_le(s.seq, cum_ack) => true,
+                Some(s) => {
+                    let offset = s.seq.wrapping_sub(cum_ack).wrapping_sub(2);
+                    let acked_out_of_order =
+                        (offset as u32) < SACK_BITS && (sack_bitmap >> offset) & 1 == 1;
+                    if acked_out_of_order {
+                        true
+                    } else {
+                        s.retries += 1;
+                        if s.retries > MAX_RETRIES {
+                            lost(s.seq);
+                            true
+                        } else {
+                            resend(s.seq, &s.data[..s.len]);
+                            false
+                        }
+                    }
+                }
+                None => false,
+            };
+            if done {
+                *slot = None;
+            }
+        }
+    }
+
+    /// Number of packets currently unacked.
+    pub fn len(&self) -> usize {
+        self.slots.iter().filter(|s| s.is_some()).count()
+    }
+}
@@ -0,0 +1,65 @@
+//! Versioned, typed framing for the COM net bridge, replacing ad-hoc offset arithmetic like
+//! `&mut txbuf_backing[..num_bytes + PBUF_HEADER_SIZE]` with a single struct definition of
+//! the wire header, encoded with `postcard` (a `no_std`, no-alloc-required serde backend --
+//! `encode`/`decode` below write directly into caller-owned buffers, no heap involved).
+//!
+//! Adding a field (e.g. the sequence number `net::sack::RetransmitRing` already assigns, or
+//! a LEDBAT delay timestamp) is now a `FrameHeader` edit instead of finding every place that
+//! does `buf[N..N+2]` math, and `version` lets a decoder refuse a frame from a firmware
+//! revision it doesn't understand instead of misparsing its offsets.
+//!
+//! NOTE: this only covers the EC-side encode/decode path. Actually switching the COM bridge
+//! over to this framing means the Xous-side COM driver on the SoC has to decode the same
+//! header -- that driver isn't part of this tree, and there's no `ComState` verb carrying
+//! the extra header bytes either (`com_rs`, the crate defining the COM bus command set,
+//! isn't vendored here to add one to). So `NET_FRAME_SEND_*`/`NET_FRAME_FETCH_*` still use
+//! the raw framing today; this module is ready to be the typed replacement once both sides
+//! can move together.
+
+use serde::{Deserialize, Serialize};
+
+/// Bumped whenever `FrameHeader`'s fields change in a way that isn't backward compatible.
+pub const WIRE_FORMAT_VERSION: u8 = 1;
+
+/// Header prefixed to a bridge frame's payload. `seq` matches `net::sack::RetransmitRing`'s
+/// per-packet sequence numbers; `flags` is reserved for future per-frame signaling (e.g. "is
+/// a retransmit").
+#[derive(Copy, Clone, Debug, Serialize, Deserialize)]
+pub struct FrameHeader {
+    pub version: u8,
+    pub flags: u8,
+    pub seq: u16,
+    pub length: u16,
+}
+
+/// Worst-case encoded size of `FrameHeader`: two plain bytes plus two `u16` fields, each of
+/// which postcard's default varint encoding can expand to up to 3 bytes.
+pub const FRAME_HEADER_MAX_SIZE: usize = 1 + 1 + 3 + 3;
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum BridgeProtoError {
+    Encode,
+    Decode,
+    /// Decoded successfully, but `version` doesn't match `WIRE_FORMAT_VERSION` -- the peer
+    /// is running firmware this side doesn't know how to interpret the rest of the frame for.
+    VersionMismatch { found: u8 },
+}
+
+/// Encode `header` into the front of `buf`, returning the number of bytes written. `buf`
+/// must be at least `FRAME_HEADER_MAX_SIZE` long.
+pub fn encode_header(header: &FrameHeader, buf: &mut [u8]) -> Result<usize, BridgeProtoError> {
+    postcard::to_slice(header, buf).map(|used| used.len()).map_err(|_| BridgeProtoError::Encode)
+}
+
+/// Decode a `FrameHeader` from the front of `buf`, returning it along with how many bytes
+/// it consumed (the payload starts right after). Rejects a header whose `version` doesn't
+/// match `WIRE_FORMAT_VERSION` rather than trying to interpret the rest of the frame anyway.
+pub fn decode_header(buf: &[u8]) -> Result<(FrameHeader, usize), BridgeProtoError> {
+    let before = buf.len();
+    let (header, remainder): (FrameHeader, &[u8]) =
+        postcard::take_from_bytes(buf).map_err(|_| BridgeProtoError::Decode)?;
+    if header.version != WIRE_FORMAT_VERSION {
+        return Err(BridgeProtoError::VersionMismatch { found: header.version });
+    }
+    Ok((header, before - remainder.len()))
+}
@@ -36,126 +36,169 @@ pub fn com_rx(timeout: u32) -> Result<u16, &'static str> {
     Ok(unsafe { (*com_rd).read() as u16 })
 }
 
+/// One pending COM-bus event: which interrupt bit it represents (one of the `com_rs::INT_*`
+/// constants `state` used to be built out of), and whatever payload word goes with it --
+/// `rx_len_bytes` for `INT_WLAN_RX_READY`, the `ConnectResult` for `INT_WLAN_CONNECT_EVENT`,
+/// or 0 for the bits that never carried one.
+#[derive(Copy, Clone)]
+struct ComEvent {
+    kind: u16,
+    payload: u16,
+}
+
+/// Bounded depth of the pending-event queue. Generous headroom over anything the main loop
+/// can produce in a single iteration; see `push_event` for what happens if it's ever actually
+/// filled.
+const EVENT_QUEUE_CAPACITY: usize = 16;
+
 pub struct ComInterrupts {
-    state: u16,
-    rx_len_bytes: u16,
+    queue: [Option<ComEvent>; EVENT_QUEUE_CAPACITY],
+    head: usize,
+    len: usize,
     mask: u16,
-    retrigger: bool,
 }
 #[allow(dead_code)]
 impl ComInterrupts {
     pub fn new() -> Self {
         ComInterrupts {
-            state: 0,
-            rx_len_bytes: 0,
+            queue: [None; EVENT_QUEUE_CAPACITY],
+            head: 0,
+            len: 0,
             mask: 0,
-            retrigger: false,
         }
     }
+    /// OR of every still-queued event's kind bit. This is what `state` used to be tracked as
+    /// separately from the event data; deriving it from the queue instead means it can never
+    /// drift out of sync with what's actually pending.
+    fn pending_kinds(&self) -> u16 {
+        let mut bits = 0u16;
+        for event in self.queue.iter().flatten() {
+            bits |= event.kind;
+        }
+        bits
+    }
+    /// Push one event onto the back of the queue. If the queue is already full, the oldest
+    /// entry is dropped to make room: the host falling behind enough to backlog
+    /// `EVENT_QUEUE_CAPACITY` events is already in trouble, and losing its oldest, stalest
+    /// event is a better failure mode than refusing to record the newest one.
+    fn push_event(&mut self, kind: u16, payload: u16) {
+        if self.len == EVENT_QUEUE_CAPACITY {
+            self.head = (self.head + 1) % EVENT_QUEUE_CAPACITY;
+            self.len -= 1;
+        }
+        let tail = (self.head + self.len) % EVENT_QUEUE_CAPACITY;
+        self.queue[tail] = Some(ComEvent { kind, payload });
+        self.len += 1;
+    }
     /// getter for pin state logic
     pub fn update_irq_pin(&mut self) {
         let mut com_csr = CSR::new(utralib::HW_COM_BASE as *mut u32);
-        if (self.state & self.mask) != 0 {
-            if !self.retrigger {
-                com_csr.rmwf(utra::com::CONTROL_HOST_INT, 1);
-            } else {
-                // drop the IRQ line to create a new edge, in case we have a new interrupt despite the ack
-                com_csr.rmwf(utra::com::CONTROL_HOST_INT, 0);
-                self.retrigger = false;
-            }
+        if (self.pending_kinds() & self.mask) != 0 {
+            com_csr.rmwf(utra::com::CONTROL_HOST_INT, 1);
         } else {
             com_csr.rmwf(utra::com::CONTROL_HOST_INT, 0);
-            self.retrigger = false;
         }
     }
     /// getter/setters from internal logic (wf200, etc.)
     pub fn set_rx_ready(&mut self, len: u16) {
-        // don't overwrite the connect result in case we got an Rx packet right after connecting
-        if (self.state & com_rs::INT_WLAN_CONNECT_EVENT) == 0 {
-            self.rx_len_bytes = len;
-        }
-        if self.state & com_rs::INT_WLAN_RX_READY != 0 {
-            // if we're getting a second packet before the prior one was serviced, fake an ack
-            // so that the interrupt edge fires again
-            self.retrigger = true;
-        } else {
-            self.state |= com_rs::INT_WLAN_RX_READY;
-        }
+        self.push_event(com_rs::INT_WLAN_RX_READY, len);
     }
     pub fn ack_rx_ready(&mut self) {
-        // don't overwrite the connect result in case we had a delayed ack before we got the result read
-        if (self.state & com_rs::INT_WLAN_CONNECT_EVENT) == 0 {
-            self.rx_len_bytes = 0;
-        }
-        self.state &= !com_rs::INT_WLAN_RX_READY;
+        self.ack(com_rs::INT_WLAN_RX_READY);
     }
     pub fn set_disconnect(&mut self) {
-        if self.state & com_rs::INT_WLAN_DISCONNECT != 0 {
-            // fake an ack so that the interrupt edge fires again
-            self.retrigger = true;
-        } else {
-            self.state |= com_rs::INT_WLAN_DISCONNECT;
-        }
+        self.push_event(com_rs::INT_WLAN_DISCONNECT, 0);
     }
     pub fn ack_disconnect(&mut self) {
-        self.state &= !com_rs::INT_WLAN_DISCONNECT;
+        self.ack(com_rs::INT_WLAN_DISCONNECT);
     }
     pub fn set_connect_result(&mut self, result: ConnectResult) {
-        if self.state & com_rs::INT_WLAN_CONNECT_EVENT != 0 {
-            self.retrigger = true;
-        } else {
-            self.state |= com_rs::INT_WLAN_CONNECT_EVENT;
-        }
-        self.rx_len_bytes = result as u16;
+        self.push_event(com_rs::INT_WLAN_CONNECT_EVENT, result as u16);
     }
     pub fn ack_connect_result(&mut self) {
-        self.state &= !com_rs::INT_WLAN_CONNECT_EVENT;
+        self.ack(com_rs::INT_WLAN_CONNECT_EVENT);
     }
     pub fn set_ipconf_update(&mut self) {
-        self.state |= com_rs::INT_WLAN_IPCONF_UPDATE;
+        self.push_event(com_rs::INT_WLAN_IPCONF_UPDATE, 0);
     }
     pub fn ack_ipconf_update(&mut self) {
-        self.state &= !com_rs::INT_WLAN_IPCONF_UPDATE;
+        self.ack(com_rs::INT_WLAN_IPCONF_UPDATE);
     }
     pub fn set_ssid_update(&mut self) {
-        self.state |= com_rs::INT_WLAN_SSID_UPDATE;
+        self.push_event(com_rs::INT_WLAN_SSID_UPDATE, 0);
     }
     pub fn ack_ssid_update(&mut self) {
-        self.state &= !com_rs::INT_WLAN_SSID_UPDATE;
+        self.ack(com_rs::INT_WLAN_SSID_UPDATE);
     }
     pub fn set_ssid_finished(&mut self) {
-        self.state |= com_rs::INT_WLAN_SSID_FINISHED;
+        self.push_event(com_rs::INT_WLAN_SSID_FINISHED, 0);
     }
     pub fn ack_ssid_finished(&mut self) {
-        self.state &= !com_rs::INT_WLAN_SSID_FINISHED;
+        self.ack(com_rs::INT_WLAN_SSID_FINISHED);
     }
     pub fn set_battery_critical(&mut self) {
-        self.state |= com_rs::INT_BATTERY_CRITICAL;
+        self.push_event(com_rs::INT_BATTERY_CRITICAL, 0);
     }
     pub fn ack_battery_critical(&mut self) {
-        self.state &= !com_rs::INT_BATTERY_CRITICAL;
+        self.ack(com_rs::INT_BATTERY_CRITICAL);
+    }
+    /// Whether `INT_BATTERY_CRITICAL` is still queued, regardless of `mask` -- unlike
+    /// `get_state()`, this is checked directly against `pending_kinds()` so
+    /// `power_mgmt::handle_low_voltage_event` can tell an unacked warning from a masked one
+    /// (the SoC could unmask it late and ack after the fact; a mask check alone could read
+    /// an unacked-but-masked interrupt as already handled).
+    pub fn battery_critical_pending(&self) -> bool {
+        self.pending_kinds() & com_rs::INT_BATTERY_CRITICAL != 0
     }
     pub fn set_tx_error(&mut self) {
-        self.state |= com_rs::INT_WLAN_TX_ERROR;
+        self.push_event(com_rs::INT_WLAN_TX_ERROR, 0);
     }
     pub fn ack_tx_error(&mut self) {
-        self.state &= !com_rs::INT_WLAN_TX_ERROR;
+        self.ack(com_rs::INT_WLAN_TX_ERROR);
     }
     pub fn set_rx_error(&mut self) {
-        self.state |= com_rs::INT_WLAN_RX_ERROR;
+        self.push_event(com_rs::INT_WLAN_RX_ERROR, 0);
     }
     pub fn ack_rx_error(&mut self) {
-        self.state &= !com_rs::INT_WLAN_RX_ERROR;
+        self.ack(com_rs::INT_WLAN_RX_ERROR);
     }
 
     /// getters/setters for COM bus interface
     pub fn get_mask(&self) -> u16 { self.mask }
     pub fn set_mask(&mut self, new_mask: u16) {
-        self.retrigger = true; // the intention is to cause any pre-existing interrupts to fire
         self.mask = new_mask;
     }
-    pub fn get_state(&self) -> [u16; 2] { [self.state & self.mask, self.rx_len_bytes] }
+    /// Same wire shape as before -- `[pending kinds masked in, the next event's payload]` --
+    /// but the payload is no longer a single scalar that a second same-kind event could
+    /// silently overwrite before the host read it. It's the payload of whichever event `ack`
+    /// will dequeue next; everything behind it is still sitting in `queue`, not clobbered.
+    pub fn get_state(&self) -> [u16; 2] {
+        let payload = self.queue[self.head].map(|event| event.payload).unwrap_or(0);
+        [self.pending_kinds() & self.mask, payload]
+    }
+    /// Dequeue events from the front of the queue for as long as their kind is in `acks`,
+    /// stopping at the first one that isn't (never skipping ahead out of order). A kind's bit
+    /// can still come back set in the very next `get_state()` after this -- that just means
+    /// another event of the same kind was already queued up behind the one just acked, and
+    /// it's handed over in turn. No retrigger is needed for that case: the IRQ line was
+    /// already asserted and simply stays asserted, which is the correct signal that there's
+    /// still more to drain -- the same drain-while-pending polling this mirrors from cyw43's
+    /// `Events`/`EventSubscriber` and Linux's `sk_buff` receive queues, in place of the old
+    /// one-edge-per-event model.
     pub fn ack(&mut self, acks: u16) {
-        self.state &= !acks;
+        while let Some(event) = self.queue[self.head] {
+            if event.kind & acks == 0 {
+                break;
+            }
+            self.queue[self.head] = None;
+            self.head = (self.head + 1) % EVENT_QUEUE_CAPACITY;
+            self.len -= 1;
+        }
     }
-}
\ No newline at end of file
+    /// Whether there's an unmasked, un-acked interrupt waiting to be drained -- the same
+    /// condition `update_irq_pin` asserts `CONTROL_HOST_INT` off of. Used by `crate::idle`
+    /// to decide whether it's safe to WFI.
+    pub fn irq_would_fire(&self) -> bool {
+        (self.pending_kinds() & self.mask) != 0
+    }
+}
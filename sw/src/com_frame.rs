@@ -0,0 +1,112 @@
+//! Length-framed, CRC-checked envelope for the COM link, modeled after the typed,
+//! length-carrying `MessageEnvelope`s Xous IPC uses for its own message passing.
+//!
+//! Today a single dropped SPI word desynchronizes the link silently -- the only recovery is
+//! the SoC and EC both noticing something looks wrong and manually issuing `ComState::LINK_SYNC`.
+//! This module adds a header word (packing a 4-bit rolling sequence number and a 12-bit word
+//! count) plus a trailing CRC-16 over the payload, so a glitched transaction can be detected
+//! and rejected with `ComState::ERROR` before it's acted on, rather than silently corrupting
+//! state.
+//!
+//! NOTE: this is the framing layer only. The actual COM verb dispatch in `main.rs` uses
+//! `ComState`/`ComSpec` from the external `com_rs` crate, not `crate::comstates` -- and
+//! `com_rs` isn't vendored in this tree, so there's nowhere to add the matching frame-aware
+//! dispatch without upstreaming this same change there. This module is ready to be adopted by
+//! both sides of the link once that happens.
+
+use crate::comstates::ComSpec;
+
+/// Number of header bits given to the rolling sequence number; the rest of the 16-bit header
+/// word is the payload word count.
+const SEQ_BITS: u32 = 4;
+const LENGTH_MASK: u16 = (1 << (16 - SEQ_BITS)) - 1;
+
+/// Maximum payload length (in words) a single frame's header can describe.
+pub const MAX_FRAME_WORDS: u16 = LENGTH_MASK;
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct FrameHeader {
+    /// Rolls over mod 16; lets a receiver notice a skipped or repeated transaction even if
+    /// the CRC happens to still check out.
+    pub seq: u8,
+    /// Number of payload words following the header, not counting the trailing CRC word.
+    pub length: u16,
+}
+
+impl FrameHeader {
+    pub fn encode(&self) -> u16 {
+        ((self.seq as u16 & ((1 << SEQ_BITS) - 1)) << (16 - SEQ_BITS)) | (self.length & LENGTH_MASK)
+    }
+    pub fn decode(word: u16) -> Self {
+        FrameHeader {
+            seq: (word >> (16 - SEQ_BITS)) as u8,
+            length: word & LENGTH_MASK,
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum ComFrameError {
+    /// CRC-16 over the payload didn't match the trailing CRC word -- the transaction was
+    /// corrupted in flight.
+    CrcMismatch,
+    /// The header's `length` doesn't fit what the caller's buffer or `ComSpec` expects.
+    LengthMismatch,
+    /// Not enough words to contain a header and a CRC.
+    Truncated,
+}
+
+/// CRC-16/CCITT-FALSE (poly 0x1021, init 0xFFFF) over a slice of 16-bit words, computed
+/// big-endian byte-by-byte the way the SPI link already shifts words out.
+pub fn crc16(words: &[u16]) -> u16 {
+    let mut crc: u16 = 0xFFFF;
+    for &word in words {
+        for byte in [(word >> 8) as u8, word as u8] {
+            crc ^= (byte as u16) << 8;
+            for _ in 0..8 {
+                crc = if crc & 0x8000 != 0 { (crc << 1) ^ 0x1021 } else { crc << 1 };
+            }
+        }
+    }
+    crc
+}
+
+/// Build a framed transaction into `out`: `[header, ...payload, crc]`. Returns the number of
+/// words written, or `None` if `payload` is longer than `MAX_FRAME_WORDS` or doesn't fit `out`.
+pub fn encode_frame(seq: u8, payload: &[u16], out: &mut [u16]) -> Option<usize> {
+    if payload.len() > MAX_FRAME_WORDS as usize || out.len() < payload.len() + 2 {
+        return None;
+    }
+    let header = FrameHeader { seq, length: payload.len() as u16 };
+    out[0] = header.encode();
+    out[1..1 + payload.len()].copy_from_slice(payload);
+    out[1 + payload.len()] = crc16(payload);
+    Some(payload.len() + 2)
+}
+
+/// Validate and unpack a framed transaction from `words`, returning the header and a slice of
+/// the raw `words` buffer holding just the payload. Does not consult `spec` unless given one
+/// to bound-check a non-dynamic verb's expected length against the header's claim.
+pub fn decode_frame<'a>(words: &'a [u16], spec: Option<&ComSpec>) -> Result<(FrameHeader, &'a [u16]), ComFrameError> {
+    if words.len() < 2 {
+        return Err(ComFrameError::Truncated);
+    }
+    let header = FrameHeader::decode(words[0]);
+    let payload_end = 1 + header.length as usize;
+    if words.len() < payload_end + 1 {
+        return Err(ComFrameError::Truncated);
+    }
+    if let Some(spec) = spec {
+        if !spec.dynamic_r_words && header.length != spec.r_words {
+            return Err(ComFrameError::LengthMismatch);
+        }
+        if spec.dynamic_r_words && header.length > spec.r_words {
+            return Err(ComFrameError::LengthMismatch);
+        }
+    }
+    let payload = &words[1..payload_end];
+    if words[payload_end] != crc16(payload) {
+        return Err(ComFrameError::CrcMismatch);
+    }
+    Ok((header, payload))
+}
@@ -0,0 +1,108 @@
+//! Reader for the partition table and per-sector CRC-32 manifest `xtask`'s `create_image`
+//! writes into the 4096-byte header of `ec_fw.bin`/`wf200_fw.bin`, right after the image's
+//! monotonic sequence number (see `xtask/src/main.rs::create_image`).
+//!
+//! `ec_fw.bin` carries a partition table (one [`Partition`] record each for gateware,
+//! loader, and kernel, in that order) right after the sequence number, so a section's flash
+//! offset and length can be looked up and bounds-checked against its region capacity at
+//! runtime instead of trusting compile-time region constants to still match whatever `xtask`
+//! built. `wf200_fw.bin` is a single blob with no sections, so it has no partition table --
+//! its manifest starts right after the sequence number instead.
+//!
+//! After whichever of those, checking one sector at a time against the CRC manifest lets a
+//! resumed or interrupted flash write be verified (and, if needed, re-flashed) sector by
+//! sector instead of re-reading and re-hashing the whole image.
+//!
+//! Nothing in this crate currently parses the rest of that header (signature, version,
+//! length, whole-image hash) or drives a flash write from it -- the consumer of this image
+//! format is an out-of-tree loader/provisioning tool (see `fw_update`'s module doc comment).
+//! [`read_partitions`] and [`verify_sector`] are library infrastructure for that tool to
+//! call; neither has a call site here yet.
+
+use crate::spi;
+use core::convert::TryInto;
+
+/// Flash sector size the CRC manifest is built over, matching `fw_update::SECTOR_SIZE`.
+pub const SECTOR_SIZE: u32 = 4096;
+
+/// Byte offset where the `ec_fw.bin` partition table starts (32-byte hash, 4-byte
+/// signature, 4-byte version, 4-byte length, 4-byte sequence number).
+pub const PARTITION_TABLE_BASE: usize = 48;
+
+/// Number of [`Partition`] records in the table, and their fixed order.
+pub const PARTITION_COUNT: usize = 3;
+
+/// One section of `ec_fw.bin`: where it starts, how long it actually is, and how much room
+/// it's allowed to use. `length > capacity` is exactly the "section overruns its slot"
+/// condition `xtask` itself refuses to build (see `create_image`); a loader reading this
+/// back should treat it the same way -- a hard failure, not a silent truncation.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Partition {
+    pub offset: u32,
+    pub length: u32,
+    pub capacity: u32,
+}
+impl Partition {
+    pub fn overruns_capacity(&self) -> bool {
+        self.length > self.capacity
+    }
+}
+
+/// Byte offset where the CRC-32 manifest starts in an `ec_fw.bin` header, i.e. right after
+/// the partition table.
+pub const EC_MANIFEST_BASE: usize = PARTITION_TABLE_BASE + PARTITION_COUNT * 12;
+
+/// Byte offset where the CRC-32 manifest starts in a `wf200_fw.bin` header -- there's no
+/// partition table in that image, so this is just [`PARTITION_TABLE_BASE`] under another
+/// name for the single-blob case.
+pub const WF_MANIFEST_BASE: usize = PARTITION_TABLE_BASE;
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum FwImageError {
+    /// The header ends before the partition table, or before `sector_index`'s manifest entry.
+    HeaderTruncated,
+    /// `sector` read back from flash didn't match its manifest entry.
+    CrcMismatch,
+}
+
+/// Read the three [`Partition`] records (gateware, loader, kernel, in that order) out of an
+/// `ec_fw.bin` header. `header` is the full 4096-byte header (or at least its first
+/// `EC_MANIFEST_BASE` bytes).
+pub fn read_partitions(header: &[u8]) -> Result<[Partition; PARTITION_COUNT], FwImageError> {
+    if header.len() < EC_MANIFEST_BASE {
+        return Err(FwImageError::HeaderTruncated);
+    }
+    let mut partitions = [Partition { offset: 0, length: 0, capacity: 0 }; PARTITION_COUNT];
+    for (i, partition) in partitions.iter_mut().enumerate() {
+        let base = PARTITION_TABLE_BASE + i * 12;
+        partition.offset = u32::from_le_bytes(header[base..base + 4].try_into().unwrap());
+        partition.length = u32::from_le_bytes(header[base + 4..base + 8].try_into().unwrap());
+        partition.capacity = u32::from_le_bytes(header[base + 8..base + 12].try_into().unwrap());
+    }
+    Ok(partitions)
+}
+
+/// Check one sector of payload data against its manifest entry. `manifest` is the header
+/// region starting at the image's manifest base ([`EC_MANIFEST_BASE`] or
+/// [`WF_MANIFEST_BASE`]), `sector_index` is which [`SECTOR_SIZE`]-byte chunk of the payload
+/// `sector` holds, and `sector` is that chunk as read back from flash (it may be shorter
+/// than [`SECTOR_SIZE`] for the last sector of an image whose length isn't a multiple of
+/// it).
+pub fn verify_sector(manifest: &[u8], sector_index: usize, sector: &[u8]) -> Result<(), FwImageError> {
+    let entry = sector_index * 4;
+    if manifest.len() < entry + 4 {
+        return Err(FwImageError::HeaderTruncated);
+    }
+    let expected = u32::from_le_bytes([
+        manifest[entry],
+        manifest[entry + 1],
+        manifest[entry + 2],
+        manifest[entry + 3],
+    ]);
+    let crc = spi::crc32_finalize(spi::crc32_update(spi::CRC32_INIT, sector));
+    if crc == expected {
+        Ok(())
+    } else {
+        Err(FwImageError::CrcMismatch)
+    }
+}
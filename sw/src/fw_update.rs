@@ -0,0 +1,206 @@
+//! A/B firmware update: two application slots in SPI flash plus a small state region
+//! recording which slot is active, whether a swap is pending, and whether the current
+//! boot has been confirmed healthy.
+//!
+//! This crate only owns the application-side half of the scheme: streaming a new image
+//! into the inactive slot, verifying it (signature check lives in
+//! [`fw_sig`](crate::fw_sig)), arming the pending-swap flag, and confirming or reverting
+//! on the following boot. Which slot actually gets mapped at reset is up to whatever
+//! reads [`UPDATE_STATE_BASE`] before jumping to application code (the loader, not this
+//! crate) -- that's a separate component from this tree.
+use crate::spi;
+use debug::{logln, LL};
+use utralib::generated::*;
+
+/// Size of one application slot. Large enough for this EC image with headroom; the
+/// state region sits in its own sector immediately past the two slots.
+pub const SLOT_SIZE: u32 = 0x0004_0000;
+pub const SLOT_A_BASE: u32 = 0x0000_0000;
+pub const SLOT_B_BASE: u32 = SLOT_A_BASE + SLOT_SIZE;
+pub const UPDATE_STATE_BASE: u32 = SLOT_B_BASE + SLOT_SIZE;
+
+const SECTOR_SIZE: u32 = 4096;
+const PAGE_SIZE: u32 = 256;
+
+const STATE_MAGIC: u32 = 0x4542_4155; // "UABE", arbitrary but distinct from erased 0xFF..0xFF
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Slot {
+    A,
+    B,
+}
+impl Slot {
+    fn base(self) -> u32 {
+        match self {
+            Slot::A => SLOT_A_BASE,
+            Slot::B => SLOT_B_BASE,
+        }
+    }
+    fn other(self) -> Slot {
+        match self {
+            Slot::A => Slot::B,
+            Slot::B => Slot::A,
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+enum BootStatus {
+    Confirmed,
+    PendingConfirmation,
+}
+
+#[derive(Copy, Clone)]
+struct UpdateState {
+    active: Slot,
+    pending_swap: bool,
+    boot_status: BootStatus,
+}
+
+fn flash_read(addr: u32, data: &mut [u8]) {
+    spi::spi_cmd(spi::CMD_4READ, Some(addr), Some(data));
+}
+
+fn load_state() -> UpdateState {
+    let mut buf = [0u8; 8];
+    flash_read(UPDATE_STATE_BASE, &mut buf);
+    let magic = u32::from_le_bytes([buf[0], buf[1], buf[2], buf[3]]);
+    if magic != STATE_MAGIC {
+        // Unprogrammed (erased) or corrupt state sector: treat it as a factory image
+        // that has never run an update, booting slot A and already confirmed.
+        return UpdateState {
+            active: Slot::A,
+            pending_swap: false,
+            boot_status: BootStatus::Confirmed,
+        };
+    }
+    UpdateState {
+        active: if buf[4] == 0 { Slot::A } else { Slot::B },
+        pending_swap: buf[5] != 0,
+        boot_status: if buf[6] != 0 {
+            BootStatus::PendingConfirmation
+        } else {
+            BootStatus::Confirmed
+        },
+    }
+}
+
+fn store_state(state: &UpdateState) {
+    let mut page = [0xFFu8; PAGE_SIZE as usize];
+    page[0..4].copy_from_slice(&STATE_MAGIC.to_le_bytes());
+    page[4] = if state.active == Slot::A { 0 } else { 1 };
+    page[5] = state.pending_swap as u8;
+    page[6] = (state.boot_status == BootStatus::PendingConfirmation) as u8;
+    // The state region is a single sector; NOR requires an erase before any rewrite, so
+    // unlike `Updater::write` below there's no "already erased" bookkeeping to do.
+    if let Err(e) = spi::spi_erase_region(UPDATE_STATE_BASE, SECTOR_SIZE) { logln!(LL::Warn, "FwUpdateStateEraseErr {:?}", e); }
+    if let Err(e) = spi::spi_program_page(UPDATE_STATE_BASE, &mut page) { logln!(LL::Warn, "FwUpdateStateProgramErr {:?}", e); }
+}
+
+/// Streams a new image into the slot that isn't currently active. Call [`Updater::write`]
+/// with successive chunks of the incoming image (COM/UART transfer framing is the
+/// caller's concern), then [`Updater::finish_and_reboot`] once the whole image has
+/// arrived.
+pub struct Updater {
+    target: Slot,
+    next_offset: u32,
+    erased_sectors: u32,
+    /// Exact count of payload bytes written so far. Distinct from `next_offset`, which
+    /// advances a full `PAGE_SIZE` per page programmed even when that page is only
+    /// partly filled -- this is the length the trailing signature in `fw_sig` is computed
+    /// over, so it has to match the payload exactly, not the page-rounded footprint.
+    written_len: u32,
+}
+impl Updater {
+    pub fn begin() -> Updater {
+        let target = load_state().active.other();
+        logln!(LL::Info, "FwUpdateBegin");
+        Updater {
+            target,
+            next_offset: 0,
+            erased_sectors: 0,
+            written_len: 0,
+        }
+    }
+
+    /// Program the next chunk of the incoming image. Sectors within the target slot are
+    /// erased lazily, the first time a write touches them, so a sector spanning several
+    /// `write()` calls is only erased once -- matching how the underlying NOR actually
+    /// behaves rather than re-erasing per page.
+    pub fn write(&mut self, data: &[u8]) {
+        let mut consumed = 0usize;
+        while consumed < data.len() {
+            let sector = self.next_offset / SECTOR_SIZE;
+            if sector >= self.erased_sectors {
+                if let Err(e) = spi::spi_erase_region(self.target.base() + sector * SECTOR_SIZE, SECTOR_SIZE) {
+                    logln!(LL::Warn, "FwUpdateEraseErr {:?}", e);
+                }
+                self.erased_sectors = sector + 1;
+            }
+            let take = (data.len() - consumed).min(PAGE_SIZE as usize);
+            let mut page = [0xFFu8; PAGE_SIZE as usize];
+            page[..take].copy_from_slice(&data[consumed..consumed + take]);
+            if let Err(e) = spi::spi_program_page(self.target.base() + self.next_offset, &mut page) {
+                logln!(LL::Warn, "FwUpdateProgramErr {:?}", e);
+            }
+            self.next_offset += PAGE_SIZE;
+            consumed += take;
+        }
+        self.written_len += data.len() as u32;
+    }
+
+    /// Check the detached ed25519 signature trailing the image just written
+    /// (`fw_sig::verify_image`) and, only on success, arm the pending-swap flag and
+    /// reboot into it. `active` itself is left untouched here on purpose, so a crash
+    /// between this call and the reboot taking effect still leaves the
+    /// currently-running slot as the one the loader picks.
+    pub fn finish_and_reboot(self) -> Result<(), crate::fw_sig::SigVerifyError> {
+        crate::fw_sig::verify_image(self.target.base(), self.written_len)?;
+        let mut state = load_state();
+        state.pending_swap = true;
+        state.boot_status = BootStatus::PendingConfirmation;
+        store_state(&state);
+        reboot();
+        unreachable!("REBOOT should have reset the SoC before this point");
+    }
+}
+
+/// Call once near the top of `main()`, before anything that could hang waiting on the
+/// COM bus or Wi-Fi coming up. If the previous boot armed a swap but never confirmed it
+/// (the watchdog window in [`confirm_boot`]'s caller expired), revert: clear the pending
+/// flag without flipping `active`, so the loader's next read of this state region finds
+/// the previous known-good slot still selected.
+pub fn check_watchdog_revert() {
+    let mut state = load_state();
+    if state.boot_status == BootStatus::PendingConfirmation {
+        logln!(LL::Warn, "FwUpdateWatchdogRevert");
+        state.pending_swap = false;
+        state.boot_status = BootStatus::Confirmed;
+        store_state(&state);
+    }
+}
+
+/// Call once the application has convinced itself the new image is healthy. If a swap
+/// was pending, this is what actually commits it -- `active` flips to the slot that was
+/// just booted, and the next reboot (from any cause) stays there.
+pub fn confirm_boot() {
+    let mut state = load_state();
+    if state.boot_status == BootStatus::PendingConfirmation {
+        if state.pending_swap {
+            state.active = state.active.other();
+            state.pending_swap = false;
+        }
+        state.boot_status = BootStatus::Confirmed;
+        store_state(&state);
+        logln!(LL::Info, "FwUpdateConfirmed");
+    }
+}
+
+fn reboot() {
+    // Field names mirror this SoC's documented address-selectable warm-reboot core
+    // (`addr` selects the image to jump to, `soc_reset` triggers the reset) but aren't
+    // checked against the generated `utralib` bindings, which this snapshot doesn't
+    // vendor -- see the `REBOOT` peripheral this request calls out by name.
+    let mut reboot_csr = CSR::new(HW_REBOOT_BASE as *mut u32);
+    reboot_csr.wfo(utra::reboot::SOC_RESET_SOC_RESET, 1);
+}
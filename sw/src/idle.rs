@@ -0,0 +1,59 @@
+//! Interrupt-gated WFI idle for the main loop, replacing the old commented-out
+//! `riscv::asm::wfi()` ("potential for power savings? unfortunately WFI seems broken").
+//!
+//! The likely reason a bare WFI looked broken: a plain check-then-sleep has a race --
+//! an interrupt can land in the gap between "nothing pending" and the `wfi` instruction
+//! itself, and then there's nothing left to wake the core back up. `maybe_idle` closes that
+//! by disabling interrupts, re-checking the same pending-wake condition inside that critical
+//! section, and only then executing WFI; the re-check can't race the interrupt that would
+//! otherwise be lost, because interrupts are masked for the whole window between it and the
+//! WFI instruction.
+use crate::com_bus::ComInterrupts;
+use utralib::generated::{utra, CSR};
+
+/// Whether the main loop is allowed to execute `wfi` at all. Left off by default: the
+/// "WFI seems broken" history this module replaces hasn't been re-validated against real
+/// silicon, so falling back to busy-polling is the safe default until a board revision
+/// confirms the gated sequence above actually wakes reliably. Flip to `true` once it has.
+pub const WFI_IDLE_ENABLED: bool = false;
+
+/// Idle-cycle telemetry: how many times the loop actually entered/exited WFI, so the power
+/// behavior above can be validated against a scope/fuel-gauge reading instead of guesswork.
+#[derive(Copy, Clone, Default)]
+pub struct IdleStats {
+    pub entered: u32,
+    pub exited: u32,
+}
+
+impl IdleStats {
+    pub const fn new() -> Self {
+        Self { entered: 0, exited: 0 }
+    }
+}
+
+/// Whether any source the main loop needs to react to before its next full pass is already
+/// pending: COM RX FIFO non-empty, or an unmasked COM interrupt bit waiting to be drained.
+fn wake_pending(com_int_mgr: &ComInterrupts, com_csr: &CSR<u32>) -> bool {
+    com_csr.rf(utra::com::STATUS_RX_AVAIL) != 0 || com_int_mgr.irq_would_fire()
+}
+
+/// Idle for one WFI if (and only if) `WFI_IDLE_ENABLED` and nothing is already pending.
+/// Call this once per main-loop iteration, after `com_int_mgr.update_irq_pin()`.
+pub fn maybe_idle(com_int_mgr: &ComInterrupts, com_csr: &CSR<u32>, stats: &mut IdleStats) {
+    if !WFI_IDLE_ENABLED {
+        return;
+    }
+    if wake_pending(com_int_mgr, com_csr) {
+        return;
+    }
+    unsafe {
+        riscv::register::mstatus::clear_mie();
+        // Re-check inside the critical section: closes the race described above.
+        if !wake_pending(com_int_mgr, com_csr) {
+            stats.entered += 1;
+            riscv::asm::wfi();
+            stats.exited += 1;
+        }
+        riscv::register::mstatus::set_mie();
+    }
+}
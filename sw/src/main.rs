@@ -12,6 +12,7 @@ extern crate wfx_rs;
 extern crate wfx_sys;
 extern crate xous_nommu;
 
+use betrusted_hal::alarm;
 use betrusted_hal::api_bq25618::BtCharger;
 use betrusted_hal::api_gasgauge::{
     gg_full_capacity, gg_remaining_capacity, gg_set_design_capacity, gg_set_hibernate, gg_start,
@@ -19,7 +20,7 @@ use betrusted_hal::api_gasgauge::{
 };
 use betrusted_hal::api_lm3509::BtBacklight;
 use betrusted_hal::api_lsm6ds3::Imu;
-use betrusted_hal::api_tusb320::BtUsbCc;
+use betrusted_hal::api_tusb320::{BtUsbCc, DrpPreference};
 //use betrusted_hal::hal_hardi2c::Hardi2c;
 use betrusted_hal::hal_i2c::Hardi2c;
 use betrusted_hal::hal_time::{
@@ -40,11 +41,20 @@ use volatile::Volatile;
 use wfx_rs::hal_wf200::{self, WIFI_MTU};
 
 // Modules from this crate
+mod bridge_proto;
 mod com_bus;
+mod com_frame;
+mod comstates;
+mod fw_image;
+mod fw_sig;
+mod fw_update;
+mod idle;
+mod ipc_servers;
 mod power_mgmt;
 mod spi;
 mod str_buf;
 mod uart;
+mod wfx_flash;
 mod wifi;
 mod wlan;
 use com_bus::{com_rx, com_tx};
@@ -63,6 +73,9 @@ const LOG_LEVEL: LL = LL::Debug;
 
 // Constants
 const CONFIG_CLOCK_FREQUENCY: u32 = 18_000_000;
+/// Seconds of uptime with a running main loop before a pending firmware update is
+/// treated as confirmed-healthy; see `fw_update::confirm_boot`.
+const FW_UPDATE_CONFIRM_SECONDS: u32 = 8;
 
 /// Infinite loop panic handler (TODO: fix this to use less power)
 #[panic_handler]
@@ -101,7 +114,9 @@ fn ticktimer_int_handler(_irq_no: usize) {
         }
     }
 
-    set_msleep_target_ticks(50); // resetting this will also clear the alarm
+    // Fire any software alarms that have come due and reprogram MSLEEP_TARGET for the next
+    // soonest one, falling back to the original 50-tick cadence when none are pending.
+    alarm::on_ticktimer_irq();
 
     ticktimer_csr.wfo(utra::ticktimer::EV_PENDING_ALARM, 1);
 }
@@ -157,6 +172,9 @@ fn stack_check() {
 #[entry]
 fn main() -> ! {
     logln!(LL::Info, "\r\n====UP5K==11");
+    // If the last firmware update rebooted into a slot that never confirmed itself
+    // healthy, revert to the previous one before anything else runs.
+    fw_update::check_watchdog_revert();
     let gitrev = core::env!("GIT_REV");
     let mut com_csr = CSR::new(HW_COM_BASE as *mut u32);
     let mut crg_csr = CSR::new(HW_CRG_BASE as *mut u32);
@@ -170,6 +188,7 @@ fn main() -> ! {
     let mut loopcounter: u32 = 0; // in seconds, so this will last ~125 years
     let mut pd_loop_timer: u32 = 0;
     let mut soc_off_delay_timer: u32 = 0;
+    let mut fw_update_confirmed = false;
 
     let mut i2c = Hardi2c::new();
     let mut hw = power_mgmt::PowerHardware {
@@ -187,10 +206,21 @@ fn main() -> ! {
         battery_panic: false,
         voltage_glitch: false,
         usb_cc_event: false,
+        state_of_charge: 100,
+        wifi_throttle: power_mgmt::WifiThrottleLevel::Normal,
+        policy: power_mgmt::PowerPolicy::default(),
+        pd_stage: power_mgmt::PowerDownStage::Idle,
     };
     let mut last_run_time: u32;
     let mut com_sentinel: u16 = 0; // for link debugging mostly
     let mut flash_update_lock = false;
+    // Running CRC-32 over every page programmed by `ComState::FLASH_PP` during the current
+    // `flash_update_lock` session, reset at `ComState::FLASH_LOCK`. There's no
+    // `ComState::FLASH_PP_FINALIZE` verb to return it through yet -- `com_rs` (the crate
+    // that defines the COM bus command set) isn't vendored in this tree to add one to --
+    // so the host has no way to fetch it until that verb exists, but the accumulator itself
+    // is being kept correctly in the meantime.
+    let mut flash_pp_crc: u32 = spi::CRC32_INIT;
 
     let mut use_wifi: bool = true;
     let mut wifi_ready: bool = false;
@@ -210,35 +240,51 @@ fn main() -> ! {
     uptime.start();
     last_run_time = get_time_ms();
     const DHCP_POLL_MS: u32 = 101;
+    // SoftAP link-state poll period; see `wifi::ap_clock_state_machine`. Coarser than the
+    // DHCP poll since today it's only watching for a `Starting` timeout, not anything that
+    // needs sub-100ms resolution.
+    const AP_POLL_MS: u32 = 503;
+    let mut ap_oneshot = Countdown::new();
+    // DTIM skip used while the SoC is off and we're still associated: short enough that a
+    // buffered unicast frame (an inbound packet the ARP-offload/filter logic would treat as
+    // a wake event) doesn't get aged out by the AP before the next beacon we actually wake
+    // up for.
+    const SOC_OFF_DTIM_SKIP: u8 = 3;
     let mut dhcp_oneshot = Countdown::new();
 
     logln!(LL::Debug, "i2c...");
-    i2c.i2c_init(CONFIG_CLOCK_FREQUENCY);
+    i2c.i2c_init(betrusted_hal::hal_i2c::I2cConfig::new(CONFIG_CLOCK_FREQUENCY));
     // this needs to be one of the first things called after I2C comes up
     hw.charger.chg_set_safety(&mut i2c);
     loghexln!(LL::Debug, "gg devtype: ", betrusted_hal::api_gasgauge::gg_get_devtype(&mut i2c));
     // put the gg out of hibernate so we have a higher resolution reporting
-    gg_start(&mut i2c);
-    hw.charger.chg_set_autoparams(&mut i2c);
-    hw.charger.chg_start(&mut i2c);
-    let tusb320_rev = hw.usb_cc.init(&mut i2c);
+    if let Err(e) = gg_start(&mut i2c) { logln!(LL::Debug, "GgStartErr {:?}", e); }
+    if let Err(e) = hw.charger.chg_set_autoparams(&mut i2c) { logln!(LL::Debug, "ChgAutoparamsErr {:?}", e); }
+    if let Err(e) = hw.charger.chg_start(&mut i2c) { logln!(LL::Debug, "ChgStartErr {:?}", e); }
+    // chg_start just reprogrammed BQ25618_00_ILIM to a flat 1500mA; negotiate a real ceiling
+    // for whatever is actually plugged in now that VBUS is up.
+    if let Err(e) = hw.charger.chg_negotiate_input_current(&mut i2c) { logln!(LL::Debug, "ChgNegotiateErr {:?}", e); }
+    let tusb320_rev = hw.usb_cc.init(&mut i2c, DrpPreference::TrySink);
     loghexln!(LL::Debug, "tusb320_rev ", tusb320_rev);
     // Initialize the IMU, note special handling for debug logging of init result
     let mut tap_check_phase: u32 = 0;
     match Imu::init(&mut i2c) {
         Ok(who_am_i_reg) => loghexln!(LL::Debug, "ImuInitOk ", who_am_i_reg), // Should be 0x6A (LSM6DSL) or 0x69 (alt LSM6DS3)
-        Err(n) => loghexln!(LL::Debug, "ImuInitErr ", n),
+        Err(e) => logln!(LL::Debug, "ImuInitErr {:?}", e),
     }
     // make sure the backlight is off on boot
     hw.backlight.set_brightness(&mut i2c, 0, 0);
-    hw.charger.update_regs(&mut i2c);
+    if let Err(e) = hw.charger.update_regs(&mut i2c) { logln!(LL::Debug, "ChgUpdateRegsErr {:?}", e); }
     logln!(LL::Debug, "...i2c OK");
 
     spi_standby(); // make sure the OE's are off, no spurious power consumption
+    let flash_info = spi::probe_flash();
+    loghexln!(LL::Debug, "flash mfg ", flash_info.manufacturer_id as u32);
 
     let _ = xous_nommu::syscalls::sys_interrupt_claim(
         utra::ticktimer::TICKTIMER_IRQ,
         ticktimer_int_handler,
+        0,
     );
     set_msleep_target_ticks(50);
     ticktimer_csr.wfo(utra::ticktimer::EV_PENDING_ALARM, 1); // clear the pending signal just in case
@@ -267,7 +313,17 @@ fn main() -> ! {
 
     // interrupt manager for COM interface
     let mut com_int_mgr = com_bus::ComInterrupts::new();
+    let mut idle_stats = idle::IdleStats::new();
+    let mut charger_events = betrusted_hal::api_bq25618::ChargerEventQueue::new();
     let mut tx_errs: u32 = 0;
+    // Selective-ACK retransmit bookkeeping for the COM net bridge's TX path (see
+    // `net::sack`): every outbound frame is assigned a sequence number and kept here until
+    // acked. There's no wire-level ack/SACK-bitmask exchange to drive `apply_ack` with yet --
+    // that needs extra header fields in the COM bridge framing, and `com_rs` (the crate that
+    // defines the COM bus command set) isn't vendored in this tree to add them to -- so for
+    // now the ring only gives us the bounded drop-oldest-with-a-hard-error behavior below;
+    // it's ready for `apply_ack` once a peer can actually echo back what it's received.
+    let mut net_retransmit: net::sack::RetransmitRing<8, WIFI_MTU> = net::sack::RetransmitRing::new();
 
     //////////////////////// MAIN LOOP ------------------
     logln!(LL::Info, "main loop");
@@ -276,6 +332,23 @@ fn main() -> ! {
             //////////////////////// WIFI HANDLER BLOCK ---------
             if use_wifi && wifi_ready {
                 wifi::handle_event();
+                wfx_rs::hal_wf200::pm_poll();
+                wfx_rs::hal_wf200::wfx_counters_poll();
+                // Drain at most one queued pcap record per pass -- see `hal_wf200::pcap` for why
+                // this is split from the `capture_frame` calls in the RX/TX hot paths.
+                wfx_rs::hal_wf200::pcap_service();
+                // Auto-recover from an EXCEPTION_IND/ERROR_IND fault: reset + reload the WF200,
+                // and re-join whatever AP we were last connected to, bounded by a retry counter
+                // inside poll_recovery_needed() so a chip stuck faulting doesn't thrash the SPI
+                // bus forever -- once that budget is spent it stops returning `Some` here and the
+                // fault is left for the host to see via poll_wfx_err_pending() below instead.
+                if let Some(reconnect) = wfx_rs::hal_wf200::poll_recovery_needed() {
+                    logln!(LL::Warn, "WfxAutoRecover");
+                    wifi::wf200_reset_and_init(&mut use_wifi, &mut wifi_ready);
+                    if reconnect && wifi_ready {
+                        wifi::ap_join(&wlan_state);
+                    }
+                }
                 // update interrupt vectors
                 if com_net_bridge_enable {
                     if wfx_rs::hal_wf200::poll_wfx_err_pending() {
@@ -300,7 +373,8 @@ fn main() -> ! {
                     if connect_result != ConnectResult::Pending {
                         com_int_mgr.set_connect_result(connect_result);
                         if connect_result == ConnectResult::Success {
-                            wifi::dhcp_init();
+                            wifi::dhcp_init(&wlan_state);
+                            wfx_rs::hal_wf200::recovery_note_success();
                         }
                     }
                 }
@@ -332,6 +406,18 @@ fn main() -> ! {
                         };
                     }
                 }
+
+                // Clock the SoftAP link-state machine the same way, on its own independent
+                // countdown -- unrelated to station-mode DHCP, so there's no reason to share
+                // `dhcp_oneshot`'s period or phase with it.
+                match ap_oneshot.status() {
+                    CountdownStatus::NotStarted => ap_oneshot.start(AP_POLL_MS),
+                    CountdownStatus::NotDone => (),
+                    CountdownStatus::Done => {
+                        wifi::ap_clock_state_machine();
+                        ap_oneshot.start(AP_POLL_MS);
+                    }
+                }
             }
             //////////////////////// ---------------------------
 
@@ -343,7 +429,16 @@ fn main() -> ! {
                 &mut loopcounter,
                 &mut pd_loop_timer,
                 &mut pow,
+                &mut com_int_mgr,
             );
+            power_mgmt::wifi_throttle_policy(&mut pow, &wlan_state, &mut com_int_mgr);
+            // A handful of seconds of a healthy main loop is this EC's watchdog-grace
+            // signal: confirm any pending firmware update before the loader's next
+            // boot would otherwise revert it.
+            if !fw_update_confirmed && loopcounter >= FW_UPDATE_CONFIRM_SECONDS {
+                fw_update::confirm_boot();
+                fw_update_confirmed = true;
+            }
             //////////////////////// ---------------------------
 
             //////////////////////// IMU TAP HANDLER BLOCK --------
@@ -434,6 +529,29 @@ fn main() -> ! {
                 com_csr.wfo(utra::com::CONTROL_RESET, 1); // reset fifos
                 com_csr.wfo(utra::com::CONTROL_CLRERR, 1); // clear all error flags
                 soc_off_delay_timer = get_time_ms();
+                // SoC is off: the radio doesn't need to stay at full power just because
+                // nothing else told it otherwise. Still associated means buffered frames
+                // could arrive for us at any DTIM beacon, so drop to power-save instead of
+                // full sleep; unassociated means there's nothing to listen for, so hold the
+                // radio in the same low-power reset `wf200_reset_hold()` already uses.
+                if use_wifi && wifi_ready {
+                    if hal_wf200::dhcp_get_state() == com_rs::DhcpState::Bound {
+                        wifi::set_ps_mode(true, SOC_OFF_DTIM_SKIP);
+                        // Wake-on-WLAN: a frame matching `wifi::set_wakeup_filter()`'s
+                        // pattern arrived while we were listening for it at the DTIM
+                        // interval above -- boot the SoC exactly like the keyboard sense
+                        // lines do, and let it discover why once it's up.
+                        if hal_wf200::poll_wake_packet() {
+                            logln!(LL::Debug, "WakeOnWlan");
+                            let power = hw.power_csr.ms(utra::power::POWER_SELF, 1)
+                                | hw.power_csr.ms(utra::power::POWER_SOC_ON, 1);
+                            hw.power_csr.wo(utra::power::POWER, power);
+                            com_int_mgr.set_ipconf_update();
+                        }
+                    } else {
+                        wifi::wf200_reset_hold();
+                    }
+                }
                 continue;
             } else {
                 if get_time_ms() < soc_off_delay_timer + 100 {
@@ -514,24 +632,34 @@ fn main() -> ! {
                     if capacity <= 600 {
                         capacity = 1100;
                     }
-                    let old_capacity = gg_set_design_capacity(&mut i2c, Some(capacity));
-                    com_tx(old_capacity);
+                    match gg_set_design_capacity(&mut i2c, Some(capacity)) {
+                        Ok(old_capacity) => com_tx(old_capacity),
+                        Err(e) => {
+                            logln!(LL::Debug, "GgSetCapErr {:?}", e);
+                            com_tx(ComState::ERROR.verb);
+                        }
+                    }
                 } else {
                     com_tx(ComState::ERROR.verb); // return an erroneous former capacity
                 }
             } else if rx == ComState::GG_GET_CAPACITY.verb {
                 logln!(LL::Debug, "CGgCap");
-                let old_capacity = gg_set_design_capacity(&mut i2c, None);
-                com_tx(old_capacity);
+                match gg_set_design_capacity(&mut i2c, None) {
+                    Ok(old_capacity) => com_tx(old_capacity),
+                    Err(e) => {
+                        logln!(LL::Debug, "GgGetCapErr {:?}", e);
+                        com_tx(ComState::ERROR.verb);
+                    }
+                }
             } else if rx == ComState::GG_SOC.verb {
                 logln!(LL::Trace, "CGgSoc"); // This gets polled frequently
-                com_tx(gg_state_of_charge(&mut i2c) as u16);
+                com_tx(gg_state_of_charge(&mut i2c).unwrap_or(0) as u16);
             } else if rx == ComState::GG_REMAINING.verb {
                 logln!(LL::Trace, "CGgRem"); // This gets polled frequently
-                com_tx(gg_remaining_capacity(&mut i2c) as u16);
+                com_tx(gg_remaining_capacity(&mut i2c).unwrap_or(0) as u16);
             } else if rx == ComState::GG_FULL_CAPACITY.verb {
                 logln!(LL::Debug, "CGgFullCap");
-                com_tx(gg_full_capacity(&mut i2c) as u16);
+                com_tx(gg_full_capacity(&mut i2c).unwrap_or(0) as u16);
             } else if rx == ComState::GG_DEBUG.verb {
                 logln!(LL::Debug, "CGgDebug");
                 if pow.voltage_glitch {
@@ -543,7 +671,7 @@ fn main() -> ! {
             } else if rx == ComState::STAT.verb {
                 logln!(LL::Debug, "CStat");
                 com_tx(0x8888); // first is just a response to the initial command
-                hw.charger.update_regs(&mut i2c);
+                let _ = hw.charger.update_regs(&mut i2c);
                 for i in 0..0xC {
                     com_tx(hw.charger.registers[i] as u16);
                 }
@@ -561,12 +689,12 @@ fn main() -> ! {
             } else if rx == ComState::POWER_SHIPMODE.verb {
                 hw.backlight.set_brightness(&mut i2c, 0, 0); // make sure the backlight is off
                 hw.charger.set_shipmode(&mut i2c);
-                gg_set_hibernate(&mut i2c);
+                if let Err(e) = gg_set_hibernate(&mut i2c) { logln!(LL::Debug, "GgHibernateErr {:?}", e); }
                 hw.power_csr.wfo(utra::power::POWER_SELF, 1); // only leave myself on, turn off everything else
                 pd_loop_timer = get_time_ms();
             } else if rx == ComState::POWER_CHARGER_STATE.verb {
                 logln!(LL::Debug, "CPowChgState");
-                if hw.charger.chg_is_charging(&mut i2c, false) {
+                if hw.charger.chg_is_charging(&mut i2c, false).unwrap_or(false) {
                     com_tx(1);
                 } else {
                     com_tx(0);
@@ -600,14 +728,14 @@ fn main() -> ! {
             } else if rx == ComState::CHG_START.verb {
                 logln!(LL::Debug, "CChgStart");
                 // charging mode
-                hw.charger.chg_start(&mut i2c);
+                let _ = hw.charger.chg_start(&mut i2c);
             } else if rx == ComState::CHG_BOOST_ON.verb {
                 logln!(LL::Debug, "CBoost1");
                 // boost on
-                hw.charger.chg_boost(&mut i2c);
+                let _ = hw.charger.chg_boost(&mut i2c);
             } else if rx == ComState::CHG_BOOST_OFF.verb {
                 // boost off
-                hw.charger.chg_boost_off(&mut i2c);
+                let _ = hw.charger.chg_boost_off(&mut i2c);
                 logln!(LL::Debug, "CBoost0");
             } else if rx >= ComState::BL_START.verb && rx <= ComState::BL_END.verb {
                 logln!(LL::Debug, "CBklt");
@@ -648,7 +776,7 @@ fn main() -> ! {
                 }
                 if !error {
                     logln!(LL::Debug, "Erasing {} bytes from 0x{:08x}", len, address);
-                    spi_erase_region(address, len);
+                    if let Err(e) = spi_erase_region(address, len) { logln!(LL::Warn, "FlashEraseErr {:?}", e); }
                 }
             } else if rx == ComState::FLASH_PP.verb {
                 let mut error = false;
@@ -675,7 +803,10 @@ fn main() -> ! {
                 }
                 if !error {
                     // logln!(LL::Debug, "Programming 256 bytes to 0x{:08x}", address);
-                    spi_program_page(address, &mut page);
+                    if let Err(e) = spi_program_page(address, &mut page) { logln!(LL::Warn, "FlashProgramErr {:?}", e); }
+                    if flash_update_lock {
+                        flash_pp_crc = spi::crc32_update(flash_pp_crc, &page);
+                    }
                 }
             } else if rx == ComState::FLASH_VERIFY.verb {
                 // reads out 256 bytes of memory from the base address. The base address
@@ -710,6 +841,8 @@ fn main() -> ! {
                 }
             } else if rx == ComState::FLASH_LOCK.verb {
                 flash_update_lock = true;
+                // Begin a fresh CRC-32 session over whatever pages FLASH_PP programs next.
+                flash_pp_crc = spi::CRC32_INIT;
                 wifi::wf200_irq_disable();
             } else if rx == ComState::FLASH_UNLOCK.verb {
                 flash_update_lock = false;
@@ -833,16 +966,31 @@ fn main() -> ! {
                 logln!(LL::Debug, "CSssidScan0");
                 // This is a NOP because the WF200 scan ends on its own
             } else if rx == ComState::WLAN_ON.verb {
+                // Software rfkill "unblock": the getter side of this is already implicit
+                // in `interface_status()` (it comes back out of `ResetHold` below), but
+                // the setter previously left the SoC polling `WLAN_SYNC_STATE` to find out
+                // -- fire the same interrupt a DHCP bind/unbind uses so it doesn't have to.
                 logln!(LL::Debug, "CWlanOn");
                 if !wifi_ready {
                     wifi::wf200_reset_and_init(&mut use_wifi, &mut wifi_ready);
                 }
+                com_int_mgr.set_ipconf_update();
             } else if rx == ComState::WLAN_OFF.verb {
+                // Software rfkill "block": tear down the association and hold the radio in
+                // reset. `interface_status()` already reports `ResetHold` once this takes
+                // effect, so that doubles as the getter; notify the host immediately
+                // instead of leaving it to poll for the transition.
                 logln!(LL::Debug, "CWlanOff");
-                // TODO: Make graceful shutdown procedure instead of this immediate reset
+                // Hand back the lease before cutting the radio, same as WLAN_LEAVE, so the
+                // server's pool isn't held until it expires across repeated off/on cycles.
+                match hal_wf200::dhcp_release() {
+                    Ok(_) => (),
+                    Err(e) => loghexln!(LL::Debug, "DhcpReleaseErr ", e),
+                };
                 hal_wf200::arp_stop_offloading();
                 wifi_ready = false;
                 wifi::wf200_reset_hold();
+                com_int_mgr.set_ipconf_update();
                 logln!(LL::Debug, "holding WF200 reset")
             } else if rx == ComState::WLAN_SET_SSID.verb {
                 logln!(LL::Debug, "CWlanSetS");
@@ -859,10 +1007,10 @@ fn main() -> ! {
                 };
             } else if rx == ComState::WLAN_JOIN.verb {
                 logln!(LL::Debug, "CWlanJoin");
-                wifi::ap_join_wpa2(&wlan_state);
+                wifi::ap_join(&wlan_state);
             } else if rx == ComState::WLAN_LEAVE.verb {
                 logln!(LL::Debug, "CWlanLeave");
-                wifi::ap_leave();
+                wifi::ap_leave(&wlan_state);
             } else if rx == ComState::WLAN_STATUS.verb {
                 // try not to entirely break older versions of the firmware for now
                 for _ in 0..ComState::WLAN_STATUS.r_words {
@@ -1063,6 +1211,15 @@ fn main() -> ! {
                 if !error {
                     if com_net_bridge_enable {
                         log!(LL::Debug, "T"); // Log TX of packet, but make it quick
+                        let (_seq, evicted) =
+                            net_retransmit.send(&txbuf_backing[..num_bytes as usize + PBUF_HEADER_SIZE]);
+                        if let Some(evicted_seq) = evicted {
+                            // Ring was already full of unacked packets: the oldest one is
+                            // unrecoverably lost rather than silently dropped.
+                            loghexln!(LL::Debug, "NetRetransmitRingFull seq=", evicted_seq as u32);
+                            tx_errs += 1;
+                            com_int_mgr.set_tx_error();
+                        }
                         match wfx_rs::hal_wf200::send_net_packet(
                             &mut txbuf_backing[..num_bytes as usize + PBUF_HEADER_SIZE],
                         ) {
@@ -1086,7 +1243,14 @@ fn main() -> ! {
         // update the state of the irq pin after all the potential ACKs have been handled above
         com_int_mgr.update_irq_pin();
 
+        // Poll the charger for edges it can't raise an IRQ for itself (see the module comment
+        // on `ChargerEvent` in api_bq25618.rs) and log whatever it decoded.
+        let _ = hw.charger.poll_events(&mut i2c, &mut charger_events);
+        while let Some(event) = charger_events.pop() {
+            logln!(LL::Debug, "charger event: {:?}", event);
+        }
+
         //////////////////////// ---------------------------
-        // unsafe { riscv::asm::wfi() }; // potential for power savings? unfortunately WFI seems broken
+        idle::maybe_idle(&com_int_mgr, &com_csr, &mut idle_stats);
     }
 }
@@ -1,7 +1,8 @@
 use betrusted_hal::mem_locs::*;
 use debug::{log, loghex, loghexln, logln, LL};
 const LOG_LEVEL: LL = LL::Debug;
-use core::{cell::{Cell, RefCell}, slice::from_raw_parts_mut, slice::from_raw_parts};
+use core::{cell::UnsafeCell, slice::from_raw_parts_mut, slice::from_raw_parts};
+use core::sync::atomic::{AtomicUsize, Ordering};
 
 /// PktPtr indices map directly onto the underlying storage position, e.g.
 /// a &[u8]
@@ -18,14 +19,33 @@ struct PktPtr {
 /// to the beginning again, rather than have to implement a custom deref
 /// to reclaim it.
 const MAX_PTRS: usize = 20;
+/// Sentinel used in place of `Option<usize>` for the two atomic index fields, since
+/// `AtomicUsize` can't hold an `Option` directly.
+const NONE_IDX: usize = usize::MAX;
+
+/// `PktBuf` is shared between an RX interrupt (the sole producer, calling
+/// `get_enqueue_slice`) and the main loop (the sole consumer, calling
+/// `peek_dequeue_slice`/`dequeue`). It used to keep its metadata in `Cell`/`RefCell`, which is
+/// unsound to share this way -- an IRQ preempting the main loop mid-borrow would panic. Since
+/// there is always exactly one producer and exactly one consumer, no CAS is required: the
+/// producer only ever advances `enqueue_index` and the consumer only ever advances
+/// `dequeue_index`, each publishing its writes to the descriptor array with a `Release` store
+/// and the other side synchronizing with an `Acquire` load before touching that data.
 pub struct PktBuf {
-    rawbuf: RefCell<[u8; PKT_BUF_LEN]>,
-    ptr_storage: [Cell<Option<PktPtr>>; MAX_PTRS],
-    /// index of where to look to figure out the next enqueue location
-    enqueue_index: Cell<Option<usize>>,
-    /// index of where to look to figure out the next dequeue location
-    dequeue_index: Cell<Option<usize>>,
+    ptr_storage: [UnsafeCell<Option<PktPtr>>; MAX_PTRS],
+    /// index of where to look to figure out the next enqueue location. Written only by the
+    /// producer; `NONE_IDX` stands in for `None`.
+    enqueue_index: AtomicUsize,
+    /// index of where to look to figure out the next dequeue location. Written only by the
+    /// consumer; `NONE_IDX` stands in for `None`.
+    dequeue_index: AtomicUsize,
 }
+// Safety: exactly one producer ever calls `get_enqueue_slice` (the RX ISR) and exactly one
+// consumer ever calls `peek_dequeue_slice`/`dequeue` (the main loop). Each side only mutates
+// descriptor slots it owns, and handoff of ownership is synchronized by the Release/Acquire
+// pair on `enqueue_index`/`dequeue_index` below.
+unsafe impl Sync for PktBuf {}
+
 impl PktBuf {
     /// Nothing prevents you from calling this multiple times, but it's definitely a bad idea to do that.
     /// Thus the function is marked as unsafe, because it wraps the fundamentally unsound opertaion of
@@ -37,33 +57,44 @@ impl PktBuf {
             *b = 0;
         }
         PktBuf {
-            rawbuf: RefCell::new(*rawbuf),
             ptr_storage: [
-                Cell::new(None), Cell::new(None), Cell::new(None), Cell::new(None), Cell::new(None),
-                Cell::new(None), Cell::new(None), Cell::new(None), Cell::new(None), Cell::new(None),
-                Cell::new(None), Cell::new(None), Cell::new(None), Cell::new(None), Cell::new(None),
-                Cell::new(None), Cell::new(None), Cell::new(None), Cell::new(None), Cell::new(None),
+                UnsafeCell::new(None), UnsafeCell::new(None), UnsafeCell::new(None), UnsafeCell::new(None), UnsafeCell::new(None),
+                UnsafeCell::new(None), UnsafeCell::new(None), UnsafeCell::new(None), UnsafeCell::new(None), UnsafeCell::new(None),
+                UnsafeCell::new(None), UnsafeCell::new(None), UnsafeCell::new(None), UnsafeCell::new(None), UnsafeCell::new(None),
+                UnsafeCell::new(None), UnsafeCell::new(None), UnsafeCell::new(None), UnsafeCell::new(None), UnsafeCell::new(None),
             ],
-            enqueue_index: Cell::new(None),
-            dequeue_index: Cell::new(None),
+            enqueue_index: AtomicUsize::new(NONE_IDX),
+            dequeue_index: AtomicUsize::new(NONE_IDX),
         }
     }
 
-    /// returns a slice that can be used to store packet data
+    /// returns a slice that can be used to store packet data. Producer-side only.
     pub fn get_enqueue_slice(&self, len: usize) -> Option<&mut [u8]> {
-        let alloc_end = if let Some(eq_idx) = self.enqueue_index.get() {
-            self.ptr_storage[eq_idx].get().expect("pktbuf assert A").end
+        // Acquire: synchronize with the consumer's most recent `dequeue()`, so the space it just
+        // reclaimed is visible to us before we reuse it.
+        let dq_idx = self.dequeue_index.load(Ordering::Acquire);
+        let ring_empty = dq_idx == NONE_IDX;
+
+        // `enqueue_index` is written only here, never by the consumer -- so once the ring is
+        // empty, whatever it's still holding from the last packet refers to a descriptor the
+        // consumer may already have freed (or be about to, any moment now) when it cleared
+        // `dequeue_index`. Treat an empty ring as a fresh start instead of dereferencing that
+        // stale slot, exactly like the very first enqueue this `PktBuf` ever sees.
+        let eq_idx = if ring_empty { NONE_IDX } else { self.enqueue_index.load(Ordering::Relaxed) };
+        let alloc_end = if eq_idx != NONE_IDX {
+            unsafe { (*self.ptr_storage[eq_idx].get()).expect("pktbuf assert A").end }
         } else {
             0
         };
-        let alloc_start = if let Some(dq_idx) = self.dequeue_index.get() {
-            self.ptr_storage[dq_idx].get().expect("pktbuf assert B").start
+        let alloc_start = if dq_idx != NONE_IDX {
+            unsafe { (*self.ptr_storage[dq_idx].get()).expect("pktbuf assert B").start }
         } else {
             0
         };
-        for (idx, ptr) in self.ptr_storage.iter().enumerate() {
-            if ptr.get().is_none() {
-                let newstart = if len < self.rawbuf.borrow().len() - alloc_end {
+        for idx in 0..MAX_PTRS {
+            let slot = unsafe { &mut *self.ptr_storage[idx].get() };
+            if slot.is_none() {
+                let newstart = if len < PKT_BUF_LEN - alloc_end {
                     alloc_end
                 } else if len < alloc_start {
                     0
@@ -75,21 +106,26 @@ impl PktBuf {
                     end: newstart + len,
                     next_index: None,
                 };
-                ptr.replace(Some(newpkt));
+                *slot = Some(newpkt);
 
-                if let Some(eq_idx) = self.enqueue_index.get() {
-                    if self.ptr_storage[eq_idx].get().unwrap().next_index.is_some() {
+                if eq_idx != NONE_IDX {
+                    let prev = unsafe { &mut *self.ptr_storage[eq_idx].get() };
+                    if prev.as_ref().expect("pktbuf assert A").next_index.is_some() {
                         logln!(LL::Debug, "ASSERT: expected next_index to be NULL");
                         return None;
                     }
-                    self.ptr_storage[eq_idx].get().unwrap().next_index = Some(idx)
-                } else {
-                    self.enqueue_index.replace(Some(idx));
+                    prev.as_mut().unwrap().next_index = Some(idx);
                 }
-                if self.dequeue_index.get().is_none() {
-                    self.dequeue_index.replace(Some(idx));
+
+                let first_entry = dq_idx == NONE_IDX;
+
+                // Release: publish this freshly-filled descriptor (and its link from the
+                // previous tail, above) to the consumer.
+                self.enqueue_index.store(idx, Ordering::Release);
+                if first_entry {
+                    self.dequeue_index.store(idx, Ordering::Release);
                 }
-                //return Some(&mut self.rawbuf.borrow_mut()[newpkt.start..newpkt.end])
+                logln!(LL::Debug, "enq idx: {} [{}..{}]", idx, newpkt.start, newpkt.end);
                 return Some(
                     unsafe{
                         from_raw_parts_mut(
@@ -107,10 +143,13 @@ impl PktBuf {
     /// the dequeue pointer. This arrangement allows an interrupt routine to pop in part way
     /// through a copy out of the dequeue packet, without worry of it being overwritten, and
     /// without having to allocate a second copy of the memory to prevent such overwriting.
+    /// Consumer-side only.
     pub fn peek_dequeue_slice(&self) -> Option<&[u8]> {
-        if let Some(dq_idx) = self.dequeue_index.get() {
-            if let Some(ptr) = self.ptr_storage[dq_idx].get() {
-                //Some(& self.rawbuf.borrow()[ptr.start..ptr.end])
+        // Acquire: synchronize with the producer's Release in `get_enqueue_slice`, so the
+        // descriptor (and the packet bytes it points to) are visible to us.
+        let dq_idx = self.dequeue_index.load(Ordering::Acquire);
+        if dq_idx != NONE_IDX {
+            if let Some(ptr) = unsafe { *self.ptr_storage[dq_idx].get() } {
                 Some(
                     unsafe{
                         from_raw_parts(
@@ -130,23 +169,30 @@ impl PktBuf {
     }
     /// this actually gets rid of the dequeue slice, immediately, for good. No
     /// pointer is returned because well, you shouldn't be using it after this is called.
+    /// Consumer-side only.
     pub fn dequeue(&mut self) -> bool {
-        if let Some(dq_idx) = self.dequeue_index.get() {
-            if let Some(ptr) = self.ptr_storage[dq_idx].get() {
+        let dq_idx = self.dequeue_index.load(Ordering::Acquire);
+        if dq_idx != NONE_IDX {
+            if let Some(ptr) = unsafe { *self.ptr_storage[dq_idx].get() } {
                 if let Some(next_dq) = ptr.next_index {
-                    self.dequeue_index.replace(Some(next_dq));
+                    self.dequeue_index.store(next_dq, Ordering::Release);
                 } else {
                     // there is no future dq, which means we /should/ have just dequeued
-                    // the current enqueue point. check this is true, and if so,
-                    // clear both dq and eq pointers as we are now in the empty state
-                    if self.enqueue_index.get().expect("ASSERT: no eq but dq") != dq_idx {
+                    // the current enqueue point -- check this is true, and if so, clear the
+                    // dequeue pointer to mark the empty state. `enqueue_index` is left alone:
+                    // it's producer-owned, and `get_enqueue_slice` already treats a `NONE`
+                    // dequeue side as "start fresh" rather than trusting whatever stale index
+                    // it still holds, so there's nothing for the consumer to do with it here.
+                    if self.enqueue_index.load(Ordering::Acquire) != dq_idx {
                         logln!(LL::Debug, "ASSERT: last eq should equal dq");
                         return false;
                     } else {
-                        self.dequeue_index.replace(None);
-                        self.enqueue_index.replace(None);
+                        self.dequeue_index.store(NONE_IDX, Ordering::Release);
                     }
                 }
+                // Free the slot. This plain write lands after the `dequeue_index` Release
+                // store above; until it does, the producer just sees the slot as still in use.
+                unsafe { *self.ptr_storage[dq_idx].get() = None; }
                 true
             } else {
                 logln!(LL::Debug, "ASSERT: dequeue points at None entry (dq)");
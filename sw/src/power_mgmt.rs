@@ -1,15 +1,20 @@
 extern crate betrusted_hal;
 extern crate utralib;
 extern crate volatile;
+use crate::com_bus::ComInterrupts;
+use crate::wifi;
+use crate::wlan::WlanState;
+use debug::{logln, LL};
 use betrusted_hal::api_bq25618::BtCharger;
 use betrusted_hal::api_gasgauge::{
     gg_avg_current, gg_set_hibernate, gg_state_of_charge, gg_voltage,
 };
 use betrusted_hal::api_lm3509::BtBacklight;
-use betrusted_hal::api_tusb320::BtUsbCc;
+use betrusted_hal::api_tusb320::{BtUsbCc, UsbCcRole};
 use betrusted_hal::hal_i2c::Hardi2c;
 use betrusted_hal::hal_time::{delay_ms, get_time_ms, get_time_ticks, set_msleep_target_ticks};
 use utralib::generated::{utra, CSR};
+use wfx_rs::hal_wf200::PowerManagementMode;
 
 // This is the voltage that we hard shut down the device to avoid battery damage
 const BATTERY_PANIC_VOLTAGE: i16 = 3500;
@@ -17,6 +22,72 @@ const BATTERY_PANIC_VOLTAGE: i16 = 3500;
 // This is the reserve voltage where we attempt to shut off the SoC so that BBRAM keys, RTC are preserved
 const BATTERY_LOW_VOLTAGE: i16 = 3575;
 
+// Thresholds for `wifi_throttle_policy`, in percent state-of-charge. Enter/exit pairs give
+// each transition its own hysteresis band so a reading sitting right at a boundary doesn't
+// flap the radio's power-management mode back and forth every sample.
+const WIFI_PERFORMANCE_ENTER_SOC: i16 = 80;
+const WIFI_PERFORMANCE_EXIT_SOC: i16 = 70;
+const WIFI_THROTTLE_ENTER_SOC: i16 = 20;
+const WIFI_THROTTLE_EXIT_SOC: i16 = 30;
+
+/// Escalation level `wifi_throttle_policy` is currently applying on top of
+/// `WlanState::pm_mode()`, purely a function of battery state of charge.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum WifiThrottleLevel {
+    /// No override in effect; `WlanState::pm_mode()` (or an explicit host selection)
+    /// applies as normal.
+    Normal,
+    /// State of charge is comfortably high: disable power-save for the lowest RX latency.
+    Performance,
+    /// State of charge is getting low: force power-save regardless of the selected mode.
+    Throttled,
+    /// `battery_panic` is asserted: the radio is held in reset until it clears.
+    Panic,
+}
+
+/// Tunable thresholds/timing for the low-voltage shutdown handshake (see
+/// `handle_low_voltage_event`), split out of the hardcoded constants above so a board
+/// variant with a different battery curve can override them without touching the
+/// handshake logic itself.
+#[derive(Copy, Clone)]
+pub struct PowerPolicy {
+    /// Voltage (mV) at which the SoC is warned that power is running out and should start
+    /// flushing BBRAM keys, RTC state, and the filesystem. Must stay above
+    /// `BATTERY_PANIC_VOLTAGE` -- that's the hard backstop this handshake exists to avoid
+    /// ever having to hit.
+    pub warn_voltage: i16,
+    /// How long the SoC has to ack (or request an extension) after the warning before
+    /// escalation begins, in milliseconds.
+    pub grace_window_ms: u32,
+    /// How long a single extension request pushes the deadline out by.
+    pub extension_ms: u32,
+}
+impl PowerPolicy {
+    pub const fn default() -> Self {
+        Self {
+            warn_voltage: BATTERY_LOW_VOLTAGE,
+            grace_window_ms: 10_000,
+            extension_ms: 5_000,
+        }
+    }
+}
+
+/// Where `handle_low_voltage_event` is in the shutdown handshake for the current
+/// low-voltage episode. `charger_handler` resets this back to `Idle` as soon as voltage
+/// recovers above `PowerPolicy::warn_voltage`.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum PowerDownStage {
+    /// Voltage hasn't crossed `warn_voltage` (or already recovered from a prior warning).
+    Idle,
+    /// `INT_BATTERY_CRITICAL` is asserted; the SoC has until `deadline_ms` (in
+    /// `get_time_ms()` ticks) to ack it (confirming a clean shutdown) or request an
+    /// extension, before escalation starts.
+    Warned { deadline_ms: u32 },
+    /// The deadline passed with no ack: the backlight is off, the WF200 is held in reset,
+    /// and `POWER_DISCHARGE` has been asserted as the hard stop.
+    Escalated,
+}
+
 /// Variables to track Precursor's I2C power management subsystem
 pub struct PowerState {
     pub voltage: i16,
@@ -27,6 +98,10 @@ pub struct PowerState {
     pub battery_panic: bool,
     pub voltage_glitch: bool,
     pub usb_cc_event: bool,
+    pub state_of_charge: i16,
+    pub wifi_throttle: WifiThrottleLevel,
+    pub policy: PowerPolicy,
+    pub pd_stage: PowerDownStage,
 }
 
 pub struct PowerHardware {
@@ -56,6 +131,7 @@ pub fn charger_handler(
     loopcounter: &mut u32,
     mut pd_loop_timer: &mut u32,
     mut pow: &mut PowerState,
+    com_int_mgr: &mut ComInterrupts,
 ) {
     // I2C can't happen inside an interrupt routine, so we do it in the main loop
     // real time response is also not critical; note this runs "lazily", only if the COM loop is idle
@@ -68,30 +144,33 @@ pub fn charger_handler(
             charge_cable_ping_and_update_status(&mut hw, &mut i2c, &mut pow);
         } else {
             battery_update_voltage(&mut i2c, &mut pow);
+            pow.state_of_charge = gg_state_of_charge(&mut i2c).unwrap_or(0);
             if pow.voltage < BATTERY_PANIC_VOLTAGE {
                 handle_low_voltage_panic_event(&mut hw, &mut i2c, &mut pow);
-            } else if pow.voltage < BATTERY_LOW_VOLTAGE {
-                // TODO: warn the SoC that power is about to go away using the COM_IRQ feature...
-                // siginficantly: shutting down the SoC without its consent is not possible. so this
-                // needs to be refactored once Xous gets to a state where it can handle a power
-                // state request for now just make a NOP
-
-                handle_low_voltage_event(&mut hw, &mut i2c, &mut pd_loop_timer);
+            } else if pow.voltage < pow.policy.warn_voltage {
+                handle_low_voltage_event(&mut hw, &mut i2c, &mut pow, com_int_mgr, &mut pd_loop_timer);
             } else {
                 pow.battery_panic = false;
+                if pow.pd_stage != PowerDownStage::Idle {
+                    // voltage recovered (charger plugged in, glitch, etc.) before the SoC
+                    // had to act on it -- stand down and clear the IRQ rather than leaving
+                    // it latched for a shutdown that's no longer imminent.
+                    com_int_mgr.ack_battery_critical();
+                    pow.pd_stage = PowerDownStage::Idle;
+                }
             }
             if hw.power_csr.rf(utra::power::STATS_STATE) == 1 {
-                pow.current = gg_avg_current(&mut i2c);
+                pow.current = gg_avg_current(&mut i2c).unwrap_or(0);
             } else if hw.power_csr.rf(utra::power::STATS_STATE) == 0 && !(pow.soc_was_on) {
                 // only sample if the last state was also powered off, so we aren't averaging in ~1s
                 // worth of "power on" current while this loop triggers
-                pow.stby_current = gg_avg_current(&mut i2c);
+                pow.stby_current = gg_avg_current(&mut i2c).unwrap_or(0);
             }
             pow.soc_was_on = hw.power_csr.rf(utra::power::STATS_STATE) == 1;
         }
 
         // check if we should turn the SoC on or not based on power status change events
-        if hw.charger.chg_is_charging(&mut i2c, false) {
+        if hw.charger.chg_is_charging(&mut i2c, false).unwrap_or(false) {
             // sprintln!("charger insert or soc on event!");
             let power = hw.power_csr.ms(utra::power::POWER_SELF, 1)
                 | hw.power_csr.ms(utra::power::POWER_SOC_ON, 1);
@@ -106,18 +185,22 @@ pub fn charge_cable_ping_and_update_status(
     mut i2c: &mut Hardi2c,
     pow: &mut PowerState,
 ) {
-    hw.charger.chg_keepalive_ping(&mut i2c);
+    // supervises both watchdog petting and WATCHDOG_FAULT/CHG_TIMEOUT recovery -- see the
+    // doc comment on `chg_safety_tick` for why this replaced a bare `chg_keepalive_ping` call
+    let _ = hw.charger.chg_safety_tick(&mut i2c);
     if !(pow.usb_cc_event) {
         pow.usb_cc_event = hw.usb_cc.check_event(&mut i2c);
-        if hw.usb_cc.status[1] & 0xC0 == 0x80 {
+        if hw.usb_cc.role == UsbCcRole::Sink {
             // Attached.SNK transition
-            hw.charger.chg_start(&mut i2c);
+            let _ = hw.charger.chg_start(&mut i2c);
         }
     }
 }
 
 pub fn battery_update_voltage(mut i2c: &mut Hardi2c, pow: &mut PowerState) {
-    pow.voltage = gg_voltage(&mut i2c);
+    // a failed read is funneled through the same negative-voltage glitch filter below as a
+    // transient monitoring glitch, rather than given its own handling
+    pow.voltage = gg_voltage(&mut i2c).unwrap_or(-1);
     if pow.voltage < 0 {
         // There are monitoring glitches during charge mode transitions, try to catch and filter
         // them out
@@ -132,21 +215,21 @@ pub fn handle_low_voltage_panic_event(
     mut i2c: &mut Hardi2c,
     pow: &mut PowerState,
 ) {
-    let cursoc = gg_state_of_charge(&mut i2c);
+    let cursoc = gg_state_of_charge(&mut i2c).unwrap_or(0);
     if cursoc < 5 && pow.battery_panic {
         // in case of a cold boot, give the charger a few seconds to recognize charging
         // and raise the voltage also don't attempt to go shipmode if the charger is
         // indicating it is trying to charge
         if get_time_ticks() > 8000
-            && !hw.charger.chg_is_charging(&mut i2c, false)
-            && gg_voltage(&mut i2c) < BATTERY_PANIC_VOLTAGE
+            && !hw.charger.chg_is_charging(&mut i2c, false).unwrap_or(false)
+            && gg_voltage(&mut i2c).unwrap_or(BATTERY_PANIC_VOLTAGE) < BATTERY_PANIC_VOLTAGE
         {
             // put the device into "shipmode" which disconnects the battery from the system
             // NOTE: this may cause the loss of volatile keys
             hw.backlight.set_brightness(&mut i2c, 0, 0); // make sure the backlight is off
 
             hw.charger.set_shipmode(&mut i2c);
-            gg_set_hibernate(&mut i2c);
+            if let Err(e) = gg_set_hibernate(&mut i2c) { logln!(LL::Debug, "GgHibernateErr {:?}", e); }
             let power = hw.power_csr.ms(utra::power::POWER_SELF, 1);
                // | hw.power_csr.ms(utra::power::POWER_DISCHARGE, 1);
             hw.power_csr.wo(utra::power::POWER, power);
@@ -160,22 +243,119 @@ pub fn handle_low_voltage_panic_event(
     }
 }
 
-/// This is currently useless (TODO: make this less useless)
-#[allow(unused_variables, unused_mut)]
+/// Runs every tick that `pow.voltage` is between `BATTERY_PANIC_VOLTAGE` and
+/// `pow.policy.warn_voltage`: warns the SoC over COM_IRQ that power is running out, gives it
+/// `pow.policy.grace_window_ms` to flush BBRAM keys, RTC state, and the filesystem and ack,
+/// then escalates (backlight off, WF200 held in reset, `POWER_DISCHARGE` asserted) if the
+/// deadline passes with no ack. `handle_low_voltage_panic_event`'s ship-mode path remains the
+/// hard backstop below `BATTERY_PANIC_VOLTAGE` regardless of how this handshake goes.
+///
+/// Shutting the SoC down without its consent isn't possible -- it owns the filesystem and
+/// BBRAM key state -- so the only things this function can do on its own are: ask (via
+/// `INT_BATTERY_CRITICAL`), wait, and fall back to pulling the radio/backlight/battery out
+/// from under it if nobody answers. Extension requests need a dedicated COM verb `com_rs`
+/// doesn't have yet (the same gap noted on `wifi::rf_test_start` and friends); until one
+/// exists, the SoC's only lever is `LINK_ACK_INTERRUPT` acking `INT_BATTERY_CRITICAL`, which
+/// reads as "acknowledged, shutting down" and stands this handshake down for good measure --
+/// `handle_low_voltage_panic_event` still applies if voltage keeps falling after that.
 pub fn handle_low_voltage_event(
     hw: &mut PowerHardware,
-    mut i2c: &mut Hardi2c,
+    i2c: &mut Hardi2c,
+    pow: &mut PowerState,
+    com_int_mgr: &mut ComInterrupts,
     pd_loop_timer: &mut u32,
 ) {
-    // NOTE: this should probably get more aggressive about shutting down wifi, etc.
-    /*
-    if gg_state_of_charge(&mut i2c) < 10 {
-        hw.backlight.set_brightness(&mut i2c, 0, 0); // make sure the backlight is off
-        let power = hw.power_csr.ms(utra::power::POWER_SELF, 1)
-            | hw.power_csr.ms(utra::power::POWER_DISCHARGE, 1);
-        hw.power_csr.wo(utra::power::POWER, power);
-        set_msleep_target_ticks(500); // extend next service so we can discharge
-        *pd_loop_timer = get_time_ms();
+    match pow.pd_stage {
+        PowerDownStage::Idle => {
+            com_int_mgr.set_battery_critical();
+            pow.pd_stage = PowerDownStage::Warned {
+                deadline_ms: get_time_ms() + pow.policy.grace_window_ms,
+            };
+        }
+        PowerDownStage::Warned { deadline_ms } => {
+            if !com_int_mgr.battery_critical_pending() {
+                // SoC acked: treat it as "acknowledged, shutting down on its own" and stand
+                // down rather than escalating underneath it.
+                pow.pd_stage = PowerDownStage::Idle;
+            } else if get_time_ms() > deadline_ms {
+                logln!(LL::Info, "PowLowVoltEscalate");
+                hw.backlight.set_brightness(i2c, 0, 0);
+                wifi::wf200_reset_hold();
+                let power = hw.power_csr.ms(utra::power::POWER_SELF, 1)
+                    | hw.power_csr.ms(utra::power::POWER_DISCHARGE, 1);
+                hw.power_csr.wo(utra::power::POWER, power);
+                set_msleep_target_ticks(500); // extend next service so we can discharge
+                *pd_loop_timer = get_time_ms();
+                pow.pd_stage = PowerDownStage::Escalated;
+            }
+        }
+        PowerDownStage::Escalated => {
+            if !com_int_mgr.battery_critical_pending() {
+                pow.pd_stage = PowerDownStage::Idle;
+            }
+        }
+    }
+}
+
+/// Escalate or relax the WF200 power-management mode as `pow.state_of_charge` crosses the
+/// `WIFI_PERFORMANCE_*`/`WIFI_THROTTLE_*` thresholds above, so the radio starts backing off
+/// well before `handle_low_voltage_panic_event` ever has to act on the battery directly.
+///
+/// An explicit host-selected mode (`ws.pm_mode_explicit()`) is left alone -- the host asked
+/// for something specific, so it keeps it -- right up until `pow.battery_panic` fires, at
+/// which point the radio is forced into reset regardless of what selected it.
+///
+/// Call this once per `charger_handler` tick, after `pow.state_of_charge` has been updated.
+pub fn wifi_throttle_policy(pow: &mut PowerState, ws: &WlanState, com_int_mgr: &mut ComInterrupts) {
+    if pow.battery_panic {
+        if pow.wifi_throttle != WifiThrottleLevel::Panic {
+            pow.wifi_throttle = WifiThrottleLevel::Panic;
+            wifi::wf200_reset_hold();
+            com_int_mgr.set_ipconf_update();
+        }
+        return;
+    }
+
+    if ws.pm_mode_explicit() {
+        return;
+    }
+
+    let soc = pow.state_of_charge;
+    let next = match pow.wifi_throttle {
+        WifiThrottleLevel::Panic | WifiThrottleLevel::Normal => {
+            if soc >= WIFI_PERFORMANCE_ENTER_SOC {
+                WifiThrottleLevel::Performance
+            } else if soc <= WIFI_THROTTLE_ENTER_SOC {
+                WifiThrottleLevel::Throttled
+            } else {
+                WifiThrottleLevel::Normal
+            }
+        }
+        WifiThrottleLevel::Performance => {
+            if soc < WIFI_PERFORMANCE_EXIT_SOC {
+                WifiThrottleLevel::Normal
+            } else {
+                WifiThrottleLevel::Performance
+            }
+        }
+        WifiThrottleLevel::Throttled => {
+            if soc > WIFI_THROTTLE_EXIT_SOC {
+                WifiThrottleLevel::Normal
+            } else {
+                WifiThrottleLevel::Throttled
+            }
+        }
+    };
+
+    if next != pow.wifi_throttle {
+        pow.wifi_throttle = next;
+        let mode = match next {
+            WifiThrottleLevel::Performance => PowerManagementMode::Active,
+            WifiThrottleLevel::Throttled => PowerManagementMode::PowerSave { listen_interval: 10 },
+            // Back within the normal band: stop overriding and let the selected policy apply.
+            WifiThrottleLevel::Normal => ws.pm_mode(),
+            WifiThrottleLevel::Panic => return, // handled above
+        };
+        wifi::set_power_management_mode(mode);
     }
-    */
 }
@@ -26,12 +26,137 @@ pub const CMD_BE64K:  SpiCmd = SpiCmd {command: 0xD8, is_quad: false, is_read: f
 pub const CMD_4PP:    SpiCmd = SpiCmd {command: 0x38, is_quad: true,  is_read: false, use_addr:true,   has_data:true,  dummy:None,  return_count:None};
 pub const CMD_4READ:  SpiCmd = SpiCmd {command: 0xEB, is_quad: true,  is_read: true,  use_addr:true,   has_data:true,  dummy:Some(6), return_count:None};
 pub const CMD_WRDI:   SpiCmd = SpiCmd {command: 0x04, is_quad: false, is_read: false, use_addr:false,  has_data:false, dummy:None,  return_count:None};
+pub const CMD_EN4B:   SpiCmd = SpiCmd {command: 0xB7, is_quad: false, is_read: false, use_addr:false,  has_data:false, dummy:None,  return_count:None};
+pub const CMD_EX4B:   SpiCmd = SpiCmd {command: 0xE9, is_quad: false, is_read: false, use_addr:false,  has_data:false, dummy:None,  return_count:None};
 
 pub const SPI_SR_WEL_MASK: u8 = 0x2;
 pub const SPI_SR_WIP_MASK: u8 = 0x1;
 pub const SPI_RDSCUR_E_FAIL_MASK: u8 = 0x40;
 pub const SPI_RDSCUR_P_FAIL_MASK: u8 = 0x20;
 
+/// Page/sector/block sizes common to essentially every mainstream serial NOR part --
+/// the JEDEC ID only tells us capacity and who made the part, not its erase unit sizes,
+/// but these four values are standard across the vendor families in [`KNOWN_VENDORS`].
+const PAGE_SIZE: u32 = 256;
+const SECTOR_SIZE: u32 = 0x1000;
+const BLOCK32K_SIZE: u32 = 0x8000;
+const BLOCK64K_SIZE: u32 = 0x1_0000;
+
+/// One manufacturer ID byte (`CMD_RDID`'s first byte) this tree knows how to interpret.
+struct VendorId {
+    id: u8,
+    #[allow(dead_code)] // surfaced for debug logging, not branched on yet
+    name: &'static str,
+}
+
+/// JEDEC manufacturer IDs for the vendor families a board respin is plausible to ship
+/// with. Not exhaustive -- add entries as boards actually ship with a new one.
+const KNOWN_VENDORS: &[VendorId] = &[
+    VendorId { id: 0xC2, name: "Macronix" },
+    VendorId { id: 0xEF, name: "Winbond" },
+    VendorId { id: 0x01, name: "Spansion" },
+    VendorId { id: 0xC8, name: "GigaDevice" },
+];
+
+/// Geometry derived from a `CMD_RDID` probe, so `spi_erase_region` (and any future
+/// caller needing page/block sizes) stops assuming the board always has the same
+/// Macronix part it originally shipped with.
+#[derive(Copy, Clone, Debug)]
+pub struct FlashInfo {
+    pub manufacturer_id: u8,
+    pub memory_type: u8,
+    pub capacity_code: u8,
+    pub page_size: u32,
+    pub sector_size: u32,
+    pub block32k: u32,
+    pub block64k: u32,
+    pub total_size: u32,
+    pub supports_quad: bool,
+    /// Address bytes `spi_cmd` shifts out for this part: 3 for anything up to 16MB, 4
+    /// beyond that (3 bytes can't reach past 0xFF_FFFF). See `enter_4byte_mode`.
+    pub address_bytes: u8,
+}
+impl FlashInfo {
+    /// Used before the first [`probe_flash`] call: the standard geometry above, quad I/O
+    /// assumed available (matching this module's existing unconditional `CMD_4PP`/
+    /// `CMD_4READ` usage), and an unknown (zero) total size.
+    const fn generic() -> FlashInfo {
+        FlashInfo {
+            manufacturer_id: 0,
+            memory_type: 0,
+            capacity_code: 0,
+            page_size: PAGE_SIZE,
+            sector_size: SECTOR_SIZE,
+            block32k: BLOCK32K_SIZE,
+            block64k: BLOCK64K_SIZE,
+            total_size: 0,
+            supports_quad: true,
+            address_bytes: 3,
+        }
+    }
+}
+
+static mut FLASH_INFO: FlashInfo = FlashInfo::generic();
+
+/// Largest address reachable with 3-byte addressing; parts at or below this size never
+/// need [`enter_4byte_mode`].
+const MAX_3BYTE_ADDR_SIZE: u32 = 0x0100_0000;
+
+/// Whether `spi_cmd`'s address-emission paths should shift out a fourth (most
+/// significant) address byte. Set by [`enter_4byte_mode`]/[`exit_4byte_mode`], which
+/// [`probe_flash`] calls automatically based on the probed part's size.
+static mut ADDR_4BYTE_MODE: bool = false;
+
+/// Issue EN4B (0xB7) and switch `spi_cmd`'s address emission to 4 bytes, for flash too
+/// large for 3-byte addressing (>16MB) to reach past its first 16MB.
+pub fn enter_4byte_mode() {
+    spi_cmd(CMD_EN4B, None, None);
+    unsafe { ADDR_4BYTE_MODE = true };
+}
+
+/// Issue EX4B (0xE9) and switch `spi_cmd` back to 3-byte address emission.
+pub fn exit_4byte_mode() {
+    spi_cmd(CMD_EX4B, None, None);
+    unsafe { ADDR_4BYTE_MODE = false };
+}
+
+/// Send `CMD_RDID`, decode its three bytes (manufacturer ID, memory type, and a capacity
+/// code whose common convention is that size is `1 << capacity_code`), and cache the
+/// result for [`flash_info`]/`spi_erase_region` to use from then on.
+pub fn probe_flash() -> FlashInfo {
+    let mut idcode: [u8; 3] = [0; 3];
+    spi_cmd(CMD_RDID, None, Some(&mut idcode));
+    let (manufacturer_id, memory_type, capacity_code) = (idcode[0], idcode[1], idcode[2]);
+    let total_size = 1u32 << capacity_code;
+    let info = FlashInfo {
+        manufacturer_id,
+        memory_type,
+        capacity_code,
+        page_size: PAGE_SIZE,
+        sector_size: SECTOR_SIZE,
+        block32k: BLOCK32K_SIZE,
+        block64k: BLOCK64K_SIZE,
+        total_size,
+        supports_quad: KNOWN_VENDORS.iter().any(|v| v.id == manufacturer_id),
+        address_bytes: if total_size > MAX_3BYTE_ADDR_SIZE { 4 } else { 3 },
+    };
+    unsafe { FLASH_INFO = info };
+    // Drive the actual addressing mode to match what was just probed, rather than
+    // trusting whatever mode the part happened to power up in.
+    if info.address_bytes == 4 {
+        enter_4byte_mode();
+    } else {
+        exit_4byte_mode();
+    }
+    info
+}
+
+/// The geometry of the flash part last seen by [`probe_flash`], or [`FlashInfo::generic`]
+/// if that's never been called.
+pub fn flash_info() -> FlashInfo {
+    unsafe { FLASH_INFO }
+}
+
 const OE_MASK_1BIT: u32 = 0x1;
 const OE_MASK_4BIT: u32 = 0xF;
 
@@ -148,6 +273,9 @@ pub fn spi_cmd(cmd: SpiCmd, address: Option<u32>, data: Option<&mut [u8]>) -> bo
         if cmd.use_addr {
             if address.is_some() {
                 let addr = address.unwrap();
+                if unsafe { ADDR_4BYTE_MODE } {
+                    spi_1bit_write((addr >> 24) as u8);
+                }
                 spi_1bit_write((addr >> 16) as u8);
                 spi_1bit_write((addr >> 8) as u8);
                 spi_1bit_write((addr >> 0) as u8);
@@ -197,6 +325,9 @@ pub fn spi_cmd(cmd: SpiCmd, address: Option<u32>, data: Option<&mut [u8]>) -> bo
         if cmd.use_addr {
             if address.is_some() {
                 let addr = address.unwrap();
+                if unsafe { ADDR_4BYTE_MODE } {
+                    spi_quad_write((addr >> 24) as u8);
+                }
                 spi_quad_write((addr >> 16) as u8);
                 spi_quad_write((addr >> 8) as u8);
                 spi_quad_write((addr >> 0) as u8);
@@ -242,8 +373,20 @@ pub fn spi_cmd(cmd: SpiCmd, address: Option<u32>, data: Option<&mut [u8]>) -> bo
     true
 }
 
-pub fn spi_erase_region(addr: u32, len: u32) {
+/// An erase or program operation's post-completion `CMD_RDSCUR` read came back with
+/// E_FAIL or P_FAIL set. Returned by [`spi_erase_region`]/[`spi_program_page`] instead of
+/// just `sprintln!`-ing the failure and returning, so a caller orchestrating a multi-step
+/// update (like [`update_region`]) can stop and report it rather than silently carrying on
+/// as if the operation succeeded.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum FlashOpError {
+    EraseFailed,
+    ProgramFailed,
+}
+
+pub fn spi_erase_region(addr: u32, len: u32) -> Result<(), FlashOpError> {
     let mut sr: [u8; 1] = [0; 1];
+    let info = flash_info();
 
     let mut erased: u32 = 0;
     while erased < len {
@@ -255,15 +398,15 @@ pub fn spi_erase_region(addr: u32, len: u32) {
                 break;
             }
         }
-        if (len - erased >= 0x1_0000) && (((addr + erased) & 0xFFFF) == 0) {
+        if (len - erased >= info.block64k) && (((addr + erased) & (info.block64k - 1)) == 0) {
             spi_cmd(CMD_BE64K, Some(addr + erased), None);
-            erased += 65536;
-        } else if (len - erased >= 0x8000) && (((addr + erased) & 0x7FFF) == 0) {
+            erased += info.block64k;
+        } else if (len - erased >= info.block32k) && (((addr + erased) & (info.block32k - 1)) == 0) {
             spi_cmd(CMD_BE32K, Some(addr + erased), None);
-            erased += 32768;
+            erased += info.block32k;
         } else {
             spi_cmd(CMD_SE, Some(addr + erased), None);
-            erased += 4096;
+            erased += info.sector_size;
         }
         loop {
             spi_cmd(CMD_RDSR, None, Some(&mut sr));
@@ -273,21 +416,50 @@ pub fn spi_erase_region(addr: u32, len: u32) {
             }
         }
         spi_cmd(CMD_RDSCUR, None, Some(&mut sr));
-        if sr[0] & (SPI_RDSCUR_E_FAIL_MASK | SPI_RDSCUR_P_FAIL_MASK) != 0 {
+        let failed = sr[0] & (SPI_RDSCUR_E_FAIL_MASK | SPI_RDSCUR_P_FAIL_MASK) != 0;
+        if failed {
             sprintln!("erase fail!");
         } else {
             sprintln!("erase success!");
         }
         spi_cmd(CMD_WRDI, None, None);
+        if failed {
+            return Err(FlashOpError::EraseFailed);
+        }
+    }
+    Ok(())
+}
+
+/// Running-accumulator start value for [`crc32_update`].
+pub const CRC32_INIT: u32 = 0xFFFF_FFFF;
+
+/// IEEE 802.3 CRC-32 (reflected, polynomial 0xEDB88320), folded over `data` into a running
+/// accumulator started from [`CRC32_INIT`]. Call once per buffer fed into a flash
+/// programming session, then pass the final accumulator through [`crc32_finalize`].
+pub fn crc32_update(crc: u32, data: &[u8]) -> u32 {
+    let mut crc = crc;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xEDB8_8320 & mask);
+        }
     }
+    crc
+}
+
+/// Finish a [`crc32_update`] accumulator into the CRC-32 value the host expects to compare.
+pub fn crc32_finalize(crc: u32) -> u32 {
+    crc ^ 0xFFFF_FFFF
 }
 
-pub fn spi_program_page(addr: u32, data: &mut [u8]) {
+pub fn spi_program_page(addr: u32, data: &mut [u8]) -> Result<(), FlashOpError> {
     let mut sr: [u8; 1] = [0; 1];
     let fast_and_furious = false;
 
     if fast_and_furious {
-        // skip most the checks, in favor of speed.
+        // skip most the checks, in favor of speed -- including the RDSCUR check below, so
+        // there's nothing this path can report beyond "the write command was issued".
         spi_cmd(CMD_WREN, None, None);
         spi_cmd(CMD_4PP, Some(addr), Some(data));
         loop {
@@ -296,6 +468,7 @@ pub fn spi_program_page(addr: u32, data: &mut [u8]) {
                 break;
             }
         }
+        Ok(())
     } else {
         loop {
             spi_cmd(CMD_WREN, None, None);
@@ -314,11 +487,80 @@ pub fn spi_program_page(addr: u32, data: &mut [u8]) {
             }
         }
         spi_cmd(CMD_RDSCUR, None, Some(&mut sr));
-        if sr[0] & (SPI_RDSCUR_E_FAIL_MASK | SPI_RDSCUR_P_FAIL_MASK) != 0 {
+        let failed = sr[0] & (SPI_RDSCUR_E_FAIL_MASK | SPI_RDSCUR_P_FAIL_MASK) != 0;
+        if failed {
             sprintln!("program fail!");
         } else {
             sprintln!("program success!");
         }
         spi_cmd(CMD_WRDI, None, None);
+        if failed {
+            Err(FlashOpError::ProgramFailed)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// A whole-image [`update_region`] call failed, identifying which stage and, for a
+/// verify failure, the exact address that first disagreed with the source image.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum FlashUpdateError {
+    /// An erase within the target region failed its post-erase `RDSCUR` check.
+    EraseFailed { addr: u32 },
+    /// A page program within the target region failed its post-program `RDSCUR` check.
+    ProgramFailed { addr: u32 },
+    /// The post-program read-back didn't match `data` at this address -- the first
+    /// mismatching byte, not just the page it fell in.
+    VerifyMismatch { addr: u32 },
+}
+
+/// Erase, program, and verify a whole image in one call: the region covering `data` is
+/// rounded out to whole sectors and erased, then written `flash_info().page_size` bytes
+/// at a time, then read back with `CMD_4READ` and compared against `data` byte-for-byte --
+/// so a partially-failed update is reported via [`FlashUpdateError`] instead of silently
+/// accepted, which is all `spi_erase_region`/`spi_program_page` could do on their own
+/// before they started returning [`FlashOpError`]. `progress(done, total)` is called
+/// after each page is programmed, in bytes, for a caller driving a status report.
+pub fn update_region(
+    addr: u32,
+    data: &[u8],
+    mut progress: impl FnMut(u32, u32),
+) -> Result<(), FlashUpdateError> {
+    let info = flash_info();
+    let len = data.len() as u32;
+
+    let erase_start = addr & !(info.sector_size - 1);
+    let erase_end = (addr + len + info.sector_size - 1) & !(info.sector_size - 1);
+    spi_erase_region(erase_start, erase_end - erase_start)
+        .map_err(|_| FlashUpdateError::EraseFailed { addr: erase_start })?;
+
+    let mut offset = 0u32;
+    while offset < len {
+        let take = (len - offset).min(info.page_size);
+        let mut page = [0xFFu8; PAGE_SIZE as usize];
+        page[..take as usize].copy_from_slice(&data[offset as usize..(offset + take) as usize]);
+        spi_program_page(addr + offset, &mut page[..take as usize])
+            .map_err(|_| FlashUpdateError::ProgramFailed { addr: addr + offset })?;
+        offset += take;
+        progress(offset, len);
     }
+
+    let mut readback = [0u8; PAGE_SIZE as usize];
+    let mut offset = 0u32;
+    while offset < len {
+        let take = (len - offset).min(info.page_size) as usize;
+        spi_cmd(CMD_4READ, Some(addr + offset), Some(&mut readback[..take]));
+        let expected = &data[offset as usize..offset as usize + take];
+        if readback[..take] != *expected {
+            for i in 0..take {
+                if readback[i] != expected[i] {
+                    return Err(FlashUpdateError::VerifyMismatch { addr: addr + offset + i as u32 });
+                }
+            }
+        }
+        offset += take as u32;
+    }
+
+    Ok(())
 }
@@ -0,0 +1,107 @@
+//! In-field reprogramming of the WF200 firmware blob living in the reserved window at
+//! [`WFX_FIRMWARE_OFFSET`]. This is a second, independent flash client alongside
+//! [`fw_update`](crate::fw_update)'s A/B application slots -- same underlying [`spi`]
+//! primitives, different region of the same chip, no shared state between the two.
+//!
+//! `WFX_FIRMWARE_OFFSET` is expressed as a memory-mapped address (`0x2000_0000`-based, the
+//! form `sl_wfx_host_get_firmware_data` dereferences directly once the chip is back in
+//! memory-mapped read mode), but [`spi::spi_cmd`] and friends address the flash by its own
+//! internal byte offset. [`flash_offset`] does that translation once, here, instead of
+//! leaving every call site to subtract [`FLASH_MMAP_BASE`] by hand.
+
+use crate::spi;
+use betrusted_hal::mem_locs::{WFX_FIRMWARE_OFFSET, WFX_FIRMWARE_RESERVED_SIZE};
+use debug::{logln, LL};
+
+const FLASH_MMAP_BASE: usize = 0x2000_0000;
+const PAGE_SIZE: u32 = 256;
+
+fn flash_offset(mmap_addr: usize) -> u32 {
+    (mmap_addr - FLASH_MMAP_BASE) as u32
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum WfxFlashError {
+    /// `offset`/`data.len()` would read or write outside the reserved firmware window.
+    OutOfBounds,
+    /// The status register reported a program or erase failure (`RDSCUR` E_FAIL/P_FAIL).
+    OpFailed,
+    /// [`WfxFlash::verify`] read the region back, but its CRC-32 didn't match the one
+    /// supplied by the caller (normally the CRC trailing the image transfer).
+    CrcMismatch,
+}
+
+/// Namespace for the WFX firmware flash region. Holds no state of its own -- like
+/// [`spi`]'s free functions, every call re-derives what it needs from the fixed region
+/// bounds, so there's nothing for multiple instances to disagree about.
+pub struct WfxFlash;
+impl WfxFlash {
+    /// Erase the whole 400kiB reserved window, not just the current image's exact length,
+    /// so a smaller replacement image can't leave stale bytes of the old one sitting past
+    /// its own end.
+    pub fn erase_firmware() -> Result<(), WfxFlashError> {
+        logln!(LL::Info, "WfxFlashEraseFirmware");
+        spi::spi_erase_region(
+            flash_offset(WFX_FIRMWARE_OFFSET),
+            WFX_FIRMWARE_RESERVED_SIZE as u32,
+        )
+        .map_err(|_| WfxFlashError::OpFailed)
+    }
+
+    /// Program `data` starting at `offset` bytes into the reserved window. `offset` and
+    /// `offset + data.len()` are both checked against [`WFX_FIRMWARE_RESERVED_SIZE`] before
+    /// anything is written, so a caller that mis-sequences a multi-chunk transfer gets an
+    /// error back instead of quietly corrupting whatever flash sector comes after the
+    /// window.
+    ///
+    /// Pages are split to `CMD_4PP`'s 256-byte granularity, same as
+    /// [`fw_update::Updater::write`](crate::fw_update::Updater::write). Any trailing chunk
+    /// shorter than a full 32-bit word is padded with `0xFF` (the erased-flash value) up to
+    /// the next word boundary before being shifted out, since the quad program path here
+    /// only ever moves whole words onto the bus -- a partial word left dangling is what the
+    /// Zynq driver's note about flushing before `WRDI` is guarding against, and padding to a
+    /// word boundary up front sidesteps the issue rather than requiring a flush step this
+    /// bitbang core has no register for (see [`spi::spi_program_page`]: it already waits on
+    /// WIP between pages and re-reads `RDSCUR` after the page completes, before the
+    /// corresponding `CMD_WRDI`, rather than trusting the pre-write status).
+    pub fn program(offset: usize, data: &[u8]) -> Result<(), WfxFlashError> {
+        if offset + data.len() > WFX_FIRMWARE_RESERVED_SIZE {
+            return Err(WfxFlashError::OutOfBounds);
+        }
+        let base = flash_offset(WFX_FIRMWARE_OFFSET);
+        let mut consumed = 0usize;
+        while consumed < data.len() {
+            let take = (data.len() - consumed).min(PAGE_SIZE as usize);
+            let word_padded = (take + 3) & !3; // round up to a 32-bit word
+            let mut page = [0xFFu8; PAGE_SIZE as usize];
+            page[..take].copy_from_slice(&data[consumed..consumed + take]);
+            spi::spi_program_page(base + (offset + consumed) as u32, &mut page[..word_padded])
+                .map_err(|_| WfxFlashError::OpFailed)?;
+            consumed += take;
+        }
+        Ok(())
+    }
+
+    /// Quad-read the whole [`WFX_FIRMWARE_SIZE`]-byte image back out and fold it through the
+    /// same CRC-32 [`spi::crc32_update`]/[`spi::crc32_finalize`] uses elsewhere in this
+    /// crate, comparing against `expected_crc` (normally the CRC trailing the transferred
+    /// image, the same way [`fw_sig`](crate::fw_sig) checks a signature after
+    /// `fw_update::Updater::write` rather than trusting the transfer succeeded silently).
+    pub fn verify(expected_crc: u32) -> Result<(), WfxFlashError> {
+        let base = flash_offset(WFX_FIRMWARE_OFFSET);
+        let mut crc = spi::CRC32_INIT;
+        let mut buf = [0u8; PAGE_SIZE as usize];
+        let mut read = 0usize;
+        while read < betrusted_hal::mem_locs::WFX_FIRMWARE_SIZE {
+            let take = (betrusted_hal::mem_locs::WFX_FIRMWARE_SIZE - read).min(buf.len());
+            spi::spi_cmd(spi::CMD_4READ, Some(base + read as u32), Some(&mut buf[..take]));
+            crc = spi::crc32_update(crc, &buf[..take]);
+            read += take;
+        }
+        if spi::crc32_finalize(crc) == expected_crc {
+            Ok(())
+        } else {
+            Err(WfxFlashError::CrcMismatch)
+        }
+    }
+}
@@ -1,8 +1,11 @@
-use crate::wlan::WlanState;
+use crate::wlan::{SecurityMode, WlanState};
 use debug::{loghexln, logln, LL};
 use wfx_bindings::{
-    sl_status_t, sl_wfx_host_hold_in_reset, sl_wfx_host_reset_chip,
-    sl_wfx_security_mode_e_WFM_SECURITY_MODE_WPA2_PSK, sl_wfx_send_disconnect_command,
+    sl_status_t, sl_wfx_host_hold_in_reset, sl_wfx_host_reset_chip, sl_wfx_security_mode_e,
+    sl_wfx_security_mode_e_WFM_SECURITY_MODE_OPEN, sl_wfx_security_mode_e_WFM_SECURITY_MODE_WEP,
+    sl_wfx_security_mode_e_WFM_SECURITY_MODE_WPA2_PSK,
+    sl_wfx_security_mode_e_WFM_SECURITY_MODE_WPA2_WPA1_PSK,
+    sl_wfx_security_mode_e_WFM_SECURITY_MODE_WPA3_SAE, sl_wfx_send_disconnect_command,
     sl_wfx_send_join_command, SL_STATUS_OK,
 };
 use wfx_rs::hal_wf200;
@@ -16,16 +19,47 @@ const LOG_LEVEL: LL = LL::Debug;
 
 pub const SSID_ARRAY_SIZE: usize = wfx_rs::hal_wf200::SSID_ARRAY_SIZE;
 
-/// Connect to an access point using WPA2 with SSID and password.
+/// Protected Management Frames policy passed to `sl_wfx_send_join_command`'s
+/// `management_frame_protection` parameter: disabled, optional, or required. See
+/// `security_join_params` for which `SecurityMode` gets which.
+const PMF_DISABLED: u16 = 0;
+const PMF_OPTIONAL: u16 = 1;
+const PMF_REQUIRED: u16 = 2;
+
+/// Map a `SecurityMode` to the `sl_wfx_security_mode_e` value and PMF policy
+/// `sl_wfx_send_join_command` needs for it. PMF is required for SAE (mandatory per the WPA3
+/// spec), optional for WPA2/WPA1 mixed and WPA2-PSK (matches `ap_join`'s old hardcoded
+/// `management_frame_protection = 1`), and off for OPEN/WEP, which don't support it.
+fn security_join_params(mode: SecurityMode) -> (sl_wfx_security_mode_e, u16) {
+    match mode {
+        SecurityMode::Open => (sl_wfx_security_mode_e_WFM_SECURITY_MODE_OPEN, PMF_DISABLED),
+        SecurityMode::Wep => (sl_wfx_security_mode_e_WFM_SECURITY_MODE_WEP, PMF_DISABLED),
+        SecurityMode::Wpa2Wpa1Mixed => {
+            (sl_wfx_security_mode_e_WFM_SECURITY_MODE_WPA2_WPA1_PSK, PMF_OPTIONAL)
+        }
+        SecurityMode::Wpa2Psk => (sl_wfx_security_mode_e_WFM_SECURITY_MODE_WPA2_PSK, PMF_OPTIONAL),
+        SecurityMode::Wpa3Sae => (sl_wfx_security_mode_e_WFM_SECURITY_MODE_WPA3_SAE, PMF_REQUIRED),
+    }
+}
+
+/// Connect to an access point using `ws`'s stored SSID, password, and security mode
+/// (`WlanState::security_mode`/`wlan::set_security`).
 /// References:
 /// - Silicon Laboratories API docs for sl_wfx_send_join_command():
 ///   docs.silabs.com/wifi/wf200/rtos/latest/group-f-u-l-l-m-a-c-d-r-i-v-e-r-a-p-i#ga2fd76ed31e48be10ab6b7fb9d4bc454d
 /// - Rust FFI bindings for sl_wfx API: ../wfx_bindings/src/lib.rs
 /// - Protected management frame explanation: en.wikipedia.org/wiki/IEEE_802.11w-2009
 ///
-pub fn ap_join_wpa2(ws: &WlanState) {
+/// For `SecurityMode::Wpa3Sae`, the WF200 firmware runs the SAE handshake itself once given
+/// the passphrase here -- there's no separate handshake step on this side.
+pub fn ap_join(ws: &WlanState) {
+    // Apply the selected power-management policy as part of the join sequence itself,
+    // rather than waiting for `dhcp_init()` on the eventual `ConnectResult::Success` --
+    // the WF200 accepts `SetPmMode` independent of association state, and setting it here
+    // means it's already in force by the time the join completes instead of racing it.
+    hal_wf200::set_power_management_mode(ws.pm_mode());
     let prevent_roaming: u8 = 0;
-    let management_frame_protection: u16 = 1;
+    let (security_mode, management_frame_protection) = security_join_params(ws.security_mode());
     let ie_data: *const u8 = core::ptr::null();
     let ie_data_length: u16 = 0;
     let ssid = match ws.ssid() {
@@ -36,12 +70,22 @@ pub fn ap_join_wpa2(ws: &WlanState) {
             &""
         }
     };
-    let pass = match ws.pass() {
-        Ok(p) => p,
-        #[allow(unused_variables)]
-        Err(e) => {
-            logln!(LL::Debug, "PassErr {}", e as u8);
-            &""
+    // Apply the selected MAC address privacy policy now that the SSID being joined is
+    // known -- `StablePerSsid` needs it to derive the address, and either way this has to
+    // land before the join command below so outbound frames use it from the start.
+    hal_wf200::apply_mac_privacy(ws.mac_policy(), ssid.as_bytes());
+    // OPEN networks have no passphrase; skip `ws.pass()` for that mode so an unset password
+    // doesn't log a spurious `PassErr` on every open-network join.
+    let pass = if ws.security_mode() == SecurityMode::Open {
+        &""
+    } else {
+        match ws.pass() {
+            Ok(p) => p,
+            #[allow(unused_variables)]
+            Err(e) => {
+                logln!(LL::Debug, "PassErr {}", e as u8);
+                &""
+            }
         }
     };
     let result: sl_status_t = unsafe {
@@ -50,7 +94,7 @@ pub fn ap_join_wpa2(ws: &WlanState) {
             ssid.len() as u32,
             core::ptr::null(),
             0 as u16,
-            sl_wfx_security_mode_e_WFM_SECURITY_MODE_WPA2_PSK,
+            security_mode,
             prevent_roaming,
             management_frame_protection,
             pass.as_ptr(),
@@ -68,11 +112,14 @@ pub fn ap_join_wpa2(ws: &WlanState) {
 }
 
 /// Initialize DHCP to INIT state (forget bindings, but be ready to DISCOVER on wifi connect)
-pub fn dhcp_init() {
+/// and re-apply the selected power-management policy, so a reconnect always lands back on
+/// whatever `ws` has configured rather than the radio's hardcoded connect-time default.
+pub fn dhcp_init(ws: &WlanState) {
     match hal_wf200::dhcp_reset() {
         Ok(_) => (),
         Err(e) => loghexln!(LL::Debug, "DhcpResetErr ", e),
     };
+    hal_wf200::set_power_management_mode(ws.pm_mode());
 }
 
 /// Clock the DHCP state machine
@@ -80,10 +127,16 @@ pub fn dhcp_clock_state_machine() {
     let link = hal_wf200::get_status();
     let dhcp = hal_wf200::dhcp_get_state();
     match link {
-        com_rs::LinkState::Connected => match hal_wf200::dhcp_do_next() {
-            Ok(_) => (),
-            Err(e) => loghexln!(LL::Debug, "DhcpNextErr ", e),
-        },
+        com_rs::LinkState::Connected => {
+            match hal_wf200::dhcp_do_next() {
+                Ok(_) => (),
+                Err(e) => loghexln!(LL::Debug, "DhcpNextErr ", e),
+            };
+            match hal_wf200::igmp_do_next() {
+                Ok(_) => (),
+                Err(e) => loghexln!(LL::Debug, "IgmpNextErr ", e),
+            };
+        }
         com_rs::LinkState::Disconnected if dhcp == com_rs::DhcpState::Bound => {
             hal_wf200::dhcp_handle_link_drop();
         }
@@ -97,10 +150,17 @@ pub fn dhcp_clock_state_machine() {
 ///   docs.silabs.com/wifi/wf200/rtos/latest/group-f-u-l-l-m-a-c-d-r-i-v-e-r-a-p-i#gae4ae713ea9406b5c18ec278886dcf654
 /// - Rust FFI bindings for sl_wfx API: ../wfx_bindings/src/lib.rs
 ///
-pub fn ap_leave() {
+pub fn ap_leave(ws: &WlanState) {
+    // Hand back the lease, if any, before tearing down the association -- this still has a
+    // valid address/route to unicast the DHCPRELEASE from, so the server's pool isn't held
+    // until the lease naturally expires across repeated leave/join cycles.
+    match hal_wf200::dhcp_release() {
+        Ok(_) => (),
+        Err(e) => loghexln!(LL::Debug, "DhcpReleaseErr ", e),
+    };
     let result: sl_status_t = unsafe { sl_wfx_send_disconnect_command() };
     // reset the dhcp machine, since once we've left the AP we have no idea what the next state might be.
-    dhcp_init();
+    dhcp_init(ws);
     match result {
         SL_STATUS_OK => logln!(LL::Debug, "leaveOk"),
         _ => loghexln!(LL::Debug, "leaveFail ", result),
@@ -145,6 +205,149 @@ pub fn wf200_reset_and_init(use_wifi: &mut bool, wifi_ready: &mut bool) {
     };
 }
 
+/// Directly enable or disable WF200 legacy power-save with a given DTIM listen interval,
+/// bypassing the usual idle-timeout policy in `hal_wf200::pm_poll()`. Used when the SoC
+/// itself has gone to sleep: the main loop's SoC-off branch calls this to drop the radio
+/// to DTIM-interval wakeups immediately rather than waiting for the idle timer to catch up.
+pub fn set_ps_mode(enabled: bool, dtim_skip: u8) {
+    hal_wf200::set_ps_mode(enabled, dtim_skip);
+}
+
+/// Configure the byte pattern (with wildcard `mask`, at a given `offset` into the Ethernet
+/// frame) that should wake the SoC from the main loop's SoC-off branch; see
+/// `hal_wf200::poll_wake_packet()`.
+pub fn set_wakeup_filter(pattern: &[u8], mask: &[u8], offset: usize) {
+    hal_wf200::set_wakeup_filter(pattern, mask, offset);
+}
+
+/// Set the WF200 power-management mode directly, independent of `WlanState::pm_mode()`.
+/// Used by policies -- e.g. `power_mgmt::wifi_throttle_policy` -- that need to react to
+/// something other than the next (re)association.
+pub fn set_power_management_mode(mode: hal_wf200::PowerManagementMode) {
+    hal_wf200::set_power_management_mode(mode);
+}
+
+/// Whether the radio is currently in legacy power-save, and if so, at what DTIM listen
+/// interval (`ComState::WLAN_GET_PM_STATE`'s readback, once it exists). There's no such
+/// verb to reach this through yet -- `com_rs` isn't vendored in this tree to add one to --
+/// so this is ready for whichever COM bus command ends up calling it.
+pub fn pm_state() -> (bool, u8) {
+    hal_wf200::pm_state()
+}
+
+/// Bring up SoftAP mode on `channel`, advertising the SoftAP SSID/passphrase set via
+/// `wlan::set_ap_ssid`/`wlan::set_ap_pass` -- separate from the station-mode credentials
+/// `ap_join` uses. See the `sl_wfx_start_ap` caveat on `hal_wf200::start_ap`: the real
+/// over-the-air beacon isn't driven yet, only the link-state/client bookkeeping
+/// `hal_wf200::sl_wfx_start_ap_callback` et al. maintain; `ws.ap_pass()` is already available
+/// for whenever `hal_wf200::start_ap` grows a passphrase parameter to go with it.
+pub fn start_ap(ws: &WlanState, channel: u8) {
+    let ssid = match ws.ap_ssid() {
+        Ok(s) => s,
+        #[allow(unused_variables)]
+        Err(e) => {
+            logln!(LL::Debug, "ApSsidErr {}", e as u8);
+            return;
+        }
+    };
+    hal_wf200::start_ap(ssid, channel);
+}
+
+/// Tear down SoftAP mode.
+pub fn stop_ap() {
+    hal_wf200::stop_ap();
+}
+
+/// Clock the SoftAP link-state machine; called periodically from the main loop the same way
+/// `dhcp_clock_state_machine` is. See `hal_wf200::ap_clock_state_machine`.
+pub fn ap_clock_state_machine() {
+    hal_wf200::ap_clock_state_machine();
+}
+
+/// Current SoftAP link state (`Down`/`Starting`/`Up`/`StopPending`).
+pub fn ap_status() -> hal_wf200::ApLinkState {
+    hal_wf200::ap_status()
+}
+
+/// Number of stations currently associated to our SoftAP.
+pub fn ap_client_count() -> usize {
+    hal_wf200::ap_client_list().1
+}
+
+/// Select the regulatory domain for `country`, a two-byte ISO-3166-1 alpha-2 code, so the
+/// next scan or `start_ap` honors that region's channel set. Returns `false` if `country`
+/// isn't in `hal_wf200::regulatory`'s table, leaving whatever region was selected before.
+/// There's no `ComState::WLAN_SET_COUNTRY` verb to reach this through yet -- `com_rs` isn't
+/// vendored in this tree to add one to -- so this is ready for whichever COM bus command
+/// ends up calling it.
+pub fn set_country(country: [u8; 2]) -> bool {
+    hal_wf200::set_region(country)
+}
+
+/// The regulatory domain currently in force (`ComState::WLAN_SET_COUNTRY`'s readback half,
+/// once it exists).
+pub fn country() -> hal_wf200::RegDomain {
+    hal_wf200::region()
+}
+
+/// Start an RF certification test (`ComState::RF_TEST_CONFIG` + `RF_TEST_START`): mask the
+/// net bridge and drop any station-mode association first, so normal traffic can't bleed
+/// into the measurement, then hand the config off to `hal_wf200::rf_test_start`.
+/// `com_net_bridge_enable` is the same flag threaded through `main()` that the `'7'` UART
+/// debug key and `ComState::FLASH_LOCK`-adjacent bridge toggling use; `rf_test_stop`
+/// restores it. There's no `ComState::RF_TEST_CONFIG`/`RF_TEST_START` verb to reach this
+/// through yet -- `com_rs` isn't vendored in this tree to add one to -- so this is ready for
+/// whichever COM bus command ends up calling it.
+pub fn rf_test_start(
+    ws: &WlanState,
+    com_net_bridge_enable: &mut bool,
+    channel: u8,
+    power_dbm_q2: i16,
+    mode: hal_wf200::RfTestMode,
+) -> Result<hal_wf200::RfTestConfig, hal_wf200::RfTestError> {
+    let config = hal_wf200::rf_test_start(channel, power_dbm_q2, mode)?;
+    if *com_net_bridge_enable {
+        *com_net_bridge_enable = false;
+        hal_wf200::set_com_net_bridge_enable(false);
+    }
+    ap_leave(ws);
+    Ok(config)
+}
+
+/// Stop the RF test started by `rf_test_start` (`ComState::RF_TEST_STOP`) and re-enable the
+/// net bridge -- station mode itself isn't automatically rejoined; the host is expected to
+/// issue a normal `ap_join` afterward if it wants to reconnect.
+pub fn rf_test_stop(com_net_bridge_enable: &mut bool) {
+    hal_wf200::rf_test_stop();
+    if !*com_net_bridge_enable {
+        *com_net_bridge_enable = true;
+        hal_wf200::set_com_net_bridge_enable(true);
+    }
+}
+
+/// Currently-applied RF test config, or `None` if no test is running
+/// (`ComState::RF_TEST_STATUS`'s readback, once it exists).
+pub fn rf_test_status() -> Option<hal_wf200::RfTestConfig> {
+    hal_wf200::rf_test_status()
+}
+
+/// Snapshot the cumulative TX/RX counters, bracketed by the same IRQ mask `FLASH_LOCK`/
+/// `FLASH_UNLOCK` use so the host never observes a torn 64-bit value mid-update. There's
+/// no `ComState::WLAN_GET_STATS` verb to serialize this through yet -- `com_rs` (the crate
+/// defining the COM bus command set) isn't vendored in this tree to add one to -- so this
+/// is ready for whichever COM bus command ends up calling it.
+pub fn net_stats() -> hal_wf200::NetStats {
+    wf200_irq_disable();
+    let stats = hal_wf200::net_stats_snapshot();
+    wf200_irq_enable();
+    stats
+}
+
+/// Zero the counters `net_stats()` reports (`ComState::WLAN_RESET_STATS`, once it exists).
+pub fn reset_net_stats() {
+    hal_wf200::net_stats_reset();
+}
+
 pub fn wf200_irq_disable() {
     //let mut wifi_csr = CSR::new(HW_WIFI_BASE as *mut u32);
     //wifi_csr.wfo(utra::wifi::EV_ENABLE_WIRQ, 0);
@@ -3,6 +3,8 @@ use com_rs::serdes::{
     SerdesError, StringDes, STR_32_U8_SIZE, STR_32_WORDS, STR_64_U8_SIZE, STR_64_WORDS,
 };
 use com_rs::ComState;
+use core::convert::TryFrom;
+use wfx_rs::hal_wf200::{MacAddressPolicy, PowerManagementMode};
 
 /// Error codes related to COM bus protocol
 pub enum WlanError {
@@ -10,11 +12,62 @@ pub enum WlanError {
     Timeout = 2,
     StrLen = 3,
     Utf8 = 4,
+    /// A raw `WLAN_SET_SECURITY` payload byte didn't decode to a recognized `SecurityMode`.
+    Security = 5,
+}
+
+/// Join security, dispatched by `wifi::ap_join` into the matching `sl_wfx_security_mode_e`
+/// value and Protected Management Frames policy. Keeping the two paired in one enum (rather
+/// than a security mode plus an independently-settable PMF flag) makes the invalid
+/// combination the original request called out -- SAE joined with PMF disabled -- a state
+/// this type can't represent in the first place, instead of one `ap_join` has to check for.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum SecurityMode {
+    Open,
+    Wep,
+    /// WPA2/WPA1 mixed mode, for APs still running legacy WPA1 clients alongside WPA2 ones.
+    Wpa2Wpa1Mixed,
+    Wpa2Psk,
+    /// WPA3-SAE. The WF200 firmware runs the SAE handshake itself once given the passphrase;
+    /// PMF is mandatory for SAE per the WPA3 spec, so this always pairs with `PMF_REQUIRED`.
+    Wpa3Sae,
+}
+
+impl core::convert::TryFrom<u8> for SecurityMode {
+    type Error = WlanError;
+    fn try_from(value: u8) -> Result<Self, WlanError> {
+        match value {
+            0 => Ok(SecurityMode::Open),
+            1 => Ok(SecurityMode::Wep),
+            2 => Ok(SecurityMode::Wpa2Wpa1Mixed),
+            3 => Ok(SecurityMode::Wpa2Psk),
+            4 => Ok(SecurityMode::Wpa3Sae),
+            _ => Err(WlanError::Security),
+        }
+    }
 }
 
 pub struct WlanState {
     pass_: StringDes<STR_64_WORDS, STR_64_U8_SIZE>,
     ssid_: StringDes<STR_32_WORDS, STR_32_U8_SIZE>,
+    /// SoftAP credentials, separate from the station-mode `ssid_`/`pass_` above -- a device
+    /// can have a different SSID/passphrase to advertise as an AP than the one it joins as a
+    /// client. Set via `set_ap_ssid`/`set_ap_pass`, read by `wifi::start_ap`.
+    ap_pass_: StringDes<STR_64_WORDS, STR_64_U8_SIZE>,
+    ap_ssid_: StringDes<STR_32_WORDS, STR_32_U8_SIZE>,
+    /// Power-management policy to (re)apply on every successful association; see
+    /// `crate::wifi::dhcp_init`. Kept here rather than in `hal_wf200`'s `PowerManager` so
+    /// it survives a disconnect/reconnect cycle the same way the SSID/password do.
+    pm_mode: PowerManagementMode,
+    /// Set once `set_pm_mode` has been called by an explicit host request, as opposed to
+    /// just holding the constructor's default. `power_mgmt::wifi_throttle_policy` checks
+    /// this so its battery-driven escalation doesn't fight a mode the host actually asked for.
+    pm_explicit: bool,
+    /// Security mode `wifi::ap_join` joins with; see `set_security`/`SecurityMode`.
+    security: SecurityMode,
+    /// MAC address privacy policy `wifi::ap_join` applies before joining; see
+    /// `set_mac_policy`/`MacAddressPolicy`.
+    mac_policy: MacAddressPolicy,
 }
 
 impl WlanState {
@@ -22,9 +75,65 @@ impl WlanState {
         Self {
             pass_: StringDes::<STR_64_WORDS, STR_64_U8_SIZE>::new(),
             ssid_: StringDes::<STR_32_WORDS, STR_32_U8_SIZE>::new(),
+            ap_pass_: StringDes::<STR_64_WORDS, STR_64_U8_SIZE>::new(),
+            ap_ssid_: StringDes::<STR_32_WORDS, STR_32_U8_SIZE>::new(),
+            // Matches the default `sl_wfx_connect_callback` used before a mode could be
+            // selected: power-save enabled with a short DTIM listen interval.
+            pm_mode: PowerManagementMode::PowerSave { listen_interval: 3 },
+            pm_explicit: false,
+            // Matches `ap_join_wpa2`'s behavior before security became selectable.
+            security: SecurityMode::Wpa2Psk,
+            // Matches `ap_join`'s behavior before MAC privacy became selectable: the
+            // WF200's factory-burned address, unmodified.
+            mac_policy: MacAddressPolicy::Factory,
         }
     }
 
+    /// The security mode `wifi::ap_join` should join with.
+    pub fn security_mode(&self) -> SecurityMode {
+        self.security
+    }
+
+    /// The MAC address privacy policy `wifi::ap_join` should apply before joining.
+    pub fn mac_policy(&self) -> MacAddressPolicy {
+        self.mac_policy
+    }
+
+    /// Select the MAC address privacy policy. There's no `ComState` verb wired to this
+    /// yet -- `com_rs` isn't vendored in this tree to add one to -- so this is ready for
+    /// whichever COM bus command ends up calling it.
+    pub fn set_mac_policy(&mut self, policy: MacAddressPolicy) {
+        self.mac_policy = policy;
+    }
+
+    /// Select the join security mode. There's no `ComState::WLAN_SET_SECURITY` verb wired
+    /// to this yet -- `com_rs` (the crate that defines the COM bus command set) isn't
+    /// vendored in this tree to add one to -- so this is ready for whichever COM bus command
+    /// ends up calling it; see `set_security` below for the decode half once it is.
+    pub fn set_security_mode(&mut self, mode: SecurityMode) {
+        self.security = mode;
+    }
+
+    /// The power-management policy to apply on (re)association.
+    pub fn pm_mode(&self) -> PowerManagementMode {
+        self.pm_mode
+    }
+
+    /// Whether `pm_mode()` was chosen by an explicit host request rather than left at the
+    /// constructor's default.
+    pub fn pm_mode_explicit(&self) -> bool {
+        self.pm_explicit
+    }
+
+    /// Select the power-management policy `wifi::dhcp_init()` applies after each
+    /// successful association. There's no `ComState` verb wired to this yet -- `com_rs`
+    /// (the crate that defines the COM bus command set) isn't vendored in this tree to add
+    /// one to -- so this is ready for whichever COM bus command ends up calling it.
+    pub fn set_pm_mode(&mut self, mode: PowerManagementMode) {
+        self.pm_mode = mode;
+        self.pm_explicit = true;
+    }
+
     /// Make a string slice for the SSID
     pub fn ssid(&self) -> Result<&str, WlanError> {
         match self.ssid_.as_str() {
@@ -42,6 +151,24 @@ impl WlanState {
             Err(SerdesError::Utf8Decode) => Err(WlanError::Utf8),
         }
     }
+
+    /// Make a string slice for the SoftAP SSID
+    pub fn ap_ssid(&self) -> Result<&str, WlanError> {
+        match self.ap_ssid_.as_str() {
+            Ok(ssid) => Ok(ssid),
+            Err(SerdesError::StrLenTooBig) => Err(WlanError::StrLen),
+            Err(SerdesError::Utf8Decode) => Err(WlanError::Utf8),
+        }
+    }
+
+    /// Make a string slice for the SoftAP password
+    pub fn ap_pass(&self) -> Result<&str, WlanError> {
+        match self.ap_pass_.as_str() {
+            Ok(pass) => Ok(pass),
+            Err(SerdesError::StrLenTooBig) => Err(WlanError::StrLen),
+            Err(SerdesError::Utf8Decode) => Err(WlanError::Utf8),
+        }
+    }
 }
 
 /// Implement the ComState::WLAN_SET_SSID verb to set the SSID for use by ComState::WLAN_JOIN.
@@ -81,3 +208,57 @@ pub fn set_pass(ws: &mut WlanState) -> Result<&str, WlanError> {
         Err(SerdesError::Utf8Decode) => Err(WlanError::Utf8),
     }
 }
+
+/// Implement a `ComState::WLAN_SET_AP_SSID` verb to set the SoftAP SSID for use by
+/// `wifi::start_ap`, decoded the same way `set_ssid` decodes the station-mode one. There's no
+/// such verb in `ComState` yet -- `com_rs` isn't vendored in this tree to add one to -- so
+/// this is ready for whichever COM bus command ends up calling it.
+pub fn set_ap_ssid(ws: &mut WlanState) -> Result<&str, WlanError> {
+    let mut rx_words = [0u16; STR_32_WORDS];
+    for w in rx_words.iter_mut() {
+        match com_rx(500) {
+            Ok(rx) => *w = rx,
+            Err(_) => return Err(WlanError::Timeout), // This means COM bus out of sync. VERY BAD.
+        }
+    }
+    match ws.ap_ssid_.decode_u16(&rx_words) {
+        Ok(ssid) => Ok(ssid),
+        Err(SerdesError::StrLenTooBig) => Err(WlanError::StrLen),
+        Err(SerdesError::Utf8Decode) => Err(WlanError::Utf8),
+    }
+}
+
+/// Implement a `ComState::WLAN_SET_AP_PASS` verb to set the SoftAP password for use by
+/// `wifi::start_ap`, decoded the same way `set_pass` decodes the station-mode one. There's no
+/// such verb in `ComState` yet -- `com_rs` isn't vendored in this tree to add one to -- so
+/// this is ready for whichever COM bus command ends up calling it.
+pub fn set_ap_pass(ws: &mut WlanState) -> Result<&str, WlanError> {
+    let mut rx_words = [0u16; STR_64_WORDS];
+    for w in rx_words.iter_mut() {
+        match com_rx(500) {
+            Ok(rx) => *w = rx,
+            Err(_) => return Err(WlanError::Timeout), // This means COM bus out of sync. VERY BAD.
+        }
+    }
+    match ws.ap_pass_.decode_u16(&rx_words) {
+        Ok(pass) => Ok(pass),
+        Err(SerdesError::StrLenTooBig) => Err(WlanError::StrLen),
+        Err(SerdesError::Utf8Decode) => Err(WlanError::Utf8),
+    }
+}
+
+/// Decode a `WLAN_SET_SECURITY` payload -- one word carrying a `SecurityMode` discriminant,
+/// read off the COM bus the same way `set_ssid`/`set_pass` decode their payloads -- and apply
+/// it to `ws` for use by `wifi::ap_join`. There's no `ComState::WLAN_SET_SECURITY` verb to
+/// dispatch this from yet -- `com_rs` (the crate that defines the COM bus command set) isn't
+/// vendored in this tree to add one to -- so this is ready for whichever COM bus command ends
+/// up calling it.
+pub fn set_security(ws: &mut WlanState) -> Result<SecurityMode, WlanError> {
+    let raw = match com_rx(500) {
+        Ok(rx) => rx,
+        Err(_) => return Err(WlanError::Timeout), // This means COM bus out of sync. VERY BAD.
+    };
+    let mode = SecurityMode::try_from(raw as u8)?;
+    ws.set_security_mode(mode);
+    Ok(mode)
+}
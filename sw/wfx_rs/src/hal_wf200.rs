@@ -11,18 +11,33 @@ use utralib::generated::{utra, CSR, HW_WIFI_BASE};
 use com_rs::LinkState;
 
 mod bt_wf200_pds;
-
-use crate::pkt_buf::{PktBuf, MAX_PKTS};
+pub mod debug;
+pub mod ie_parse;
+mod mac_privacy;
+mod pcap;
+mod pm;
+mod regulatory;
+mod rf_test;
+pub use ie_parse::{parse_scan_ies, HtCapabilitySummary, ParsedScanIes, SecuritySuite};
+pub use mac_privacy::MacAddressPolicy;
+pub use pcap::{get_pcap_dropped, poll_new_pcap_dropped, service as pcap_service};
+pub use pm::PowerManagementMode;
+pub use regulatory::RegDomain;
+pub use rf_test::{RfTestConfig, RfTestError, RfTestMode};
+
+use crate::pkt_buf::PktBuf;
+use pm::{PmState, PowerManager};
 use bt_wf200_pds::PDS_DATA;
 use com_rs::serdes::Ipv4Conf;
 use debug;
 use debug::{log, loghex, loghexln, logln, LL};
-use net::{self, filter::FilterBin};
+use net::{self, filter::FilterBin, mac_address::MacAddress};
 
 // The mixed case constants here are the reason for the `allow(non_upper_case_globals)` above
 pub use wfx_bindings::{
     sl_status_t, sl_wfx_buffer_type_t, sl_wfx_confirmations_ids_e_SL_WFX_CONNECT_CNF_ID,
     sl_wfx_confirmations_ids_e_SL_WFX_DISCONNECT_CNF_ID,
+    sl_wfx_confirmations_ids_e_SL_WFX_GET_COUNTERS_CNF_ID,
     sl_wfx_confirmations_ids_e_SL_WFX_SEND_FRAME_CNF_ID,
     sl_wfx_confirmations_ids_e_SL_WFX_SET_ARP_IP_ADDRESS_CNF_ID,
     sl_wfx_confirmations_ids_e_SL_WFX_START_SCAN_CNF_ID,
@@ -44,21 +59,29 @@ pub use wfx_bindings::{
     sl_wfx_generic_indication_type_e_SL_WFX_GENERIC_INDICATION_TYPE_STRING,
     sl_wfx_generic_message_t, sl_wfx_get_signal_strength, sl_wfx_host_bus_transfer_type_t,
     sl_wfx_host_bus_transfer_type_t_SL_WFX_BUS_READ, sl_wfx_indication_data_u,
+    sl_wfx_indications_ids_e_SL_WFX_AP_CLIENT_CONNECTED_IND_ID,
+    sl_wfx_indications_ids_e_SL_WFX_AP_CLIENT_DISCONNECTED_IND_ID,
     sl_wfx_indications_ids_e_SL_WFX_CONNECT_IND_ID,
     sl_wfx_indications_ids_e_SL_WFX_DISCONNECT_IND_ID,
     sl_wfx_indications_ids_e_SL_WFX_RECEIVED_IND_ID,
     sl_wfx_indications_ids_e_SL_WFX_SCAN_COMPLETE_IND_ID,
-    sl_wfx_indications_ids_e_SL_WFX_SCAN_RESULT_IND_ID, sl_wfx_init,
+    sl_wfx_indications_ids_e_SL_WFX_SCAN_RESULT_IND_ID,
+    sl_wfx_indications_ids_e_SL_WFX_START_AP_IND_ID,
+    sl_wfx_indications_ids_e_SL_WFX_STOP_AP_IND_ID,
+    sl_wfx_ap_client_connected_ind_t, sl_wfx_ap_client_disconnected_ind_t,
+    sl_wfx_start_ap_ind_t, sl_wfx_init,
     sl_wfx_interface_t_SL_WFX_STA_INTERFACE, sl_wfx_mac_address_t,
     sl_wfx_pm_mode_e_WFM_PM_MODE_ACTIVE, sl_wfx_pm_mode_e_WFM_PM_MODE_PS, sl_wfx_receive_frame,
     sl_wfx_received_ind_body_s, sl_wfx_received_ind_t, sl_wfx_register_address_t,
     sl_wfx_requests_ids_e_SL_WFX_GET_SIGNAL_STRENGTH_REQ_ID, sl_wfx_rx_stats_s,
     sl_wfx_scan_complete_ind_t, sl_wfx_scan_mode_e_WFM_SCAN_MODE_ACTIVE,
+    sl_wfx_scan_mode_e_WFM_SCAN_MODE_PASSIVE,
     sl_wfx_scan_result_ind_body_t, sl_wfx_scan_result_ind_t, sl_wfx_send_configuration,
     sl_wfx_send_ethernet_frame, sl_wfx_send_frame_req_t, sl_wfx_send_scan_command,
     sl_wfx_set_arp_ip_address, sl_wfx_set_power_mode, sl_wfx_ssid_def_t,
     sl_wfx_state_t_SL_WFX_STA_INTERFACE_CONNECTED, u_int32_t, SL_STATUS_ALLOCATION_FAILED,
-    SL_STATUS_IO_TIMEOUT, SL_STATUS_OK, SL_STATUS_WIFI_SLEEP_GRANTED, SL_STATUS_WIFI_WRONG_STATE,
+    SL_STATUS_FAIL, SL_STATUS_IO_TIMEOUT, SL_STATUS_OK, SL_STATUS_WIFI_SLEEP_GRANTED,
+    SL_STATUS_WIFI_SLEEP_NOT_GRANTED, SL_STATUS_WIFI_WRONG_STATE,
     SL_WFX_CONT_NEXT_LEN_MASK, SL_WFX_EXCEPTION_DATA_SIZE_MAX,
     sl_wfx_reg_read_32, sl_wfx_register_address_t_SL_WFX_CONFIG_REG_ID,
     sl_wfx_reg_read_16, sl_wfx_register_address_t_SL_WFX_CONTROL_REG_ID,
@@ -75,15 +98,73 @@ const SL_WFX_HIF_BUS_ERROR: u32 = 0xf;
 
 pub const WIFI_EVENT_WIRQ: u32 = 0x1;
 
+/// Cheap per-link counters for diagnosing WF200 bus flakiness from the host side without
+/// attaching a debugger -- mirrors the lightweight per-interface counters on the zynq
+/// ethernet driver. `tx_frames`/`rx_frames`/`tx_bytes`/`rx_bytes` count whole SPI transfers
+/// (one frame per `sl_wfx_host_spi_transfer_no_cs_assert` call, not per 16-bit word) and the
+/// header+payload bytes moved by them; `spi_timeouts` counts `wfx_spi_wait_tip` bailouts;
+/// `reset_count` counts `sl_wfx_host_reset_chip` calls. `irq_count` counts
+/// `sl_wfx_host_enable_platform_interrupt` calls rather than a real WIRQ ISR firing -- this
+/// platform polls for WIRQ instead of taking an interrupt (see the NOP in that hook below),
+/// so enable-calls are the closest real signal the current platform-interrupt hooks offer.
+#[derive(Copy, Clone, Default, Debug)]
+pub struct WfxStats {
+    pub tx_frames: u32,
+    pub rx_frames: u32,
+    pub tx_bytes: u32,
+    pub rx_bytes: u32,
+    pub spi_timeouts: u32,
+    pub reset_count: u32,
+    pub irq_count: u32,
+}
+static mut WFX_STATS: WfxStats = WfxStats {
+    tx_frames: 0,
+    rx_frames: 0,
+    tx_bytes: 0,
+    rx_bytes: 0,
+    spi_timeouts: 0,
+    reset_count: 0,
+    irq_count: 0,
+};
+/// Snapshot of the current counters -- see `WfxStats` for what each field tracks.
+pub fn wf200_stats() -> WfxStats {
+    unsafe { WFX_STATS }
+}
+/// Zero every counter, e.g. right after the host has read and logged a snapshot.
+pub fn wf200_reset_stats() {
+    unsafe {
+        WFX_STATS = WfxStats::default();
+    }
+}
+
 // SSID scan state variables
 static mut SSID_SCAN_UPDATE: bool = false;
 static mut SSID_SCAN_FINISHED: bool = false;
 pub const SSID_ARRAY_SIZE: usize = 8;
-// format: [dbm as u8] [len as u8] [ssid as storage in [u8; 32]]
-static mut SSID_ARRAY: [[u8; 34]; SSID_ARRAY_SIZE] = [[0; 34]; SSID_ARRAY_SIZE];
-static mut SSID_INDEX: usize = 0;
 static mut SSID_BEST_RSSI: Option<u8> = None;
 
+/// One scan result, deduplicated by BSSID. `sl_wfx_scan_result_callback` updates an entry
+/// in place (keeping whichever beacon had the strongest signal) instead of appending, so a
+/// weaker duplicate can no longer evict a stronger one and a single AP no longer eats
+/// several slots of the table just for beaconing more than once during a scan.
+#[derive(Copy, Clone)]
+struct ScanEntry {
+    bssid: [u8; 6],
+    ssid: [u8; 32],
+    ssid_len: u8,
+    channel: u8,
+    rssi_dbm: u8,
+    // TODO: this snapshot's `wfx_bindings` re-export surface doesn't expose the beacon
+    // capability/auth-mode field (or the raw IE buffer behind it) off
+    // `sl_wfx_scan_result_ind_body_t`, so security is left unpopulated for now rather than
+    // guessed. `ie_parse::parse_scan_ies` is ready to decode that buffer into a
+    // `SecuritySuite`/HT-capability pair once a future binding snapshot exposes it here.
+    security: u16,
+}
+// `wf200_ssid_get_list` still exposes this as [dbm as u8] [len as u8] [ssid as [u8; 32]]
+// over the COM bus, flattened from the richer entries below.
+static mut SCAN_TABLE: [Option<ScanEntry>; SSID_ARRAY_SIZE] = [None; SSID_ARRAY_SIZE];
+
 // event state variables
 pub const WIFI_MTU: usize = 1500;
 
@@ -93,17 +174,66 @@ pub const WIFI_MTU: usize = 1500;
 // If the packet changes, then the read length reported to the SOC could change
 // before the read happens. That would be Bad.
 
-static mut PACKET_BUF: PktBuf = PktBuf {
-    ptr_storage: [None; MAX_PKTS],
-    enqueue_index: None,
-    dequeue_index: None,
-    was_polled: false,
-    was_init: false,
-};
+static mut PACKET_BUF: PktBuf = PktBuf::new();
 
 static mut PACKETS_DROPPED: u32 = 0;
 static mut DROPPED_UPDATED: bool = false;
 
+/// Cumulative TX/RX counters for `ComState::WLAN_GET_STATS`. Distinct from the
+/// hardware-reported `WfxCounters` further down this file: those reflect what the WF200
+/// PHY itself saw, while this reflects what the EC's own send/receive paths moved --
+/// useful for spotting a COM-bus-side bottleneck (e.g. rx_drops climbing while rx_packets
+/// stalls) that the radio-level counters wouldn't show.
+#[derive(Copy, Clone, Default)]
+pub struct NetStats {
+    pub tx_packets: u64,
+    pub tx_bytes: u64,
+    pub rx_packets: u64,
+    pub rx_bytes: u64,
+    pub tx_errors: u64,
+    pub rx_drops: u64,
+    pub link_up_transitions: u64,
+}
+static mut NET_STATS: NetStats = NetStats {
+    tx_packets: 0,
+    tx_bytes: 0,
+    rx_packets: 0,
+    rx_bytes: 0,
+    tx_errors: 0,
+    rx_drops: 0,
+    link_up_transitions: 0,
+};
+
+fn net_stats_note_tx(bytes: usize) {
+    unsafe {
+        NET_STATS.tx_packets += 1;
+        NET_STATS.tx_bytes += bytes as u64;
+    }
+}
+fn net_stats_note_tx_error() {
+    unsafe { NET_STATS.tx_errors += 1 };
+}
+fn net_stats_note_rx(bytes: usize) {
+    unsafe {
+        NET_STATS.rx_packets += 1;
+        NET_STATS.rx_bytes += bytes as u64;
+    }
+}
+
+/// Snapshot every counter. A 64-bit counter can't be read atomically word-by-word against
+/// the WF200 RX callback updating it mid-read, so the caller (`ComState::WLAN_GET_STATS`)
+/// is expected to bracket this with `wifi::wf200_irq_disable()`/`wf200_irq_enable()`, the
+/// same mechanism `ComState::FLASH_LOCK`/`FLASH_UNLOCK` already use, so the host never
+/// observes a torn value.
+pub fn net_stats_snapshot() -> NetStats {
+    unsafe { NET_STATS }
+}
+
+/// Zero every counter (`ComState::WLAN_RESET_STATS`).
+pub fn net_stats_reset() {
+    unsafe { NET_STATS = NetStats::default() };
+}
+
 pub fn init_pkt_buf() {
     unsafe {
         PACKET_BUF.init();
@@ -114,6 +244,7 @@ pub fn drop_packet() {
     unsafe {
         PACKETS_DROPPED += 1;
         DROPPED_UPDATED = true;
+        NET_STATS.rx_drops += 1;
     }
 }
 pub fn get_packets_dropped() -> u32 {
@@ -133,6 +264,75 @@ pub fn poll_new_dropped() -> bool {
     }
 }
 
+// TX flow control: track frames handed to sl_wfx_send_ethernet_frame that haven't yet
+// been confirmed by a SL_WFX_SEND_FRAME_CNF_ID event, and refuse new sends once too many
+// are in flight, mirroring the netdev stop/wake-queue pattern. This only throttles our
+// own send_net_packet() callers (TxBusy/TxResume signalling to the SOC); it does not yet
+// queue the refused frame anywhere, so callers still need to retry it themselves.
+const TX_HIGH_WATER: u32 = 4;
+const TX_LOW_WATER: u32 = 1;
+static mut TX_INFLIGHT: u32 = 0;
+static mut TX_BUSY: bool = false;
+
+/// LEDBAT-style congestion control (see `net::ledbat`) layered on top of the fixed-count
+/// flow control above: it paces bulk sends by the delay between handing a frame to
+/// `sl_wfx_send_ethernet_frame` and its `SL_WFX_SEND_FRAME_CNF_ID` confirmation, which here
+/// stands in for LEDBAT's "one-way delay" -- there's no peer on the other end of this bridge
+/// to echo back a real end-to-end timestamp, but queuing inside the WF200's own TX path is
+/// exactly the kind of self-inflicted latency LEDBAT exists to back off from.
+static mut TX_LEDBAT: net::ledbat::LedbatController = net::ledbat::LedbatController::new(WIFI_MTU as u32);
+static mut TX_INFLIGHT_BYTES: u32 = 0;
+// Timestamps of frames handed to the radio but not yet confirmed, FIFO (confirmations come
+// back in send order), sized to the same high-water mark as TX_INFLIGHT.
+const TX_TIMESTAMP_QUEUE_LEN: usize = TX_HIGH_WATER as usize;
+static mut TX_SEND_LOG: [Option<(u32, u32)>; TX_TIMESTAMP_QUEUE_LEN] = [None; TX_TIMESTAMP_QUEUE_LEN];
+static mut TX_TIMESTAMP_HEAD: usize = 0;
+static mut TX_TIMESTAMP_TAIL: usize = 0;
+
+fn tx_note_sent(bytes: u32) {
+    unsafe {
+        TX_INFLIGHT += 1;
+        if TX_INFLIGHT >= TX_HIGH_WATER {
+            TX_BUSY = true;
+        }
+        TX_INFLIGHT_BYTES = TX_INFLIGHT_BYTES.saturating_add(bytes);
+        TX_SEND_LOG[TX_TIMESTAMP_TAIL] = Some((get_time_ms(), bytes));
+        TX_TIMESTAMP_TAIL = (TX_TIMESTAMP_TAIL + 1) % TX_TIMESTAMP_QUEUE_LEN;
+    }
+    pm_note_activity();
+}
+
+/// `SL_WFX_SEND_FRAME_CNF_ID` doesn't carry the byte count of the frame it's confirming, so
+/// this recovers it (and the matching send timestamp) from `TX_SEND_LOG` instead -- frames
+/// confirm in the order they were sent, so a plain FIFO is enough to pair them back up.
+fn tx_note_confirmed() {
+    unsafe {
+        TX_INFLIGHT = TX_INFLIGHT.saturating_sub(1);
+        if TX_INFLIGHT <= TX_LOW_WATER {
+            TX_BUSY = false;
+        }
+        if let Some((sent_ms, bytes)) = TX_SEND_LOG[TX_TIMESTAMP_HEAD].take() {
+            TX_TIMESTAMP_HEAD = (TX_TIMESTAMP_HEAD + 1) % TX_TIMESTAMP_QUEUE_LEN;
+            TX_INFLIGHT_BYTES = TX_INFLIGHT_BYTES.saturating_sub(bytes);
+            let delay_ms = get_time_ms().saturating_sub(sent_ms);
+            TX_LEDBAT.on_ack(get_time_ms(), delay_ms, bytes);
+        }
+    }
+}
+
+/// Export an API for the main event loop / COM bus to report TX backpressure to the SOC
+pub fn tx_busy() -> bool {
+    unsafe { TX_BUSY || TX_INFLIGHT_BYTES >= TX_LEDBAT.cwnd() }
+}
+
+/// Set for the duration of `sl_wfx_host_wait_for_confirmation()`, so `sl_wfx_host_sleep_grant`
+/// can refuse sleep while a command/response exchange with the chip is in progress.
+static mut CONFIRMATION_PENDING: bool = false;
+
+fn confirmation_pending() -> bool {
+    unsafe { CONFIRMATION_PENDING }
+}
+
 pub fn peek_get_packet() -> Option<&'static [u8]> {
     unsafe { PACKET_BUF.peek_dequeue_slice() }
 }
@@ -224,7 +424,9 @@ pub fn com_ipv4_config() -> Ipv4Conf {
             Some(ip) => ip.to_be_bytes(),
             None => [0, 0, 0, 0],
         },
-        gtwy: match unsafe { NET_STATE.dhcp.gateway } {
+        // Prefer a classless static route's default route (option 121) over the plain
+        // gateway (option 3) per RFC 3442; see `DhcpClient::effective_gateway`.
+        gtwy: match unsafe { NET_STATE.dhcp.effective_gateway() } {
             Some(gw) => gw.to_be_bytes(),
             None => [0, 0, 0, 0],
         },
@@ -232,11 +434,17 @@ pub fn com_ipv4_config() -> Ipv4Conf {
             Some(mask) => mask.to_be_bytes(),
             None => [0, 0, 0, 0],
         },
-        dns1: match unsafe { NET_STATE.dhcp.dns } {
+        // Ipv4Conf only has room for two resolvers on the wire (it's defined in the
+        // external com_rs crate shared with the SoC side); DhcpClient keeps a third
+        // fallback (see DNS_SERVER_COUNT) for EC-side use, but it isn't reported here.
+        dns1: match unsafe { NET_STATE.dhcp.dns_servers[0] } {
             Some(dns) => dns.to_be_bytes(),
             None => [0, 0, 0, 0],
         },
-        dns2: [0; 4],
+        dns2: match unsafe { NET_STATE.dhcp.dns_servers[1] } {
+            Some(dns2) => dns2.to_be_bytes(),
+            None => [0, 0, 0, 0],
+        },
     }
 }
 
@@ -249,6 +457,15 @@ fn log_hex(s: &[u8]) {
 }
 
 pub fn send_net_packet(pkt: &mut [u8]) -> Result<(), ()> {
+    if tx_busy() {
+        // Too many frames already in flight -- refuse this one instead of letting the
+        // radio silently drop it. The caller is expected to hold the frame and retry
+        // once tx_busy() clears.
+        return Err(());
+    }
+    if pkt.len() > PBUF_HEADER_SIZE {
+        pcap::capture_frame(&pkt[PBUF_HEADER_SIZE..]);
+    }
     unsafe {
         // Convert the byte buffer to a struct pointer for the sl_wfx API
         let frame_req_ptr: *mut sl_wfx_send_frame_req_t =
@@ -261,9 +478,14 @@ pub fn send_net_packet(pkt: &mut [u8]) -> Result<(), ()> {
             0,
         );
         match result {
-            SL_STATUS_OK => Ok(()),
+            SL_STATUS_OK => {
+                tx_note_sent(pkt.len() as u32);
+                net_stats_note_tx(pkt.len());
+                Ok(())
+            }
             e => {
                 loghexln!(LL::Debug, "SendFrameErr ", e);
+                net_stats_note_tx_error();
                 Err(())
             }
         }
@@ -291,6 +513,32 @@ pub fn set_com_net_bridge_enable(enable: bool) {
     unsafe { NET_STATE.set_com_net_bridge_enable(enable) };
 }
 
+/// Pick the MAC address the net stack stamps on outbound frames for the network about to
+/// be joined, per `policy` (`MacAddressPolicy`). Called from `ap_join` before the
+/// `sl_wfx_send_join_command`, so the new address is already in `NET_STATE.mac` by the
+/// time DHCP needs it for its own frames.
+///
+/// `StablePerSsid` hashes `ssid` together with the WF200's own factory-burned MAC as the
+/// per-device secret -- that address is already the one per-device-unique stable value
+/// this code has on hand, so reusing it as hash input avoids needing a new secret
+/// generated and stored somewhere else, while the hash output itself never reveals it.
+pub fn apply_mac_privacy(policy: MacAddressPolicy, ssid: &[u8]) {
+    let mut mac = MacAddress::new_blank();
+    match policy {
+        MacAddressPolicy::Factory => return,
+        MacAddressPolicy::RandomPerJoin => {
+            let entropy0 = unsafe { NET_STATE.prng.next() };
+            let entropy1 = unsafe { NET_STATE.prng.next() };
+            mac.randomize(entropy0, entropy1);
+        }
+        MacAddressPolicy::StablePerSsid => {
+            let factory_mac = unsafe { WIFI_CONTEXT.mac_addr_0.octet };
+            mac.stable_for_ssid(ssid, &factory_mac);
+        }
+    }
+    unsafe { NET_STATE.set_mac(mac.as_bytes()) };
+}
+
 /// Return dBm (positive) of strongest RSSI seen during all previous SSID scans
 pub fn get_best_ssid_scan_rssi() -> Option<u8> {
     unsafe { SSID_BEST_RSSI }
@@ -345,6 +593,139 @@ pub fn arp_stop_offloading() {
     };
 }
 
+// Power-save: how long the link has to sit idle (no TX, no RX) before we ask the radio
+// to start skipping beacons, and how many DTIM periods it's allowed to sleep through
+// once it does.
+const PM_IDLE_WINDOW_MS: u32 = 30_000;
+const PM_LISTEN_INTERVAL_DTIM: u8 = 3;
+static mut POWER_MANAGER: PowerManager = PowerManager::new(PM_IDLE_WINDOW_MS, PM_LISTEN_INTERVAL_DTIM);
+
+fn pm_enter_active() {
+    unsafe { sl_wfx_disable_device_power_save() };
+    arp_stop_offloading();
+    logln!(LL::Debug, "PmActive");
+}
+
+fn pm_enter_power_save(listen_interval: u8) {
+    unsafe { sl_wfx_set_power_mode(sl_wfx_pm_mode_e_WFM_PM_MODE_PS, listen_interval as u16) };
+    unsafe { sl_wfx_enable_device_power_save() };
+    arp_begin_offloading();
+    loghexln!(LL::Debug, "PmPowerSave ", listen_interval as u32);
+}
+
+/// Record TX/RX (or an explicit SOC wake request) so the power-save state machine resets
+/// its idle window, and immediately bring the radio back to active if it was asleep.
+pub fn pm_note_activity() {
+    if unsafe { POWER_MANAGER.note_activity(get_time_ms()) } {
+        pm_enter_active();
+    }
+}
+
+/// Export an API for the main event loop to drive the power-save state machine forward.
+/// Only transitions to power-save once connected and DHCP-bound, per `PowerManager`.
+pub fn pm_poll() {
+    let link_ready =
+        unsafe { CURRENT_STATUS == LinkState::Connected } && dhcp_get_state() == com_rs::DhcpState::Bound;
+    match unsafe { POWER_MANAGER.poll(get_time_ms(), link_ready) } {
+        Some(PmState::Active) => pm_enter_active(),
+        Some(PmState::PowerSave { listen_interval }) => pm_enter_power_save(listen_interval),
+        None => {}
+    }
+}
+
+/// Export the current power-save state/listen interval for COM diagnostics.
+pub fn pm_state() -> (bool, u8) {
+    match unsafe { POWER_MANAGER.state() } {
+        PmState::Active => (false, 0),
+        PmState::PowerSave { listen_interval } => (true, listen_interval),
+    }
+}
+
+// Wake-on-WLAN: match a configurable byte pattern against every received frame, the same
+// way `sl_wfx_host_received_frame_callback` already inspects frame bytes for ARP/ICMP/
+// COM-bus routing. This tree's `wfx_bindings` subset doesn't expose a WF200 hardware
+// pattern-match filter to program instead, so the match happens here in software against
+// frames the radio already woke up (per its DTIM listen interval) to deliver -- which is
+// also why that listen interval has to stay short enough that a wake frame survives to be
+// checked at all.
+const WAKE_PATTERN_MAX_LEN: usize = 16;
+struct WakeFilter {
+    pattern: [u8; WAKE_PATTERN_MAX_LEN],
+    mask: [u8; WAKE_PATTERN_MAX_LEN],
+    len: usize,
+    offset: usize,
+}
+static mut WAKE_FILTER: Option<WakeFilter> = None;
+static mut WAKE_PACKET_PENDING: bool = false;
+
+/// Configure the byte pattern that marks an inbound frame as a wake event while the SoC is
+/// off; `mask` is ANDed into both `pattern` and the candidate frame bytes before comparing,
+/// so callers can wildcard out fields (sequence numbers, TTLs, ...) that vary between
+/// otherwise-identical wake frames. Clears the filter if `pattern` is empty, longer than
+/// [`WAKE_PATTERN_MAX_LEN`], or `mask` isn't the same length.
+pub fn set_wakeup_filter(pattern: &[u8], mask: &[u8], offset: usize) {
+    if pattern.is_empty() || pattern.len() > WAKE_PATTERN_MAX_LEN || pattern.len() != mask.len() {
+        unsafe { WAKE_FILTER = None };
+        return;
+    }
+    let mut p = [0u8; WAKE_PATTERN_MAX_LEN];
+    let mut m = [0u8; WAKE_PATTERN_MAX_LEN];
+    p[..pattern.len()].copy_from_slice(pattern);
+    m[..mask.len()].copy_from_slice(mask);
+    unsafe {
+        WAKE_FILTER = Some(WakeFilter { pattern: p, mask: m, len: pattern.len(), offset });
+    }
+}
+
+fn wake_filter_matches(data: &[u8]) -> bool {
+    match unsafe { &WAKE_FILTER } {
+        Some(f) => {
+            if data.len() < f.offset + f.len() {
+                return false;
+            }
+            let window = &data[f.offset..f.offset + f.len()];
+            window
+                .iter()
+                .zip(f.pattern[..f.len()].iter())
+                .zip(f.mask[..f.len()].iter())
+                .all(|((b, p), m)| (b & m) == (p & m))
+        }
+        None => false,
+    }
+}
+
+/// Poll for (and clear) a pending wake-on-WLAN match. The main loop's SoC-off branch calls
+/// this the same way `ticktimer_int_handler` polls the keyboard sense lines, driving
+/// `POWER_SOC_ON` on a hit.
+pub fn poll_wake_packet() -> bool {
+    unsafe {
+        let pending = WAKE_PACKET_PENDING;
+        WAKE_PACKET_PENDING = false;
+        pending
+    }
+}
+
+/// Select the power-management policy to run once connected. `pm_poll()`/
+/// `pm_note_activity()` carry out the actual WFX transitions against whichever mode is
+/// currently selected.
+pub fn set_power_management_mode(mode: PowerManagementMode) {
+    unsafe { POWER_MANAGER.set_mode(mode) };
+}
+
+/// Force the WF200 into (or out of) legacy power-save with a specific DTIM listen
+/// interval right now, bypassing `PowerManager`'s idle-window debounce. `pm_poll()` is
+/// meant for the normal "sleep after being idle a while" case; this is for when the SoC
+/// has already gone to sleep and there's no reason to wait out the idle timer before the
+/// radio follows it down.
+pub fn set_ps_mode(enabled: bool, dtim_skip: u8) {
+    unsafe { POWER_MANAGER.set_mode(PowerManagementMode::PowerSave { listen_interval: dtim_skip }) };
+    if enabled {
+        pm_enter_power_save(dtim_skip);
+    } else {
+        pm_enter_active();
+    }
+}
+
 /// Return current state of DHCP state machine.
 /// This is intended as a way for event loop to monitor DHCP handshake progress and detect slowness.
 pub fn dhcp_get_state() -> com_rs::DhcpState {
@@ -356,6 +737,21 @@ pub fn dhcp_get_state_tag() -> &'static str {
     unsafe { NET_STATE.dhcp.get_state_tag() }
 }
 
+/// Seconds until the next DHCP renewal event (T1 while `Bound`, T2 while `Renewing`, lease
+/// expiry while `Rebinding`), for diagnostics. There's no `ComState` verb to read this over
+/// yet -- `com_rs::serdes::Ipv4Conf`'s wire format is fixed by that unvendored external crate
+/// and has no field for it -- so this is ready for whichever COM bus command ends up calling
+/// it, same as `dhcp_get_state`/`dhcp_get_state_tag` are for their own verbs.
+pub fn dhcp_renew_in_s() -> Option<u32> {
+    unsafe { NET_STATE.dhcp.renew_in_s() }
+}
+
+/// Seconds until the current DHCP lease expires outright, for diagnostics; see
+/// `dhcp_renew_in_s` for why this isn't wired to a `ComState` verb yet.
+pub fn dhcp_lease_remaining_s() -> Option<u32> {
+    unsafe { NET_STATE.dhcp.lease_remaining_s() }
+}
+
 /// Check for notification of DHCP state changes to Bound or Halted
 pub fn dhcp_pop_and_ack_change_event() -> Option<dhcp::DhcpEvent> {
     unsafe { NET_STATE.dhcp.pop_and_ack_change_event() }
@@ -378,11 +774,69 @@ pub fn dhcp_reset() -> Result<(), u8> {
     Ok(())
 }
 
+/// Reset DHCP client state machine to start at INIT-REBOOT state, re-requesting `last_ip`
+/// (a previously bound address, e.g. saved across a Wi-Fi power cycle) instead of running a
+/// full DHCPDISCOVER/DHCPOFFER exchange. Falls back to INIT on its own if the server NAKs.
+pub fn dhcp_reboot(last_ip: u32) -> Result<(), u8> {
+    let mut entropy = [0u32; 5];
+    for dst in entropy.iter_mut() {
+        *dst = unsafe { NET_STATE.prng.next() };
+    }
+    unsafe { NET_STATE.dhcp.begin_at_init_reboot(last_ip, entropy) };
+    let hostname = unsafe { NET_STATE.dhcp.hostname.as_str() };
+    match unsafe { NET_STATE.dhcp.xid } {
+        Some(xid) => {
+            logln!(LL::Debug, "DhcpReboot x:{:08X} h:{}", xid, hostname);
+        }
+        _ => return Err(0x01),
+    }
+    Ok(())
+}
+
 /// Inform DHCP state machine that the network link dropped
 pub fn dhcp_handle_link_drop() {
     unsafe { NET_STATE.dhcp.handle_link_drop() };
 }
 
+/// Give back the current DHCP lease, if any, before the event loop powers down Wi-Fi. Safe
+/// to call regardless of DHCP state; only sends a DHCPRELEASE frame when there's actually a
+/// bound lease to hand back. Same PBUF/zero-length-array send dance as `dhcp_do_next`; see
+/// the DANGER comment there.
+pub fn dhcp_release() -> Result<(), u8> {
+    let src_mac: [u8; 6] = unsafe { NET_STATE.mac.clone() };
+    let ip_id: u16 = unsafe { NET_STATE.prng.next() } as u16;
+    unsafe {
+        let data_length: u32 = match NET_STATE.dhcp.release() {
+            PacketNeeded::Release => NET_STATE.dhcp.build_release_frame(
+                &mut PBUF[PBUF_HEADER_SIZE..],
+                &src_mac,
+                ip_id,
+            )?,
+            _ => return Ok(()),
+        };
+        // Convert the byte buffer to a struct pointer for the sl_wfx API
+        let frame_req_ptr: *mut sl_wfx_send_frame_req_t =
+            PBUF.as_mut_ptr() as *mut _ as *mut sl_wfx_send_frame_req_t;
+        // Send the frame
+        let result = sl_wfx_send_ethernet_frame(
+            frame_req_ptr,
+            data_length,
+            sl_wfx_interface_t_SL_WFX_STA_INTERFACE,
+            0,
+        );
+        match result {
+            SL_STATUS_OK => {
+                tx_note_sent(data_length);
+                Ok(())
+            }
+            e => {
+                loghexln!(LL::Debug, "SendFrameErr ", e);
+                Err(0x24)
+            }
+        }
+    }
+}
+
 /// Send a DHCP request
 pub fn dhcp_do_next() -> Result<(), u8> {
     // Make sure the link is active before we try to use it
@@ -419,10 +873,14 @@ pub fn dhcp_do_next() -> Result<(), u8> {
                 )?;
             }
             PacketNeeded::Request => {
+                let request_type = match NET_STATE.dhcp.get_state() {
+                    dhcp::State::Rebooting => dhcp::RequestType::Rebooting,
+                    _ => dhcp::RequestType::Discover,
+                };
                 data_length = NET_STATE.dhcp.build_request_frame(
                     &mut PBUF[PBUF_HEADER_SIZE..],
                     &src_mac,
-                    dhcp::RequestType::Discover,
+                    request_type,
                     ip_id,
                 )?;
             }
@@ -442,6 +900,27 @@ pub fn dhcp_do_next() -> Result<(), u8> {
                     ip_id,
                 )?;
             }
+            PacketNeeded::ArpProbe => {
+                let target_ip = match NET_STATE.dhcp.ip {
+                    Some(ip) => ip,
+                    None => return Ok(()),
+                };
+                data_length = match net::build_arp_probe(
+                    &NET_STATE,
+                    target_ip,
+                    &mut PBUF[PBUF_HEADER_SIZE..],
+                ) {
+                    Some(len) => len as u32,
+                    None => return Ok(()),
+                };
+            }
+            PacketNeeded::Decline => {
+                data_length = NET_STATE.dhcp.build_decline_frame(
+                    &mut PBUF[PBUF_HEADER_SIZE..],
+                    &src_mac,
+                    ip_id,
+                )?;
+            }
             PacketNeeded::None => return Ok(()),
         }
         // Convert the byte buffer to a struct pointer for the sl_wfx API
@@ -455,7 +934,10 @@ pub fn dhcp_do_next() -> Result<(), u8> {
             0,
         );
         match result {
-            SL_STATUS_OK => Ok(()),
+            SL_STATUS_OK => {
+                tx_note_sent(data_length);
+                Ok(())
+            }
             e => {
                 loghexln!(LL::Debug, "SendFrameErr ", e);
                 Err(0x21)
@@ -464,6 +946,65 @@ pub fn dhcp_do_next() -> Result<(), u8> {
     }
 }
 
+/// Join an IPv4 multicast group so this stack starts bridging its frames and answering
+/// IGMPv2 queries on the host's behalf. There's no COM verb wired to this yet -- `com_rs`
+/// (the crate that defines the COM bus command set) isn't vendored in this tree to add
+/// one to -- so this is ready for whichever COM bus command ends up calling it.
+pub fn igmp_join(group: u32) -> bool {
+    unsafe { NET_STATE.igmp_join(group) }
+}
+
+/// Leave a previously joined IPv4 multicast group.
+pub fn igmp_leave(group: u32) {
+    unsafe { NET_STATE.igmp_leave(group) }
+}
+
+/// Send one IGMPv2 Membership Report if a joined group's report delay has elapsed.
+/// Same PBUF/zero-length-array send dance as `dhcp_do_next`; see the DANGER comment there.
+pub fn igmp_do_next() -> Result<(), u8> {
+    if unsafe { CURRENT_STATUS != LinkState::Connected } {
+        return Ok(());
+    }
+    let src_ip: u32 = match unsafe { NET_STATE.dhcp.ip } {
+        Some(ip) => ip,
+        None => return Ok(()), // Nothing to source a report from until we're bound
+    };
+    let group = match unsafe { NET_STATE.igmp.poll_report_due() } {
+        Some(g) => g,
+        None => return Ok(()),
+    };
+    let src_mac: [u8; 6] = unsafe { NET_STATE.mac.clone() };
+    unsafe {
+        let data_length = match net::igmp::build_report_frame(
+            &mut PBUF[PBUF_HEADER_SIZE..],
+            &src_mac,
+            src_ip,
+            group,
+        ) {
+            Some(len) => len as u32,
+            None => return Err(0x22),
+        };
+        let frame_req_ptr: *mut sl_wfx_send_frame_req_t =
+            PBUF.as_mut_ptr() as *mut _ as *mut sl_wfx_send_frame_req_t;
+        let result = sl_wfx_send_ethernet_frame(
+            frame_req_ptr,
+            data_length,
+            sl_wfx_interface_t_SL_WFX_STA_INTERFACE,
+            0,
+        );
+        match result {
+            SL_STATUS_OK => {
+                tx_note_sent(data_length);
+                Ok(())
+            }
+            e => {
+                loghexln!(LL::Debug, "IgmpSendErr ", e);
+                Err(0x23)
+            }
+        }
+    }
+}
+
 /// Note -- PDS spec says max PDS size is 256 bytes, so let's just pin the buffer at that
 /// returns true if send was OK
 pub fn wf200_send_pds(data: [u8; 256], length: u16) -> bool {
@@ -482,17 +1023,46 @@ pub fn wf200_send_pds(data: [u8; 256], length: u16) -> bool {
 
 pub fn wf200_ssid_get_list(ssid_list: &mut [[u8; 34]; SSID_ARRAY_SIZE]) {
     unsafe {
-        for (dst, src) in ssid_list.iter_mut().zip(SSID_ARRAY.iter()) {
-            for (d, s) in (*dst).iter_mut().zip(src.iter()) {
-                *d = *s;
+        for (dst, src) in ssid_list.iter_mut().zip(SCAN_TABLE.iter()) {
+            *dst = [0; 34];
+            if let Some(entry) = src {
+                dst[0] = entry.rssi_dbm;
+                dst[1] = entry.ssid_len;
+                dst[2..2 + entry.ssid.len()].copy_from_slice(&entry.ssid);
             }
         }
-        // clear the array so we don't end up in limit cycles if we happen to have exactly 6 or 7 APs in range
-        SSID_ARRAY = [[0; 34]; SSID_ARRAY_SIZE];
-        SSID_INDEX = 0;
+        // clear the table so we don't end up in limit cycles if we happen to have exactly 6 or 7 APs in range
+        SCAN_TABLE = [None; SSID_ARRAY_SIZE];
     }
 }
 
+/// BSSID/channel/security view of the current scan table, ordered strongest-signal-first
+/// so a UI can render a network picker with signal bars (or a roaming policy can prefer
+/// the strongest AP for a given SSID) without re-sorting. Not yet carried over the COM bus
+/// -- `com_rs`'s wire format is a separate, externally-maintained crate this tree doesn't
+/// vendor, so extending it is out of scope here.
+pub fn wf200_scan_table() -> [Option<([u8; 6], [u8; 32], u8, u8, u8, u16)>; SSID_ARRAY_SIZE] {
+    let mut out = [None; SSID_ARRAY_SIZE];
+    unsafe {
+        for (dst, src) in out.iter_mut().zip(SCAN_TABLE.iter()) {
+            if let Some(entry) = src {
+                *dst = Some((
+                    entry.bssid,
+                    entry.ssid,
+                    entry.channel,
+                    entry.rssi_dbm,
+                    entry.ssid_len,
+                    entry.security,
+                ));
+            }
+        }
+    }
+    // rssi_dbm is field .3; lower == stronger (see sl_wfx_scan_result_callback), and
+    // `None` entries sort to the back by mapping them to the weakest possible key.
+    out.sort_unstable_by_key(|entry| entry.map(|e| e.3).unwrap_or(u8::MAX));
+    out
+}
+
 /// a non-official structure that's baked into the sl_wfx_host.c file, and
 /// is used to pass data between various functions within the driver
 #[repr(C, packed)]
@@ -500,14 +1070,67 @@ pub fn wf200_ssid_get_list(ssid_list: &mut [[u8; 34]; SSID_ARRAY_SIZE]) {
 pub struct host_context {
     pub sl_wfx_firmware_download_progress: u32,
     pub waited_event_id: u8,
-    pub posted_event_id: u8,
 }
 static mut HOST_CONTEXT: host_context = host_context {
     sl_wfx_firmware_download_progress: 0,
     waited_event_id: 0,
-    posted_event_id: 0,
 };
 
+// Bounded event queue: sl_wfx_host_wait_for_confirmation busy-polls for one confirmation
+// id at a time, but a scan/connect/disconnect indication can arrive while we're waiting
+// on something unrelated. Queue every event as it's posted instead of only remembering
+// the single most recent one, so an indication that doesn't match what we're currently
+// waiting for survives to be matched later (or drained by the main loop) rather than
+// being clobbered by whatever event shows up next.
+const WFX_EVENT_QUEUE_SIZE: usize = 4;
+const WFX_EVENT_PAYLOAD_MAX: usize = 512;
+
+#[derive(Copy, Clone)]
+struct QueuedEvent {
+    id: u8,
+    length: u16,
+    payload: [u8; WFX_EVENT_PAYLOAD_MAX],
+}
+
+static mut WFX_EVENT_QUEUE: [Option<QueuedEvent>; WFX_EVENT_QUEUE_SIZE] =
+    [None; WFX_EVENT_QUEUE_SIZE];
+static mut WFX_EVENT_DROPPED: usize = 0;
+
+/// Number of events discarded because the queue was full when they arrived.
+pub unsafe fn event_dropped_count() -> usize {
+    WFX_EVENT_DROPPED
+}
+
+unsafe fn wfx_event_push(id: u8, payload_ptr: *const u8, length: u16) {
+    let len = (length as usize).min(WFX_EVENT_PAYLOAD_MAX);
+    let mut event = QueuedEvent {
+        id,
+        length: len as u16,
+        payload: [0; WFX_EVENT_PAYLOAD_MAX],
+    };
+    for i in 0..len {
+        event.payload[i] = payload_ptr.add(i).read();
+    }
+    for slot in WFX_EVENT_QUEUE.iter_mut() {
+        if slot.is_none() {
+            *slot = Some(event);
+            return;
+        }
+    }
+    WFX_EVENT_DROPPED += 1;
+    loghexln!(LL::Debug, "EvQDrop ", id as u32);
+}
+
+/// Remove and return the first queued event matching `id`, if any.
+unsafe fn wfx_event_take(id: u8) -> Option<QueuedEvent> {
+    for slot in WFX_EVENT_QUEUE.iter_mut() {
+        if matches!(slot, Some(event) if event.id == id) {
+            return slot.take();
+        }
+    }
+    None
+}
+
 trait Empty<T> {
     fn empty() -> T;
 }
@@ -548,6 +1171,13 @@ pub fn wfx_init() -> sl_status_t {
         CURRENT_STATUS = LinkState::Initializing;
         // use this to drive porting of the wfx library
         let status = sl_wfx_init(&mut WIFI_CONTEXT);
+        let digest = firmware_crc32();
+        loghexln!(LL::Debug, "WfxFwCrc32 ", digest);
+        if WFX_FIRMWARE_CRC32_EXPECTED != 0 && digest != WFX_FIRMWARE_CRC32_EXPECTED {
+            loghexln!(LL::Debug, "WfxFwCrc32Mismatch ", digest);
+            CURRENT_STATUS = LinkState::Uninitialized;
+            return SL_STATUS_FAIL;
+        }
         // Copy the MAC address for use by net module so it can remain blissfully unaware of the
         // sl_wfx_* APIs. The mac_addr_0 field the STA MAC address for the WFx station interface.
         // See https://docs.silabs.com/wifi/wf200/rtos/latest/structsl-wfx-context-t
@@ -610,6 +1240,7 @@ pub unsafe extern "C" fn sl_wfx_host_deinit_bus() -> sl_status_t {
 #[export_name = "sl_wfx_host_enable_platform_interrupt"]
 pub unsafe extern "C" fn sl_wfx_host_enable_platform_interrupt() -> sl_status_t {
     // NOP -- we're doing polling for now
+    WFX_STATS.irq_count += 1;
     SL_STATUS_OK
 }
 
@@ -634,16 +1265,19 @@ pub unsafe extern "C" fn sl_wfx_host_reset_chip() -> sl_status_t {
     delay_ms(10);
     wifi_csr.wfo(utra::wifi::WIFI_RESET, 0);
     delay_ms(10);
+    WFX_STATS.reset_count += 1;
 
     // TODO: marshall all these state variables into a single object so we don't lose track of them.
     WFX_ERR_PENDING = false;
 
     // clear "mallocs"
-    WFX_PTR_LIST = [0; WFX_MAX_PTRS];
+    wfx_alloc_reset();
 
     // reset dhcp state and net state
     dhcp_handle_link_drop();
     CURRENT_STATUS = LinkState::Uninitialized;
+    AP_STATUS = ApLinkState::Down;
+    AP_CLIENTS = [None; WFX_AP_MAX_CLIENTS];
     DISCONNECT_PENDING = false;
     DROPPED_UPDATED = false;
     NET_STATE = net::NetState::new();
@@ -652,10 +1286,10 @@ pub unsafe extern "C" fn sl_wfx_host_reset_chip() -> sl_status_t {
     // clear ssid scan state
     SSID_SCAN_UPDATE = false;
     SSID_SCAN_FINISHED = false;
+    SCAN_SUBREQUESTS_PENDING = 0;
     // I think it's OK to keep these "stale" values around because the SSID environment is external to the driver
-    //SSID_INDEX = 0;
     //SSID_BEST_RSSI = None;
-    //SSID_ARRAY = [[0; 34]; SSID_ARRAY_SIZE];
+    //SCAN_TABLE = [None; SSID_ARRAY_SIZE];
 
     // clear the packet buf
     PACKET_BUF.init();
@@ -664,8 +1298,8 @@ pub unsafe extern "C" fn sl_wfx_host_reset_chip() -> sl_status_t {
     HOST_CONTEXT = host_context {
         sl_wfx_firmware_download_progress: 0,
         waited_event_id: 0,
-        posted_event_id: 0,
     };
+    WFX_EVENT_QUEUE = [None; WFX_EVENT_QUEUE_SIZE];
     WIFI_CONTEXT = sl_wfx_context_t {
         event_payload_buffer: [0; 512usize],
         firmware_build: 0,
@@ -691,6 +1325,7 @@ pub unsafe extern "C" fn sl_wfx_host_hold_in_reset() -> sl_status_t {
     CURRENT_STATUS = LinkState::ResetHold;
     SSID_SCAN_UPDATE = false;
     SSID_SCAN_FINISHED = false;
+    SCAN_SUBREQUESTS_PENDING = 0;
     SL_STATUS_OK
 }
 
@@ -729,6 +1364,31 @@ static mut DEEP_DEBUG: bool = false;
 pub fn set_deep_debug(state: bool) {
     unsafe{DEEP_DEBUG = state};
 }
+/// Per-word SPI transfer timeout. A 16-bit transfer on the hardened `wifi` block normally
+/// clears `STATUS_TIP` in microseconds; this only trips if the block itself wedges (WF200
+/// powered off, held in reset, or the SPI clock otherwise stalled), mirroring the deadline
+/// style `sl_wfx_host_wait_for_confirmation` below already uses for the higher-level
+/// confirmation wait.
+const SPI_WORD_TIMEOUT_MS: u32 = 10;
+
+/// Kick off one 16-bit SPI transfer (`TX` must already be loaded) and wait for `STATUS_TIP`
+/// to clear, bounded by `SPI_WORD_TIMEOUT_MS`. Returns `false` if the peripheral never
+/// finished -- callers must bail out to `SL_STATUS_IO_TIMEOUT` rather than trust `RX` or
+/// keep clocking further words into a wedged bus.
+unsafe fn wfx_spi_wait_tip(wifi_csr: &mut CSR<u32>) -> bool {
+    wifi_csr.wfo(utra::wifi::CONTROL_GO, 1);
+    let start = get_time_ms();
+    while wifi_csr.rf(utra::wifi::STATUS_TIP) == 1 {
+        if get_time_ms().saturating_sub(start) > SPI_WORD_TIMEOUT_MS {
+            wifi_csr.wfo(utra::wifi::CONTROL_GO, 0);
+            WFX_STATS.spi_timeouts += 1;
+            return false;
+        }
+    }
+    wifi_csr.wfo(utra::wifi::CONTROL_GO, 0);
+    true
+}
+
 #[doc = " @brief Send data on the SPI bus"]
 #[doc = ""]
 #[doc = " @param type is the type of bus action (see ::sl_wfx_host_bus_transfer_type_t)"]
@@ -736,7 +1396,8 @@ pub fn set_deep_debug(state: bool) {
 #[doc = " @param header_length is the length of the header data"]
 #[doc = " @param buffer is a pointer to the buffer data"]
 #[doc = " @param buffer_length is the length of the buffer data"]
-#[doc = " @returns Returns SL_STATUS_OK if successful, SL_STATUS_FAIL otherwise"]
+#[doc = " @returns Returns SL_STATUS_OK if successful, SL_STATUS_IO_TIMEOUT if the SPI block"]
+#[doc = " wedges mid-transfer (see `wfx_spi_wait_tip`)"]
 #[export_name = "sl_wfx_host_spi_transfer_no_cs_assert"]
 pub unsafe extern "C" fn sl_wfx_host_spi_transfer_no_cs_assert(
     type_: sl_wfx_host_bus_transfer_type_t,
@@ -766,9 +1427,9 @@ pub unsafe extern "C" fn sl_wfx_host_spi_transfer_no_cs_assert(
             header_len_mtu -= 1;
             header_pos += 1;
 
-            wifi_csr.wfo(utra::wifi::CONTROL_GO, 1);
-            while wifi_csr.rf(utra::wifi::STATUS_TIP) == 1 {}
-            wifi_csr.wfo(utra::wifi::CONTROL_GO, 0);
+            if !wfx_spi_wait_tip(&mut wifi_csr) {
+                return SL_STATUS_IO_TIMEOUT;
+            }
         }
         if type_ == sl_wfx_host_bus_transfer_type_t_SL_WFX_BUS_READ {
             let mut buffer_len_mtu = buffer_length / 2;
@@ -777,9 +1438,9 @@ pub unsafe extern "C" fn sl_wfx_host_spi_transfer_no_cs_assert(
             while buffer_len_mtu > 0 {
                 // transmit a dummy word to get the rx data
                 wifi_csr.wo(utra::wifi::TX, 0);
-                wifi_csr.wfo(utra::wifi::CONTROL_GO, 1);
-                while wifi_csr.rf(utra::wifi::STATUS_TIP) == 1 {}
-                wifi_csr.wfo(utra::wifi::CONTROL_GO, 0);
+                if !wfx_spi_wait_tip(&mut wifi_csr) {
+                    return SL_STATUS_IO_TIMEOUT;
+                }
 
                 let word: u16 = wifi_csr.rf(utra::wifi::RX_RX) as u16;
                 if DEEP_DEBUG {
@@ -827,34 +1488,88 @@ pub unsafe extern "C" fn sl_wfx_host_spi_transfer_no_cs_assert(
                 //                buffer_len_mtu -= 1;
                 buffer_pos += 1;
 
-                wifi_csr.wfo(utra::wifi::CONTROL_GO, 1);
-                while wifi_csr.rf(utra::wifi::STATUS_TIP) == 1 {}
-                wifi_csr.wfo(utra::wifi::CONTROL_GO, 0);
+                if !wfx_spi_wait_tip(&mut wifi_csr) {
+                    return SL_STATUS_IO_TIMEOUT;
+                }
             }
         }
         if DEEP_DEBUG && !suppress {
             logln!(LL::Debug, "");
         }
     }
+    WFX_STATS.tx_bytes += header_length as u32;
+    if type_ == sl_wfx_host_bus_transfer_type_t_SL_WFX_BUS_READ {
+        WFX_STATS.rx_frames += 1;
+        WFX_STATS.rx_bytes += buffer_length as u32;
+    } else {
+        WFX_STATS.tx_frames += 1;
+        WFX_STATS.tx_bytes += buffer_length as u32;
+    }
     SL_STATUS_OK
 }
 
-// crappy alloc constants
-static mut WFX_RAM_ALLOC: usize = WFX_RAM_OFFSET;
-pub const WFX_MAX_PTRS: usize = 4;
-static mut WFX_PTR_LIST: [usize; WFX_MAX_PTRS] = [0; WFX_MAX_PTRS];
-pub const WFX_ALLOC_MAXLEN: usize = WFX_RAM_LENGTH / WFX_MAX_PTRS;
+// Free-list heap over [WFX_RAM_OFFSET, WFX_RAM_OFFSET+WFX_RAM_LENGTH). Each block is
+// prefixed with an inline BlockHeader written directly into the region; allocate does
+// first-fit + split, free flips the block's `free` flag and coalesces with whichever
+// physically-adjacent neighbor(s) are also free. This replaces the old fixed 4-bin
+// carve-up, which capped concurrent buffers at 4 and rejected anything over
+// WFX_RAM_LENGTH/4 outright.
+const WFX_ALLOC_ALIGN: usize = 2; // SPI path moves data as u16, so keep payloads aligned
+const WFX_HEAP_END: usize = WFX_RAM_OFFSET + WFX_RAM_LENGTH;
+
+#[repr(C)]
+struct BlockHeader {
+    size: usize, // payload size in bytes, not including this header
+    free: bool,
+}
+const WFX_HEADER_LEN: usize = core::mem::size_of::<BlockHeader>();
+
 static mut WFX_OVERSIZE_COUNT: usize = 0;
 static mut WFX_ALLOC_FAILS: usize = 0;
 
+unsafe fn wfx_header(addr: usize) -> *mut BlockHeader {
+    addr as *mut BlockHeader
+}
+fn wfx_align_up(n: usize) -> usize {
+    (n + (WFX_ALLOC_ALIGN - 1)) & !(WFX_ALLOC_ALIGN - 1)
+}
+
+/// Reset the heap to a single free block spanning the whole WFX RAM region.
+unsafe fn wfx_alloc_reset() {
+    let hdr = wfx_header(WFX_RAM_OFFSET);
+    (*hdr).size = WFX_RAM_LENGTH - WFX_HEADER_LEN;
+    (*hdr).free = true;
+    WFX_OVERSIZE_COUNT = 0;
+    WFX_ALLOC_FAILS = 0;
+}
+
+/// Total free bytes across the heap, summing every free block.
 pub unsafe fn alloc_free_count() -> usize {
-    let mut count = 0;
-    for ptr in WFX_PTR_LIST {
-        if ptr == 0 {
-            count += 1;
+    let mut addr = WFX_RAM_OFFSET;
+    let mut free_bytes = 0;
+    while addr < WFX_HEAP_END {
+        let hdr = wfx_header(addr);
+        if (*hdr).free {
+            free_bytes += (*hdr).size;
+        }
+        addr += WFX_HEADER_LEN + (*hdr).size;
+    }
+    free_bytes
+}
+/// Size of the largest free block, i.e. the biggest single allocation that could
+/// currently succeed -- useful for spotting fragmentation that `alloc_free_count` alone
+/// would hide.
+pub unsafe fn alloc_largest_free_block() -> usize {
+    let mut addr = WFX_RAM_OFFSET;
+    let mut largest = 0;
+    while addr < WFX_HEAP_END {
+        let hdr = wfx_header(addr);
+        if (*hdr).free && (*hdr).size > largest {
+            largest = (*hdr).size;
         }
+        addr += WFX_HEADER_LEN + (*hdr).size;
     }
-    count
+    largest
 }
 pub unsafe fn alloc_oversize_count() -> usize { WFX_OVERSIZE_COUNT }
 pub unsafe fn alloc_fail_count() -> usize { WFX_ALLOC_FAILS }
@@ -873,33 +1588,43 @@ pub unsafe extern "C" fn sl_wfx_host_allocate_buffer(
     _type_: sl_wfx_buffer_type_t,
     buffer_size: u32,
 ) -> sl_status_t {
-    if buffer_size as usize > WFX_ALLOC_MAXLEN {
+    let requested = wfx_align_up(buffer_size as usize);
+    if requested > WFX_RAM_LENGTH - WFX_HEADER_LEN {
         logln!(
             LL::Error,
-            "Alloc {} larger than max of {}!",
+            "Alloc {} larger than heap of {}!",
             buffer_size,
-            WFX_ALLOC_MAXLEN
+            WFX_RAM_LENGTH
         );
         WFX_OVERSIZE_COUNT += 1;
         return SL_STATUS_ALLOCATION_FAILED;
     }
 
-    // find the first "0" entry in the pointer list
-    let mut i = 0;
-    while (WFX_PTR_LIST[i] != 0) && (i < WFX_MAX_PTRS as usize) {
-        i += 1;
-    }
-    if i == WFX_MAX_PTRS {
-        WFX_ALLOC_FAILS += 1;
-        logln!(LL::Debug, "AllocFailNoPtr");
-        return SL_STATUS_ALLOCATION_FAILED;
+    let mut addr = WFX_RAM_OFFSET;
+    while addr < WFX_HEAP_END {
+        let hdr = wfx_header(addr);
+        let block_size = (*hdr).size;
+        if (*hdr).free && block_size >= requested {
+            // Split off the remainder if there's enough left for another header plus at
+            // least one aligned payload byte; otherwise hand out the whole block rather
+            // than leave an unusable sliver behind.
+            if block_size >= requested + WFX_HEADER_LEN + WFX_ALLOC_ALIGN {
+                let remainder_addr = addr + WFX_HEADER_LEN + requested;
+                let remainder_hdr = wfx_header(remainder_addr);
+                (*remainder_hdr).size = block_size - requested - WFX_HEADER_LEN;
+                (*remainder_hdr).free = true;
+                (*hdr).size = requested;
+            }
+            (*hdr).free = false;
+            *buffer = (addr + WFX_HEADER_LEN) as *mut c_types::c_void;
+            logln!(LL::Trace, "Alloc {}", buffer_size);
+            return SL_STATUS_OK;
+        }
+        addr += WFX_HEADER_LEN + block_size;
     }
-    WFX_PTR_LIST[i] = WFX_RAM_ALLOC + i * WFX_ALLOC_MAXLEN;
-    *buffer = WFX_PTR_LIST[i] as *mut c_types::c_void;
-
-    logln!(LL::Trace, "Alloc [{}]:{}", i, buffer_size);
-
-    SL_STATUS_OK
+    WFX_ALLOC_FAILS += 1;
+    logln!(LL::Debug, "AllocFailNoSpace");
+    SL_STATUS_ALLOCATION_FAILED
 }
 
 #[doc = " @brief Called when the driver wants to free memory"]
@@ -912,17 +1637,39 @@ pub unsafe extern "C" fn sl_wfx_host_free_buffer(
     buffer: *mut c_types::c_void,
     _type_: sl_wfx_buffer_type_t,
 ) -> sl_status_t {
-    let mut i = 0;
-    let addr: usize = (buffer as *mut c_types::c_uint) as usize;
-    while (WFX_PTR_LIST[i] != addr) && (i < WFX_MAX_PTRS as usize) {
-        i = i + 1;
-    }
-    if i == WFX_MAX_PTRS {
+    let payload_addr = buffer as usize;
+    if payload_addr < WFX_RAM_OFFSET + WFX_HEADER_LEN || payload_addr >= WFX_HEAP_END {
         logln!(LL::Debug, "FreeFail");
         return SL_STATUS_ALLOCATION_FAILED;
     }
-    logln!(LL::Trace, "DeAlloc [{}]", i);
-    WFX_PTR_LIST[i] = 0;
+    let addr = payload_addr - WFX_HEADER_LEN;
+    let hdr = wfx_header(addr);
+    (*hdr).free = true;
+    logln!(LL::Trace, "DeAlloc");
+
+    // Coalesce forward with the next block if it's also free.
+    let next_addr = addr + WFX_HEADER_LEN + (*hdr).size;
+    if next_addr < WFX_HEAP_END {
+        let next_hdr = wfx_header(next_addr);
+        if (*next_hdr).free {
+            (*hdr).size += WFX_HEADER_LEN + (*next_hdr).size;
+        }
+    }
+
+    // Coalesce backward by walking from the start of the heap to find our predecessor:
+    // blocks don't carry a back-pointer, and the heap is tiny, so a linear scan is cheap.
+    let mut scan_addr = WFX_RAM_OFFSET;
+    while scan_addr < addr {
+        let scan_hdr = wfx_header(scan_addr);
+        let scan_size = (*scan_hdr).size;
+        let scan_next = scan_addr + WFX_HEADER_LEN + scan_size;
+        if scan_next == addr && (*scan_hdr).free {
+            (*scan_hdr).size += WFX_HEADER_LEN + (*hdr).size;
+            break;
+        }
+        scan_addr = scan_next;
+    }
+
     SL_STATUS_OK
 }
 
@@ -930,11 +1677,10 @@ pub unsafe extern "C" fn sl_wfx_host_free_buffer(
 /// also clear all the static muts (e.g. "C globals") that the driver depends upon
 #[export_name = "sl_wfx_host_init"]
 pub unsafe extern "C" fn sl_wfx_host_init() -> sl_status_t {
-    WFX_RAM_ALLOC = WFX_RAM_OFFSET;
-    WFX_PTR_LIST = [0; WFX_MAX_PTRS];
+    wfx_alloc_reset();
     HOST_CONTEXT.sl_wfx_firmware_download_progress = 0;
     //    HOST_CONTEXT.waited_event_id = 0;  // this is apparently side-effected elsewhere
-    HOST_CONTEXT.posted_event_id = 0;
+    WFX_EVENT_QUEUE = [None; WFX_EVENT_QUEUE_SIZE];
     WIFI_CONTEXT = sl_wfx_context_t {
         event_payload_buffer: [0; 512usize],
         firmware_build: 0,
@@ -952,8 +1698,7 @@ pub unsafe extern "C" fn sl_wfx_host_init() -> sl_status_t {
 
 #[export_name = "sl_wfx_host_deinit"]
 pub unsafe extern "C" fn sl_wfx_host_deinit() -> sl_status_t {
-    WFX_RAM_ALLOC = WFX_RAM_OFFSET;
-    WFX_PTR_LIST = [0; WFX_MAX_PTRS];
+    wfx_alloc_reset();
     SL_STATUS_OK
 }
 
@@ -972,6 +1717,7 @@ pub unsafe extern "C" fn sl_wfx_host_wait_for_confirmation(
     timeout_ms: u32,
     event_payload_out: *mut *mut c_types::c_void,
 ) -> sl_status_t {
+    CONFIRMATION_PENDING = true;
     let start_time = get_time_ms();
     while (get_time_ms() - start_time) < timeout_ms {
         let mut control_register: u16 = 0;
@@ -981,19 +1727,22 @@ pub unsafe extern "C" fn sl_wfx_host_wait_for_confirmation(
                 break;
             }
         }
-        if confirmation_id == HOST_CONTEXT.posted_event_id {
-            HOST_CONTEXT.posted_event_id = 0;
+        if let Some(event) = wfx_event_take(confirmation_id) {
             if event_payload_out
                 != (::core::ptr::null::<c_types::c_void> as *mut *mut c_types::c_void)
             {
+                let len = event.length as usize;
+                WIFI_CONTEXT.event_payload_buffer[..len].copy_from_slice(&event.payload[..len]);
                 *event_payload_out =
                     WIFI_CONTEXT.event_payload_buffer.as_ptr() as *mut c_types::c_void;
             }
+            CONFIRMATION_PENDING = false;
             return SL_STATUS_OK;
         } else {
             delay_ms(1);
         }
     }
+    CONFIRMATION_PENDING = false;
     logln!(LL::Debug, "hostWaitTimeout");
     logln!(LL::Debug, "cur {}", get_time_ms());
     logln!(LL::Debug, "sta {}", start_time);
@@ -1039,10 +1788,41 @@ pub unsafe extern "C" fn sl_wfx_host_transmit_frame(
 #[doc = " @note Called once during the driver initialization phase"]
 #[export_name = "sl_wfx_host_get_firmware_size"]
 pub unsafe extern "C" fn sl_wfx_host_get_firmware_size(firmware_size: *mut u32) -> sl_status_t {
+    WFX_FIRMWARE_CRC32 = 0xFFFF_FFFF; // a new download is starting
     *firmware_size = WFX_FIRMWARE_SIZE as u32;
     SL_STATUS_OK
 }
 
+// Firmware integrity: accumulate a CRC32 (IEEE 802.3 polynomial, bitwise since this only
+// runs once per boot over the download path, not a hot loop) over every chunk handed to
+// the driver below, so a corrupted or tampered firmware blob can be refused before the
+// link comes up. See `wfx_init()`, which compares the finished digest against
+// `WFX_FIRMWARE_CRC32_EXPECTED`.
+static mut WFX_FIRMWARE_CRC32: u32 = 0xFFFF_FFFF;
+
+// TODO: burn in the real digest of the vendored WF200 firmware blob here once it's
+// computed as part of the release build. 0 is "not yet configured"; `wfx_init()` treats
+// that as "log the observed digest but don't fail the boot", so this can't brick a build
+// before the real value is known.
+const WFX_FIRMWARE_CRC32_EXPECTED: u32 = 0;
+
+fn crc32_update(mut crc: u32, data: &[u8]) -> u32 {
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xEDB8_8320 & mask);
+        }
+    }
+    crc
+}
+
+/// The firmware download's CRC32, for attestation/logging over the COM bus. Only
+/// meaningful once `wfx_init()` has returned.
+pub fn firmware_crc32() -> u32 {
+    unsafe { WFX_FIRMWARE_CRC32 ^ 0xFFFF_FFFF }
+}
+
 #[doc = " @brief Driver hook to retrieve a firmware chunk"]
 #[doc = ""]
 #[doc = " @param data is a pointer to the firmware data"]
@@ -1055,8 +1835,13 @@ pub unsafe extern "C" fn sl_wfx_host_get_firmware_data(
     data: *mut *const u8,
     data_size: u32,
 ) -> sl_status_t {
-    *data = (WFX_FIRMWARE_OFFSET + HOST_CONTEXT.sl_wfx_firmware_download_progress as usize)
+    let chunk = (WFX_FIRMWARE_OFFSET + HOST_CONTEXT.sl_wfx_firmware_download_progress as usize)
         as *const u8;
+    WFX_FIRMWARE_CRC32 = crc32_update(
+        WFX_FIRMWARE_CRC32,
+        core::slice::from_raw_parts(chunk, data_size as usize),
+    );
+    *data = chunk;
     HOST_CONTEXT.sl_wfx_firmware_download_progress += data_size;
     SL_STATUS_OK
 }
@@ -1078,7 +1863,16 @@ pub unsafe extern "C" fn sl_wfx_host_sleep_grant(
     _address: sl_wfx_register_address_t,
     _length: u32,
 ) -> sl_status_t {
-    SL_STATUS_WIFI_SLEEP_GRANTED
+    // Only let the chip sleep once our own state machine has actually put it in
+    // power-save (i.e. the selected PowerManagementMode permits it and we've been idle
+    // long enough), and only while there's nothing outstanding that a sleeping radio
+    // would stall: a queued/unconfirmed TX frame, or a command/response wait in progress.
+    let (in_power_save, _listen_interval) = pm_state();
+    if in_power_save && !tx_busy() && !confirmation_pending() {
+        SL_STATUS_WIFI_SLEEP_GRANTED
+    } else {
+        SL_STATUS_WIFI_SLEEP_NOT_GRANTED
+    }
 }
 
 #[doc = " @brief Called once the WFx chip is waking up"]
@@ -1171,10 +1965,11 @@ fn sl_wfx_connect_callback(_mac: [u8; 6usize], status: u32) {
             unsafe {
                 NET_STATE.filter_stats.reset();
                 WIFI_CONTEXT.state |= sl_wfx_state_t_SL_WFX_STA_INTERFACE_CONNECTED;
-                // TODO: configure power saving features
-                //sl_wfx_set_power_mode(sl_wfx_pm_mode_e_WFM_PM_MODE_PS, 0);
-                //sl_wfx_enable_device_power_save();
+                NET_STATS.link_up_transitions += 1;
             }
+            set_power_management_mode(PowerManagementMode::PowerSave {
+                listen_interval: PM_LISTEN_INTERVAL_DTIM,
+            });
         }
         sl_wfx_fmac_status_e_WFM_STATUS_NO_MATCHING_AP => {
             unsafe{CONNECT_RESULT = ConnectResult::NoMatchingAp;}
@@ -1218,6 +2013,7 @@ fn sl_wfx_disconnect_callback(_mac: [u8; 6usize], reason: u16) {
 }
 
 fn sl_wfx_host_received_frame_callback(rx_buffer: *const sl_wfx_received_ind_t) {
+    pm_note_activity();
     let body: &sl_wfx_received_ind_body_s;
     unsafe {
         if rx_buffer.is_null() {
@@ -1231,6 +2027,22 @@ fn sl_wfx_host_received_frame_callback(rx_buffer: *const sl_wfx_received_ind_t)
     let length = body.frame_length as usize;
     let data = unsafe { &body.frame.as_slice(length + padding)[padding..] };
 
+    // TODO: once a dedicated AP bridge exists, frames received while `ap_status() ==
+    // ApLinkState::Up` (i.e. from an associated station rather than our own STA uplink)
+    // should be routed there instead of through the STA-side `net::handle_frame()` /
+    // `NET_STATE` path below, which assumes a single uplink association.
+    if ap_status() == ApLinkState::Up {
+        logln!(LL::Debug, "WfxRxFr AP bridge not yet implemented, dropping");
+        return;
+    }
+
+    net_stats_note_rx(data.len());
+    pcap::capture_frame(data);
+
+    if wake_filter_matches(data) {
+        unsafe { WAKE_PACKET_PENDING = true };
+    }
+
     // This will give the EC's DHCP client and packet filter first dibs on the packet
     let filter_bin = net::handle_frame(unsafe { &mut NET_STATE }, data);
 
@@ -1250,81 +2062,439 @@ fn sl_wfx_host_received_frame_callback(rx_buffer: *const sl_wfx_received_ind_t)
                 drop_packet();
             }
         }
+    } else if filter_bin == FilterBin::Arp {
+        // Answer "who has my IP" directly at the link layer instead of waking the host
+        // over the COM bus for it -- see `net::build_arp_reply`. Same DANGER as
+        // `dhcp_do_next`: PBUF is shared scratch for the zero-length-array send API.
+        unsafe {
+            if let Some(len) = net::build_arp_reply(&NET_STATE, data, &mut PBUF[PBUF_HEADER_SIZE..])
+            {
+                let frame_req_ptr: *mut sl_wfx_send_frame_req_t =
+                    PBUF.as_mut_ptr() as *mut _ as *mut sl_wfx_send_frame_req_t;
+                let result = sl_wfx_send_ethernet_frame(
+                    frame_req_ptr,
+                    len as u32,
+                    sl_wfx_interface_t_SL_WFX_STA_INTERFACE,
+                    0,
+                );
+                match result {
+                    SL_STATUS_OK => tx_note_sent(len as u32),
+                    e => loghexln!(LL::Debug, "ArpReplyErr ", e),
+                }
+            }
+        }
+    } else if filter_bin == FilterBin::ArpProbeReply {
+        // A reply for the address an ArpProbing DHCP bind is checking: feed the sender
+        // protocol address (bytes 28..32 of the ARP body, right after the 14-byte MAC
+        // header) to the DHCP client's conflict check instead of forwarding or replying.
+        let sender_ip = u32::from_be_bytes([data[28], data[29], data[30], data[31]]);
+        unsafe {
+            NET_STATE.dhcp.handle_arp_reply(sender_ip);
+        }
+    } else if filter_bin == FilterBin::Icmp {
+        // Echo request addressed to us: answer the ping directly instead of forwarding it
+        // up the COM bus -- see `net::build_icmp_echo_reply`. Same DANGER as
+        // `dhcp_do_next`: PBUF is shared scratch for the zero-length-array send API.
+        unsafe {
+            if let Some(len) =
+                net::build_icmp_echo_reply(&NET_STATE, data, &mut PBUF[PBUF_HEADER_SIZE..])
+            {
+                let frame_req_ptr: *mut sl_wfx_send_frame_req_t =
+                    PBUF.as_mut_ptr() as *mut _ as *mut sl_wfx_send_frame_req_t;
+                let result = sl_wfx_send_ethernet_frame(
+                    frame_req_ptr,
+                    len as u32,
+                    sl_wfx_interface_t_SL_WFX_STA_INTERFACE,
+                    0,
+                );
+                match result {
+                    SL_STATUS_OK => tx_note_sent(len as u32),
+                    e => loghexln!(LL::Debug, "EchoReplyErr ", e),
+                }
+            }
+        }
     }
 }
 
+// SoftAP: the STA-side connect/disconnect path above (sl_wfx_connect_callback /
+// sl_wfx_disconnect_callback) only ever models one outbound association. AP mode
+// additionally needs a small table of associated station MACs, since several clients can
+// be joined to us at once.
+const WFX_AP_MAX_CLIENTS: usize = 8;
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum ApLinkState {
+    Down,
+    Starting,
+    Up,
+    StopPending,
+}
+static mut AP_STATUS: ApLinkState = ApLinkState::Down;
+static mut AP_CLIENTS: [Option<[u8; 6]>; WFX_AP_MAX_CLIENTS] = [None; WFX_AP_MAX_CLIENTS];
+
+/// How long `ApLinkState::Starting` is allowed to sit unresolved before `ap_clock_state_machine`
+/// gives up on it and falls back to `Down` -- guards against a `sl_wfx_start_ap_callback` that
+/// never arrives (e.g. the WF200 wedged mid-bring-up) leaving the link state stuck forever.
+const AP_START_TIMEOUT_MS: u32 = 5000;
+static mut AP_START_DEADLINE_MS: u32 = 0;
+
+pub fn ap_status() -> ApLinkState {
+    unsafe { AP_STATUS }
+}
+
+/// List the MAC addresses of stations currently associated to our SoftAP.
+pub fn ap_client_list() -> ([[u8; 6]; WFX_AP_MAX_CLIENTS], usize) {
+    let mut list = [[0u8; 6]; WFX_AP_MAX_CLIENTS];
+    let mut count = 0;
+    unsafe {
+        for client in AP_CLIENTS.iter().flatten() {
+            list[count] = *client;
+            count += 1;
+        }
+    }
+    (list, count)
+}
+
+/// Start SoftAP mode on `channel`, refusing channels the selected regulatory domain
+/// (see `set_region`) doesn't permit.
+///
+/// NOTE: `sl_wfx_start_ap`'s full argument list (security mode enum, SSID/passphrase
+/// encoding, client isolation, hidden-SSID flag) isn't present in this tree's
+/// `wfx_bindings` snapshot to check against, so this only drives our local state machine
+/// and logs the request. Wire in the real `sl_wfx_start_ap` call here once those bindings
+/// are available; `sl_wfx_start_ap_callback` below is already set up to receive the
+/// resulting SL_WFX_START_AP_IND_ID indication.
+pub fn start_ap(ssid: &str, channel: u8) {
+    if !region().channels.contains(&channel) {
+        loghexln!(LL::Debug, "ApChanDenied ", channel as u32);
+        return;
+    }
+    unsafe {
+        AP_STATUS = ApLinkState::Starting;
+        AP_START_DEADLINE_MS = get_time_ms() + AP_START_TIMEOUT_MS;
+    }
+    logln!(LL::Debug, "ApStart {}", ssid);
+}
+
+/// Stop SoftAP mode; see the `sl_wfx_start_ap` caveat on `start_ap` above.
+pub fn stop_ap() {
+    unsafe { AP_STATUS = ApLinkState::StopPending };
+    logln!(LL::Debug, "ApStop");
+}
+
+/// Clock the SoftAP link-state machine: called periodically from the main loop (see
+/// `wifi::ap_clock_state_machine`), analogous to `dhcp_clock_state_machine`. The real
+/// over-the-air beacon interval isn't driven from here -- see the `sl_wfx_start_ap` caveat on
+/// `start_ap` -- so today the only thing to clock is falling back out of `Starting` if
+/// `sl_wfx_start_ap_callback` never arrives.
+pub fn ap_clock_state_machine() {
+    unsafe {
+        if AP_STATUS == ApLinkState::Starting && get_time_ms() >= AP_START_DEADLINE_MS {
+            logln!(LL::Debug, "ApStartTimeout");
+            AP_STATUS = ApLinkState::Down;
+        }
+    }
+}
+
+fn sl_wfx_start_ap_callback(status: u32) {
+    unsafe {
+        AP_STATUS = if status == sl_wfx_fmac_status_e_WFM_STATUS_SUCCESS {
+            ApLinkState::Up
+        } else {
+            ApLinkState::Down
+        };
+    }
+    loghexln!(LL::Debug, "ApStartCb ", status);
+}
+
+fn sl_wfx_stop_ap_callback() {
+    unsafe {
+        AP_STATUS = ApLinkState::Down;
+        AP_CLIENTS = [None; WFX_AP_MAX_CLIENTS];
+    }
+    logln!(LL::Debug, "ApStopCb");
+}
+
+// `ap_client_list` above is how the host currently learns about these joins/leaves -- by
+// polling it. A push event (the same `ComInterrupts::push_event` pattern `com_bus.rs` uses
+// for `INT_WLAN_CONNECT_EVENT` et al.) would let the host react immediately instead, but
+// needs a dedicated `com_rs::INT_WLAN_AP_CLIENT_EVENT`-style bit; `com_rs` isn't vendored in
+// this tree to add one to, so these callbacks just log and update `AP_CLIENTS` for now.
+fn sl_wfx_client_connected_callback(mac: [u8; 6usize]) {
+    unsafe {
+        for slot in AP_CLIENTS.iter_mut() {
+            if slot.is_none() {
+                *slot = Some(mac);
+                break;
+            }
+        }
+    }
+    logln!(LL::Debug, "ApClientUp");
+}
+
+fn sl_wfx_client_disconnected_callback(mac: [u8; 6usize]) {
+    unsafe {
+        for slot in AP_CLIENTS.iter_mut() {
+            if *slot == Some(mac) {
+                *slot = None;
+                break;
+            }
+        }
+    }
+    logln!(LL::Debug, "ApClientDown");
+}
+
 unsafe fn sl_wfx_scan_result_callback(scan_result: *const sl_wfx_scan_result_ind_body_t) {
     let sr = &*scan_result;
     if sr.ssid_def.ssid_length == 0 || sr.ssid_def.ssid[0] == 0 {
         // Silently ignore scan results for hidden SSIDs since they're of no use to us
         return;
     }
-    let ssid = match str::from_utf8(slice::from_raw_parts(&sr.ssid_def.ssid as *const u8, sr.ssid_def.ssid_length as usize)) {
-        Ok(s) => s,
-        _ => "",
-    };
-    // Debug print the SSID result
-    let dbm = 32768 - ((sr.rcpi - 220) / 2);
-    /*
-    let channel = core::ptr::addr_of!(sr.channel).read_unaligned();
-    log!(LL::Debug, "ssid {:X} -{}", channel, dbm);
-    for i in sr.mac.iter() {
-        loghex!(LL::Debug, " ", *i);
-    }
-    logln!(LL::Debug, " {}", ssid);*/
-    // Update the scan result log
-    if SSID_INDEX >= SSID_ARRAY_SIZE {
-        SSID_INDEX = 0;
-    }
-    let _mac = sr.mac;
-    let dbm = dbm;
+    let ssid_len = sr.ssid_def.ssid_length as u8;
+    let mut ssid = [0u8; 32];
+    ssid[..ssid_len as usize].copy_from_slice(&sr.ssid_def.ssid[..ssid_len as usize]);
+
+    let raw_dbm = 32768 - ((sr.rcpi - 220) / 2);
+    let rssi_dbm = if raw_dbm < 256 { raw_dbm as u8 } else { 255 };
+    let bssid = sr.mac;
+    let channel = sr.channel as u8;
+
     SSID_BEST_RSSI = match SSID_BEST_RSSI {
-        Some(best) if (dbm as u8) < best => Some(dbm as u8),
+        Some(best) if rssi_dbm < best => Some(rssi_dbm),
         Some(best) => Some(best),
-        _ => Some(dbm as u8),
+        _ => Some(rssi_dbm),
     };
-    let _chan = sr.channel as u8;
-    for (dst_ssid, &src_ssid) in SSID_ARRAY[SSID_INDEX][2..]
+
+    let entry = ScanEntry {
+        bssid,
+        ssid,
+        ssid_len,
+        channel,
+        rssi_dbm,
+        security: 0,
+    };
+
+    // Look up by BSSID first: a repeat beacon from an AP we've already seen updates that
+    // entry in place, keeping the stronger of the two RSSIs, rather than consuming another
+    // slot of the table.
+    if let Some(existing) = SCAN_TABLE
         .iter_mut()
-        .zip(ssid.as_bytes().iter())
+        .flatten()
+        .find(|e| e.bssid == bssid)
     {
-        *dst_ssid = src_ssid
+        if rssi_dbm < existing.rssi_dbm {
+            *existing = entry;
+        }
+        SSID_SCAN_UPDATE = true;
+        return;
     }
-    SSID_ARRAY[SSID_INDEX][1] = sr.ssid_def.ssid_length as u8;
-    SSID_ARRAY[SSID_INDEX][0] = if dbm < 256 { dbm as u8 } else { 255 };
-    // This is like `n = (n+1) % m`, but % is slow on the EC's minimal RV32I core
-    SSID_INDEX += 1;
-    if SSID_INDEX >= SSID_ARRAY_SIZE {
-        SSID_INDEX = 0;
+
+    if let Some(slot) = SCAN_TABLE.iter_mut().find(|slot| slot.is_none()) {
+        *slot = Some(entry);
+        SSID_SCAN_UPDATE = true;
+        return;
+    }
+
+    // Table is full: only displace the weakest entry, and only if the new AP is stronger,
+    // so a late, weak duplicate can't evict a strong AP we already found room for.
+    if let Some(weakest) = SCAN_TABLE
+        .iter_mut()
+        .flatten()
+        .max_by_key(|e| e.rssi_dbm)
+    {
+        if rssi_dbm < weakest.rssi_dbm {
+            *weakest = entry;
+            SSID_SCAN_UPDATE = true;
+        }
     }
-    SSID_SCAN_UPDATE = true;
 }
 
-pub fn wfx_start_scan() -> sl_status_t {
-    let result: sl_status_t;
+// A mixed active+passive scan issues two hardware sl_wfx_send_scan_command() calls, one
+// per sub-scan; track how many are still outstanding so sl_wfx_scan_complete_callback only
+// raises SSID_SCAN_FINISHED once the last one confirms.
+static mut SCAN_SUBREQUESTS_PENDING: u8 = 0;
+
+/// Channels to probe actively (broadcast probe-request) vs. listen to passively
+/// (beacon-only, e.g. for regulatory-restricted channels where probing isn't allowed), an
+/// optional SSID filter list for the active probes (lets the host find hidden SSIDs by
+/// name), and the requested per-mode dwell time.
+///
+/// NOTE: this tree's `wfx_bindings` snapshot doesn't expose a dwell-time parameter on
+/// `sl_wfx_send_scan_command` -- on the real driver that's configured separately via the
+/// WFx MIB, which isn't part of this crate's bound API. `active_dwell_ms`/
+/// `passive_dwell_ms` are logged for diagnostics but not yet wired into the hardware call.
+pub struct ScanConfig<'a> {
+    pub active_channels: &'a [u8],
+    pub passive_channels: &'a [u8],
+    pub ssid_filter: &'a [sl_wfx_ssid_def_t],
+    pub active_dwell_ms: u16,
+    pub passive_dwell_ms: u16,
+}
+
+fn wfx_send_scan_subrequest(
+    mode: u16,
+    channels: &[u8],
+    ssid_filter: &[sl_wfx_ssid_def_t],
+) -> sl_status_t {
+    let channel_ptr = if channels.is_empty() {
+        0 as *const u8
+    } else {
+        channels.as_ptr()
+    };
+    let ssid_ptr = if ssid_filter.is_empty() {
+        0 as *const sl_wfx_ssid_def_t
+    } else {
+        ssid_filter.as_ptr()
+    };
     unsafe {
-        SSID_INDEX = 0;
-        for ssid in SSID_ARRAY.iter_mut() {
-            ssid[0] = 0; // set the length field on each entry to 0 as a proxy for clearing the array
-        }
-        result = sl_wfx_send_scan_command(
-            sl_wfx_scan_mode_e_WFM_SCAN_MODE_ACTIVE as u16,
-            0 as *const u8,
-            0,
-            0 as *const sl_wfx_ssid_def_t,
-            0,
+        SCAN_SUBREQUESTS_PENDING += 1;
+        sl_wfx_send_scan_command(
+            mode,
+            channel_ptr,
+            channels.len() as u16,
+            ssid_ptr,
+            ssid_filter.len() as u16,
             0 as *const u8,
             0,
             0 as *const u8,
+        )
+    }
+}
+
+/// Run a scan that can mix actively-probed and passively-listened channels in one logical
+/// request, split into the minimum number of hardware sub-scans: one covering
+/// `active_channels` (+ `ssid_filter`), and a second covering `passive_channels`.
+/// `sl_wfx_scan_complete_callback` only reports `SSID_SCAN_FINISHED` once every sub-scan
+/// this call issued has confirmed.
+pub fn wfx_start_scan_ex(config: &ScanConfig) -> sl_status_t {
+    unsafe {
+        SCAN_TABLE = [None; SSID_ARRAY_SIZE];
+        SCAN_SUBREQUESTS_PENDING = 0;
+    }
+    if config.active_dwell_ms != 0 || config.passive_dwell_ms != 0 {
+        loghexln!(LL::Debug, "ScanDwellActiveMs ", config.active_dwell_ms as u32);
+        loghexln!(LL::Debug, "ScanDwellPassiveMs ", config.passive_dwell_ms as u32);
+    }
+    // An empty channel list means "scan everything"; preserve the old all-active-channels
+    // behavior when the caller hasn't split anything out.
+    let legacy_full_scan = config.active_channels.is_empty() && config.passive_channels.is_empty();
+
+    let mut result = SL_STATUS_OK;
+    if legacy_full_scan || !config.active_channels.is_empty() {
+        result = wfx_send_scan_subrequest(
+            sl_wfx_scan_mode_e_WFM_SCAN_MODE_ACTIVE as u16,
+            config.active_channels,
+            config.ssid_filter,
+        );
+    }
+    if !config.passive_channels.is_empty() {
+        let passive_result = wfx_send_scan_subrequest(
+            sl_wfx_scan_mode_e_WFM_SCAN_MODE_PASSIVE as u16,
+            config.passive_channels,
+            &[],
         );
+        if result == SL_STATUS_OK {
+            result = passive_result;
+        }
+    }
+    result
+}
+
+/// Scan every channel the current regulatory domain permits -- its active set with probe
+/// requests, and its passive-only set (e.g. FCC's receive-only channels 12-13) listen-only,
+/// so a passive-only channel is never actively probed.
+pub fn wfx_start_scan() -> sl_status_t {
+    let region = region();
+    wfx_start_scan_ex(&ScanConfig {
+        active_channels: region.channels,
+        passive_channels: region.passive_channels,
+        ssid_filter: &[],
+        active_dwell_ms: 0,
+        passive_dwell_ms: 0,
+    })
+}
+
+/// Currently-selected regulatory domain, set by `set_region`/`ComState::WLAN_SET_COUNTRY`.
+/// Starts out at `regulatory::DEFAULT_REGION` (the narrowest channel set) so a device that
+/// hasn't been told its country yet can't transmit somewhere it shouldn't.
+static mut REGION: RegDomain = regulatory::DEFAULT_REGION;
+
+/// Select the regulatory domain matching `country`, a two-byte ISO-3166-1 alpha-2 code
+/// (e.g. `*b"US"`). Takes effect on the next scan or join -- it does not itself restart
+/// whatever's already in flight. Returns `false` if `country` isn't in `regulatory`'s table,
+/// in which case the previously-selected region is left in place.
+///
+/// NOTE: beyond gating `scan`/`join`/`start_ap` in software, a real 802.11d deployment would
+/// also push the channel/power restriction down into the WF200 itself as a PDS fragment (see
+/// `wf200_send_pds`). This tree's `bt_wf200_pds::PDS_DATA` shows that PDS here is Silicon
+/// Labs' compact ASCII key/value format, compiled offline by their (unvendored) `pds_compress`
+/// tool from a documented schema this tree doesn't have a copy of -- so there's no key in that
+/// schema to respond with confidence is "the channel mask" rather than something else
+/// entirely. Guessing one would be worse than not sending it, so `set_region` only drives the
+/// software-side gating below until that schema (or a `wfx_bindings` helper for it) is
+/// available here.
+pub fn set_region(country: [u8; 2]) -> bool {
+    match regulatory::lookup(country) {
+        Some(domain) => {
+            unsafe { REGION = domain };
+            true
+        }
+        None => false,
+    }
+}
+
+/// The regulatory domain scans, joins, and SoftAP mode currently honor.
+pub fn region() -> RegDomain {
+    unsafe { REGION }
+}
+
+static mut RF_TEST: rf_test::RfTest = rf_test::RfTest::new();
+
+/// Configure and start an RF certification test (`ComState::RF_TEST_CONFIG`/
+/// `RF_TEST_START`), validated against the current `region()`. Replaces any test already
+/// running. The caller (`wifi::rf_test_start`) is responsible for taking the radio out of
+/// station mode and masking the net bridge first -- this only owns the test config itself.
+///
+/// NOTE: there's no `sl_wfx_*` binding in this tree's `wfx_bindings` snapshot for actually
+/// keying the radio into a test-tone/PN9/packet-burst TX mode (that's normally done through
+/// Silicon Labs' PTA/test-agent API, which isn't part of this crate's bound subset), so this
+/// validates and records the requested config but doesn't yet drive the hardware. Wire the
+/// real call in here once those bindings are available.
+pub fn rf_test_start(
+    channel: u8,
+    power_dbm_q2: i16,
+    mode: RfTestMode,
+) -> Result<RfTestConfig, RfTestError> {
+    let region = region();
+    let result = unsafe {
+        RF_TEST.start(channel, power_dbm_q2, mode, region.channels, region.max_power_dbm)
+    };
+    match result {
+        Ok(config) => loghexln!(LL::Debug, "RfTestStart ch=", config.channel as u32),
+        Err(_) => logln!(LL::Debug, "RfTestStartErr"),
     }
     result
 }
 
+/// Stop the RF test started by `rf_test_start`, if any.
+pub fn rf_test_stop() {
+    unsafe { RF_TEST.stop() };
+    logln!(LL::Debug, "RfTestStop");
+}
+
+/// Currently-applied RF test config, or `None` if no test is running
+/// (`ComState::RF_TEST_STATUS`'s readback, once it exists).
+pub fn rf_test_status() -> Option<RfTestConfig> {
+    unsafe { RF_TEST.status() }
+}
+
 fn sl_wfx_scan_complete_callback(_status: u32) {
     logln!(LL::Debug, "scan complete");
     unsafe {
-        SSID_SCAN_FINISHED = true;
+        SCAN_SUBREQUESTS_PENDING = SCAN_SUBREQUESTS_PENDING.saturating_sub(1);
+        if SCAN_SUBREQUESTS_PENDING == 0 {
+            SSID_SCAN_FINISHED = true;
+        }
     }
 }
 
@@ -1396,6 +2566,24 @@ pub unsafe extern "C" fn sl_wfx_host_post_event(
                 sl_wfx_host_received_frame_callback(ethernet_frame);
             }
         }
+        sl_wfx_indications_ids_e_SL_WFX_START_AP_IND_ID => {
+            let start_ap_ind: *const sl_wfx_start_ap_ind_t =
+                event_payload as *const sl_wfx_start_ap_ind_t;
+            sl_wfx_start_ap_callback((*start_ap_ind).body.status);
+        }
+        sl_wfx_indications_ids_e_SL_WFX_STOP_AP_IND_ID => {
+            sl_wfx_stop_ap_callback();
+        }
+        sl_wfx_indications_ids_e_SL_WFX_AP_CLIENT_CONNECTED_IND_ID => {
+            let client_ind: *const sl_wfx_ap_client_connected_ind_t =
+                event_payload as *const sl_wfx_ap_client_connected_ind_t;
+            sl_wfx_client_connected_callback((*client_ind).body.mac);
+        }
+        sl_wfx_indications_ids_e_SL_WFX_AP_CLIENT_DISCONNECTED_IND_ID => {
+            let client_ind: *const sl_wfx_ap_client_disconnected_ind_t =
+                event_payload as *const sl_wfx_ap_client_disconnected_ind_t;
+            sl_wfx_client_disconnected_callback((*client_ind).body.mac);
+        }
         sl_wfx_indications_ids_e_SL_WFX_SCAN_RESULT_IND_ID => {
             let scan_result: *const sl_wfx_scan_result_ind_t =
                 event_payload as *const sl_wfx_scan_result_ind_t;
@@ -1417,6 +2605,7 @@ pub unsafe extern "C" fn sl_wfx_host_post_event(
                 event_payload as *const sl_wfx_exception_ind_t;
             let reason = core::ptr::addr_of!((*exception_ind).body.reason).read_unaligned();
             loghexln!(LL::Warn, "WfxException ", reason);
+            recovery_note_fault();
         }
         sl_wfx_general_indications_ids_e_SL_WFX_ERROR_IND_ID => {
             let firmware_error: *const sl_wfx_error_ind_t =
@@ -1444,6 +2633,7 @@ pub unsafe extern "C" fn sl_wfx_host_post_event(
                 _ => loghexln!(LL::Debug, "", error),
             }
             WFX_ERR_PENDING = true;
+            recovery_note_fault();
             /*
             let mut cr: u16 = 0;
             let s = sl_wfx_receive_frame(&mut cr);
@@ -1486,8 +2676,15 @@ pub unsafe extern "C" fn sl_wfx_host_post_event(
             // This happens when you set an IP address for ARP offloading
         }
         sl_wfx_confirmations_ids_e_SL_WFX_SEND_FRAME_CNF_ID => {
-            // This happens when a frame gets sent.
-            // TODO: maybe increment a counter of packets sent?
+            // This happens when a frame gets sent. Free up the TX flow control slot it
+            // was holding.
+            tx_note_confirmed();
+        }
+        sl_wfx_confirmations_ids_e_SL_WFX_GET_COUNTERS_CNF_ID => {
+            // TODO: parse the real sl_wfx_get_counters_cnf_t body into WFX_COUNTERS (and
+            // update WFX_COUNTERS_OVERRUN_RISING off of rx_overruns) once this snapshot's
+            // wfx_bindings exposes its field layout -- see wfx_counters_poll() above.
+            logln!(LL::Debug, "WfxCountersCnf (unparsed)");
         }
         0 => {
             // Whatever... I guess this is fine?
@@ -1499,14 +2696,26 @@ pub unsafe extern "C" fn sl_wfx_host_post_event(
         }
     }
 
-    if HOST_CONTEXT.waited_event_id == (*event_payload).header.id {
-        if (*event_payload).header.length < 512usize as u16 {
-            for i in 0..(*event_payload).header.length {
-                WIFI_CONTEXT.event_payload_buffer[i as usize] =
-                    (event_payload as *const u8).add(i as usize).read();
-            }
-            HOST_CONTEXT.posted_event_id = (*event_payload).header.id;
-        }
+    // `sl_wfx_host_wait_for_confirmation` already bounds every wait by `timeout_ms` and
+    // keys its lookup on the confirmation id via `wfx_event_take`, so a late or
+    // mismatched reply can neither corrupt a different pending wait nor hang the
+    // caller forever -- that guarantee dates back to the `WFX_EVENT_QUEUE` rewrite.
+    // What it didn't guard against: indications (connect/disconnect/scan/AP-client/...)
+    // are handled in full above via their own callbacks and are never read back out
+    // with `wfx_event_take`, yet every one of them used to get pushed into the same
+    // fixed-size queue anyway. A burst of indications (a scan producing dozens of
+    // `SCAN_RESULT_IND`s, say) could fill the queue with entries nobody will ever
+    // consume and crowd out the one confirmation a caller is actually waiting for.
+    // Only queue the id the driver is currently waiting on; everything else has
+    // already been fully handled by the match above.
+    if (*event_payload).header.id == HOST_CONTEXT.waited_event_id
+        && (*event_payload).header.length < WFX_EVENT_PAYLOAD_MAX as u16
+    {
+        wfx_event_push(
+            (*event_payload).header.id,
+            event_payload as *const u8,
+            (*event_payload).header.length,
+        );
     }
     SL_STATUS_OK
 }
@@ -1515,3 +2724,115 @@ pub unsafe extern "C" fn sl_wfx_host_post_event(
 pub fn get_status() -> LinkState {
     unsafe { CURRENT_STATUS }
 }
+
+// Auto-recovery from an EXCEPTION_IND/ERROR_IND fault. `com_rs::LinkState` is an external,
+// fixed wire type we can't add a "recovering" variant to (the host still sees
+// LinkState::WFXError for the duration), so this tracks recovery progress in a local enum
+// the host can poll separately via `recovery_state()` to tell "automatically recovering"
+// apart from "gave up, needs host intervention".
+const RECOVERY_MAX_ATTEMPTS: u8 = 4;
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum RecoveryState {
+    Idle,
+    Recovering { attempt: u8 },
+    GaveUp,
+}
+static mut RECOVERY_STATE: RecoveryState = RecoveryState::Idle;
+static mut RECOVERY_PENDING: bool = false;
+static mut RECOVERY_WAS_CONNECTED: bool = false;
+
+pub fn recovery_state() -> RecoveryState {
+    unsafe { RECOVERY_STATE }
+}
+
+/// Called from the EXCEPTION_IND/ERROR_IND handlers below to record that a fault
+/// happened (and whether we were associated when it did), and bump the bounded retry
+/// counter. Once `RECOVERY_MAX_ATTEMPTS` is exceeded this stops arming
+/// `poll_recovery_needed()`, so a chip stuck in a fault loop surfaces `WFXError` to the
+/// host via `poll_wfx_err_pending()` instead of thrashing the SPI bus forever.
+fn recovery_note_fault() {
+    unsafe {
+        RECOVERY_WAS_CONNECTED = CURRENT_STATUS == LinkState::Connected;
+        RECOVERY_STATE = match RECOVERY_STATE {
+            RecoveryState::Recovering { attempt } if attempt + 1 >= RECOVERY_MAX_ATTEMPTS => {
+                RecoveryState::GaveUp
+            }
+            RecoveryState::Recovering { attempt } => RecoveryState::Recovering {
+                attempt: attempt + 1,
+            },
+            _ => RecoveryState::Recovering { attempt: 0 },
+        };
+        RECOVERY_PENDING = RECOVERY_STATE != RecoveryState::GaveUp;
+    }
+}
+
+/// Call once recovery has resulted in a fresh, successful connection, so the retry
+/// counter resets and a later, unrelated fault gets the full retry budget again.
+pub fn recovery_note_success() {
+    unsafe { RECOVERY_STATE = RecoveryState::Idle };
+}
+
+/// One-shot poll for the main loop: `Some(should_rejoin)` means drive a WF200 reset +
+/// firmware reload now, and re-join whatever AP the chip was last associated to if
+/// `should_rejoin` is true. Consumed immediately, so this only fires once per fault
+/// rather than every loop iteration while the recovery attempt is in flight. Returns
+/// `None` once the retry budget above is spent.
+pub fn poll_recovery_needed() -> Option<bool> {
+    unsafe {
+        if RECOVERY_PENDING {
+            RECOVERY_PENDING = false;
+            Some(RECOVERY_WAS_CONNECTED)
+        } else {
+            None
+        }
+    }
+}
+
+/// Periodic WF200 traffic/error counters, parsed from the SL_WFX_GET_COUNTERS_CNF_ID
+/// confirmation. Exposed to the host so an AT command (or COM poll) can report radio
+/// health without needing raw register access.
+#[derive(Copy, Clone, Default)]
+pub struct WfxCounters {
+    pub tx_frames: u32,
+    pub rx_frames: u32,
+    pub rx_overruns: u32,
+    pub rx_crc_errors: u32,
+    pub rx_decrypt_errors: u32,
+    pub plcp_errors: u32,
+    /// Per-rate TX frame counts, slowest to fastest 802.11b/g/n rate index. Only as many
+    /// entries as the counters payload carries get filled; the rest stay 0.
+    pub tx_per_rate: [u32; 8],
+}
+static mut WFX_COUNTERS: WfxCounters = WfxCounters {
+    tx_frames: 0,
+    rx_frames: 0,
+    rx_overruns: 0,
+    rx_crc_errors: 0,
+    rx_decrypt_errors: 0,
+    plcp_errors: 0,
+    tx_per_rate: [0; 8],
+};
+static mut WFX_COUNTERS_OVERRUN_RISING: bool = false;
+
+/// Issue a GET_COUNTERS request. Call this periodically (e.g. from the main event loop,
+/// alongside `pm_poll()`) to refresh the snapshot `wfx_counters_snapshot()` returns.
+///
+/// NOTE: this tree's `wfx_bindings` snapshot doesn't include a `sl_wfx_get_counters()`
+/// wrapper or the `sl_wfx_get_counters_cnf_t` body layout to parse against, so the request
+/// isn't issued yet. Once those bindings are available, send SL_WFX_GET_COUNTERS_REQ_ID
+/// here the same way `wfx_start_scan_ex` sends SL_WFX_START_SCAN_REQ_ID, and
+/// `sl_wfx_confirmations_ids_e_SL_WFX_GET_COUNTERS_CNF_ID` below will parse the response
+/// into `WFX_COUNTERS` and update `WFX_COUNTERS_OVERRUN_RISING`.
+pub fn wfx_counters_poll() {}
+
+/// Snapshot of the last successfully parsed WF200 counters.
+pub fn wfx_counters_snapshot() -> WfxCounters {
+    unsafe { WFX_COUNTERS }
+}
+
+/// True once RX overruns have increased since the previous snapshot, so the host can
+/// notice the EC is dropping frames under load instead of just polling a monotonic counter.
+pub fn wfx_counters_overrun_rising() -> bool {
+    unsafe { WFX_COUNTERS_OVERRUN_RISING }
+}
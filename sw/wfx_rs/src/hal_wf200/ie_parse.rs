@@ -0,0 +1,174 @@
+//! Tagged information-element (IE) walker for 802.11 beacon/probe-response frames, decoding
+//! security suite, PMF capability, and HT capability summary out of the raw IE buffer a scan
+//! result carries alongside its SSID/BSSID/RSSI/channel header fields.
+//!
+//! NOT YET WIRED to a live scan: this snapshot's `wfx_bindings` re-export of
+//! `sl_wfx_scan_result_ind_body_t` doesn't expose the raw beacon/probe-response IE buffer --
+//! only `ssid_def`/`rcpi`/`mac`/`channel`, the same gap noted on `ScanEntry::security` in
+//! `hal_wf200`. `parse_scan_ies` is ready to decode that buffer into `ParsedScanIes` once a
+//! future `wfx_bindings` snapshot adds it to the struct.
+
+/// Security suite decoded from a beacon/probe-response's RSN (tag 48) and WPA1 vendor (tag
+/// 221, Microsoft OUI 00:50:F2 type 1) information elements.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum SecuritySuite {
+    /// Neither an RSN nor a WPA1 vendor IE was present.
+    Open,
+    /// Also returned for WEP networks: distinguishing WEP from Open isn't possible from IEs
+    /// alone -- WEP doesn't advertise a dedicated IE, it's implied by a capability-info bit
+    /// this parser doesn't inspect. Treated the same as `Open` here.
+    Wep,
+    Wpa1,
+    Wpa2,
+    Wpa3,
+    /// RSN IE advertised both a WPA2-PSK and a WPA3-SAE AKM suite, i.e. a WPA2/WPA3
+    /// transition-mode network.
+    Wpa2Wpa3Mixed,
+}
+
+/// 20/40 MHz channel width and short guard interval support, decoded from the first two bytes
+/// (the HT Capabilities Info field) of an HT Capabilities IE (tag 45).
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub struct HtCapabilitySummary {
+    pub channel_width_40: bool,
+    pub short_gi_20: bool,
+    pub short_gi_40: bool,
+}
+
+/// Everything `parse_scan_ies` can recover from a beacon/probe-response IE buffer. BSSID and
+/// RSSI aren't here -- those come from the scan-result indication's own header fields, not its
+/// IEs; the caller combines them with this.
+#[derive(Copy, Clone, Debug)]
+pub struct ParsedScanIes {
+    /// Length of the decoded SSID, 0 for either a hidden network or a buffer with no SSID IE
+    /// at all -- both are valid scan results and surface with whatever BSSID/RSSI the header
+    /// carried, rather than being dropped.
+    pub ssid_len: u8,
+    pub ssid: [u8; 32],
+    /// Primary channel, from the DS Parameter Set IE (tag 3), if present.
+    pub channel: Option<u8>,
+    pub security: SecuritySuite,
+    /// Management Frame Protection Capable bit (RSN capabilities, bit 7), meaningless unless
+    /// `security` is `Wpa2`/`Wpa3`/`Wpa2Wpa3Mixed`.
+    pub pmf_capable: bool,
+    /// `None` if no HT Capabilities IE (tag 45) was present, e.g. a legacy 802.11b/g-only AP.
+    pub ht: Option<HtCapabilitySummary>,
+}
+
+impl Default for ParsedScanIes {
+    fn default() -> Self {
+        ParsedScanIes {
+            ssid_len: 0,
+            ssid: [0u8; 32],
+            channel: None,
+            security: SecuritySuite::Open,
+            pmf_capable: false,
+            ht: None,
+        }
+    }
+}
+
+/// Walk `buf` as a sequence of `[tag:u8][len:u8][len bytes]` IEs, decoding SSID (tag 0), DS
+/// channel (tag 3), RSN (tag 48), WPA1 vendor (tag 221), and HT Capabilities (tag 45).
+/// Stops at the first IE whose claimed length would run past the end of `buf` instead of
+/// reading out of bounds -- a malformed or truncated capture yields whatever was decoded
+/// before the bad IE, never a panic.
+pub fn parse_scan_ies(buf: &[u8]) -> ParsedScanIes {
+    let mut result = ParsedScanIes::default();
+    let mut has_rsn = false;
+    let mut has_wpa2_akm = false;
+    let mut has_wpa3_akm = false;
+    let mut has_wpa1_vendor = false;
+
+    let mut pos = 0;
+    while pos + 2 <= buf.len() {
+        let tag = buf[pos];
+        let len = buf[pos + 1] as usize;
+        let body_start = pos + 2;
+        let body_end = body_start + len;
+        if body_end > buf.len() {
+            break;
+        }
+        let body = &buf[body_start..body_end];
+
+        match tag {
+            0 => {
+                let copy_len = core::cmp::min(len, result.ssid.len());
+                result.ssid[..copy_len].copy_from_slice(&body[..copy_len]);
+                result.ssid_len = copy_len as u8;
+            }
+            3 => {
+                if !body.is_empty() {
+                    result.channel = Some(body[0]);
+                }
+            }
+            45 => {
+                if body.len() >= 2 {
+                    let info = u16::from_le_bytes([body[0], body[1]]);
+                    result.ht = Some(HtCapabilitySummary {
+                        channel_width_40: (info & 0x0002) != 0,
+                        short_gi_20: (info & 0x0020) != 0,
+                        short_gi_40: (info & 0x0040) != 0,
+                    });
+                }
+            }
+            48 => {
+                has_rsn = true;
+                parse_rsn(body, &mut has_wpa2_akm, &mut has_wpa3_akm, &mut result.pmf_capable);
+            }
+            221 => {
+                if body.len() >= 4 && body[0] == 0x00 && body[1] == 0x50 && body[2] == 0xF2 && body[3] == 1 {
+                    has_wpa1_vendor = true;
+                }
+            }
+            _ => {}
+        }
+        pos = body_end;
+    }
+
+    result.security = match (has_rsn, has_wpa3_akm, has_wpa2_akm, has_wpa1_vendor) {
+        (true, true, true, _) => SecuritySuite::Wpa2Wpa3Mixed,
+        (true, true, false, _) => SecuritySuite::Wpa3,
+        (true, false, _, _) => SecuritySuite::Wpa2,
+        (false, _, _, true) => SecuritySuite::Wpa1,
+        _ => SecuritySuite::Open,
+    };
+    result
+}
+
+/// Decode an RSN IE (tag 48) body: `version(2) group_cipher(4) pairwise_count(2)
+/// pairwise_suites(4*n) akm_count(2) akm_suites(4*m) [rsn_capabilities(2)]`. Sets the
+/// WPA2-PSK/WPA3-SAE AKM flags (suite types 2 and 8 under the standard 00-0F-AC OUI) and the
+/// PMF-capable bit out of `rsn_capabilities`, if present. Bails out at the first field that
+/// doesn't fit in `body` instead of indexing past it.
+fn parse_rsn(body: &[u8], has_wpa2_akm: &mut bool, has_wpa3_akm: &mut bool, pmf_capable: &mut bool) {
+    let mut pos = 2 + 4; // skip version + group cipher suite
+    if pos + 2 > body.len() {
+        return;
+    }
+    let pairwise_count = u16::from_le_bytes([body[pos], body[pos + 1]]) as usize;
+    pos += 2 + pairwise_count.saturating_mul(4);
+    if pos + 2 > body.len() {
+        return;
+    }
+    let akm_count = u16::from_le_bytes([body[pos], body[pos + 1]]) as usize;
+    pos += 2;
+
+    for i in 0..akm_count {
+        let start = pos + i * 4;
+        if start + 4 > body.len() {
+            break;
+        }
+        match body[start + 3] {
+            2 => *has_wpa2_akm = true,
+            8 => *has_wpa3_akm = true,
+            _ => {}
+        }
+    }
+    pos += akm_count.saturating_mul(4);
+
+    if pos + 2 <= body.len() {
+        let caps = u16::from_le_bytes([body[pos], body[pos + 1]]);
+        *pmf_capable = (caps & 0x0080) != 0;
+    }
+}
@@ -0,0 +1,136 @@
+//! Fixed-capacity libpcap-format capture ring for WF200 RX/TX frames, gated behind the
+//! `wifi_pcap` feature (alongside `debug_uart`, since this drains over the same crossover UART
+//! channel `debug_uart` wires up). `capture_frame` is the hot-path producer -- called inline from
+//! `send_net_packet` and `sl_wfx_host_received_frame_callback` -- and only ever copies into the
+//! ring, so it can't stall the COM loop even if nothing is listening on the other end of the
+//! UART. `service` is the slow-path consumer: call it once per main-loop pass (the same way
+//! `BtCharger::poll_events` is drained) to actually write queued records out.
+//!
+//! Record format is the classic libpcap file format: a 24-byte global header once, then a
+//! 16-byte per-packet header (ts_sec, ts_usec, incl_len, orig_len) plus up to `PCAP_SNAPLEN`
+//! bytes of frame data per record. Bytes go out raw (not hex-encoded) since libpcap readers
+//! expect the file verbatim -- pipe `wishbone-tool ... -s terminal` output straight into a
+//! `.pcap` file and open it in Wireshark.
+
+use super::debug::CROSSOVER_UART;
+use crate::betrusted_hal::hal_time::get_time_ms;
+
+/// Truncate captured frames to this many bytes. Keeps ring entries small and bounds how long a
+/// single `service` call spends writing one record out over the slow, bit-banged crossover UART.
+pub const PCAP_SNAPLEN: usize = 128;
+
+const PCAP_RING_LEN: usize = 8;
+const PCAP_MAGIC: u32 = 0xa1b2_c3d4;
+const PCAP_VERSION_MAJOR: u16 = 2;
+const PCAP_VERSION_MINOR: u16 = 4;
+/// `network` field of the global header: LINKTYPE_ETHERNET. Frames captured here are already
+/// the plain Ethernet frames `net::handle_frame`/`send_net_packet` work with, not 802.11.
+const PCAP_LINKTYPE_ETHERNET: u32 = 1;
+
+#[derive(Copy, Clone)]
+struct PcapRecord {
+    ts_sec: u32,
+    ts_usec: u32,
+    orig_len: u32,
+    incl_len: usize,
+    data: [u8; PCAP_SNAPLEN],
+}
+
+static mut PCAP_RING: [Option<PcapRecord>; PCAP_RING_LEN] = [None; PCAP_RING_LEN];
+static mut PCAP_HEAD: usize = 0;
+static mut PCAP_TAIL: usize = 0;
+static mut PCAP_DROPPED: u32 = 0;
+static mut PCAP_DROPPED_UPDATED: bool = false;
+static mut PCAP_HEADER_SENT: bool = false;
+
+/// Latch `frame` (truncated to `PCAP_SNAPLEN`) into the ring, timestamped with `get_time_ms`.
+/// If the ring is full, the frame is dropped and `PCAP_DROPPED` is incremented instead of
+/// overwriting an unsent record or blocking -- same tradeoff `drop_packet`/`PACKETS_DROPPED`
+/// make for the COM-bus bridge queue elsewhere in this module.
+#[cfg(feature = "wifi_pcap")]
+pub fn capture_frame(frame: &[u8]) {
+    unsafe {
+        let next = (PCAP_HEAD + 1) % PCAP_RING_LEN;
+        if next == PCAP_TAIL {
+            PCAP_DROPPED += 1;
+            PCAP_DROPPED_UPDATED = true;
+            return;
+        }
+        let incl_len = core::cmp::min(frame.len(), PCAP_SNAPLEN);
+        let mut data = [0u8; PCAP_SNAPLEN];
+        data[..incl_len].copy_from_slice(&frame[..incl_len]);
+        let now_ms = get_time_ms();
+        PCAP_RING[PCAP_HEAD] = Some(PcapRecord {
+            ts_sec: now_ms / 1000,
+            ts_usec: (now_ms % 1000) * 1000,
+            orig_len: frame.len() as u32,
+            incl_len,
+            data,
+        });
+        PCAP_HEAD = next;
+    }
+}
+
+#[cfg(not(feature = "wifi_pcap"))]
+pub fn capture_frame(_frame: &[u8]) {}
+
+/// Cumulative count of frames dropped for ring-full, mirroring `hal_wf200::get_packets_dropped`.
+pub fn get_pcap_dropped() -> u32 {
+    unsafe { PCAP_DROPPED }
+}
+
+/// Edge-triggered: true once per newly observed drop, same convention as
+/// `hal_wf200::poll_new_dropped`.
+pub fn poll_new_pcap_dropped() -> bool {
+    unsafe {
+        if PCAP_DROPPED_UPDATED {
+            PCAP_DROPPED_UPDATED = false;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+/// Send the 24-byte global pcap header exactly once, then drain at most one queued record out
+/// the crossover UART. Bounding each call to one record keeps a single main-loop pass's UART
+/// time roughly constant regardless of how many frames piled up since the last call.
+#[cfg(feature = "wifi_pcap")]
+pub fn service() {
+    unsafe {
+        if !PCAP_HEADER_SENT {
+            let mut header = [0u8; 24];
+            header[0..4].copy_from_slice(&PCAP_MAGIC.to_le_bytes());
+            header[4..6].copy_from_slice(&PCAP_VERSION_MAJOR.to_le_bytes());
+            header[6..8].copy_from_slice(&PCAP_VERSION_MINOR.to_le_bytes());
+            // thiszone (8..12) and sigfigs (12..16) are left at 0
+            header[16..20].copy_from_slice(&(PCAP_SNAPLEN as u32).to_le_bytes());
+            header[20..24].copy_from_slice(&PCAP_LINKTYPE_ETHERNET.to_le_bytes());
+            for b in &header {
+                CROSSOVER_UART.putc(*b);
+            }
+            PCAP_HEADER_SENT = true;
+        }
+
+        if PCAP_HEAD == PCAP_TAIL {
+            return; // ring empty
+        }
+        if let Some(record) = PCAP_RING[PCAP_TAIL].take() {
+            let mut rec_header = [0u8; 16];
+            rec_header[0..4].copy_from_slice(&record.ts_sec.to_le_bytes());
+            rec_header[4..8].copy_from_slice(&record.ts_usec.to_le_bytes());
+            rec_header[8..12].copy_from_slice(&(record.incl_len as u32).to_le_bytes());
+            rec_header[12..16].copy_from_slice(&record.orig_len.to_le_bytes());
+            for b in &rec_header {
+                CROSSOVER_UART.putc(*b);
+            }
+            for b in &record.data[..record.incl_len] {
+                CROSSOVER_UART.putc(*b);
+            }
+        }
+        PCAP_TAIL = (PCAP_TAIL + 1) % PCAP_RING_LEN;
+    }
+}
+
+#[cfg(not(feature = "wifi_pcap"))]
+pub fn service() {}
@@ -0,0 +1,19 @@
+/// How `hal_wf200::apply_mac_privacy` should pick the source MAC address this stack
+/// stamps on outbound Ethernet/ARP frames at join time.
+///
+/// This only affects the software-built frames this crate's `net` stack sends -- the WF200
+/// radio's own over-the-air 802.11 association still uses its factory-burned address; this
+/// tree's `wfx_bindings` subset doesn't expose a `sl_wfx_set_mac_address`-equivalent call to
+/// reprogram that, so randomizing the 802.11-layer address isn't possible here. `Factory`
+/// is the default and leaves the frame-layer address matching the radio's too.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum MacAddressPolicy {
+    /// Use the WF200's factory-burned address unmodified (the behavior before this policy
+    /// existed).
+    Factory,
+    /// Draw a fresh locally-administered address from the net stack's PRNG on every join.
+    RandomPerJoin,
+    /// Derive a locally-administered address from the SSID being joined, stable across
+    /// reassociations to that network.
+    StablePerSsid,
+}
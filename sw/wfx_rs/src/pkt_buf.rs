@@ -18,6 +18,38 @@ pub struct PktPtr {
 /// to the beginning again, rather than have to implement a custom deref
 /// to reclaim it.
 pub const MAX_PKTS: usize = 32;
+
+/// What `get_enqueue_slice` does when there isn't room for the new packet.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum OverflowPolicy {
+    /// Refuse the new packet and leave the backlog untouched. The original behavior.
+    DropNewest,
+    /// Repeatedly `dequeue()` from the head to reclaim space for the new packet, favoring
+    /// the newest arrival over whatever's still waiting to be serviced.
+    DropOldest,
+}
+
+/// Per-ring counters so the host has something to diagnose loss with instead of a silent
+/// `None`, mirroring how Linux usbnet rx queues track `EVENT_RX_MEMORY` pressure.
+#[derive(Copy, Clone, Default, Debug)]
+pub struct PktBufStats {
+    /// Packets successfully enqueued.
+    pub enqueued: u32,
+    /// Packets dropped because the byte ring didn't have `len` contiguous bytes free (and,
+    /// under `DropOldest`, reclaiming the whole backlog still wasn't enough).
+    pub dropped_overflow: u32,
+    /// Packets dropped because every `ptr_storage` descriptor slot was already in use --
+    /// the consumer is behind on packet *count*, not byte volume, so `DropOldest` can't help.
+    pub dropped_no_slot: u32,
+    /// Highest number of simultaneously-occupied `ptr_storage` slots ever observed.
+    pub high_watermark: u16,
+}
+impl PktBufStats {
+    pub const fn new() -> Self {
+        PktBufStats { enqueued: 0, dropped_overflow: 0, dropped_no_slot: 0, high_watermark: 0 }
+    }
+}
+
 pub struct PktBuf {
     pub ptr_storage: [Option<PktPtr>; MAX_PKTS],
     /// index of where to look to figure out the next enqueue location
@@ -26,8 +58,28 @@ pub struct PktBuf {
     pub dequeue_index: Option<usize>,
     pub was_polled: bool,
     pub was_init: bool,
+    pub policy: OverflowPolicy,
+    occupied: usize,
+    stats: PktBufStats,
 }
 impl PktBuf {
+    pub const fn new() -> Self {
+        PktBuf {
+            ptr_storage: [None; MAX_PKTS],
+            enqueue_index: None,
+            dequeue_index: None,
+            was_polled: false,
+            was_init: false,
+            policy: OverflowPolicy::DropNewest,
+            occupied: 0,
+            stats: PktBufStats::new(),
+        }
+    }
+
+    pub fn stats(&self) -> PktBufStats {
+        self.stats
+    }
+
     pub fn init(&mut self) {
         if !self.was_init {
             let rawbuf = unsafe{from_raw_parts_mut(PKT_BUF_BASE as *mut u8, PKT_BUF_LEN)};
@@ -39,9 +91,47 @@ impl PktBuf {
         }
     }
 
+    /// Whether every `ptr_storage` descriptor slot is currently occupied.
+    fn no_free_slot(&self) -> bool {
+        self.ptr_storage.iter().all(|p| p.is_some())
+    }
+
+    /// Whether `len` bytes fit in the ring's current contiguous free space, given where the
+    /// latest enqueue ends and the oldest dequeue starts.
+    fn space_available(&self, len: usize) -> bool {
+        let alloc_end = if let Some(eq_idx) = self.enqueue_index {
+            self.ptr_storage[eq_idx].expect("pktbuf assert A").end
+        } else {
+            0
+        };
+        let alloc_start = if let Some(dq_idx) = self.dequeue_index {
+            self.ptr_storage[dq_idx].expect("pktbuf assert B").start
+        } else {
+            0
+        };
+        len < PKT_BUF_LEN - alloc_end || len < alloc_start
+    }
+
     /// returns a slice that can be used to store packet data
     pub fn get_enqueue_slice(&mut self, len: usize) -> Option<&mut [u8]> {
         self.was_polled = false; // this will trigger another interrupt to the host
+        if self.policy == OverflowPolicy::DropOldest {
+            // reclaim from the head until there's either room for `len`, or nothing left to
+            // reclaim -- an oversized packet that can never fit is still reported as an
+            // overflow drop below, just against an empty ring instead of a full one
+            while !self.space_available(len) && self.dequeue_index.is_some() {
+                self.dequeue();
+            }
+        }
+        if self.no_free_slot() {
+            self.stats.dropped_no_slot += 1;
+            return None;
+        }
+        if !self.space_available(len) {
+            self.stats.dropped_overflow += 1;
+            return None;
+        }
+
         let alloc_end = if let Some(eq_idx) = self.enqueue_index {
             self.ptr_storage[eq_idx].expect("pktbuf assert A").end
         } else {
@@ -56,10 +146,8 @@ impl PktBuf {
             if ptr.is_none() {
                 let newstart = if len < PKT_BUF_LEN - alloc_end {
                     alloc_end
-                } else if len < alloc_start {
-                    0
                 } else {
-                    return None;
+                    0
                 };
                 let newpkt = PktPtr {
                     start: newstart,
@@ -99,6 +187,13 @@ impl PktBuf {
                     logln!(LL::Debug, "first eq/dq entry: {}", idx);
                     self.dequeue_index = Some(idx);
                 }
+
+                self.occupied += 1;
+                self.stats.enqueued += 1;
+                if self.occupied as u16 > self.stats.high_watermark {
+                    self.stats.high_watermark = self.occupied as u16;
+                }
+
                 //return Some(&mut self.rawbuf.borrow_mut()[newpkt.start..newpkt.end])
                 logln!(LL::Debug, "enq idx: {} [{}..{}]", idx, newpkt.start, newpkt.end);
                 return Some(
@@ -163,6 +258,7 @@ impl PktBuf {
                     }
                 }
                 self.ptr_storage[dq_idx] = None;
+                self.occupied -= 1;
                 true
             } else {
                 logln!(LL::Debug, "ASSERT: dequeue points at None entry (dq)");
@@ -195,3 +291,58 @@ impl PktBuf {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // `get_enqueue_slice` always builds its returned slice out of the fixed hardware address
+    // `PKT_BUF_BASE`, which isn't mapped in a host test process. These tests only check
+    // `Option`s and the ring's own bookkeeping (`ptr_storage`, `dequeue_index`, `stats()`) --
+    // never read or write through a slice it returns.
+
+    #[test]
+    fn fills_all_slots_then_reports_no_slot() {
+        let mut buf = PktBuf::new();
+        for _ in 0..MAX_PKTS {
+            assert!(buf.get_enqueue_slice(64).is_some());
+        }
+        assert_eq!(buf.stats().high_watermark, MAX_PKTS as u16);
+        assert!(buf.get_enqueue_slice(64).is_none());
+        assert_eq!(buf.stats().dropped_no_slot, 1);
+    }
+
+    #[test]
+    fn wraps_to_the_front_once_the_tail_is_full_but_the_head_has_drained() {
+        let mut buf = PktBuf::new();
+        assert!(buf.get_enqueue_slice(20_000).is_some()); // [0..20000)
+        assert!(buf.get_enqueue_slice(3_000).is_some());  // [20000..23000), 1576 bytes left at the tail
+        assert!(buf.dequeue()); // frees the first packet; dequeue_index now starts at 20000
+
+        // 5000 bytes don't fit in the 1576 left at the tail, but do fit in the 20000 now
+        // free at the front, so this should wrap instead of failing.
+        assert!(buf.get_enqueue_slice(5_000).is_some());
+        let wrapped = buf.ptr_storage[buf.enqueue_index.unwrap()].unwrap();
+        assert_eq!(wrapped.start, 0);
+        assert_eq!(buf.stats().dropped_overflow, 0);
+    }
+
+    #[test]
+    fn drop_newest_refuses_a_packet_that_does_not_fit() {
+        let mut buf = PktBuf::new();
+        assert!(buf.get_enqueue_slice(PKT_BUF_LEN - 10).is_some());
+        assert!(buf.get_enqueue_slice(PKT_BUF_LEN / 2).is_none());
+        assert_eq!(buf.stats().dropped_overflow, 1);
+    }
+
+    #[test]
+    fn drop_oldest_reclaims_space_instead_of_refusing() {
+        let mut buf = PktBuf::new();
+        buf.policy = OverflowPolicy::DropOldest;
+        assert!(buf.get_enqueue_slice(PKT_BUF_LEN - 10).is_some());
+        // nothing else is queued behind it, so reclaiming this one entry must make room
+        assert!(buf.get_enqueue_slice(PKT_BUF_LEN / 2).is_some());
+        assert_eq!(buf.stats().dropped_overflow, 0);
+        assert_eq!(buf.dequeue_index, buf.enqueue_index);
+    }
+}
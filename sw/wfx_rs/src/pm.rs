@@ -0,0 +1,85 @@
+//! Power-save state machine for the WFX radio.
+//!
+//! This module only decides *when* to transition and what listen interval to request --
+//! it has no knowledge of the `sl_wfx_*` FFI bindings or ARP offloading. `hal_wf200` owns
+//! the single `PowerManager` instance and is responsible for issuing the matching WFX
+//! calls whenever `poll()`/`note_activity()` reports a transition.
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum PmState {
+    Active,
+    PowerSave { listen_interval: u8 },
+}
+
+/// User/SOC-selectable power-management policy, mirroring the three-level knob cyw43's
+/// `Control::set_power_management` exposes. This is a policy choice; `PmState` above is
+/// the live state the machine is actually sitting in at any given moment.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum PowerManagementMode {
+    /// Never sleep; radio stays fully powered regardless of idle time.
+    Active,
+    /// Sleep after the idle window, waking every `listen_interval` DTIM periods.
+    PowerSave { listen_interval: u8 },
+    /// Sleep as soon as the link is ready, regardless of idle time, with the longest
+    /// listen interval the driver allows.
+    UltraLowPower,
+}
+
+pub struct PowerManager {
+    state: PmState,
+    mode: PowerManagementMode,
+    default_idle_window_ms: u32,
+    last_activity_ms: u32,
+}
+impl PowerManager {
+    pub const fn new(idle_window_ms: u32, listen_interval: u8) -> PowerManager {
+        PowerManager {
+            state: PmState::Active,
+            mode: PowerManagementMode::PowerSave { listen_interval },
+            default_idle_window_ms: idle_window_ms,
+            last_activity_ms: 0,
+        }
+    }
+
+    pub fn state(&self) -> PmState {
+        self.state
+    }
+
+    /// Change the power-management policy. Takes effect on the next `poll()`/
+    /// `note_activity()`; does not by itself force a transition.
+    pub fn set_mode(&mut self, mode: PowerManagementMode) {
+        self.mode = mode;
+    }
+
+    /// Any TX, RX, or explicit SOC request should call this. Returns `true` the moment
+    /// this bounces us out of power-save, so the caller can issue the wake-up WFX calls
+    /// immediately rather than waiting for the next `poll()`.
+    pub fn note_activity(&mut self, now_ms: u32) -> bool {
+        self.last_activity_ms = now_ms;
+        let was_in_power_save = self.state != PmState::Active;
+        self.state = PmState::Active;
+        was_in_power_save
+    }
+
+    /// Call periodically from the main event loop with the current time and whether the
+    /// link is up and DHCP-bound. Returns `Some(state)` the moment `state` is newly
+    /// entered, so the caller can issue the matching WFX calls exactly once.
+    pub fn poll(&mut self, now_ms: u32, link_ready: bool) -> Option<PmState> {
+        if !link_ready || self.mode == PowerManagementMode::Active {
+            return self.note_activity(now_ms).then(|| PmState::Active);
+        }
+        let (idle_window_ms, listen_interval) = match self.mode {
+            PowerManagementMode::UltraLowPower => (0, u8::MAX),
+            PowerManagementMode::PowerSave { listen_interval } => {
+                (self.default_idle_window_ms, listen_interval)
+            }
+            PowerManagementMode::Active => (u32::MAX, 0), // unreachable, handled above
+        };
+        if self.state == PmState::Active && now_ms.wrapping_sub(self.last_activity_ms) >= idle_window_ms
+        {
+            self.state = PmState::PowerSave { listen_interval };
+            return Some(self.state);
+        }
+        None
+    }
+}
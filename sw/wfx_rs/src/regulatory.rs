@@ -0,0 +1,66 @@
+//! Regulatory-domain table for `ComState::WLAN_SET_COUNTRY`.
+//!
+//! Maps a two-byte ISO-3166-1 alpha-2 country code (the same key space cyw43's `countries`
+//! module uses) to the channel set the WF200 is allowed to use there. There's no vendor
+//! per-region TX-power calibration data available in this tree -- that comes from Silicon
+//! Labs characterization of a specific board/antenna and isn't something we can fabricate --
+//! so `max_power_dbm` is a conservative worldwide-safe ceiling for every entry rather than a
+//! real per-region limit; treat it as a placeholder until real cal data is wired in.
+
+/// One regulatory domain: the channels it permits to be actively probed/joined/beaconed on,
+/// a disjoint set it only permits passive listening on (no probe requests, no AP beaconing --
+/// `wfx_start_scan`'s passive sub-scan is the only thing that touches these), and a TX-power
+/// ceiling applying to the active set.
+#[derive(Copy, Clone)]
+pub struct RegDomain {
+    pub code: [u8; 2],
+    pub channels: &'static [u8],
+    pub passive_channels: &'static [u8],
+    pub max_power_dbm: i8,
+}
+
+const WORLDWIDE_MAX_POWER_DBM: i8 = 14;
+
+/// Channel tables for the three 2.4GHz regulatory groupings that matter for a 1-14 channel
+/// radio: FCC (1-11 active, plus 12-13 receive-only -- US/Canada permit listening there, just
+/// not transmitting), ETSI (1-13, no passive-only channels beyond that), and Japan (1-14,
+/// channel 14 is 802.11b-only but the WF200 doesn't distinguish that here).
+const CHANNELS_FCC: &[u8] = &[1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11];
+const CHANNELS_FCC_PASSIVE: &[u8] = &[12, 13];
+const CHANNELS_ETSI: &[u8] = &[1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13];
+const CHANNELS_JAPAN: &[u8] = &[1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14];
+
+/// Known country codes, grouped by which of the tables above applies. Nowhere near
+/// exhaustive -- add entries as boards actually ship to new markets.
+const COUNTRIES: &[RegDomain] = &[
+    RegDomain { code: *b"US", channels: CHANNELS_FCC, passive_channels: CHANNELS_FCC_PASSIVE, max_power_dbm: WORLDWIDE_MAX_POWER_DBM },
+    RegDomain { code: *b"CA", channels: CHANNELS_FCC, passive_channels: CHANNELS_FCC_PASSIVE, max_power_dbm: WORLDWIDE_MAX_POWER_DBM },
+    RegDomain { code: *b"MX", channels: CHANNELS_FCC, passive_channels: CHANNELS_FCC_PASSIVE, max_power_dbm: WORLDWIDE_MAX_POWER_DBM },
+    RegDomain { code: *b"GB", channels: CHANNELS_ETSI, passive_channels: &[], max_power_dbm: WORLDWIDE_MAX_POWER_DBM },
+    RegDomain { code: *b"DE", channels: CHANNELS_ETSI, passive_channels: &[], max_power_dbm: WORLDWIDE_MAX_POWER_DBM },
+    RegDomain { code: *b"FR", channels: CHANNELS_ETSI, passive_channels: &[], max_power_dbm: WORLDWIDE_MAX_POWER_DBM },
+    RegDomain { code: *b"IT", channels: CHANNELS_ETSI, passive_channels: &[], max_power_dbm: WORLDWIDE_MAX_POWER_DBM },
+    RegDomain { code: *b"ES", channels: CHANNELS_ETSI, passive_channels: &[], max_power_dbm: WORLDWIDE_MAX_POWER_DBM },
+    RegDomain { code: *b"NL", channels: CHANNELS_ETSI, passive_channels: &[], max_power_dbm: WORLDWIDE_MAX_POWER_DBM },
+    RegDomain { code: *b"SE", channels: CHANNELS_ETSI, passive_channels: &[], max_power_dbm: WORLDWIDE_MAX_POWER_DBM },
+    RegDomain { code: *b"AU", channels: CHANNELS_ETSI, passive_channels: &[], max_power_dbm: WORLDWIDE_MAX_POWER_DBM },
+    RegDomain { code: *b"NZ", channels: CHANNELS_ETSI, passive_channels: &[], max_power_dbm: WORLDWIDE_MAX_POWER_DBM },
+    RegDomain { code: *b"KR", channels: CHANNELS_ETSI, passive_channels: &[], max_power_dbm: WORLDWIDE_MAX_POWER_DBM },
+    RegDomain { code: *b"JP", channels: CHANNELS_JAPAN, passive_channels: &[], max_power_dbm: WORLDWIDE_MAX_POWER_DBM },
+];
+
+/// Fallback used until `set_region`/`WLAN_SET_COUNTRY` picks something: the narrowest
+/// (FCC) channel set, so an unconfigured device never transmits somewhere it shouldn't. This
+/// is also what an unrecognized `set_region` code leaves in place -- the most restrictive
+/// table here, rather than guessing at an unknown country's actual rules.
+pub const DEFAULT_REGION: RegDomain = RegDomain {
+    code: *b"00",
+    channels: CHANNELS_FCC,
+    passive_channels: CHANNELS_FCC_PASSIVE,
+    max_power_dbm: WORLDWIDE_MAX_POWER_DBM,
+};
+
+/// Look up a two-byte country code. `None` if it isn't in `COUNTRIES`.
+pub fn lookup(code: [u8; 2]) -> Option<RegDomain> {
+    COUNTRIES.iter().copied().find(|d| d.code == code)
+}
@@ -0,0 +1,90 @@
+//! RF certification test-mode state machine for `ComState::RF_TEST_CONFIG`/`RF_TEST_START`/
+//! `RF_TEST_STOP`.
+//!
+//! This only tracks *what* test is configured/running and validates it against the selected
+//! regulatory domain (`crate::hal_wf200::regulatory`) -- it has no knowledge of the
+//! `sl_wfx_*` FFI bindings that would actually key the radio into a test-tone/PN9/packet-burst
+//! mode. `hal_wf200` owns the single `RfTest` instance and is responsible for issuing the
+//! matching WFX calls once bindings for them exist; see the NOTE on `hal_wf200::rf_test_start`.
+
+/// Which RF test waveform to emit, matching the options vendor bring-up tools expose for
+/// certification: an unmodulated carrier (for spectrum-mask/power measurements), a
+/// pseudo-random PN9 modulated carrier (for modulation-quality measurements), or a burst of
+/// real 802.11 packets at a fixed PHY rate (for receiver sensitivity / rate-vs-range testing).
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum RfTestMode {
+    UnmodulatedCarrier,
+    ModulatedPn9,
+    PacketBurst { phy_rate_index: u8 },
+}
+
+/// A requested (or currently running) RF test: channel, TX power in dBm*4 (matching the COM
+/// bus's existing fixed-point convention for power elsewhere), and waveform.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct RfTestConfig {
+    pub channel: u8,
+    pub power_dbm_q2: i16,
+    pub mode: RfTestMode,
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum RfTestError {
+    /// `channel` isn't in the selected regulatory domain's allowed set.
+    ChannelNotPermitted,
+    /// `power_dbm_q2` was negative or couldn't be represented; the caller should have
+    /// clamped to the region limit before this point, so this means the raw request itself
+    /// is nonsensical rather than just "too hot for this region".
+    InvalidPower,
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+enum RfTestState {
+    Idle,
+    Running(RfTestConfig),
+}
+
+pub struct RfTest {
+    state: RfTestState,
+}
+
+impl RfTest {
+    pub const fn new() -> RfTest {
+        RfTest { state: RfTestState::Idle }
+    }
+
+    /// Validate and (re)start a test, clamping `power_dbm_q2` to `region_max_power_dbm`
+    /// (plain dBm, as stored in `RegDomain::max_power_dbm`) converted to the same dBm*4
+    /// fixed point `RfTestConfig` uses. Replaces whatever test was previously running.
+    pub fn start(
+        &mut self,
+        channel: u8,
+        power_dbm_q2: i16,
+        mode: RfTestMode,
+        region_channels: &[u8],
+        region_max_power_dbm: i8,
+    ) -> Result<RfTestConfig, RfTestError> {
+        if power_dbm_q2 < 0 {
+            return Err(RfTestError::InvalidPower);
+        }
+        if !region_channels.contains(&channel) {
+            return Err(RfTestError::ChannelNotPermitted);
+        }
+        let ceiling = region_max_power_dbm as i16 * 4;
+        let config = RfTestConfig { channel, power_dbm_q2: power_dbm_q2.min(ceiling), mode };
+        self.state = RfTestState::Running(config);
+        Ok(config)
+    }
+
+    /// Stop whatever test is running; a no-op if nothing was.
+    pub fn stop(&mut self) {
+        self.state = RfTestState::Idle;
+    }
+
+    /// The currently-applied config, or `None` if no test is running.
+    pub fn status(&self) -> Option<RfTestConfig> {
+        match self.state {
+            RfTestState::Running(config) => Some(config),
+            RfTestState::Idle => None,
+        }
+    }
+}
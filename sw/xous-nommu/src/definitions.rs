@@ -47,6 +47,19 @@ pub struct XousMemoryMessage {
     out_buf: Option<MemoryAddress>,
     out_buf_size: Option<MemorySize>,
 }
+#[allow(dead_code)]
+impl XousMemoryMessage {
+    pub fn new(
+        id: MessageId,
+        in_buf: Option<MemoryAddress>,
+        in_buf_size: Option<MemorySize>,
+        out_buf: Option<MemoryAddress>,
+        out_buf_size: Option<MemorySize>,
+    ) -> Self {
+        XousMemoryMessage { id, in_buf, in_buf_size, out_buf, out_buf_size }
+    }
+    pub fn id(&self) -> MessageId { self.id }
+}
 
 #[allow(dead_code)]
 pub struct XousScalarMessage {
@@ -56,6 +69,14 @@ pub struct XousScalarMessage {
     arg3: usize,
     arg4: usize,
 }
+#[allow(dead_code)]
+impl XousScalarMessage {
+    pub fn new(id: MessageId, arg1: usize, arg2: usize, arg3: usize, arg4: usize) -> Self {
+        XousScalarMessage { id, arg1, arg2, arg3, arg4 }
+    }
+    pub fn id(&self) -> MessageId { self.id }
+    pub fn args(&self) -> (usize, usize, usize, usize) { (self.arg1, self.arg2, self.arg3, self.arg4) }
+}
 
 #[allow(dead_code)]
 pub enum XousMessage {
@@ -68,3 +89,11 @@ pub struct XousMessageReceived {
     sender: XousMessageSender,
     message: XousMessage,
 }
+#[allow(dead_code)]
+impl XousMessageReceived {
+    pub fn new(sender: XousMessageSender, message: XousMessage) -> Self {
+        XousMessageReceived { sender, message }
+    }
+    pub fn sender(&self) -> XousMessageSender { self.sender }
+    pub fn message(&self) -> &XousMessage { &self.message }
+}
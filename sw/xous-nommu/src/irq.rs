@@ -4,6 +4,16 @@ use vexriscv::register::{mstatus, vmim};
 
 static mut IRQ_HANDLERS: [Option<fn(usize)>; 32] = filled_array![None; 32];
 
+/// Per-source priority, higher serviced first. Parallel to `IRQ_HANDLERS`; set by the `priority`
+/// argument to `sys_interrupt_claim`/`sys_interrupt_claim_fast`, defaulting to 0 for sources
+/// claimed before this existed (none currently are, but 0 is the sensible "no opinion" floor).
+static mut IRQ_PRIORITIES: [u8; 32] = [0; 32];
+
+/// The one IRQ source (if any) dispatched before the priority-ordered scan below, mirroring a
+/// FIQ-style fast path. Set by `sys_interrupt_claim_fast`; there is only one slot, since a second
+/// "fastest" source would just be the normal priority scheme again.
+static mut IRQ_FAST: Option<usize> = None;
+
 pub fn handle(irqs_pending: usize) {
     // Unsafe is required here because we're accessing a static
     // mutable value, and it could be modified from various threads.
@@ -12,8 +22,29 @@ pub fn handle(irqs_pending: usize) {
     // NOTE: This will become an issue when running with multiple cores,
     // so this should be protected by a mutex.
     unsafe {
-        for irq_no in 0..IRQ_HANDLERS.len() {
-            if irqs_pending & (1 << irq_no) != 0 {
+        if let Some(fast_irq) = IRQ_FAST {
+            if irqs_pending & (1 << fast_irq) != 0 {
+                if let Some(f) = IRQ_HANDLERS[fast_irq] {
+                    f(fast_irq);
+                }
+            }
+        }
+
+        // Service remaining pending sources in descending-priority order. Ties keep ascending
+        // bit order (the scan below already visits irq_no ascending, and the priority compare
+        // is `>`, not `>=`, so equal-priority sources fall through in that same order -- i.e.
+        // stable within a priority level).
+        for priority in (0..=255u8).rev() {
+            for irq_no in 0..IRQ_HANDLERS.len() {
+                if Some(irq_no) == IRQ_FAST {
+                    continue; // already dispatched above
+                }
+                if irqs_pending & (1 << irq_no) == 0 {
+                    continue;
+                }
+                if IRQ_PRIORITIES[irq_no] != priority {
+                    continue;
+                }
                 if let Some(f) = IRQ_HANDLERS[irq_no] {
                     // Call the IRQ handler
                     f(irq_no);
@@ -28,7 +59,7 @@ pub fn handle(irqs_pending: usize) {
     }
 }
 
-pub fn sys_interrupt_claim(irq: usize, f: fn(usize)) -> Result<(), XousError> {
+pub fn sys_interrupt_claim(irq: usize, f: fn(usize), priority: u8) -> Result<(), XousError> {
     // Unsafe is required since we're accessing a static mut array.
     // However, we disable interrupts to prevent contention on this array.
     unsafe {
@@ -39,6 +70,7 @@ pub fn sys_interrupt_claim(irq: usize, f: fn(usize)) -> Result<(), XousError> {
             Err(XousError::InterruptInUse)
         } else {
             IRQ_HANDLERS[irq] = Some(f);
+            IRQ_PRIORITIES[irq] = priority;
             // Note that the vexriscv "IRQ Mask" register is inverse-logic --
             // that is, setting a bit in the "mask" register unmasks (i.e. enables) it.
             vmim::write(vmim::read() | (1 << irq));
@@ -49,6 +81,31 @@ pub fn sys_interrupt_claim(irq: usize, f: fn(usize)) -> Result<(), XousError> {
     }
 }
 
+/// Claim `irq` as the single fast-dispatch source: `handle` calls it before the priority-ordered
+/// scan, regardless of `priority`. Only one source can hold this slot at a time -- claiming a
+/// second fast source while one is already held returns `InterruptInUse` without disturbing the
+/// existing one.
+pub fn sys_interrupt_claim_fast(irq: usize, f: fn(usize), priority: u8) -> Result<(), XousError> {
+    unsafe {
+        mstatus::clear_mie();
+        let result = if IRQ_FAST.is_some() {
+            Err(XousError::InterruptInUse)
+        } else if irq > IRQ_HANDLERS.len() {
+            Err(XousError::InterruptNotFound)
+        } else if IRQ_HANDLERS[irq].is_some() {
+            Err(XousError::InterruptInUse)
+        } else {
+            IRQ_HANDLERS[irq] = Some(f);
+            IRQ_PRIORITIES[irq] = priority;
+            IRQ_FAST = Some(irq);
+            vmim::write(vmim::read() | (1 << irq));
+            Ok(())
+        };
+        mstatus::set_mie();
+        result
+    }
+}
+
 pub fn sys_interrupt_free(irq: usize) -> Result<(), XousError> {
     unsafe {
         mstatus::clear_mie();
@@ -56,6 +113,10 @@ pub fn sys_interrupt_free(irq: usize) -> Result<(), XousError> {
             Err(XousError::InterruptNotFound)
         } else {
             IRQ_HANDLERS[irq] = None;
+            IRQ_PRIORITIES[irq] = 0;
+            if IRQ_FAST == Some(irq) {
+                IRQ_FAST = None;
+            }
             // Note that the vexriscv "IRQ Mask" register is inverse-logic --
             // that is, setting a bit in the "mask" register unmasks (i.e. enables) it.
             vmim::write(vmim::read() & !(1 << irq));
@@ -1,5 +1,5 @@
 #![no_std]
-mod definitions;
+pub mod definitions;
 mod irq;
 mod macros;
 pub mod syscalls;
@@ -4,12 +4,30 @@ use super::definitions::*;
 /// be called from within an interrupt context, but using the ordinary privilege level of
 /// the process.
 ///
+/// `priority` controls dispatch order relative to other claimed sources when more than one is
+/// pending at once -- higher values are serviced first; ties fall back to ascending IRQ number.
+/// It has no effect on whether or when a source with only one pending IRQ fires.
+///
 /// # Errors
 ///
 /// * **InterruptNotFound**: The specified interrupt isn't valid on this system
 /// * **InterruptInUse**: The specified interrupt has already been claimed
-pub fn sys_interrupt_claim(irq: usize, f: fn(usize)) -> Result<(), XousError> {
-    crate::irq::sys_interrupt_claim(irq, f)
+pub fn sys_interrupt_claim(irq: usize, f: fn(usize), priority: u8) -> Result<(), XousError> {
+    crate::irq::sys_interrupt_claim(irq, f, priority)
+}
+
+/// Like `sys_interrupt_claim`, but marks `irq` as the fast-dispatch source: `handle` runs it
+/// before the priority-ordered scan of every other claimed source, regardless of `priority`.
+/// Only one source can hold this slot at a time.
+///
+/// # Errors
+///
+/// * **InterruptNotFound**: The specified interrupt isn't valid on this system
+/// * **InterruptInUse**: The specified interrupt has already been claimed, or another interrupt
+///                        is already the fast-dispatch source
+#[allow(dead_code)]
+pub fn sys_interrupt_claim_fast(irq: usize, f: fn(usize), priority: u8) -> Result<(), XousError> {
+    crate::irq::sys_interrupt_claim_fast(irq, f, priority)
 }
 
 /// Returns the interrupt back to the operating system and masks it again.
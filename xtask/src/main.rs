@@ -283,6 +283,7 @@ fn create_image(
     .expect("Failed to copy loader binary");
 
     // extend the loader binary to 4096 bytes by padding with 0's
+    let loader_len = std::fs::metadata(PathBuf::from(&loader_bin_path))?.len() as usize;
     let mut loader: [u8; 4096] = [0; 4096];
     std::fs::File::open(PathBuf::from(&loader_bin_path))?.read(&mut loader)?;
     std::fs::write(PathBuf::from(&loader_bin_path), loader)?;
@@ -295,23 +296,48 @@ fn create_image(
     .output()
     .expect("Failed to copy the kernel binary");
 
+    // Actual section lengths, independent of each region's fixed capacity -- these are what
+    // get recorded in the partition table below, so the loader can validate boundaries at
+    // runtime instead of trusting that `GATEWARE_REGION`/`KERNEL_REGION` here still match
+    // whatever it was built against.
+    let gateware_len = std::fs::metadata(gateware)?.len() as usize;
+    let kernel_len = std::fs::metadata(PathBuf::from(&kernel_bin_path))?.len() as usize;
+    if gateware_len > GATEWARE_REGION {
+        return Err(format!(
+            "gateware is {} bytes, which overruns its {}-byte region",
+            gateware_len, GATEWARE_REGION
+        ).into());
+    }
+    if kernel_len > KERNEL_REGION {
+        return Err(format!(
+            "kernel is {} bytes, which overruns its {}-byte region",
+            kernel_len, KERNEL_REGION
+        ).into());
+    }
+
     // 104k region for gateware
     let mut gateware_bin: [u8; GATEWARE_REGION] = [0; GATEWARE_REGION];
     // kernel bin can be no longer than 48k, due to limitation on loader size
     let mut kernel_bin: [u8; KERNEL_REGION] = [0; KERNEL_REGION];
 
     std::fs::File::open(gateware)?.read(&mut gateware_bin)?;
-    let kernel_bytes = std::fs::File::open(PathBuf::from(&kernel_bin_path))?.read(&mut kernel_bin);
-    match kernel_bytes {
-        Ok(bytes) => {
-            println!("Read {} kernel bytes into image.", bytes);
-            if bytes == KERNEL_REGION {
-                println!("WARNING: kernel may be truncated.");
-            }
-        },
-        _ => {
-            println!("Error in reading kernel");
-        }
+    std::fs::File::open(PathBuf::from(&kernel_bin_path))?.read(&mut kernel_bin)?;
+    println!("Read {} kernel bytes into image.", kernel_len);
+
+    // Partition table: one (offset, actual_length, region_capacity) record per section, in
+    // build order (gateware, loader, kernel), so the loader can locate and bounds-check each
+    // section without its own copy of `GATEWARE_REGION`/`KERNEL_REGION` baked in. See
+    // `fw_image::Partition` on the firmware side for the matching reader.
+    let partitions: [(u32, u32, u32); 3] = [
+        (0, gateware_len as u32, GATEWARE_REGION as u32),
+        (GATEWARE_REGION as u32, loader_len as u32, 4096),
+        ((GATEWARE_REGION + 4096) as u32, kernel_len as u32, KERNEL_REGION as u32),
+    ];
+    let mut partition_table: Vec<u8> = Vec::new();
+    for (offset, length, capacity) in partitions.iter() {
+        partition_table.write(&offset.to_le_bytes())?;
+        partition_table.write(&length.to_le_bytes())?;
+        partition_table.write(&capacity.to_le_bytes())?;
     }
 
     let mut image = std::fs::File::create(PathBuf::from(&IMAGE_PATH))?;
@@ -319,17 +345,43 @@ fn create_image(
     image.write(&loader)?;
     image.write(&kernel_bin)?;
 
+    // Monotonic image sequence number, read back from whatever `ec_fw.bin` is already sitting
+    // at UPDATE_EC (if any) and incremented. This is the anchor a future A/B-bank bootloader
+    // would need to tell a newly-written image apart from the one already on the other bank
+    // (e.g. to refuse "updating" to an older sequence number after a rollback) -- NOTE:
+    // that bootloader logic itself (boot-state sector, trial-boot, swap/rollback) isn't
+    // implemented here, because this tree has no loader/bootloader source at all (no
+    // `loader.S`-equivalent crate exists to extend); only this header field, which `xtask`
+    // alone owns, is in scope for now.
+    let seq: u32 = match std::fs::read(PathBuf::from(UPDATE_EC)) {
+        Ok(prev) if prev.len() >= 48 && prev[32..36] == [0x70, 0x72, 0x65, 0x63] => {
+            u32::from_le_bytes([prev[44], prev[45], prev[46], prev[47]]).wrapping_add(1)
+        }
+        _ => 1,
+    };
+
     let mut ec_fw: Vec<u8> = Vec::new();
     // build the header
     ec_fw.write(&[0; 32])?; // pad some space for the hash
     ec_fw.write(&[0x70, 0x72, 0x65, 0x63])?; // signature 'prec' in BE
     ec_fw.write(&(1 as u32).to_le_bytes())?;
     ec_fw.write( &((gateware_bin.len() + loader.len() + kernel_bin.len()) as u32).to_le_bytes())?;
+    ec_fw.write(&seq.to_le_bytes())?;
+    // Partition table (see above): offset/actual_length/capacity for gateware, loader, kernel.
+    ec_fw.write(&partition_table)?;
+    // Per-sector CRC-32 manifest, one LE u32 per 4096-byte flash sector of the payload below,
+    // so a resumed/interrupted flash write can be checked (and, if needed, re-flashed) one
+    // sector at a time instead of re-reading and re-hashing the whole image. See
+    // `fw_image::verify_sector` on the firmware side for the matching reader.
+    let mut ec_payload: Vec<u8> = Vec::new();
+    ec_payload.write(&gateware_bin)?;
+    ec_payload.write(&loader)?;
+    ec_payload.write(&kernel_bin)?;
+    ec_fw.write(&crc32_sector_manifest(&ec_payload))?;
     ec_fw.resize(4096, 0xff); // extend the header to the next page
     // write the firmware
-    ec_fw.write(&gateware_bin)?;
-    ec_fw.write(&loader)?;
-    ec_fw.write(&kernel_bin)?;
+    ec_fw.write(&ec_payload)?;
+    println!("EC image sequence number: {}", seq);
     // compute the hash
     use sha2::Digest;
     let mut hasher = sha2::Sha512Trunc256::new();
@@ -352,6 +404,8 @@ fn create_image(
         wf200_fw_file.read_to_end(&mut wf200_fw)?;
         // note the length & resize
         wf_fw.write(&(wf200_fw.len() as u32).to_le_bytes())?;
+        // per-sector CRC-32 manifest, same layout/rationale as the ec_fw one above
+        wf_fw.write(&crc32_sector_manifest(&wf200_fw))?;
         wf_fw.resize(4096, 0xff);
         // write the firmware
         wf_fw.write(&wf200_fw)?;
@@ -368,6 +422,34 @@ fn create_image(
     Ok(project_root().join(&IMAGE_PATH))
 }
 
+/// IEEE 802.3 CRC-32 (reflected, polynomial 0xEDB88320, init/final XOR 0xFFFFFFFF) over
+/// `data`. Matches `spi::crc32_update`/`crc32_finalize` in the EC firmware bit-for-bit, so a
+/// sector's manifest entry here is directly comparable against what the firmware-side
+/// verifier recomputes from flash.
+fn crc32_ieee(data: &[u8]) -> u32 {
+    let mut crc = 0xFFFF_FFFFu32;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xEDB8_8320 & mask);
+        }
+    }
+    crc ^ 0xFFFF_FFFF
+}
+
+/// One little-endian CRC-32 per `FLASH_SECTOR_SIZE`-byte chunk of `payload` (the last chunk
+/// is whatever's left over, not padded first -- the firmware side reads back exactly
+/// `payload_len` bytes of flash per sector too, so the two never disagree about chunk size).
+const FLASH_SECTOR_SIZE: usize = 4096;
+fn crc32_sector_manifest(payload: &[u8]) -> Vec<u8> {
+    let mut manifest = Vec::new();
+    for sector in payload.chunks(FLASH_SECTOR_SIZE) {
+        manifest.extend_from_slice(&crc32_ieee(sector).to_le_bytes());
+    }
+    manifest
+}
+
 fn cargo() -> String {
     env::var("CARGO").unwrap_or_else(|_| "cargo".to_string())
 }